@@ -0,0 +1,50 @@
+use std::collections::BTreeMap;
+
+use reqwest::Version;
+
+use crate::stats::RequestRecord;
+
+// TLS 핸드셰이크에서 제안할 ALPN 프로토콜 목록
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Offer {
+    Http1,
+    H2,
+    Both,
+}
+
+impl Offer {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "http1" => Ok(Offer::Http1),
+            "h2" => Ok(Offer::H2),
+            "both" => Ok(Offer::Both),
+            _ => Err(format!("unknown ALPN offer '{}' (http1/h2/both)", value)),
+        }
+    }
+
+    pub fn protocols(&self) -> &'static [&'static str] {
+        match self {
+            Offer::Http1 => &["http/1.1"],
+            Offer::H2 => &["h2"],
+            Offer::Both => &["h2", "http/1.1"],
+        }
+    }
+}
+
+// reqwest 는 협상 결과를 직접 알려주지 않으므로 응답의 HTTP 버전으로 대신한다
+pub fn from_version(version: Version) -> String {
+    String::from(if version == Version::HTTP_2 { "h2" } else { "http/1.1" })
+}
+
+// 실행 종료 보고: 제안한 목록과 TLS 응답별로 협상된 프로토콜 수
+pub fn summarize(offer: Offer, records: &[RequestRecord]) -> Option<String> {
+    let mut negotiated: BTreeMap<&str, usize> = BTreeMap::new();
+    for alpn in records.iter().filter_map(|r| r.alpn.as_deref()) {
+        *negotiated.entry(alpn).or_default() += 1;
+    }
+    if negotiated.is_empty() {
+        return None;
+    }
+    let counts: Vec<String> = negotiated.iter().map(|(alpn, count)| format!("{} {}", alpn, count)).collect();
+    Some(format!("ALPN: offered {} | negotiated {}", offer.protocols().join(","), counts.join(", ")))
+}
@@ -0,0 +1,636 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use h2::{server, RecvStream};
+use hyper::{
+    header::{HeaderMap, HeaderValue},
+    Request, Response,
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    runtime::Runtime,
+    task::{AbortHandle, JoinSet},
+};
+
+use crate::{
+    stats::{self, format_ms, RequestRecord},
+    AppState,
+};
+
+// Envoy 가 접근 로그를 스트리밍하는 gRPC 메서드 (envoy.service.accesslog.v3)
+const METHOD_PATH: &str = "/envoy.service.accesslog.v3.AccessLogService/StreamAccessLogs";
+// 요청마다 붙이는 id 헤더. Envoy 설정의 additional_request_headers_to_log 에 넣어야 로그에 실린다
+const ID_HEADER: &str = "my_id";
+// 실행이 끝난 뒤 아직 오지 않은 접근 로그를 기다리는 시간 (Envoy 기본 버퍼 비우기 간격 1s 보다 길게)
+pub const FLUSH_GRACE: Duration = Duration::from_millis(2500);
+// 메시지 하나의 최대 크기 (넘으면 스트림을 끊는다)
+const MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+// ResponseFlags 필드 번호 순서의 짧은 이름 (%RESPONSE_FLAGS% 와 같은 표기) 과 뜻
+const RESPONSE_FLAGS: [(&str, &str); 28] = [
+    ("LH", "local service failed its health check"),
+    ("UH", "no healthy upstream host in the cluster"),
+    ("UT", "upstream request timed out"),
+    ("LR", "connection reset locally by Envoy"),
+    ("UR", "upstream reset the connection"),
+    ("UF", "upstream connection failed"),
+    ("UC", "upstream connection terminated"),
+    ("UO", "upstream overflow (circuit breaker open)"),
+    ("NR", "no route configured for the request"),
+    ("DI", "request delayed by fault injection"),
+    ("FI", "request aborted by fault injection"),
+    ("RL", "rate limited locally (HTTP 429)"),
+    ("UAEX", "denied by the external authorization service"),
+    ("RLSE", "rate limit service error"),
+    ("DC", "downstream connection terminated"),
+    ("URX", "retry limit or max connect attempts reached"),
+    ("SI", "stream idle timeout"),
+    ("IH", "invalid x-envoy-* request header value"),
+    ("DPE", "downstream HTTP protocol error"),
+    ("UMSDR", "upstream max stream duration reached"),
+    ("RFCF", "response served from the cache filter"),
+    ("NFCF", "no filter config found"),
+    ("DT", "request or connection exceeded max duration"),
+    ("UPE", "upstream HTTP protocol error"),
+    ("NC", "upstream cluster not found"),
+    ("OM", "overload manager terminated the request"),
+    ("DF", "DNS resolution failed"),
+    ("DR", "downstream remote reset the stream"),
+];
+
+// 응답 플래그의 뜻 (모르는 플래그면 None)
+pub fn explain(flag: &str) -> Option<&'static str> {
+    RESPONSE_FLAGS.iter().find(|(code, _)| *code == flag).map(|(_, meaning)| *meaning)
+}
+
+// Envoy 가 보고한 요청 하나의 처리 결과
+#[derive(Clone)]
+pub struct ProxyLog {
+    // 요청 시작 ~ 응답 마지막 바이트 전송 (duration, 없으면 time_to_last_downstream_tx_byte)
+    pub latency: Option<Duration>,
+    // 요청 시작 ~ 업스트림 응답 첫 바이트 (업스트림 처리 시간 어림)
+    pub upstream_ttfb: Option<Duration>,
+    pub status: Option<u16>,
+    pub flags: Vec<&'static str>,
+    pub details: Option<String>,
+    pub cluster: Option<String>,
+    pub upstream_host: Option<String>,
+    pub attempts: Option<u32>,
+}
+
+impl ProxyLog {
+    pub fn flags(&self) -> String {
+        if self.flags.is_empty() { String::from("-") } else { self.flags.join(",") }
+    }
+
+    // 인스펙터 한 줄 ("Proxy: 503 in 12.3ms (upstream 10.1ms) via web/10.0.0.1:8080 | flags UF,URX | 2 attempts | details")
+    pub fn describe(&self) -> String {
+        let mut line = format!("Proxy: {}", self.status.map_or(String::from("-"), |s| s.to_string()));
+        if let Some(latency) = self.latency {
+            line.push_str(&format!(" in {}", format_ms(latency)));
+        }
+        if let Some(ttfb) = self.upstream_ttfb {
+            line.push_str(&format!(" (upstream {})", format_ms(ttfb)));
+        }
+        let via: Vec<&str> = [self.cluster.as_deref(), self.upstream_host.as_deref()].into_iter().flatten().collect();
+        if !via.is_empty() {
+            line.push_str(&format!(" via {}", via.join("/")));
+        }
+        line.push_str(&format!(" | flags {}", self.flags()));
+        if let Some(attempts) = self.attempts.filter(|a| *a > 1) {
+            line.push_str(&format!(" | {} attempts", attempts));
+        }
+        if let Some(details) = self.details.as_ref().filter(|d| d.as_str() != "via_upstream") {
+            line.push_str(&format!(" | {}", details));
+        }
+        line
+    }
+}
+
+// 요청 id -> 접근 로그. 로그가 요청 기록보다 먼저 와도 id 로 나중에 붙는다
+#[derive(Clone, Default)]
+pub struct ProxyLogs {
+    logs: HashMap<String, ProxyLog>,
+    // 받은 로그 수 (id 가 없어 버린 로그 포함)
+    pub received: usize,
+    pub without_id: usize,
+}
+
+impl ProxyLogs {
+    pub fn get(&self, id: &str) -> Option<&ProxyLog> {
+        self.logs.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.logs.len()
+    }
+
+    // 보관 기간이 지나 구간 집계로 합친 요청
+    pub fn forget(&mut self, id: &str) {
+        self.logs.remove(id);
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn insert(&mut self, id: Option<String>, log: ProxyLog) {
+        self.received += 1;
+        match id {
+            Some(id) => {
+                self.logs.insert(id, log);
+            }
+            None => self.without_id += 1,
+        }
+    }
+}
+
+// 실행하는 동안 떠 있는 ALS 수신기. 버리면 수신 작업과 연결을 모두 끝낸다
+pub struct Listener {
+    addr: SocketAddr,
+    task: AbortHandle,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+// als_listen 설정에 맞게 수신기를 켜거나 끈다 (주소가 같으면 이전 실행의 수신기를 그대로 쓴다)
+pub fn ensure(rt: &Runtime, state: &Arc<Mutex<AppState>>, listen: &str) -> Result<(), String> {
+    let addr = match listen.trim() {
+        "" => {
+            state.lock().unwrap().als_listener = None;
+            return Ok(());
+        }
+        addr => addr.parse::<SocketAddr>().map_err(|_| format!("invalid ALS listen address '{}'", addr))?,
+    };
+    if state.lock().unwrap().als_listener.as_ref().is_some_and(|l| l.addr == addr) {
+        return Ok(());
+    }
+    // 주소가 바뀌었으면 이전 수신기를 먼저 닫아 포트를 비운다
+    state.lock().unwrap().als_listener = None;
+    let listener = rt.block_on(TcpListener::bind(addr)).map_err(|e| format!("failed to listen for ALS on {}: {}", addr, e))?;
+    let task = rt.spawn(serve(listener, state.clone())).abort_handle();
+    let mut state = state.lock().unwrap();
+    state.als_listener = Some(Listener { addr, task });
+    state.add_log(&format!("ALS: listening for Envoy access logs on {} (log the {} request header to join them)", addr, ID_HEADER));
+    Ok(())
+}
+
+async fn serve(listener: TcpListener, state: Arc<Mutex<AppState>>) {
+    // 수신 작업을 끝내면 연결 작업도 함께 끝난다
+    let mut connections = JoinSet::new();
+    loop {
+        if let Ok((stream, _)) = listener.accept().await {
+            connections.spawn(connection(stream, state.clone()));
+        }
+        while connections.try_join_next().is_some() {}
+    }
+}
+
+async fn connection(stream: TcpStream, state: Arc<Mutex<AppState>>) {
+    let _ = stream.set_nodelay(true);
+    let Ok(mut conn) = server::handshake(stream).await else {
+        return;
+    };
+    let mut streams = JoinSet::new();
+    while let Some(Ok((request, respond))) = conn.accept().await {
+        streams.spawn(stream_logs(request, respond, state.clone()));
+    }
+}
+
+// 요청 스트림 하나 (Envoy 는 연결마다 스트림 하나를 열어 두고 계속 보낸다)
+async fn stream_logs(request: Request<RecvStream>, mut respond: server::SendResponse<Bytes>, state: Arc<Mutex<AppState>>) {
+    if request.uri().path() != METHOD_PATH {
+        let _ = respond.send_response(grpc_response(Some(12)), true);
+        return;
+    }
+    let mut body = request.into_body();
+    let mut pending = Vec::new();
+    let mut reported = false;
+    while let Some(chunk) = body.data().await {
+        let Ok(chunk) = chunk else {
+            return;
+        };
+        let _ = body.flow_control().release_capacity(chunk.len());
+        pending.extend_from_slice(&chunk);
+        loop {
+            let message = match take_message(&mut pending) {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(e) => {
+                    state.lock().unwrap().add_log(&format!("ALS: closing stream: {}", e));
+                    let _ = respond.send_response(grpc_response(Some(3)), true);
+                    return;
+                }
+            };
+            let decoded = decode(&message);
+            let mut state = state.lock().unwrap();
+            match decoded {
+                Ok(entries) => {
+                    for (id, log) in entries {
+                        state.proxy_logs.insert(id, log);
+                    }
+                }
+                // 같은 스트림에서는 한 번만 알린다
+                Err(e) if !reported => {
+                    reported = true;
+                    state.add_log(&format!("ALS: failed to decode access log message: {}", e));
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    // 클라이언트가 스트림을 닫으면 빈 StreamAccessLogsResponse 로 끝낸다
+    let Ok(mut send) = respond.send_response(grpc_response(None), false) else {
+        return;
+    };
+    let _ = send.send_data(Bytes::from_static(&[0, 0, 0, 0, 0]), false);
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", HeaderValue::from_static("0"));
+    let _ = send.send_trailers(trailers);
+}
+
+// status 가 있으면 트레일러 없이 바로 끝내는 응답 (Trailers-Only)
+fn grpc_response(status: Option<u32>) -> Response<()> {
+    let mut response = Response::builder().status(200).header("content-type", "application/grpc");
+    if let Some(status) = status {
+        response = response.header("grpc-status", status.to_string());
+    }
+    response.body(()).expect("valid gRPC response")
+}
+
+// gRPC 길이 접두 메시지 (압축 플래그 1바이트 + 길이 4바이트)
+fn take_message(pending: &mut Vec<u8>) -> Result<Option<Vec<u8>>, String> {
+    if pending.len() < 5 {
+        return Ok(None);
+    }
+    if pending[0] != 0 {
+        return Err(String::from("compressed messages are not supported"));
+    }
+    let length = u32::from_be_bytes([pending[1], pending[2], pending[3], pending[4]]) as usize;
+    if length > MAX_MESSAGE_BYTES {
+        return Err(format!("message of {} bytes is too large", length));
+    }
+    if pending.len() < 5 + length {
+        return Ok(None);
+    }
+    let message = pending[5..5 + length].to_vec();
+    pending.drain(..5 + length);
+    Ok(Some(message))
+}
+
+// protobuf 필드 값 (필요한 필드만 읽으므로 고정 길이 값은 건너뛴다)
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+// protobuf 메시지의 필드를 차례로 읽는다
+struct Fields<'a> {
+    buf: &'a [u8],
+}
+
+fn fields(buf: &[u8]) -> Fields<'_> {
+    Fields { buf }
+}
+
+impl<'a> Fields<'a> {
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self.buf.split_first().ok_or("truncated varint")?;
+            self.buf = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(String::from("varint is too long"))
+    }
+
+    fn skip(&mut self, length: usize) -> Result<&'a [u8], String> {
+        if self.buf.len() < length {
+            return Err(String::from("truncated field"));
+        }
+        let (value, rest) = self.buf.split_at(length);
+        self.buf = rest;
+        Ok(value)
+    }
+
+    fn next(&mut self) -> Result<Option<(u64, Value<'a>)>, String> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let value = match key & 7 {
+            0 => Value::Varint(self.varint()?),
+            1 => self.skip(8).map(|_| Value::Fixed)?,
+            2 => {
+                let length = self.varint()? as usize;
+                Value::Bytes(self.skip(length)?)
+            }
+            5 => self.skip(4).map(|_| Value::Fixed)?,
+            wire => return Err(format!("unsupported wire type {}", wire)),
+        };
+        Ok(Some((key >> 3, value)))
+    }
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+// google.protobuf.Duration (seconds = 1, nanos = 2)
+fn duration(message: &[u8]) -> Result<Duration, String> {
+    let (mut seconds, mut nanos) = (0, 0);
+    let mut fields = fields(message);
+    while let Some((number, value)) = fields.next()? {
+        match (number, value) {
+            (1, Value::Varint(v)) => seconds = v,
+            (2, Value::Varint(v)) => nanos = v as u32,
+            _ => {}
+        }
+    }
+    Ok(Duration::new(seconds, nanos))
+}
+
+// UInt32Value (value = 1)
+fn wrapped_u32(message: &[u8]) -> Result<u32, String> {
+    let mut fields = fields(message);
+    while let Some((number, value)) = fields.next()? {
+        if let (1, Value::Varint(v)) = (number, value) {
+            return Ok(v as u32);
+        }
+    }
+    Ok(0)
+}
+
+// config.core.v3.Address (socket_address = 1 { address = 2, port_value = 3 })
+fn address(message: &[u8]) -> Result<Option<String>, String> {
+    let mut fields = fields(message);
+    while let Some((number, value)) = fields.next()? {
+        if let (1, Value::Bytes(socket)) = (number, value) {
+            let (mut host, mut port) = (String::new(), 0);
+            let mut socket = self::fields(socket);
+            while let Some((number, value)) = socket.next()? {
+                match (number, value) {
+                    (2, Value::Bytes(v)) => host = text(v),
+                    (3, Value::Varint(v)) => port = v,
+                    _ => {}
+                }
+            }
+            return Ok(Some(if host.contains(':') { format!("[{}]:{}", host, port) } else { format!("{}:{}", host, port) }));
+        }
+    }
+    Ok(None)
+}
+
+// AccessLogCommon 의 ResponseFlags (플래그마다 bool 필드, unauthorized_details 만 메시지)
+fn response_flags(message: &[u8]) -> Result<Vec<&'static str>, String> {
+    let mut flags = Vec::new();
+    let mut fields = fields(message);
+    while let Some((number, value)) = fields.next()? {
+        let set = match value {
+            Value::Varint(v) => v != 0,
+            Value::Bytes(_) => true,
+            Value::Fixed => false,
+        };
+        if let Some((flag, _)) = RESPONSE_FLAGS.get((number as usize).wrapping_sub(1)).filter(|_| set) {
+            flags.push(*flag);
+        }
+    }
+    Ok(flags)
+}
+
+// 맵 항목 (key = 1, value = 2)
+fn map_entry(message: &[u8]) -> Result<(String, String), String> {
+    let (mut key, mut text_value) = (String::new(), String::new());
+    let mut fields = fields(message);
+    while let Some((number, value)) = fields.next()? {
+        match (number, value) {
+            (1, Value::Bytes(v)) => key = text(v),
+            (2, Value::Bytes(v)) => text_value = text(v),
+            _ => {}
+        }
+    }
+    Ok((key, text_value))
+}
+
+// data.accesslog.v3.HTTPAccessLogEntry 하나. id 는 my_id 요청 헤더, 없으면 x-request-id
+fn entry(message: &[u8]) -> Result<(Option<String>, ProxyLog), String> {
+    let mut log = ProxyLog { latency: None, upstream_ttfb: None, status: None, flags: Vec::new(), details: None, cluster: None, upstream_host: None, attempts: None };
+    let (mut header_id, mut request_id) = (None, None);
+    let mut last_tx = None;
+    let mut fields = fields(message);
+    while let Some((number, value)) = fields.next()? {
+        let Value::Bytes(value) = value else {
+            continue;
+        };
+        match number {
+            // common_properties
+            1 => {
+                let mut common = self::fields(value);
+                while let Some((number, value)) = common.next()? {
+                    match (number, value) {
+                        (9, Value::Bytes(v)) => log.upstream_ttfb = Some(duration(v)?),
+                        (12, Value::Bytes(v)) => last_tx = Some(duration(v)?),
+                        (13, Value::Bytes(v)) => log.upstream_host = address(v)?,
+                        (15, Value::Bytes(v)) if !v.is_empty() => log.cluster = Some(text(v)),
+                        (16, Value::Bytes(v)) => log.flags = response_flags(v)?,
+                        (23, Value::Bytes(v)) => log.latency = Some(duration(v)?),
+                        (24, Value::Varint(v)) => log.attempts = Some(v as u32),
+                        _ => {}
+                    }
+                }
+            }
+            // request
+            3 => {
+                let mut request = self::fields(value);
+                while let Some((number, value)) = request.next()? {
+                    match (number, value) {
+                        (9, Value::Bytes(v)) if !v.is_empty() => request_id = Some(text(v)),
+                        (13, Value::Bytes(v)) => {
+                            let (name, value) = map_entry(v)?;
+                            if name.eq_ignore_ascii_case(ID_HEADER) {
+                                header_id = Some(value);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            // response
+            4 => {
+                let mut response = self::fields(value);
+                while let Some((number, value)) = response.next()? {
+                    match (number, value) {
+                        (1, Value::Bytes(v)) => log.status = Some(wrapped_u32(v)? as u16),
+                        (6, Value::Bytes(v)) if !v.is_empty() => log.details = Some(text(v)),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    log.latency = log.latency.or(last_tx);
+    Ok((header_id.or(request_id), log))
+}
+
+// StreamAccessLogsMessage 의 HTTP 로그 (http_logs = 2 { log_entry = 1 }). TCP 로그는 건너뛴다
+fn decode(message: &[u8]) -> Result<Vec<(Option<String>, ProxyLog)>, String> {
+    let mut entries = Vec::new();
+    let mut fields = fields(message);
+    while let Some((number, value)) = fields.next()? {
+        if let (2, Value::Bytes(logs)) = (number, value) {
+            let mut logs = self::fields(logs);
+            while let Some((number, value)) = logs.next()? {
+                if let (1, Value::Bytes(v)) = (number, value) {
+                    entries.push(entry(v)?);
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
+
+// 실행 종료 보고: 접근 로그를 붙인 요청 수, 프록시가 잰 지연, 클라이언트와 프록시 지연 차이, 응답 플래그
+pub fn summarize(records: &[RequestRecord], logs: &ProxyLogs, percentiles: &[f64]) -> Vec<String> {
+    let joined: Vec<(&RequestRecord, &ProxyLog)> = records.iter().filter_map(|r| logs.get(&r.id).map(|log| (r, log))).collect();
+    let mut lines = vec![format!("ALS: {} of {} requests joined to Envoy access logs ({} logs received)", joined.len(), records.len(), logs.received)];
+    if logs.without_id > 0 {
+        lines[0].push_str(&format!(", {} without a request id (add {} to additional_request_headers_to_log)", logs.without_id, ID_HEADER));
+    }
+    if joined.is_empty() {
+        return lines;
+    }
+
+    let mut proxy: Vec<Duration> = joined.iter().filter_map(|(_, log)| log.latency).collect();
+    proxy.sort();
+    // 클라이언트 지연 - 프록시 지연 (네트워크와 클라이언트 쪽 시간)
+    let mut overhead: Vec<Duration> = joined.iter().filter_map(|(r, log)| log.latency.map(|l| r.latency.saturating_sub(l))).collect();
+    overhead.sort();
+    let quantiles = |values: &[Duration]| percentiles.iter().map(|p| format!("{} {}", stats::percentile_label(*p), format_ms(stats::percentile(values, *p)))).collect::<Vec<_>>().join(" ");
+    if !proxy.is_empty() {
+        lines.push(format!("ALS: proxy latency {} | client overhead {}", quantiles(&proxy), quantiles(&overhead)));
+    }
+
+    let mut flags: BTreeMap<&str, usize> = BTreeMap::new();
+    for flag in joined.iter().flat_map(|(_, log)| &log.flags) {
+        *flags.entry(flag).or_default() += 1;
+    }
+    if !flags.is_empty() {
+        let flags: Vec<String> = flags.iter().map(|(flag, count)| format!("{} {} ({})", flag, count, explain(flag).unwrap_or("unknown"))).collect();
+        lines.push(format!("ALS: response flags {}", flags.join(", ")));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 테스트용 protobuf 인코더
+    fn varint(mut v: u64, out: &mut Vec<u8>) {
+        while v >= 0x80 {
+            out.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        out.push(v as u8);
+    }
+
+    fn int(number: u64, v: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint(number << 3, &mut out);
+        varint(v, &mut out);
+        out
+    }
+
+    fn msg(number: u64, body: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint(number << 3 | 2, &mut out);
+        varint(body.len() as u64, &mut out);
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn duration_ms(number: u64, ms: u64) -> Vec<u8> {
+        msg(number, &[int(1, ms / 1000), int(2, ms % 1000 * 1_000_000)].concat())
+    }
+
+    fn http_entry(id: &str) -> Vec<u8> {
+        let common = [
+            duration_ms(9, 8),
+            msg(13, &msg(1, &[msg(2, b"10.0.0.7"), int(3, 8080)].concat())),
+            msg(15, b"web"),
+            // UF (6) 와 URX (16), 모르는 필드 번호는 건너뛴다
+            msg(16, &[int(6, 1), int(16, 1), int(2, 0), int(99, 1)].concat()),
+            duration_ms(23, 1500),
+            int(24, 2),
+            // 고정 길이 필드도 건너뛴다
+            vec![5 << 3 | 1, 0, 0, 0, 0, 0, 0, 0, 0],
+        ]
+        .concat();
+        let request = [msg(9, b"x-req"), msg(13, &[msg(1, b"My_Id"), msg(2, id.as_bytes())].concat())].concat();
+        let response = [msg(1, &int(1, 503)), msg(6, b"upstream_reset")].concat();
+        [msg(1, &common), msg(3, &request), msg(4, &response)].concat()
+    }
+
+    #[test]
+    fn decodes_http_entries() {
+        let message = [msg(1, b"identifier"), msg(2, &[msg(1, &http_entry("r-1")), msg(1, &http_entry("r-2"))].concat())].concat();
+        let entries = decode(&message).unwrap();
+        assert_eq!(entries.len(), 2);
+        let (id, log) = &entries[0];
+        assert_eq!(id.as_deref(), Some("r-1"));
+        assert_eq!(entries[1].0.as_deref(), Some("r-2"));
+        assert_eq!(log.status, Some(503));
+        assert_eq!(log.latency, Some(Duration::from_millis(1500)));
+        assert_eq!(log.upstream_ttfb, Some(Duration::from_millis(8)));
+        assert_eq!(log.cluster.as_deref(), Some("web"));
+        assert_eq!(log.upstream_host.as_deref(), Some("10.0.0.7:8080"));
+        assert_eq!(log.flags, vec!["UF", "URX"]);
+        assert_eq!(log.attempts, Some(2));
+        assert_eq!(log.details.as_deref(), Some("upstream_reset"));
+    }
+
+    #[test]
+    fn falls_back_to_request_id_and_last_tx() {
+        let common = [duration_ms(12, 42), msg(13, &msg(1, &[msg(2, b"::1"), int(3, 9)].concat()))].concat();
+        let (id, log) = entry(&[msg(1, &common), msg(3, &msg(9, b"x-req"))].concat()).unwrap();
+        assert_eq!(id.as_deref(), Some("x-req"));
+        assert_eq!(log.latency, Some(Duration::from_millis(42)));
+        assert_eq!(log.upstream_host.as_deref(), Some("[::1]:9"));
+        assert_eq!(log.flags(), "-");
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let message = msg(2, &msg(1, &http_entry("r-1")));
+        assert!(decode(&message[..message.len() - 3]).is_err());
+        assert!(decode(&[0x80]).is_err());
+        // 그룹 (wire type 3) 은 지원하지 않는다
+        assert!(decode(&[3 << 3 | 3]).is_err());
+    }
+
+    #[test]
+    fn frames_length_prefixed_messages() {
+        let mut pending = vec![0, 0, 0, 0, 3, 1, 2, 3, 0, 0, 0];
+        assert_eq!(take_message(&mut pending).unwrap(), Some(vec![1, 2, 3]));
+        // 다음 메시지는 아직 다 오지 않았다
+        assert_eq!(take_message(&mut pending).unwrap(), None);
+        assert_eq!(pending, vec![0, 0, 0]);
+        assert!(take_message(&mut vec![1, 0, 0, 0, 0]).is_err());
+        assert!(take_message(&mut vec![0, 0xff, 0xff, 0xff, 0xff]).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinSet;
+
+use crate::{
+    config::RunConfig,
+    stats::{self, format_ms, RequestRecord},
+    utils::RequestTemplate,
+};
+
+// 동시 요청 수를 정하지 않았을 때의 작업 수 (wrk 기본 연결 수와 같게)
+const DEFAULT_CONCURRENCY: usize = 10;
+// wrk 와 같은 순서의 지연 분포
+const DISTRIBUTION: [f64; 4] = [50.0, 75.0, 90.0, 99.0];
+
+// 벤치마크 프리셋: 간격/속도 제한 없이 정해진 반복 횟수만큼 동시 요청 수를 채워 보낸다
+pub fn preset(config: &mut RunConfig) {
+    config.delay_ms = 0;
+    config.rps = 0.0;
+    config.duration_s = 0;
+    config.probe_interval_ms = 0;
+    if config.concurrency == 0 {
+        config.concurrency = DEFAULT_CONCURRENCY;
+    }
+}
+
+// 측정 전에 공유 클라이언트마다 HEAD 요청을 보내 연결(TLS 포함)을 미리 맺어 둔다. 응답 상태는 보지 않는다
+pub async fn warm(template: &RequestTemplate) -> Result<(usize, Duration), String> {
+    let started = Instant::now();
+    let mut warming = JoinSet::new();
+    for client in template.clients() {
+        let (client, url) = (client.clone(), template.url().clone());
+        warming.spawn(async move { client.head(url).send().await.map(|_| ()) });
+    }
+    let mut warmed = 0;
+    while let Some(result) = warming.join_next().await {
+        result.map_err(|e| e.to_string())?.map_err(|e| e.to_string())?;
+        warmed += 1;
+    }
+    Ok((warmed, started.elapsed()))
+}
+
+// wrk 형식 요약 (요청 수와 걸린 시간, 지연 평균/표준편차/최대, 분포, 초당 요청 수와 전송량)
+pub fn summarize(records: &[RequestRecord]) -> Vec<String> {
+    let Some(first) = records.iter().map(|r| r.sent_at).min() else {
+        return Vec::new();
+    };
+    let last = records.iter().map(|r| r.sent_at + r.latency).max().unwrap_or(first);
+    let elapsed = last.duration_since(first).max(Duration::from_micros(1)).as_secs_f64();
+    let mut latency: Vec<Duration> = records.iter().map(|r| r.latency).collect();
+    latency.sort();
+
+    let ms: Vec<f64> = latency.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+    let mean = ms.iter().sum::<f64>() / ms.len() as f64;
+    let stdev = (ms.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / ms.len() as f64).sqrt();
+    let within = ms.iter().filter(|v| (*v - mean).abs() <= stdev).count() as f64 / ms.len() as f64 * 100.0;
+    let read: u64 = records.iter().filter_map(|r| r.response_size).sum();
+    let failed = records.iter().filter(|r| !r.success).count();
+    let distribution: Vec<String> = DISTRIBUTION.iter().map(|p| format!("{}% {}", p, format_ms(stats::percentile(&latency, *p)))).collect();
+
+    let mut lines = vec![
+        format!("Benchmark: {} requests in {:.2}s, {} read", records.len(), elapsed, stats::format_bytes(read)),
+        format!(
+            "Benchmark latency: avg {} stdev {} max {} +/- stdev {:.2}%",
+            format_ms(Duration::from_secs_f64(mean / 1000.0)),
+            format_ms(Duration::from_secs_f64(stdev / 1000.0)),
+            format_ms(latency.last().copied().unwrap_or_default()),
+            within
+        ),
+        format!("Benchmark latency distribution: {}", distribution.join(" ")),
+        format!("Requests/sec: {:.2} | Transfer/sec: {}", records.len() as f64 / elapsed, stats::format_bytes((read as f64 / elapsed) as u64)),
+    ];
+    if failed > 0 {
+        lines.push(format!("Failed or non-success responses: {}", failed));
+    }
+    lines
+}
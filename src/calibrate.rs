@@ -0,0 +1,158 @@
+use std::{io, time::Duration};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    runtime::Runtime,
+};
+
+use crate::{
+    bench,
+    config::RunConfig,
+    headless,
+    load_generator::LoadGenerator,
+    stats::{self, format_ms, RequestRecord},
+};
+
+// 요청 하나의 부담을 재는 순차 실행 요청 수
+const SEQUENTIAL_REQUESTS: usize = 200;
+// 반복 횟수를 정하지 않았을 때 상한을 재는 동시 실행 요청 수
+const CONCURRENT_REQUESTS: usize = 2000;
+// 더미 서버 응답 (본문 "ok", 연결 유지)
+const RESPONSE: &[u8] = b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\ncontent-type: text/plain\r\n\r\nok";
+const HEAD_RESPONSE: &[u8] = b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\ncontent-type: text/plain\r\n\r\n";
+
+fn usage(program: &str) -> String {
+    format!("usage: {} calibrate [--config <config.json>] [--iterations N] [--concurrency N] [--<option-key> <value> ...]", program)
+}
+
+// 요청을 받자마자 같은 응답을 돌려주는 HTTP/1.1 서버. 서버 쪽 시간이 거의 없으므로 지연은 모두 생성기 몫이다
+async fn serve(listener: TcpListener) {
+    loop {
+        if let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(respond(stream));
+        }
+    }
+}
+
+async fn respond(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut pending = Vec::with_capacity(16 * 1024);
+    let mut chunk = vec![0u8; 16 * 1024];
+    loop {
+        // 헤더와 Content-Length 만큼의 본문을 다 받은 요청마다 응답 (파이프라인 요청도 차례로)
+        while let Some(end) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&pending[..end]).to_ascii_lowercase();
+            let length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if pending.len() < end + 4 + length {
+                break;
+            }
+            pending.drain(..end + 4 + length);
+            stream.write_all(if head.starts_with("head ") { HEAD_RESPONSE } else { RESPONSE }).await?;
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        pending.extend_from_slice(&chunk[..read]);
+    }
+}
+
+// 한 번 실행하고 결과를 돌려준다. 시작하지 못했거나 실패한 요청이 있으면 실행 로그의 마지막 줄들
+fn measure(config: RunConfig) -> Result<Vec<RequestRecord>, Vec<String>> {
+    let mut report = LoadGenerator::new(config).run();
+    if report.records.is_empty() || report.failed > 0 {
+        let from = report.logs.len().saturating_sub(5);
+        return Err(report.logs.split_off(from));
+    }
+    Ok(report.records)
+}
+
+// 첫 요청을 보낸 때부터 마지막 응답까지
+fn elapsed(records: &[RequestRecord]) -> Duration {
+    let first = records.iter().map(|r| r.sent_at).min();
+    let last = records.iter().map(|r| r.sent_at + r.latency).max();
+    match (first, last) {
+        (Some(first), Some(last)) => last.duration_since(first).max(Duration::from_micros(1)),
+        _ => Duration::from_micros(1),
+    }
+}
+
+// calibrate 하위 명령: 같은 프로세스에 띄운 더미 서버로 요청을 보내 이 장비에서 생성기 자체의 상한과 요청당 부담을 잰다
+// 실제 대상에 대한 결과가 이 값에 가까우면 Envoy 가 아니라 클라이언트가 병목이다
+pub fn run(program: &str, args: &[String]) -> i32 {
+    let mut config = match headless::parse(args) {
+        Ok(config) => config,
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            eprintln!("{}", usage(program));
+            return 2;
+        }
+    };
+
+    // 서버는 따로 둔 런타임에서 돌려 생성기의 작업 스레드와 섞이지 않게 한다
+    let server = Runtime::new().expect("Failed to create Tokio runtime");
+    let listener = match server.block_on(TcpListener::bind("127.0.0.1:0")) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to start the calibration server: {}", e);
+            return 1;
+        }
+    };
+    let address = listener.local_addr().expect("bound listener has an address");
+    server.spawn(serve(listener));
+
+    // 더미 서버는 평문 HTTP/1.1 만 받는다. 간격과 속도 제한 없이 보낸다
+    config.dst_url = format!("http://{}/", address);
+    config.alpn = String::from("http1");
+    config.h2_connections = 0;
+    config.export_file.clear();
+    config.notify_url.clear();
+    config.sinks.clear();
+    // 결과를 모두 원본으로 남겨야 백분위가 정확하다
+    config.raw_window_s = 0;
+    config.memory_budget_mb = 0;
+    bench::preset(&mut config);
+    let concurrency = config.concurrency;
+    let requests = if config.iteration > 1 { config.iteration } else { CONCURRENT_REQUESTS };
+    println!("Calibrating against an in-process server at {} ({})", address, config.describe());
+
+    let sequential = RunConfig { iteration: SEQUENTIAL_REQUESTS, concurrency: 1, ..config.clone() };
+    let concurrent = RunConfig { iteration: requests, ..config };
+    let (sequential, concurrent) = match measure(sequential).and_then(|s| measure(concurrent).map(|c| (s, c))) {
+        Ok(results) => results,
+        Err(logs) => {
+            eprintln!("Calibration run did not complete cleanly:");
+            for line in logs {
+                eprintln!("  {}", line);
+            }
+            return 1;
+        }
+    };
+
+    let mut latency: Vec<Duration> = sequential.iter().map(|r| r.latency).collect();
+    latency.sort();
+    let overhead = latency.iter().sum::<Duration>() / latency.len() as u32;
+    let took = elapsed(&concurrent);
+    let ceiling = concurrent.len() as f64 / took.as_secs_f64();
+    println!(
+        "Per-request overhead: avg {} p50 {} p99 {} ({} sequential requests)",
+        format_ms(overhead),
+        format_ms(stats::percentile(&latency, 50.0)),
+        format_ms(stats::percentile(&latency, 99.0)),
+        sequential.len()
+    );
+    println!("Generator ceiling: {:.2} req/s with {} in flight ({} requests in {:.2}s)", ceiling, concurrency, concurrent.len(), took.as_secs_f64());
+    println!(
+        "Results near {:.0} req/s, or latencies close to {}, are bound by this client rather than the target",
+        ceiling,
+        format_ms(overhead)
+    );
+    0
+}
@@ -0,0 +1,125 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use reqwest::{Client, Url};
+
+use crate::{
+    config::RunConfig,
+    stats::{self, format_ms},
+    tls,
+    utils::RequestTemplate,
+    AppState,
+};
+
+// 부하를 걸기 전에 재는 기준 표본 수
+const BASELINE_PROBES: usize = 5;
+
+// 부하와 별개로 낮은 속도로 보내는 카나리 요청 (헬스 체크, 제어 트래픽 흉내)
+// 자기 연결을 따로 써서 부하 요청의 연결 풀 대기와 섞이지 않는다
+pub struct Canary {
+    pub url: Url,
+    interval: Duration,
+    client: Client,
+}
+
+// 카나리 요청 하나의 결과
+#[derive(Clone)]
+pub struct CanarySample {
+    pub at: Instant,
+    pub latency: Duration,
+    // 실패 이유 (2xx 가 아니거나 전송 실패)
+    pub error: Option<String>,
+    // 부하를 걸기 전에 잰 기준 표본
+    pub baseline: bool,
+}
+
+impl Canary {
+    // canary_path 는 대상 URL 기준 경로 또는 전체 URL. 비어 있으면 None
+    pub fn new(config: &RunConfig, target: &Url, tls: &tls::Settings, timeout: Duration) -> Result<Option<Self>, String> {
+        let path = config.canary_path.trim();
+        if path.is_empty() {
+            return Ok(None);
+        }
+        let url = target.join(path).map_err(|e| format!("invalid canary path '{}': {}", path, e))?;
+        if config.canary_rps <= 0.0 {
+            return Err(String::from("canary rate must be above 0 rps"));
+        }
+        let builder = tls.apply(Client::builder().timeout(timeout)).map_err(|e| e.to_string())?;
+        let client = builder.build().map_err(|e| e.to_string())?;
+        Ok(Some(Self { url, interval: Duration::from_secs_f64(1.0 / config.canary_rps), client }))
+    }
+
+    async fn probe(&self, baseline: bool) -> CanarySample {
+        let at = Instant::now();
+        let error = match self.client.get(self.url.clone()).send().await {
+            Ok(response) if response.status().is_success() => response.bytes().await.err().map(|e| e.to_string()),
+            Ok(response) => Some(format!("HTTP {}", response.status())),
+            Err(e) => Some(e.to_string()),
+        };
+        CanarySample { at, latency: at.elapsed(), error, baseline }
+    }
+
+    // 부하 전에 연달아 보내 기준 지연을 잰다 (연결을 맺는 첫 요청은 기준에서 뺀다)
+    pub async fn baseline(&self) -> Vec<CanarySample> {
+        self.probe(true).await;
+        let mut samples = Vec::with_capacity(BASELINE_PROBES);
+        for _ in 0..BASELINE_PROBES {
+            samples.push(self.probe(true).await);
+        }
+        samples
+    }
+}
+
+// 실행 동안 정해진 속도로 카나리 요청을 보낸다 (실패만 로그에 남김)
+pub async fn run(template: Arc<RequestTemplate>, state: Arc<Mutex<AppState>>) {
+    let Some(canary) = template.canary.as_ref() else {
+        return;
+    };
+    while state.lock().unwrap().running {
+        let sample = canary.probe(false).await;
+        let wait = canary.interval.saturating_sub(sample.at.elapsed());
+        {
+            let mut state = state.lock().unwrap();
+            if let Some(e) = &sample.error {
+                state.add_log(&format!("Canary {} failed: {}", canary.url.path(), e));
+            }
+            state.canaries.push(sample);
+        }
+        tokio::time::sleep(wait).await;
+    }
+}
+
+// 기준 대비 부하 중 카나리 지연이 얼마나 늘었는지
+pub fn summarize(canary: &Canary, samples: &[CanarySample], percentiles: &[f64]) -> Vec<String> {
+    let (baseline, loaded): (Vec<&CanarySample>, Vec<&CanarySample>) = samples.iter().partition(|s| s.baseline);
+    if loaded.is_empty() {
+        return Vec::new();
+    }
+    let sorted = |samples: &[&CanarySample]| {
+        let mut latency: Vec<Duration> = samples.iter().filter(|s| s.error.is_none()).map(|s| s.latency).collect();
+        latency.sort();
+        latency
+    };
+    let (idle, busy) = (sorted(&baseline), sorted(&loaded));
+    let failed = loaded.iter().filter(|s| s.error.is_some()).count();
+    let mut summary = format!(
+        "Canary {}: {} probes under load ({} failed, {:.1}%) | {} max {}",
+        canary.url.path(),
+        loaded.len(),
+        failed,
+        failed as f64 * 100.0 / loaded.len() as f64,
+        stats::format_percentiles(&busy, percentiles),
+        format_ms(busy.last().copied().unwrap_or_default())
+    );
+    if !idle.is_empty() && !busy.is_empty() {
+        let (idle_p50, busy_p50) = (stats::percentile(&idle, 50.0), stats::percentile(&busy, 50.0));
+        summary.push_str(&format!(
+            " | idle baseline p50 {}, under load x{:.1}",
+            format_ms(idle_p50),
+            busy_p50.as_secs_f64() / idle_p50.as_secs_f64().max(f64::EPSILON)
+        ));
+    }
+    vec![summary]
+}
@@ -0,0 +1,246 @@
+use std::sync::Arc;
+
+use crate::{
+    config::{RunConfig, CONFIG_FILE},
+    echo::{self, Chaos},
+    events::{Bus, Event},
+    profiles, queue, replay::Replay, sinks, AppState,
+};
+
+// 입력창에 반영할 프로필 변경 (불러오거나 저장한 프로필이 지금 프로필이 된다)
+pub enum ProfileChange {
+    Loaded(String, Box<RunConfig>),
+    Saved(String),
+    Deleted(String),
+}
+
+// 명령 팔레트(:)에서 입력한 명령 실행. 결과는 로그에 남긴다
+// current 는 지금 입력값/옵션으로 만든 실행 설정 (큐에 단계를 추가하거나 프로필로 저장할 때 사용)
+pub fn run(state: &mut AppState, bus: &Bus, input: &str, current: Result<RunConfig, String>) -> Option<ProfileChange> {
+    let input = input.trim();
+    let mut words = input.split_whitespace();
+    let command = words.next()?;
+    state.add_log(&format!(":{}", input));
+
+    match command {
+        // "upstream Xy12ab" 또는 "which upstream served request Xy12ab?"
+        "upstream" | "which" => {
+            let Some(id) = words.last().map(|w| w.trim_end_matches('?')) else {
+                state.add_log("Usage: upstream <request id>");
+                return None;
+            };
+            let answer = match state.upstreams.get(id) {
+                Some(Some(upstream)) => format!("Request {} was served by {}", id, upstream),
+                Some(None) => format!("Request {} has no upstream header (check the upstream header option)", id),
+                None => format!("Request {} not found", id),
+            };
+            state.add_log(&answer);
+        }
+        "queue" => queue_command(state, bus, words.collect(), current),
+        "set" => set_command(state, bus, words.collect()),
+        "config" => config_command(state, words.collect(), current),
+        "profile" => return profile_command(state, words.collect(), current),
+        "chaos" => chaos_command(state, words.collect()),
+        "replay" => replay_command(state, words.collect()),
+        "help" => state.add_log("Commands: upstream <request id> | queue add [name] | queue list | queue clear | queue run | set delay|rps|burst|concurrency <value> | config save [file] | profile save|load|delete <name> | profile list | chaos <host:port> key=value...|off | replay <file> | help"),
+        _ => state.add_log(&format!("Unknown command '{}' (try 'help')", command)),
+    }
+    None
+}
+
+// 결과 파일과 함께 저장한 재생 파일을 연다 (실행 중에는 불가)
+fn replay_command(state: &mut AppState, args: Vec<&str>) {
+    let [path] = args[..] else {
+        return state.add_log("Usage: replay <file> (saved next to exported results as <name>.replay.json)");
+    };
+    if state.running {
+        return state.add_log("Replay is available after the run finishes");
+    }
+    match Replay::load(path) {
+        Ok(replay) => state.pending_replay = Some(replay),
+        Err(e) => state.add_log(&format!("Replay: failed to open {}: {}", path, e)),
+    }
+}
+
+// 프로필: 지금 입력값/옵션을 이름 붙여 저장하고, 저장한 프로필을 입력창에 불러온다
+// 실행 중인 echo-server 의 장애 주입 설정을 바꾼다. 값은 여기서 먼저 검증하고 제어 경로로 보낸다 (결과는 전송 실패만 로그에 남는다)
+fn chaos_command(state: &mut AppState, args: Vec<&str>) {
+    let usage = format!("Usage: chaos <host:port> {}=<value>... | chaos <host:port> off", echo::CHAOS_KEYS.join("|"));
+    let Some((&addr, settings)) = args.split_first() else {
+        return state.add_log(&usage);
+    };
+    let mut update = serde_json::Map::new();
+    if settings == ["off"] {
+        // 기본값 (지연, 오류, 끊기 없음) 으로 되돌린다. 오류 상태 코드는 그대로 둔다
+        for (key, value) in [("error_rate", "0"), ("delay_ms", "0"), ("delay_percent", "100"), ("reset_rate", "0")] {
+            update.insert(key.to_owned(), value.into());
+        }
+    } else {
+        let mut check = Chaos::default();
+        for setting in settings {
+            let result = match setting.split_once('=') {
+                Some((key, value)) => check.apply(key, value).map(|()| update.insert(key.to_owned(), value.into())),
+                None => Err(format!("expected key=value, got '{}'", setting)),
+            };
+            if let Err(e) = result {
+                return state.add_log(&format!("Chaos: {}", e));
+            }
+        }
+    }
+    if update.is_empty() {
+        return state.add_log(&usage);
+    }
+
+    let body = serde_json::Value::Object(update).to_string();
+    state.add_log(&format!("Chaos: sending {} to {}", body, addr));
+    state.pending_posts.push(sinks::Post {
+        sink: format!("chaos {}", addr),
+        url: format!("http://{}{}", addr, echo::CHAOS_PATH),
+        content_type: "application/json",
+        body,
+    });
+}
+
+fn profile_command(state: &mut AppState, args: Vec<&str>, current: Result<RunConfig, String>) -> Option<ProfileChange> {
+    let result = match args[..] {
+        ["list"] => match profiles::names() {
+            Ok(names) if names.is_empty() => Ok(String::from("No profiles yet (save one with 'profile save <name>' or P)")),
+            Ok(names) => Ok(format!("Profiles: {}", names.join(", "))),
+            Err(e) => Err(e),
+        },
+        ["save", name] => match current.and_then(|config| profiles::save(name, &config)) {
+            Ok(path) => {
+                state.add_log(&format!("Profile '{}' saved to {}", name, path.display()));
+                return Some(ProfileChange::Saved(name.to_owned()));
+            }
+            Err(e) => Err(e),
+        },
+        ["load", name] => match profiles::load(name) {
+            Ok(config) => {
+                state.add_log(&format!("Profile '{}' loaded: {} | {}", name, config.dst_url, config.describe()));
+                return Some(ProfileChange::Loaded(name.to_owned(), Box::new(config)));
+            }
+            Err(e) => Err(e),
+        },
+        ["delete", name] => match profiles::delete(name) {
+            Ok(()) => {
+                state.add_log(&format!("Profile '{}' deleted", name));
+                return Some(ProfileChange::Deleted(name.to_owned()));
+            }
+            Err(e) => Err(e),
+        },
+        _ => Ok(String::from("Usage: profile save <name> | profile load <name> | profile delete <name> | profile list")),
+    };
+    match result {
+        Ok(line) => state.add_log(&line),
+        Err(e) => state.add_log(&format!("Profile: {}", e)),
+    }
+    None
+}
+
+// 실행 큐: 현재 설정을 단계로 추가하고, 쌓인 단계를 차례로 실행
+fn queue_command(state: &mut AppState, bus: &Bus, args: Vec<&str>, current: Result<RunConfig, String>) {
+    match args.first().copied() {
+        Some("add") => {
+            let config = match current {
+                Ok(config) => config,
+                Err(e) => {
+                    state.add_log(&format!("Invalid {}", e));
+                    return;
+                }
+            };
+            if config.probe_interval_ms > 0 {
+                state.add_log("Queue: probe mode runs until stopped and cannot be queued");
+                return;
+            }
+            let name = if args.len() > 1 { args[1..].join(" ") } else { format!("stage-{}", state.queue.len() + 1) };
+            state.add_log(&format!("Queue: added {} ({})", name, config.describe()));
+            state.queue.push(queue::Stage { name, config });
+        }
+        Some("list") => {
+            if state.queue.is_empty() {
+                state.add_log("Queue is empty");
+            }
+            let lines: Vec<String> = state.queue.iter().enumerate().map(|(i, stage)| format!("Queue {}. {}: {}", i + 1, stage.name, stage.config.describe())).collect();
+            for line in lines {
+                state.add_log(&line);
+            }
+        }
+        Some("clear") => {
+            state.queue.clear();
+            state.add_log("Queue cleared");
+        }
+        Some("run") => {
+            if state.running || state.queue_run.is_some() {
+                state.add_log("Queue: a run is already in progress");
+                return;
+            }
+            if state.queue.is_empty() {
+                state.add_log("Queue is empty (add stages with 'queue add [name]')");
+                return;
+            }
+            // 무인 실행이므로 사전 점검 없이 바로 시작. 큐는 다시 실행할 수 있도록 남겨 둔다
+            let run = queue::QueueRun::new(state.queue.clone());
+            let config = run.current().config.clone();
+            state.add_log(&format!("Queue: starting stage 1/{} {}", run.stages.len(), run.current().name));
+            state.queue_run = Some(run);
+            crate::engine::start_run(state, bus, config);
+        }
+        _ => state.add_log("Usage: queue add [name] | queue list | queue clear | queue run"),
+    }
+}
+
+// 지금 설정을 설정 파일(JSON)로 저장. 실행 중이면 set 으로 바꾼 값과 정해진 시드까지 담긴 실행 설정을 저장한다
+fn config_command(state: &mut AppState, args: Vec<&str>, current: Result<RunConfig, String>) {
+    let path = match args[..] {
+        ["save"] => CONFIG_FILE,
+        ["save", path] => path,
+        _ => {
+            state.add_log("Usage: config save [file]");
+            return;
+        }
+    };
+    let config = if state.running {
+        state.config.clone()
+    } else {
+        match current {
+            Ok(config) => config,
+            Err(e) => {
+                state.add_log(&format!("Invalid {}", e));
+                return;
+            }
+        }
+    };
+    match config.save(path) {
+        Ok(()) => state.add_log(&format!("Config saved to {} (check it with --validate-config {})", path, path)),
+        Err(e) => state.add_log(&format!("Failed to save config to {}: {}", path, e)),
+    }
+}
+
+// 실행 중인 부하 조절. 작업 스레드가 매 요청마다 설정을 다시 읽으므로 바로 반영된다
+fn set_command(state: &mut AppState, bus: &Bus, args: Vec<&str>) {
+    let [name, value] = args[..] else {
+        state.add_log("Usage: set delay <ms> | set rps <req/s> | set burst <n> | set concurrency <n>");
+        return;
+    };
+    if !state.running {
+        state.add_log("Set: no run in progress (edit the inputs or options before starting)");
+        return;
+    }
+    let mut config = state.config.clone();
+    let result = match name {
+        "delay" => value.parse::<u64>().map(|ms| config.delay_ms = ms).map_err(|_| format!("'{}' is not a valid delay", value)),
+        "rps" => config.apply_option("rps", value),
+        "burst" => config.apply_option("rps_burst", value),
+        "concurrency" => config.apply_option("concurrency", value),
+        _ => Err(format!("unknown parameter '{}' (delay/rps/burst/concurrency)", name)),
+    };
+    match result {
+        Ok(()) => {
+            state.add_log(&format!("Set: {} = {} ({})", name, value, config.describe()));
+            bus.publish(Event::ConfigChanged(Arc::new(config.clone())));
+            state.config = config;
+        }
+        Err(e) => state.add_log(&format!("Set: {}", e)),
+    }
+}
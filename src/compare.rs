@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use crate::stats::{format_ms, percentile, RequestRecord};
+
+// 유의 수준 (p 값이 이보다 작으면 차이가 있다고 판단)
+const ALPHA: f64 = 0.05;
+
+// A/B 비교 실행에서 요청을 받은 대상 (A: 대상 URL, B: 비교 URL)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    A,
+    B,
+}
+
+impl Side {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Side::A => "A",
+            Side::B => "B",
+        }
+    }
+}
+
+// 대상 하나의 지표. 지연은 성공한 요청만 사용
+#[derive(Clone, Default)]
+pub struct SideStats {
+    pub count: usize,
+    pub failed: usize,
+    pub avg: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+}
+
+impl SideStats {
+    pub fn error_rate(&self) -> f64 {
+        self.failed as f64 * 100.0 / self.count.max(1) as f64
+    }
+}
+
+fn latencies(records: &[RequestRecord], side: Side) -> Vec<Duration> {
+    let mut latency: Vec<Duration> = records.iter().filter(|r| r.side == Some(side) && r.success).map(|r| r.latency).collect();
+    latency.sort();
+    latency
+}
+
+pub fn side_stats(records: &[RequestRecord], side: Side) -> SideStats {
+    let latency = latencies(records, side);
+    SideStats {
+        count: records.iter().filter(|r| r.side == Some(side)).count(),
+        failed: records.iter().filter(|r| r.side == Some(side) && !r.success).count(),
+        avg: if latency.is_empty() { Duration::ZERO } else { latency.iter().sum::<Duration>() / latency.len() as u32 },
+        p50: percentile(&latency, 50.0),
+        p90: percentile(&latency, 90.0),
+        p99: percentile(&latency, 99.0),
+    }
+}
+
+// 표준정규분포 누적분포 (Abramowitz-Stegun 7.1.26 근사)
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
+
+fn two_sided_p(z: f64) -> f64 {
+    2.0 * (1.0 - normal_cdf(z.abs()))
+}
+
+// Mann-Whitney U 검정 (정규 근사, 동순위는 평균 순위). 표본이 부족하면 None
+fn mann_whitney(a: &[Duration], b: &[Duration]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let mut all: Vec<(Duration, bool)> = a.iter().map(|d| (*d, true)).chain(b.iter().map(|d| (*d, false))).collect();
+    all.sort_by_key(|(d, _)| *d);
+
+    let mut rank_sum_a = 0.0;
+    let mut i = 0;
+    while i < all.len() {
+        let mut j = i;
+        while j + 1 < all.len() && all[j + 1].0 == all[i].0 {
+            j += 1;
+        }
+        // 순위는 1부터, 같은 값은 평균 순위
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        rank_sum_a += rank * all[i..=j].iter().filter(|(_, is_a)| *is_a).count() as f64;
+        i = j + 1;
+    }
+
+    let (na, nb) = (a.len() as f64, b.len() as f64);
+    let u = rank_sum_a - na * (na + 1.0) / 2.0;
+    let mean = na * nb / 2.0;
+    let sd = (na * nb * (na + nb + 1.0) / 12.0).sqrt();
+    Some(two_sided_p((u - mean) / sd))
+}
+
+// 두 비율 z 검정 (오류율 비교)
+fn two_proportion(failed_a: usize, count_a: usize, failed_b: usize, count_b: usize) -> Option<f64> {
+    if count_a == 0 || count_b == 0 {
+        return None;
+    }
+    let (na, nb) = (count_a as f64, count_b as f64);
+    let pooled = (failed_a + failed_b) as f64 / (na + nb);
+    let se = (pooled * (1.0 - pooled) * (1.0 / na + 1.0 / nb)).sqrt();
+    if se == 0.0 {
+        return Some(1.0);
+    }
+    Some(two_sided_p((failed_a as f64 / na - failed_b as f64 / nb) / se))
+}
+
+fn verdict(p: Option<f64>) -> String {
+    match p {
+        Some(p) if p < ALPHA => format!("p={:.4}, significant", p),
+        Some(p) => format!("p={:.4}, not significant", p),
+        None => String::from("not enough samples"),
+    }
+}
+
+// B 가 A 보다 얼마나 느린지 (음수면 빠름)
+fn delta(a: Duration, b: Duration) -> String {
+    let diff = b.as_secs_f64() - a.as_secs_f64();
+    let percent = diff * 100.0 / a.as_secs_f64().max(f64::EPSILON);
+    format!("{}{:.3}ms ({:+.1}%)", if diff >= 0.0 { "+" } else { "-" }, diff.abs() * 1000.0, percent)
+}
+
+// 최종 비교 보고
+pub fn report(records: &[RequestRecord]) -> Vec<String> {
+    let (a, b) = (side_stats(records, Side::A), side_stats(records, Side::B));
+    let mut lines: Vec<String> = [(Side::A, &a), (Side::B, &b)]
+        .iter()
+        .map(|(side, s)| {
+            format!(
+                "A/B {}: {} requests ({} failed, {:.2}%) | avg {} p50 {} p90 {} p99 {}",
+                side.as_str(), s.count, s.failed, s.error_rate(), format_ms(s.avg), format_ms(s.p50), format_ms(s.p90), format_ms(s.p99)
+            )
+        })
+        .collect();
+    lines.push(format!(
+        "A/B latency (B vs A): avg {} p50 {} p99 {} | Mann-Whitney U {}",
+        delta(a.avg, b.avg), delta(a.p50, b.p50), delta(a.p99, b.p99),
+        verdict(mann_whitney(&latencies(records, Side::A), &latencies(records, Side::B)))
+    ));
+    lines.push(format!(
+        "A/B errors: A {:.2}% vs B {:.2}% | two-proportion z-test {}",
+        a.error_rate(), b.error_rate(), verdict(two_proportion(a.failed, a.count, b.failed, b.count))
+    ));
+    lines
+}
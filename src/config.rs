@@ -0,0 +1,814 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{alpn, deadline, fault, header_matrix, headers, idle, payload, priority, proxy_protocol, query, sinks, stats, success, transport, udp};
+
+// 한 번의 실행(Start ~ Done)에 사용되는 설정값
+// 필드 이름이 옵션 키이자 설정 파일(JSON)의 키 (TUI 옵션 창, --validate-config, 설정 파일이 같은 검증을 거친다)
+#[derive(Clone, Serialize)]
+pub struct RunConfig {
+    pub dst_url: String,
+    pub delay_ms: u64,
+    pub header_size_kb: usize,
+    pub protocol: String,
+    pub iteration: usize,
+    // 실행 마커 (0이면 주기 마커를 남기지 않음)
+    pub marker_interval_s: u64,
+    pub markers_file: String,
+    pub annotation_url: String,
+    pub annotation_token: String,
+    // 실행 완료/중단/첫 오류 알림 (Slack 호환 웹훅)
+    pub notify_url: String,
+    // 실패가 이 횟수에 도달하면 실행 중단 (0이면 사용 안 함)
+    pub abort_after_failures: usize,
+    // 시작 전 사전 점검 (DNS, TCP, TLS, 단일 요청)
+    pub preflight: bool,
+    // 요청마다 순환할 소스 주소/포트 (비어 있으면 OS 가 선택)
+    pub source_addrs: String,
+    pub source_ports: String,
+    // 새 연결마다 PROXY protocol 헤더 전송 (off/v1/v2) 과 헤더에 넣을 클라이언트 주소
+    pub proxy_protocol: String,
+    pub proxy_source: String,
+    // 단계별 타임아웃 (TLS 타임아웃을 따로 쓰려면 전송 계층을 사용)
+    pub connect_timeout_ms: u64,
+    pub tls_timeout_ms: u64,
+    pub request_timeout_ms: u64,
+    // 대상 TLS: 추가로 믿을 CA 묶음, mTLS 클라이언트 인증서와 키 (PEM), 인증서 검증 생략
+    pub tls_ca_file: String,
+    pub tls_client_cert: String,
+    pub tls_client_key: String,
+    pub tls_insecure: bool,
+    // 호스트가 v4/v6 를 모두 가질 때 사용할 주소 계열과 다음 주소로 넘어가기까지의 대기 (Happy Eyeballs)
+    pub address_family: String,
+    pub fallback_delay_ms: u64,
+    // 응답을 처리한 업스트림을 알려주는 응답 헤더 (쉼표로 여러 개, 먼저 있는 값 사용)
+    pub upstream_header: String,
+    // least-request 검증: 요청 일부에 백엔드 지연 헤더("이름: 값")를 붙이고 트래픽 이동을 주기적으로 보고
+    pub slow_header: String,
+    pub slow_percent: u64,
+    pub shift_window_s: u64,
+    // 0 보다 크면 반복 횟수 대신 이 간격으로 중지할 때까지 프로브하고 장애 구간을 보고
+    pub probe_interval_ms: u64,
+    // 경로 목록 파일이 있으면 요청마다 경로를 순환하며 기대 상태코드/클러스터 헤더를 검사
+    pub routes_file: String,
+    pub cluster_header: String,
+    // Envoy admin 주소 (http://host:9901)
+    pub admin_url: String,
+    // Envoy 접근 로그 (gRPC ALS) 를 받을 주소 (비어 있으면 사용 안 함). 요청 id 로 클라이언트 결과에 붙인다
+    pub als_listen: String,
+    // 읽기 전용 제어 API 주소 (비어 있으면 사용 안 함). 다른 곳의 TUI 가 attach 로 붙어 로그와 지표를 본다
+    pub control_listen: String,
+    // fault 주입 (off/headers/admin) 과 지연/abort 비율
+    pub fault_mode: String,
+    pub fault_delay_ms: u64,
+    pub fault_delay_percent: u64,
+    pub fault_abort_status: u16,
+    pub fault_abort_percent: u64,
+    // 응답이 지금까지 지연의 이 백분위를 넘기면 같은 요청을 한 번 더 보냄 (0이면 사용 안 함)
+    // 표본이 모이기 전에는 hedge_delay_ms 사용
+    pub hedge_percentile: u64,
+    pub hedge_delay_ms: u64,
+    // 요청별 데드라인 전달 (off/envoy/grpc) 과 데드라인 분포 (ms 또는 min-max)
+    // grpc 는 grpc-timeout 헤더만 붙인다. 본문은 gRPC 프레임이 아니므로 Envoy 가 gRPC 로 다루는 라우트에서만 의미가 있다
+    pub deadline_mode: String,
+    pub deadline_ms: String,
+    // 0 보다 크면 Delay 대신 공유 토큰 버킷으로 초당 요청 수를 맞춤 (TUI 에서는 지연 입력창의 Target RPS 모델)
+    pub rps: f64,
+    pub rps_burst: u64,
+    // 속도 모드에서 실행 시작부터 목표 속도까지 선형으로 올리는 시간 (초, 0이면 처음부터 목표 속도)
+    pub ramp_up_s: u64,
+    // 동시에 응답을 기다리는 요청 수 상한 (0이면 제한 없음). 상한에 닿으면 응답이 올 때까지 다음 요청을 미룸
+    pub concurrency: usize,
+    // 0 보다 크면 반복 횟수 대신 이 시간(초) 동안 실행
+    pub duration_s: u64,
+    // 비어 있지 않으면 같은 요청을 이 주소(B)에도 동시에 보내 대상 URL(A)과 비교
+    pub compare_url: String,
+    // 실행이 끝나면 요약과 구간별 집계(RPS, 오류, p50/p99)를 저장할 파일 (.json/.csv)
+    pub export_file: String,
+    pub export_interval_s: u64,
+    // 실행이 끝나면 요청별 결과 (시각, id, 상태, 지연, 업스트림, 오류) 와 요약을 저장할 파일 (.json/.csv)
+    pub results_file: String,
+    // 모든 로그를 이어 쓸 폴더 (시각을 붙인 파일 하나, 메모리의 3000 줄 제한과 상관없이 남는다. 비어 있으면 사용 안 함)
+    pub log_dir: String,
+    // 요약, 업스트림별 보고, 내보내기에 표시할 지연 백분위
+    pub percentiles: Vec<f64>,
+    // 성공으로 볼 상태코드 (2xx, 404, 400-499) 와 실패로 볼 응답 지연 (0이면 사용 안 함)
+    // 경로 목록에 기대 상태코드가 있는 경로는 그 값으로 판단
+    pub success_status: String,
+    pub slow_failure_ms: u64,
+    // 페이로드, 요청 id, 지연/데드라인 선택에 쓰는 난수 시드 (None 이면 실행마다 새로 정함)
+    pub seed: Option<u64>,
+    // 무작위 헤더/쿼리스트링 값의 문자 (alnum/visible/percent/utf8)
+    pub header_charset: String,
+    // 무작위 문자열 대신 헤더/쿼리스트링 값을 채울 사전 파일 (한 줄에 항목 하나)
+    pub payload_dictionary: String,
+    // 비정상 입력 (off/compressible/gzip-bomb/nested-json/long-header) 과 크기 (kb, nested-json 은 깊이)
+    // 위험 페이로드 옵션을 켜야만 전송
+    pub pathological_payload: String,
+    pub pathological_size: usize,
+    pub dangerous_payloads: bool,
+    // JSON 요청 본문 (직접 입력 또는 @파일) 과 grpc_json_transcoder 응답 검증 (JSON 본문, gRPC 상태 -> HTTP 상태 매핑)
+    // JSON 클라이언트 -> gRPC 업스트림 방향만 검증한다. gRPC 로 보내는 반대 방향은 메시지 스키마가 있어야 해서 지원하지 않는다
+    pub json_body: String,
+    pub grpc_transcode: bool,
+    // GraphQL 쿼리와 변수 템플릿 (직접 입력 또는 @파일, 변수의 {{id}} {{index}} {{random}} 은 요청마다 치환)
+    pub graphql_query: String,
+    pub graphql_variables: String,
+    // 요청마다 붙이는 쿼리 파라미터 템플릿 ({{id}} {{iter}} {{uuid}} {{random}} 을 요청마다 치환)
+    pub query_params: String,
+    // 0 보다 크면 롱폴링 모드: 업스트림이 이 시간(초) 동안 잡아 두는 요청으로 보고 일찍 끊긴 요청과 동시 유지 수를 집계
+    pub long_poll_s: u64,
+    // 경로 목록으로 여러 호스트를 대상으로 할 때 호스트별 동시 요청 수와 초당 요청 수 상한 (0이면 사용 안 함)
+    pub host_concurrency: usize,
+    pub host_rps: f64,
+    // 요청마다 DNS/연결/TLS/전송/대기/수신 시간을 재기 위해 전송 계층 사용 (인스펙터 워터폴)
+    pub phase_timing: bool,
+    // 연결 열림/TLS 협상/재사용/닫힘/리셋을 로그로 남긴다 (전송 계층으로 보낸다)
+    pub conn_events: bool,
+    // 구간 사이에 업스트림 하나의 트래픽 비율이 이 값(%p)보다 크게 바뀌면 경보 (0이면 사용 안 함)
+    pub share_alert_pct: f64,
+    pub share_alert_interval_s: u64,
+    // 같은 멱등성 키 헤더를 연속한 N 개 요청에 붙여 보내고 프록시/업스트림이 중복을 걸러내는지 검증 (헤더 이름이 비어 있으면 사용 안 함)
+    pub idempotency_header: String,
+    pub idempotency_repeat: usize,
+    // 요청마다 순서대로 바꿔 붙이는 우선순위 헤더 ("x-priority: high|low"), 우선순위별 지연/오류 보고
+    pub priority_header: String,
+    // 헤더 값 조합을 모두 돌려 보내고 조합별 업스트림/클러스터를 보고 ("x-version: v1|v2; x-region: us|eu")
+    pub header_matrix: String,
+    // tcp:// 대상의 연결마다 보내는 페이로드 크기(바이트)와 횟수, 에코 응답 검증 여부 (tcp_proxy 리스너)
+    pub tcp_payload_bytes: usize,
+    pub tcp_rounds: usize,
+    pub tcp_echo: bool,
+    // udp:// 대상의 패킷 크기(바이트, 앞 8바이트는 순번)와 초당 패킷 수, 에코 응답 검증 여부 (UDP proxy 리스너)
+    pub udp_packet_bytes: usize,
+    pub udp_pps: f64,
+    pub udp_echo: bool,
+    // 사전 점검에 이어 잘못된 프로토콜(평문 리스너에 TLS, TLS 리스너에 평문, http1 리스너에 h2 프리페이스)을 보내 반응을 분류
+    pub mismatch_probes: bool,
+    // 사전 점검에서 같은 주소로 SNI 마다 핸드셰이크해 받은 인증서와 라우트를 비교 (쉼표 구분, 비어 있으면 사용 안 함)
+    pub sni_list: String,
+    // TLS 핸드셰이크에서 제안할 ALPN 목록 (http1/h2/both), 협상 결과는 실행 종료 보고에 집계
+    pub alpn: String,
+    // 유휴 연결 실험: 반복마다 연결을 열어 요청 하나 뒤 유휴로 두고 상대가 끊을 때까지 관찰 (off/none/h2-ping/tcp)
+    pub idle_keepalive: String,
+    pub idle_keepalive_s: u64,
+    pub idle_limit_s: u64,
+    // 실행 동안 별도 h2 연결로 PING 을 보내 연결 수준 RTT 를 잴 주기 (0 이면 사용 안 함)
+    pub ping_interval_ms: u64,
+    // 부하와 별개로 낮은 속도로 보내는 카나리 요청의 경로 (대상 기준 경로 또는 전체 URL, 비어 있으면 사용 안 함) 와 속도
+    pub canary_path: String,
+    pub canary_rps: f64,
+    // 사전 점검에서 한 h2 연결의 동시 스트림을 이 수까지 늘려 REFUSED_STREAM 이 오는 한도를 찾는다 (0 이면 사용 안 함)
+    pub stream_limit_max: usize,
+    // h2 로 응답한 주소마다 SETTINGS 를 받아 로그와 인스펙터에 표시
+    pub h2_settings: bool,
+    // 모든 요청에 붙이는 Authorization 헤더 값 (비어 있으면 붙이지 않음)
+    pub auth_header: String,
+    // 모든 요청에 붙이는 사용자 헤더 ("name: value" 줄마다 하나, 헤더 편집 창에서 고친다)
+    pub custom_headers: Vec<String>,
+    // 요청마다 새 연결 대신 돌려 가며 쓰는 HTTP/2 연결 수. 요청이 연결마다 스트림으로 다중화된다 (0 이면 사용 안 함)
+    pub h2_connections: usize,
+    // 실행마다 클라이언트 하나를 만들어 연결을 재사용한다. 켜면 요청마다 새 클라이언트, 곧 새 연결 (idle timeout, 연결 변동 실험)
+    pub new_connection: bool,
+    // 결과 싱크 목록 (console, file:경로, prometheus:URL, statsd:호스트:포트, webhook:URL). 내보내기 파일과 알림 웹훅 옵션도 싱크로 더해진다
+    pub sinks: String,
+    // 요청별 결과를 남길 최근 시간(초)과 결과 보관 메모리 상한(MB). 넘은 오래된 결과는 구간 집계로 합친다 (0이면 사용 안 함)
+    pub raw_window_s: u64,
+    pub memory_budget_mb: u64,
+    // wrk 형식 벤치마크: 연결을 미리 맺고 반복 횟수만큼 간격 없이 동시 요청 수를 채워 보낸 뒤 요약 (bench::preset)
+    pub benchmark: bool,
+    // 요청 메서드 (GET/POST/PUT) 와 정해진 크기의 본문 (kb, 0이면 없음), 본문 내용 (text/json/binary). 헤더 크기와는 따로 정한다
+    pub method: String,
+    pub body_size_kb: usize,
+    pub body_type: String,
+    // 색 없이 글자 표시로 그리는 TUI (실행에는 영향 없음, 시작할 때 --no-color 나 NO_COLOR 로도 켠다)
+    pub plain_ui: bool,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            dst_url: String::from(""),
+            delay_ms: 100,
+            header_size_kb: 1,
+            protocol: "queryString".to_owned(),
+            iteration: 1,
+            marker_interval_s: 0,
+            markers_file: String::new(),
+            annotation_url: String::new(),
+            annotation_token: String::new(),
+            notify_url: String::new(),
+            abort_after_failures: 0,
+            preflight: true,
+            source_addrs: String::new(),
+            source_ports: String::new(),
+            proxy_protocol: String::from("off"),
+            proxy_source: String::new(),
+            connect_timeout_ms: 10000,
+            tls_timeout_ms: 0,
+            request_timeout_ms: 30000,
+            tls_ca_file: String::new(),
+            tls_client_cert: String::new(),
+            tls_client_key: String::new(),
+            tls_insecure: false,
+            address_family: String::from("auto"),
+            fallback_delay_ms: transport::DEFAULT_FALLBACK_DELAY_MS,
+            upstream_header: String::from("x-upstream-host"),
+            slow_header: String::new(),
+            slow_percent: 0,
+            shift_window_s: 5,
+            probe_interval_ms: 0,
+            routes_file: String::new(),
+            cluster_header: String::from("x-envoy-cluster"),
+            admin_url: String::new(),
+            als_listen: String::new(),
+            control_listen: String::new(),
+            fault_mode: String::from("off"),
+            fault_delay_ms: 0,
+            fault_delay_percent: 0,
+            fault_abort_status: 0,
+            fault_abort_percent: 0,
+            hedge_percentile: 0,
+            hedge_delay_ms: 100,
+            deadline_mode: String::from("off"),
+            deadline_ms: String::from("1000"),
+            rps: 0.0,
+            rps_burst: 1,
+            ramp_up_s: 0,
+            duration_s: 0,
+            compare_url: String::new(),
+            export_file: String::new(),
+            export_interval_s: 1,
+            results_file: String::new(),
+            log_dir: String::new(),
+            percentiles: vec![50.0, 99.0],
+            success_status: String::from("2xx"),
+            slow_failure_ms: 0,
+            seed: None,
+            header_charset: String::from("alnum"),
+            payload_dictionary: String::new(),
+            pathological_payload: String::from("off"),
+            pathological_size: 1024,
+            dangerous_payloads: false,
+            json_body: String::new(),
+            grpc_transcode: false,
+            graphql_query: String::new(),
+            graphql_variables: String::new(),
+            query_params: String::new(),
+            long_poll_s: 0,
+            concurrency: 0,
+            host_concurrency: 0,
+            host_rps: 0.0,
+            phase_timing: false,
+            conn_events: false,
+            share_alert_pct: 0.0,
+            share_alert_interval_s: 5,
+            idempotency_header: String::new(),
+            idempotency_repeat: 2,
+            priority_header: String::new(),
+            header_matrix: String::new(),
+            tcp_payload_bytes: 1024,
+            tcp_rounds: 10,
+            tcp_echo: true,
+            udp_packet_bytes: 512,
+            udp_pps: 100.0,
+            udp_echo: true,
+            mismatch_probes: false,
+            sni_list: String::new(),
+            alpn: String::from("http1"),
+            idle_keepalive: String::from("off"),
+            idle_keepalive_s: 10,
+            idle_limit_s: 300,
+            ping_interval_ms: 0,
+            canary_path: String::new(),
+            canary_rps: 1.0,
+            stream_limit_max: 0,
+            h2_settings: false,
+            auth_header: String::new(),
+            custom_headers: Vec::new(),
+            h2_connections: 0,
+            new_connection: false,
+            sinks: String::new(),
+            raw_window_s: 0,
+            memory_budget_mb: 0,
+            benchmark: false,
+            method: String::from("POST"),
+            body_size_kb: 0,
+            body_type: String::from("text"),
+            plain_ui: false,
+        }
+    }
+}
+
+// 페이로드를 싣는 방식 (입력 칸에서 순환)
+pub const PROTOCOLS: [&str; 2] = ["queryString", "headerKey"];
+pub const METHODS: [&str; 3] = ["GET", "POST", "PUT"];
+
+// 옵션 창에 표시되는 고급 옵션 목록 (키, 이름, 기본값)
+pub const OPTIONS: &[(&str, &str, &str)] = &[
+    ("marker_interval_s", "Marker interval (s)", "0"),
+    ("markers_file", "Markers file (JSONL)", ""),
+    ("annotation_url", "Marker webhook URL", ""),
+    ("annotation_token", "Marker webhook token", ""),
+    ("notify_url", "Notify webhook URL (Slack)", ""),
+    ("abort_after_failures", "Abort after N failures", "0"),
+    ("preflight", "Pre-flight check (on/off)", "on"),
+    ("source_addrs", "Source addresses (a,b,...)", ""),
+    ("source_ports", "Source port range (from-to)", ""),
+    ("proxy_protocol", "PROXY protocol (off/v1/v2)", "off"),
+    ("proxy_source", "PROXY source (ip:port/random)", ""),
+    ("connect_timeout_ms", "Connect timeout (ms)", "10000"),
+    ("tls_timeout_ms", "TLS handshake timeout (ms, 0=off)", "0"),
+    ("request_timeout_ms", "Request timeout (ms)", "30000"),
+    ("tls_ca_file", "TLS CA bundle (PEM file)", ""),
+    ("tls_client_cert", "TLS client certificate (PEM file)", ""),
+    ("tls_client_key", "TLS client key (PKCS#8 PEM file)", ""),
+    ("tls_insecure", "Skip TLS verification (on/off)", "off"),
+    ("address_family", "Address family (auto/v4/v6/prefer-v4/prefer-v6)", "auto"),
+    ("fallback_delay_ms", "Happy Eyeballs fallback delay (ms, 0=sequential)", "300"),
+    ("upstream_header", "Upstream response header(s) (a,b,...)", "x-upstream-host"),
+    ("slow_header", "Slow request header (name: value)", ""),
+    ("slow_percent", "Slow request ratio (%)", "0"),
+    ("shift_window_s", "Least-request report window (s)", "5"),
+    ("probe_interval_ms", "Continuous probe interval (ms, 0=off)", "0"),
+    ("routes_file", "Routes file (path status [cluster])", ""),
+    ("cluster_header", "Cluster response header", "x-envoy-cluster"),
+    ("admin_url", "Envoy admin URL", ""),
+    ("als_listen", "Envoy ALS gRPC listen address (ip:port, empty=off)", ""),
+    ("control_listen", "Read-only control API listen address (ip:port, empty=off)", ""),
+    ("fault_mode", "Fault injection (off/headers/admin)", "off"),
+    ("fault_delay_ms", "Fault delay (ms)", "0"),
+    ("fault_delay_percent", "Fault delay ratio (%)", "0"),
+    ("fault_abort_status", "Fault abort status (0=off)", "0"),
+    ("fault_abort_percent", "Fault abort ratio (%)", "0"),
+    ("hedge_percentile", "Hedge after latency percentile (0=off)", "0"),
+    ("hedge_delay_ms", "Initial hedge delay (ms)", "100"),
+    ("deadline_mode", "Deadline header (off/envoy/grpc)", "off"),
+    ("deadline_ms", "Deadline (ms or min-max)", "1000"),
+    ("rps_burst", "Rate limit burst", "1"),
+    ("ramp_up_s", "Rate ramp-up (s, 0=off)", "0"),
+    ("duration_s", "Run duration (s, 0=use iterations)", "0"),
+    ("compare_url", "A/B compare URL (B target)", ""),
+    ("export_file", "Export file (.json/.csv)", ""),
+    ("export_interval_s", "Export interval (s)", "1"),
+    ("results_file", "Per-request results file (.json/.csv)", ""),
+    ("log_dir", "Tee all logs to a timestamped file in this folder (empty=off)", ""),
+    ("percentiles", "Report percentiles (a,b,...)", "50,99"),
+    ("success_status", "Success status (2xx,404,400-499)", "2xx"),
+    ("slow_failure_ms", "Fail responses slower than (ms, 0=off)", "0"),
+    ("seed", "Random seed (empty=new per run)", ""),
+    ("header_charset", "Random value charset (alnum/visible/percent/utf8)", "alnum"),
+    ("payload_dictionary", "Payload dictionary file (one entry per line)", ""),
+    ("pathological_payload", "Pathological payload (off/compressible/gzip-bomb/nested-json/long-header)", "off"),
+    ("pathological_size", "Pathological size (kb, depth for nested-json)", "1024"),
+    ("dangerous_payloads", "Allow dangerous payloads (on/off)", "off"),
+    ("json_body", "JSON request body (inline or @file)", ""),
+    ("grpc_transcode", "gRPC-JSON transcoding check, JSON->gRPC only (on/off)", "off"),
+    ("graphql_query", "GraphQL query (inline or @file)", ""),
+    ("graphql_variables", "GraphQL variables ({{id}} {{index}} {{random}})", ""),
+    ("query_params", "Templated query params (user={{uuid}}&n={{iter}})", ""),
+    ("long_poll_s", "Long-poll hold time (s, 0=off)", "0"),
+    ("host_concurrency", "Per-host concurrency cap (0=off)", "0"),
+    ("host_rps", "Per-host rate cap (req/s, 0=off)", "0"),
+    ("phase_timing", "Per-phase timing for the inspector (on/off)", "off"),
+    ("conn_events", "Log connection events (on/off, f on the log filters them)", "off"),
+    ("share_alert_pct", "Upstream share change alert (% pts, 0=off)", "0"),
+    ("share_alert_interval_s", "Upstream share alert interval (s)", "5"),
+    ("idempotency_header", "Idempotency key header (empty=off)", ""),
+    ("idempotency_repeat", "Requests per idempotency key", "2"),
+    ("priority_header", "Priority header sweep (name: v1|v2|...)", ""),
+    ("header_matrix", "Header routing matrix (name: v1|v2; name: a|b)", ""),
+    ("tcp_payload_bytes", "TCP payload size per round (bytes)", "1024"),
+    ("tcp_rounds", "TCP payload rounds per connection", "10"),
+    ("tcp_echo", "TCP expect echoed payload (on/off)", "on"),
+    ("udp_packet_bytes", "UDP packet size (bytes, 8-65507)", "512"),
+    ("udp_pps", "UDP packets per second (0=use delay/rps)", "100"),
+    ("udp_echo", "UDP expect echoed packets (on/off)", "on"),
+    ("mismatch_probes", "Protocol mismatch probes in pre-flight (on/off)", "off"),
+    ("sni_list", "SNI routing matrix in pre-flight (a,b,...)", ""),
+    ("alpn", "ALPN offer (http1/h2/both)", "http1"),
+    ("idle_keepalive", "Idle connection probe keepalive (off/none/h2-ping/tcp)", "off"),
+    ("idle_keepalive_s", "Idle probe keepalive interval (s)", "10"),
+    ("idle_limit_s", "Idle probe max hold time per connection (s)", "300"),
+    ("ping_interval_ms", "HTTP/2 PING RTT sample interval (ms, 0=off)", "0"),
+    ("canary_path", "Canary probe path or URL during runs (empty=off)", ""),
+    ("canary_rps", "Canary probe rate (rps)", "1"),
+    ("stream_limit_max", "Concurrent stream limit probe in pre-flight (max streams, 0=off)", "0"),
+    ("h2_settings", "Capture HTTP/2 SETTINGS per address (on/off)", "off"),
+    ("auth_header", "Authorization header (e.g. Bearer <token>, empty=off)", ""),
+    ("h2_connections", "Shared HTTP/2 connections (0=off)", "0"),
+    ("new_connection", "New connection per request (on/off)", "off"),
+    ("sinks", "Result sinks (console,file:path,results:path,prometheus:url,statsd:host:port,webhook:url)", ""),
+    ("raw_window_s", "Keep per-request results for the last (s, 0=whole run)", "0"),
+    ("memory_budget_mb", "Results memory budget (MB, 0=unlimited)", "0"),
+    ("benchmark", "Benchmark: warm connections, iterations as fast as concurrency allows (on/off)", "off"),
+    ("method", "HTTP method (GET/POST/PUT)", "POST"),
+    ("body_size_kb", "Request body size (kb, 0=none)", "0"),
+    ("body_type", "Request body content (text/json/binary)", "text"),
+    ("plain_ui", "Monochrome UI with text state markers (on/off)", "off"),
+];
+
+impl RunConfig {
+    pub fn fault(&self) -> Result<fault::FaultConfig, String> {
+        Ok(fault::FaultConfig {
+            mode: fault::parse_mode(&self.fault_mode)?,
+            delay_ms: self.fault_delay_ms,
+            delay_percent: self.fault_delay_percent,
+            abort_status: self.fault_abort_status,
+            abort_percent: self.fault_abort_percent,
+        })
+    }
+
+    // 실행 시작 로그와 실행 큐 목록에 쓰는 설정 설명
+    pub fn describe(&self) -> String {
+        let mut suffix = String::new();
+        if self.concurrency > 0 {
+            suffix.push_str(&format!(", Concurrency {}", self.concurrency));
+        }
+        if !self.compare_url.is_empty() {
+            suffix.push_str(&format!(", A/B vs {}", self.compare_url));
+        }
+        if self.h2_connections > 0 {
+            suffix.push_str(&format!(", {} over {} shared connection(s)", self.alpn, self.h2_connections));
+        }
+        if self.new_connection {
+            suffix.push_str(", new connection per request");
+        }
+        if !self.tls_client_cert.is_empty() {
+            suffix.push_str(", mTLS");
+        }
+        if let Ok(Some(matrix)) = header_matrix::parse(&self.header_matrix) {
+            suffix.push_str(&format!(", header matrix of {} combination(s)", matrix.combinations()));
+        }
+        if !self.canary_path.is_empty() {
+            suffix.push_str(&format!(", canary {} at {}rps", self.canary_path, self.canary_rps));
+        }
+        if !self.query_params.is_empty() {
+            suffix.push_str(", templated query");
+        }
+        if self.header_charset != "alnum" {
+            suffix.push_str(&format!(", {} values", self.header_charset));
+        }
+        if !self.custom_headers.is_empty() {
+            suffix.push_str(&format!(", {} custom header(s)", self.custom_headers.len()));
+        }
+        if self.tls_insecure {
+            suffix.push_str(", TLS verification off");
+        }
+        if self.benchmark {
+            suffix.push_str(", Benchmark");
+        }
+        if self.method != "POST" || self.body_size_kb > 0 {
+            suffix.push_str(&format!(", {}", self.method));
+            if self.body_size_kb > 0 {
+                suffix.push_str(&format!(" {}kb {} body", self.body_size_kb, self.body_type));
+            }
+        }
+        let length = if self.duration_s > 0 { format!("Duration {}s", self.duration_s) } else { format!("Iter {}", self.iteration) };
+        if self.probe_interval_ms > 0 {
+            format!("Probe every {}ms until stopped, Header Size {}kb, Protocol {}{}", self.probe_interval_ms, self.header_size_kb, self.protocol, suffix)
+        } else if self.rps > 0.0 {
+            let ramp = if self.ramp_up_s > 0 { format!(", ramp-up {}s", self.ramp_up_s) } else { String::new() };
+            format!("Rate {}rps (burst {}{}), Header Size {}kb, Protocol {}, {}{}", self.rps, self.rps_burst, ramp, self.header_size_kb, self.protocol, length, suffix)
+        } else {
+            format!("Delay {}ms, Header Size {}kb, Protocol {}, {}{}", self.delay_ms, self.header_size_kb, self.protocol, length, suffix)
+        }
+    }
+
+    // 옵션 창의 문자열 값을 설정에 반영 (입력 칸의 대상, 지연, 헤더 크기, 프로토콜, 반복 횟수도 설정 파일에서 같은 방식으로)
+    pub fn apply_option(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match key {
+            "dst_url" => self.dst_url = value.to_owned(),
+            "delay_ms" => self.delay_ms = parse_num(key, value)?,
+            "header_size_kb" => self.header_size_kb = parse_num(key, value)?,
+            "protocol" => {
+                if !PROTOCOLS.contains(&value) {
+                    return Err(format!("option '{}': '{}' is not one of {}", key, value, PROTOCOLS.join("/")));
+                }
+                self.protocol = value.to_owned();
+            }
+            "iteration" => self.iteration = parse_num(key, value)?,
+            "marker_interval_s" => self.marker_interval_s = parse_num(key, value)?,
+            "markers_file" => self.markers_file = value.to_owned(),
+            "annotation_url" => self.annotation_url = value.to_owned(),
+            "annotation_token" => self.annotation_token = value.to_owned(),
+            "notify_url" => self.notify_url = value.to_owned(),
+            "abort_after_failures" => self.abort_after_failures = parse_num(key, value)?,
+            "preflight" => self.preflight = parse_bool(key, value)?,
+            "source_addrs" => self.source_addrs = value.to_owned(),
+            "source_ports" => self.source_ports = value.to_owned(),
+            "proxy_protocol" => {
+                proxy_protocol::parse_version(value)?;
+                self.proxy_protocol = value.to_owned();
+            }
+            "proxy_source" => {
+                proxy_protocol::parse_source(value)?;
+                self.proxy_source = value.to_owned();
+            }
+            "connect_timeout_ms" => self.connect_timeout_ms = parse_num(key, value)?,
+            "tls_timeout_ms" => self.tls_timeout_ms = parse_num(key, value)?,
+            "tls_ca_file" => self.tls_ca_file = value.to_owned(),
+            "tls_client_cert" => self.tls_client_cert = value.to_owned(),
+            "tls_client_key" => self.tls_client_key = value.to_owned(),
+            "tls_insecure" => self.tls_insecure = parse_bool(key, value)?,
+            "request_timeout_ms" => self.request_timeout_ms = parse_num(key, value)?,
+            "address_family" => {
+                transport::AddressFamily::parse(value)?;
+                self.address_family = value.to_owned();
+            }
+            "fallback_delay_ms" => self.fallback_delay_ms = parse_num(key, value)?,
+            "upstream_header" => self.upstream_header = value.to_owned(),
+            "slow_header" => self.slow_header = value.to_owned(),
+            "slow_percent" => self.slow_percent = parse_percent(key, value)?,
+            "shift_window_s" => self.shift_window_s = parse_num(key, value)?,
+            "probe_interval_ms" => self.probe_interval_ms = parse_num(key, value)?,
+            "routes_file" => self.routes_file = value.to_owned(),
+            "cluster_header" => self.cluster_header = value.to_owned(),
+            "admin_url" => self.admin_url = value.to_owned(),
+            "als_listen" => {
+                if !value.trim().is_empty() && value.trim().parse::<std::net::SocketAddr>().is_err() {
+                    return Err(format!("option '{}': '{}' is not an ip:port address", key, value));
+                }
+                self.als_listen = value.trim().to_owned();
+            }
+            "control_listen" => {
+                if !value.trim().is_empty() && value.trim().parse::<std::net::SocketAddr>().is_err() {
+                    return Err(format!("option '{}': '{}' is not an ip:port address", key, value));
+                }
+                self.control_listen = value.trim().to_owned();
+            }
+            "fault_mode" => {
+                fault::parse_mode(value)?;
+                self.fault_mode = value.to_owned();
+            }
+            "fault_delay_ms" => self.fault_delay_ms = parse_num(key, value)?,
+            "fault_delay_percent" => self.fault_delay_percent = parse_percent(key, value)?,
+            "fault_abort_status" => self.fault_abort_status = parse_num(key, value)?,
+            "fault_abort_percent" => self.fault_abort_percent = parse_percent(key, value)?,
+            "hedge_percentile" => self.hedge_percentile = parse_percent(key, value)?,
+            "hedge_delay_ms" => self.hedge_delay_ms = parse_num(key, value)?,
+            "deadline_mode" => {
+                deadline::parse_mode(value)?;
+                self.deadline_mode = value.to_owned();
+            }
+            "rps" => self.rps = parse_rate(key, value)?,
+            "rps_burst" => self.rps_burst = parse_num(key, value)?,
+            "ramp_up_s" => self.ramp_up_s = parse_num(key, value)?,
+            "duration_s" => self.duration_s = parse_num(key, value)?,
+            "compare_url" => self.compare_url = value.to_owned(),
+            "export_file" => self.export_file = value.to_owned(),
+            "results_file" => self.results_file = value.to_owned(),
+            "log_dir" => self.log_dir = value.trim().to_owned(),
+            "export_interval_s" => {
+                self.export_interval_s = parse_num(key, value)?;
+                if self.export_interval_s == 0 {
+                    return Err(format!("option '{}': interval must be at least 1 second", key));
+                }
+            }
+            "percentiles" => self.percentiles = stats::parse_percentiles(value).map_err(|e| format!("option '{}': {}", key, e))?,
+            "success_status" => {
+                success::parse_statuses(value).map_err(|e| format!("option '{}': {}", key, e))?;
+                self.success_status = value.to_owned();
+            }
+            "slow_failure_ms" => self.slow_failure_ms = parse_num(key, value)?,
+            "header_charset" => {
+                payload::parse_charset(value)?;
+                self.header_charset = value.to_owned();
+            }
+            "payload_dictionary" => self.payload_dictionary = value.to_owned(),
+            "pathological_payload" => {
+                payload::parse_pathological(value)?;
+                self.pathological_payload = value.to_owned();
+            }
+            "pathological_size" => self.pathological_size = parse_num(key, value)?,
+            "dangerous_payloads" => self.dangerous_payloads = parse_bool(key, value)?,
+            "json_body" => self.json_body = value.to_owned(),
+            "grpc_transcode" => self.grpc_transcode = parse_bool(key, value)?,
+            "graphql_query" => self.graphql_query = value.to_owned(),
+            "graphql_variables" => self.graphql_variables = value.to_owned(),
+            "query_params" => {
+                query::QueryTemplate::new(value)?;
+                self.query_params = value.trim().to_owned();
+            }
+            "long_poll_s" => self.long_poll_s = parse_num(key, value)?,
+            "concurrency" => self.concurrency = parse_num(key, value)?,
+            "host_concurrency" => self.host_concurrency = parse_num(key, value)?,
+            "host_rps" => self.host_rps = parse_rate(key, value)?,
+            "phase_timing" => self.phase_timing = parse_bool(key, value)?,
+            "conn_events" => self.conn_events = parse_bool(key, value)?,
+            "idempotency_header" => self.idempotency_header = value.to_owned(),
+            "idempotency_repeat" => {
+                self.idempotency_repeat = parse_num(key, value)?;
+                if self.idempotency_repeat == 0 {
+                    return Err(format!("option '{}': at least 1 request per key is required", key));
+                }
+            }
+            "priority_header" => {
+                priority::parse(value)?;
+                self.priority_header = value.to_owned();
+            }
+            "header_matrix" => {
+                header_matrix::parse(value)?;
+                self.header_matrix = value.to_owned();
+            }
+            "tcp_payload_bytes" => {
+                self.tcp_payload_bytes = parse_num(key, value)?;
+                if self.tcp_payload_bytes == 0 {
+                    return Err(format!("option '{}': payload must be at least 1 byte", key));
+                }
+            }
+            "tcp_rounds" => {
+                self.tcp_rounds = parse_num(key, value)?;
+                if self.tcp_rounds == 0 {
+                    return Err(format!("option '{}': at least 1 round is required", key));
+                }
+            }
+            "tcp_echo" => self.tcp_echo = parse_bool(key, value)?,
+            "udp_packet_bytes" => {
+                self.udp_packet_bytes = parse_num(key, value)?;
+                if !(8..=udp::MAX_PACKET_BYTES).contains(&self.udp_packet_bytes) {
+                    return Err(format!("option '{}': packet size must be 8-{} bytes", key, udp::MAX_PACKET_BYTES));
+                }
+            }
+            "udp_pps" => self.udp_pps = parse_rate(key, value)?,
+            "udp_echo" => self.udp_echo = parse_bool(key, value)?,
+            "mismatch_probes" => self.mismatch_probes = parse_bool(key, value)?,
+            "sni_list" => self.sni_list = value.to_owned(),
+            "alpn" => {
+                alpn::Offer::parse(value)?;
+                self.alpn = value.to_owned();
+            }
+            "idle_keepalive" => {
+                idle::Keepalive::parse(value)?;
+                self.idle_keepalive = value.to_owned();
+            }
+            "idle_keepalive_s" => {
+                self.idle_keepalive_s = parse_num(key, value)?;
+                if self.idle_keepalive_s == 0 {
+                    return Err(format!("option '{}': interval must be at least 1 second", key));
+                }
+            }
+            "ping_interval_ms" => self.ping_interval_ms = parse_num(key, value)?,
+            "canary_path" => self.canary_path = value.trim().to_owned(),
+            "canary_rps" => self.canary_rps = parse_rate(key, value)?,
+            "stream_limit_max" => self.stream_limit_max = parse_num(key, value)?,
+            "h2_settings" => self.h2_settings = parse_bool(key, value)?,
+            "auth_header" => self.auth_header = value.to_owned(),
+            // 줄바꿈으로 나눈 "name: value" 목록 (옵션 창이 아니라 헤더 편집 창과 설정 파일의 배열로 받는다)
+            "custom_headers" => {
+                let lines: Vec<String> = value.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_owned).collect();
+                for line in &lines {
+                    headers::parse(line).map_err(|e| format!("option '{}': {}", key, e))?;
+                }
+                self.custom_headers = lines;
+            }
+            "h2_connections" => self.h2_connections = parse_num(key, value)?,
+            "new_connection" => self.new_connection = parse_bool(key, value)?,
+            "sinks" => {
+                sinks::parse_list(value)?;
+                self.sinks = value.to_owned();
+            }
+            "raw_window_s" => self.raw_window_s = parse_num(key, value)?,
+            "memory_budget_mb" => self.memory_budget_mb = parse_num(key, value)?,
+            "benchmark" => self.benchmark = parse_bool(key, value)?,
+            "method" => {
+                let method = value.to_ascii_uppercase();
+                if !METHODS.contains(&method.as_str()) {
+                    return Err(format!("option '{}': '{}' is not one of {}", key, value, METHODS.join("/")));
+                }
+                self.method = method;
+            }
+            "body_size_kb" => self.body_size_kb = parse_num(key, value)?,
+            "body_type" => {
+                payload::parse_body_type(value)?;
+                self.body_type = value.to_owned();
+            }
+            "plain_ui" => self.plain_ui = parse_bool(key, value)?,
+            "idle_limit_s" => {
+                self.idle_limit_s = parse_num(key, value)?;
+                if self.idle_limit_s == 0 {
+                    return Err(format!("option '{}': limit must be at least 1 second", key));
+                }
+            }
+            "share_alert_pct" => self.share_alert_pct = parse_rate(key, value)?,
+            "share_alert_interval_s" => {
+                self.share_alert_interval_s = parse_num(key, value)?;
+                if self.share_alert_interval_s == 0 {
+                    return Err(format!("option '{}': interval must be at least 1 second", key));
+                }
+            }
+            "seed" => self.seed = if value.is_empty() { None } else { Some(parse_num(key, value)?) },
+            "deadline_ms" => {
+                deadline::Distribution::parse(value)?;
+                self.deadline_ms = value.to_owned();
+            }
+            _ => return Err(format!("unknown option '{}'", key)),
+        }
+        Ok(())
+    }
+}
+
+// 제어 API 로 내보내지 않는 옵션 (토큰, 인증 헤더, 웹훅 주소)
+pub const SECRET_OPTIONS: [&str; 3] = ["annotation_token", "auth_header", "notify_url"];
+
+// 설정 파일 기본 이름 (TUI 는 시작할 때 이 파일을 불러온다)
+pub const CONFIG_FILE: &str = "envoy-lb-client.json";
+
+// 설정 파일에서 틀린 필드 하나 (필드 이름, 이유)
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+impl RunConfig {
+    // JSON 설정 파일을 필드마다 옵션 창과 같은 검증으로 반영한다. 빠진 필드는 기본값, 틀린 필드는 모두 모아 돌려준다
+    pub fn from_json(text: &str) -> Result<Self, Vec<FieldError>> {
+        let error = |field: &str, reason: String| FieldError { field: field.to_owned(), reason };
+        let fields = match serde_json::from_str::<Value>(text) {
+            Ok(Value::Object(fields)) => fields,
+            Ok(_) => return Err(vec![error("(root)", String::from("expected a JSON object of option keys"))]),
+            Err(e) => return Err(vec![error("(root)", format!("invalid JSON: {}", e))]),
+        };
+        let mut config = Self::default();
+        let mut errors = Vec::new();
+        for (key, value) in &fields {
+            // 헤더 값에 쉼표가 들어갈 수 있어 사용자 헤더 배열은 줄바꿈으로 잇는다
+            let text = match (key.as_str(), value) {
+                ("custom_headers", Value::Array(items)) => items.iter().map(option_text).collect::<Result<Vec<_>, _>>().map(|lines| lines.join("\n")),
+                _ => option_text(value),
+            };
+            let result = text.and_then(|text| match config.apply_option(key, &text) {
+                Err(e) if e.starts_with("unknown option") => Err(String::from("unknown field")),
+                // 옵션 창 메시지의 "option 'key': " 접두어는 필드 이름과 겹치므로 뺀다
+                Err(e) => Err(e.strip_prefix(&format!("option '{}': ", key)).map(str::to_owned).unwrap_or(e)),
+                Ok(()) => Ok(()),
+            });
+            if let Err(reason) = result {
+                errors.push(error(key, reason));
+            }
+        }
+        if errors.is_empty() { Ok(config) } else { Err(errors) }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json + "\n").map_err(|e| e.to_string())
+    }
+
+    // 비밀 옵션을 비운 설정 JSON (인증 없이 읽을 수 있는 곳에 내보낼 때)
+    pub fn redacted(&self) -> Value {
+        let mut fields = serde_json::to_value(self).unwrap_or_default();
+        for key in SECRET_OPTIONS {
+            if let Some(value) = fields.get_mut(key) {
+                *value = Value::String(String::new());
+            }
+        }
+        fields
+    }
+
+    // 옵션 창에 보일 값 (설정 파일과 같은 변환)
+    pub fn option_value(&self, key: &str) -> String {
+        let fields = serde_json::to_value(self).unwrap_or_default();
+        fields.get(key).and_then(|value| option_text(value).ok()).unwrap_or_default()
+    }
+}
+
+// 설정 파일의 값을 옵션 창 문자열로 (on/off, 숫자, 목록은 쉼표 구분, null 은 빈 값)
+fn option_text(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Bool(on) => Ok(String::from(if *on { "on" } else { "off" })),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Null => Ok(String::new()),
+        Value::Array(items) => Ok(items.iter().map(option_text).collect::<Result<Vec<_>, _>>()?.join(",")),
+        Value::Object(_) => Err(String::from("expected a string, number, bool or list")),
+    }
+}
+
+fn parse_num<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("option '{}': '{}' is not a valid number", key, value))
+}
+
+fn parse_rate(key: &str, value: &str) -> Result<f64, String> {
+    let rate: f64 = parse_num(key, value)?;
+    if !rate.is_finite() || rate < 0.0 {
+        return Err(format!("option '{}': '{}' is not a valid rate", key, value));
+    }
+    Ok(rate)
+}
+
+fn parse_percent(key: &str, value: &str) -> Result<u64, String> {
+    match parse_num(key, value)? {
+        percent @ 0..=100 => Ok(percent),
+        _ => Err(format!("option '{}': '{}' is not between 0 and 100", key, value)),
+    }
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "on" | "true" | "yes" | "1" => Ok(true),
+        "off" | "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("option '{}': '{}' is not on/off", key, value)),
+    }
+}
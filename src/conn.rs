@@ -0,0 +1,101 @@
+use std::{net::SocketAddr, time::Duration};
+
+use bytes::Bytes;
+use h2::client::{Connection, SendRequest};
+use hyper::Request;
+use socket2::{SockRef, TcpKeepalive};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+    time::timeout,
+};
+use tokio_native_tls::TlsConnector;
+
+use crate::utils::RequestTemplate;
+
+// 실험용으로 직접 맺는 연결 (유휴 타임아웃, PING RTT, 동시 스트림 한도)
+// 요청 경로의 전송 계층과 달리 연결 하나를 오래 붙잡고 프레임 수준으로 다룬다
+pub trait Io: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Io for T {}
+
+// 대상에 TCP(https 면 TLS 까지) 연결. keepalive 가 있으면 그 주기로 TCP keepalive 를 켠다
+// TLS 연결이면 협상된 ALPN 도 돌려준다
+pub async fn open(template: &RequestTemplate, alpn: &[&str], keepalive: Option<Duration>) -> Result<(Box<dyn Io>, SocketAddr, Option<String>), String> {
+    let url = template.url();
+    let host = url.host_str().ok_or_else(|| format!("'{}' has no host", url))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs = tokio::net::lookup_host((host, port)).await.map_err(|e| format!("DNS lookup failed: {}", e))?.collect();
+    let addr = *template.family.order(addrs).first().ok_or_else(|| String::from("no address for the selected address family"))?;
+    let (io, alpn) = open_to(template, addr, alpn, keepalive).await?;
+    Ok((io, addr, alpn))
+}
+
+// 이미 아는 주소로 연결 (요청이 도착한 주소를 다시 볼 때)
+pub async fn open_to(template: &RequestTemplate, addr: SocketAddr, alpn: &[&str], keepalive: Option<Duration>) -> Result<(Box<dyn Io>, Option<String>), String> {
+    let url = template.url();
+    let host = url.host_str().unwrap_or_default();
+    let tcp = match timeout(template.connect_timeout, TcpStream::connect(addr)).await {
+        Ok(Ok(tcp)) => tcp,
+        Ok(Err(e)) => return Err(format!("connect to {} failed: {}", addr, e)),
+        Err(_) => return Err(format!("connect to {} timed out", addr)),
+    };
+    tcp.set_nodelay(true).map_err(|e| e.to_string())?;
+    if let Some(interval) = keepalive {
+        SockRef::from(&tcp).set_tcp_keepalive(&TcpKeepalive::new().with_time(interval).with_interval(interval)).map_err(|e| format!("failed to enable TCP keepalive: {}", e))?;
+    }
+    if url.scheme() != "https" {
+        return Ok((Box::new(tcp), None));
+    }
+
+    let connector = template.tls.connector().request_alpns(alpn).build().map_err(|e| e.to_string())?;
+    let tls = match timeout(template.tls_timeout, TlsConnector::from(connector).connect(host, tcp)).await {
+        Ok(Ok(tls)) => tls,
+        Ok(Err(e)) => return Err(format!("TLS handshake failed: {}", e)),
+        Err(_) => return Err(String::from("TLS handshake timed out")),
+    };
+    let negotiated = tls.get_ref().negotiated_alpn().ok().flatten().map(|p| String::from_utf8_lossy(&p).into_owned());
+    Ok((Box::new(tls), negotiated))
+}
+
+pub type H2Connection = Connection<Box<dyn Io>, Bytes>;
+
+// HTTP/2 로 쓸 연결 (TLS 는 ALPN h2 가 협상되어야 하고, 평문은 prior knowledge)
+pub async fn open_h2(template: &RequestTemplate) -> Result<(Box<dyn Io>, SocketAddr), String> {
+    let (io, remote, alpn) = open(template, &["h2"], None).await?;
+    require_h2(template, alpn.as_deref())?;
+    Ok((io, remote))
+}
+
+pub fn require_h2(template: &RequestTemplate, alpn: Option<&str>) -> Result<(), String> {
+    if template.url().scheme() == "https" && alpn != Some("h2") {
+        return Err(format!("h2 was not negotiated (ALPN {})", alpn.unwrap_or("none")));
+    }
+    Ok(())
+}
+
+// HTTP/2 연결
+pub async fn h2(template: &RequestTemplate) -> Result<(SendRequest<Bytes>, H2Connection, SocketAddr), String> {
+    let (io, remote) = open_h2(template).await?;
+    let (send, connection) = match timeout(template.request_timeout, h2::client::handshake(io)).await {
+        Ok(Ok(handshake)) => handshake,
+        Ok(Err(e)) => return Err(format!("HTTP/2 handshake failed: {}", e)),
+        Err(_) => return Err(String::from("HTTP/2 handshake timed out")),
+    };
+    Ok((send, connection, remote))
+}
+
+// 실험용 요청 (대상 URL 로 GET, 본문 없음)
+pub fn request(template: &RequestTemplate) -> Request<()> {
+    Request::get(template.url().as_str()).body(()).unwrap()
+}
+
+// h2 에러를 보고용 문구로 (GOAWAY/RST_STREAM 이면 에러 코드)
+pub fn describe_h2_error(e: &h2::Error) -> String {
+    match e.reason() {
+        Some(reason) if e.is_go_away() => format!("GOAWAY {:?}", reason),
+        Some(reason) if e.is_reset() => format!("RST_STREAM {:?}", reason),
+        _ if e.is_io() => format!("I/O error: {}", e),
+        _ => e.to_string(),
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{atomic::{AtomicU64, AtomicUsize, Ordering}, Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+
+use crate::stats::format_ms;
+
+// 연결 이벤트 로그의 접두어. 연결 id 는 요청 id 와 겹치지 않도록 c1, c2, ... 로 붙인다
+pub const PREFIX: &str = "Conn c";
+
+// ServerHello 를 찾는 데 필요한 만큼만 핸드셰이크 바이트를 모은다
+const HELLO_LIMIT: usize = 16 * 1024;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+// 로그 줄이 연결 이벤트인지 ("[12:00:00.000000] Conn c3 opened ...")
+pub fn is_event(log: &str) -> bool {
+    log.split_once("] ").is_some_and(|(_, message)| message.starts_with(PREFIX))
+}
+
+// 연결 이벤트 하나 (일어난 시각, 내용)
+pub type ConnEvent = (DateTime<Local>, String);
+
+// 전송 계층이 쌓아 두고 작업 스레드가 로그로 옮기는 연결 이벤트
+#[derive(Clone, Default)]
+pub struct ConnEvents(Arc<Mutex<Vec<ConnEvent>>>);
+
+impl ConnEvents {
+    fn push(&self, id: u64, event: String) {
+        self.0.lock().unwrap().push((Local::now(), format!("{}{} {}", PREFIX, id, event)));
+    }
+
+    pub fn drain(&self) -> Vec<ConnEvent> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+// 응답의 연결 정보에 실려 요청이 어느 연결을 몇 번째로 썼는지 남긴다
+#[derive(Clone)]
+pub struct Tracker {
+    pub id: u64,
+    requests: Arc<AtomicUsize>,
+    events: ConnEvents,
+}
+
+impl Tracker {
+    // 요청 하나가 이 연결로 응답을 받았다. 두 번째 요청부터 재사용으로 남긴다
+    pub fn served(&self, request_id: &str) {
+        let n = self.requests.fetch_add(1, Ordering::Relaxed) + 1;
+        if n > 1 {
+            self.events.push(self.id, format!("reused by request {} (request {} on this connection)", request_id, n));
+        }
+    }
+}
+
+// 우리가 닫기 전에 연결이 끝난 방식
+enum End {
+    // 상대가 먼저 닫음 (EOF)
+    Peer,
+    Reset(String),
+    // 핸드셰이크 실패 같은 그 밖의 에러
+    Failed(String),
+}
+
+// 연결 하나의 생애. 연결이 버려질 때 닫힘 또는 리셋을 남긴다
+pub struct Lifecycle {
+    pub tracker: Tracker,
+    opened: Instant,
+    ended: Option<End>,
+}
+
+impl Lifecycle {
+    pub fn open(events: &ConnEvents, local: SocketAddr, remote: SocketAddr, took: Duration) -> Self {
+        let tracker = Tracker { id: NEXT_ID.fetch_add(1, Ordering::Relaxed), requests: Arc::new(AtomicUsize::new(0)), events: events.clone() };
+        tracker.events.push(tracker.id, format!("opened {} -> {} in {}", local, remote, format_ms(took)));
+        Self { tracker, opened: Instant::now(), ended: None }
+    }
+
+    pub fn tls(&self, version: Option<&str>, alpn: Option<&str>, took: Duration) {
+        let event = format!("TLS {} negotiated (ALPN {}) in {}", version.unwrap_or("(unknown version)"), alpn.unwrap_or("none"), format_ms(took));
+        self.tracker.events.push(self.tracker.id, event);
+    }
+
+    // 핸드셰이크 실패처럼 닫기 전에 이유를 남길 때 (처음 남긴 이유만 쓴다)
+    pub fn fail(&mut self, reason: String) {
+        self.ended.get_or_insert(End::Failed(reason));
+    }
+
+    // 읽기 결과로 상대가 닫았는지 (0 바이트), 리셋됐는지 기록
+    pub fn observe_read(&mut self, poll: &Poll<io::Result<()>>, read: usize) {
+        match poll {
+            Poll::Ready(Ok(())) if read == 0 => {
+                self.ended.get_or_insert(End::Peer);
+            }
+            Poll::Ready(Err(e)) => self.observe_error(e),
+            _ => {}
+        }
+    }
+
+    pub fn observe_write(&mut self, poll: &Poll<io::Result<usize>>) {
+        if let Poll::Ready(Err(e)) = poll {
+            self.observe_error(e);
+        }
+    }
+
+    fn observe_error(&mut self, e: &io::Error) {
+        let end = match e.kind() {
+            io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted | io::ErrorKind::BrokenPipe => End::Reset(e.to_string()),
+            _ => End::Failed(e.to_string()),
+        };
+        self.ended.get_or_insert(end);
+    }
+}
+
+impl Drop for Lifecycle {
+    fn drop(&mut self) {
+        let summary = format!("after {} request(s), open {}", self.tracker.requests.load(Ordering::Relaxed), format_ms(self.opened.elapsed()));
+        let event = match self.ended.take() {
+            None => format!("closed {}", summary),
+            Some(End::Peer) => format!("closed by peer {}", summary),
+            Some(End::Reset(e)) => format!("reset {}: {}", summary, e),
+            Some(End::Failed(e)) => format!("failed {}: {}", summary, e),
+        };
+        self.tracker.events.push(self.tracker.id, event);
+    }
+}
+
+// TLS 핸드셰이크 동안 받은 바이트를 모아 두는 TCP 스트림 (native-tls 는 협상된 TLS 버전을 알려 주지 않아 ServerHello 를 직접 읽는다)
+pub struct Recorder {
+    tcp: TcpStream,
+    hello: Option<Vec<u8>>,
+}
+
+impl Recorder {
+    pub fn new(tcp: TcpStream, record: bool) -> Self {
+        Self { tcp, hello: record.then(Vec::new) }
+    }
+
+    // 핸드셰이크가 끝나면 모은 바이트를 넘기고 기록을 멈춘다
+    pub fn finish(&mut self) -> Vec<u8> {
+        self.hello.take().unwrap_or_default()
+    }
+}
+
+impl AsyncRead for Recorder {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut this.tcp).poll_read(cx, buf);
+        if let Some(hello) = this.hello.as_mut().filter(|hello| hello.len() < HELLO_LIMIT) {
+            hello.extend_from_slice(&buf.filled()[filled..]);
+        }
+        poll
+    }
+}
+
+impl AsyncWrite for Recorder {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().tcp).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().tcp).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().tcp).poll_shutdown(cx)
+    }
+}
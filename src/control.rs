@@ -0,0 +1,233 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    runtime::Runtime,
+    task::{AbortHandle, JoinSet},
+};
+
+use crate::{
+    config::RunConfig,
+    error_code::ErrorCode,
+    events::{Bus, Event, Subscription},
+    metrics::{LiveMetrics, Tracker},
+    AppState,
+};
+
+// 실행 상태를 읽는 경로. 다른 메서드는 405 로 거절한다 (제어 API 로는 실행을 바꿀 수 없다)
+pub const STATUS_PATH: &str = "/status";
+// 요청 머리의 최대 크기
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+// 관찰 모드가 상태를 다시 읽는 간격
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// 관찰 모드가 들고 있는 로그 줄 수 (AppState 와 같다)
+const MAX_LOGS: usize = 3000;
+
+// GET /status?since=<로그 번호> 응답. 로그는 since 번째 줄부터 보낸다 (앞쪽이 잘렸으면 남은 첫 줄부터)
+#[derive(Serialize, Deserialize)]
+pub struct Status {
+    pub running: bool,
+    // 실행 설정 (옵션 키 JSON, 설정 파일과 같은 모양). 비밀 옵션은 비워서 보낸다
+    pub config: Value,
+    pub logs: Vec<String>,
+    // 다음 요청의 since
+    pub next: usize,
+    pub metrics: Option<LiveMetrics>,
+    // 실패 코드별 개수 (E_SEND 같은 코드 -> 요청 수, 이번 실행 전체)
+    pub errors: BTreeMap<ErrorCode, usize>,
+}
+
+// 실행하는 동안 떠 있는 제어 API 수신기. 버리면 수신 작업과 연결을 모두 끝낸다
+pub struct Listener {
+    addr: SocketAddr,
+    task: AbortHandle,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+// control_listen 설정에 맞게 수신기를 켜거나 끈다 (주소가 같으면 이전 실행의 수신기를 그대로 쓴다)
+pub fn ensure(rt: &Runtime, state: &Arc<Mutex<AppState>>, bus: &Bus, listen: &str) -> Result<(), String> {
+    let addr = match listen.trim() {
+        "" => {
+            state.lock().unwrap().control_listener = None;
+            return Ok(());
+        }
+        addr => addr.parse::<SocketAddr>().map_err(|_| format!("invalid control listen address '{}'", addr))?,
+    };
+    if state.lock().unwrap().control_listener.as_ref().is_some_and(|l| l.addr == addr) {
+        return Ok(());
+    }
+    // 주소가 바뀌었으면 이전 수신기를 먼저 닫아 포트를 비운다
+    state.lock().unwrap().control_listener = None;
+    let listener = rt.block_on(TcpListener::bind(addr)).map_err(|e| format!("failed to listen for the control API on {}: {}", addr, e))?;
+    let task = rt.spawn(serve(listener, bus.subscribe("control API"), state.clone())).abort_handle();
+    let mut state = state.lock().unwrap();
+    state.control_listener = Some(Listener { addr, task });
+    state.add_log(&format!("Control: read-only API on http://{}{} (attach with 'attach {}')", addr, STATUS_PATH, addr));
+    // 인증이 없으므로 다른 호스트에서 닿는 주소면 알린다
+    if !addr.ip().is_loopback() {
+        state.add_log(&format!("Warning: the control API on {} has no authentication; anyone who can reach it can read this run's logs and metrics", addr));
+    }
+    Ok(())
+}
+
+// 버스에서 응답을 받아 지표를 쌓으면서 연결을 받는다
+async fn serve(listener: TcpListener, mut events: Subscription, state: Arc<Mutex<AppState>>) {
+    let live = Arc::new(Mutex::new(Tracker::default()));
+    // 수신 작업을 끝내면 연결 작업도 함께 끝난다
+    let mut connections = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                if let Ok((stream, _)) = accepted {
+                    connections.spawn(connection(stream, state.clone(), live.clone()));
+                }
+            }
+            event = events.recv() => match event {
+                Some(Event::RunStarted(_)) => *live.lock().unwrap() = Tracker::default(),
+                Some(Event::RequestFinished(record)) => live.lock().unwrap().add(&record),
+                Some(_) => {}
+                // 버스가 닫히면 워크스페이스도 닫힌 것이다
+                None => return,
+            },
+        }
+        while connections.try_join_next().is_some() {}
+    }
+}
+
+// 연결마다 요청 하나만 받고 닫는다
+async fn connection(mut stream: TcpStream, state: Arc<Mutex<AppState>>, live: Arc<Mutex<Tracker>>) {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => head.extend_from_slice(&chunk[..read]),
+        }
+        if head.len() > MAX_HEAD_BYTES {
+            return;
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut start = head.lines().next().unwrap_or_default().split(' ');
+    let (method, target) = (start.next().unwrap_or_default(), start.next().unwrap_or("/"));
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let (status, body) = match (method, path) {
+        ("GET", STATUS_PATH) => {
+            let since = query.split('&').find_map(|pair| pair.strip_prefix("since=")).and_then(|n| n.parse().ok()).unwrap_or(0);
+            let status = status(&state.lock().unwrap(), &mut live.lock().unwrap(), since);
+            ("200 OK", serde_json::to_string(&status).unwrap_or_default())
+        }
+        (_, STATUS_PATH) => ("405 Method Not Allowed", String::from("{\"error\":\"the control API is read-only\"}")),
+        _ => ("404 Not Found", format!("{{\"error\":\"try GET {}\"}}", STATUS_PATH)),
+    };
+    let response = format!("HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", status, body.len(), body);
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+fn status(state: &AppState, live: &mut Tracker, since: usize) -> Status {
+    // logs 에는 마지막 줄들만 남아 있다
+    let first = state.logged - state.logs.len();
+    let from = since.clamp(first, state.logged);
+    Status {
+        running: state.running,
+        config: state.config.redacted(),
+        logs: state.logs[from - first..].to_vec(),
+        next: state.logged,
+        metrics: live.live(state.sent_primary(), state.history.folded(), state.running.then(Instant::now), &state.config.percentiles),
+        errors: live.error_codes().clone(),
+    }
+}
+
+// 관찰 모드에서 읽어 온 실행 상태
+#[derive(Default)]
+pub struct Observed {
+    pub running: bool,
+    pub logs: Vec<String>,
+    pub metrics: Option<LiveMetrics>,
+    // 바뀐 실행 설정 (화면이 입력창에 반영하고 비운다)
+    pub config: Option<RunConfig>,
+    // 마지막으로 읽지 못한 이유
+    pub error: Option<String>,
+}
+
+// 다른 곳에서 띄운 실행을 읽기만 하는 관찰자. 버리면 읽기를 멈춘다
+pub struct Observer {
+    pub addr: String,
+    pub observed: Arc<Mutex<Observed>>,
+    task: AbortHandle,
+}
+
+impl Drop for Observer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Observer {
+    pub fn attach(rt: &Runtime, addr: &str) -> Self {
+        let observed = Arc::new(Mutex::new(Observed::default()));
+        let url = format!("http://{}{}", addr, STATUS_PATH);
+        let task = rt.spawn(poll(url, observed.clone())).abort_handle();
+        Self { addr: addr.to_owned(), observed, task }
+    }
+}
+
+// reqwest 오류 문구에는 URL 만 있어 맨 안쪽 원인을 보여 준다
+fn root_cause(err: &(dyn Error + 'static)) -> String {
+    let mut cause = err;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause.to_string()
+}
+
+async fn poll(url: String, observed: Arc<Mutex<Observed>>) {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(2)).build().unwrap_or_default();
+    let mut since = 0;
+    let mut config = Value::Null;
+    loop {
+        let result = match client.get(&url).query(&[("since", since)]).send().await.and_then(|r| r.error_for_status()) {
+            Ok(response) => response.json::<Status>().await.map_err(|e| e.to_string()),
+            Err(e) => Err(format!("disconnected ({})", root_cause(&e))),
+        };
+        {
+            let mut observed = observed.lock().unwrap();
+            match result {
+                Ok(status) => {
+                    since = status.next;
+                    observed.error = None;
+                    observed.running = status.running;
+                    observed.metrics = status.metrics;
+                    observed.logs.extend(status.logs);
+                    let excess = observed.logs.len().saturating_sub(MAX_LOGS);
+                    observed.logs.drain(0..excess);
+                    if status.config != config {
+                        observed.config = RunConfig::from_json(&status.config.to_string()).ok();
+                        config = status.config;
+                    }
+                }
+                // 실행한 쪽이 끝났거나 연결이 끊겼다
+                Err(e) => {
+                    observed.running = false;
+                    observed.error = Some(e);
+                }
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
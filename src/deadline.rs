@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::{header::{HeaderName, HeaderValue}, StatusCode};
+
+use crate::stats::{format_ms, RequestRecord};
+
+// 데드라인을 넘긴 것으로 볼 여유 (네트워크, 스케줄링 지연)
+const GRACE: Duration = Duration::from_millis(50);
+
+// 요청별 데드라인을 전달하는 방식
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    // x-envoy-upstream-rq-timeout-ms, 초과 시 504 기대
+    Envoy,
+    // grpc-timeout, 초과 시 grpc-status 4 (DEADLINE_EXCEEDED) 기대
+    // gRPC 가 아닌 요청에는 Envoy 가 그냥 504 로 답하므로 그것도 초과로 본다
+    Grpc,
+}
+
+pub fn parse_mode(value: &str) -> Result<Option<Mode>, String> {
+    match value {
+        "" | "off" => Ok(None),
+        "envoy" => Ok(Some(Mode::Envoy)),
+        "grpc" => Ok(Some(Mode::Grpc)),
+        _ => Err(format!("unknown deadline mode '{}' (off/envoy/grpc)", value)),
+    }
+}
+
+// 데드라인 분포: "200" 고정 또는 "100-500" 균등 분포 (ms)
+#[derive(Clone, Copy)]
+pub struct Distribution {
+    min_ms: u64,
+    max_ms: u64,
+}
+
+impl Distribution {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid deadline distribution '{}' (ms or min-max)", value);
+        let (min, max) = value.split_once('-').unwrap_or((value, value));
+        let min_ms = min.trim().parse::<u64>().map_err(|_| invalid())?;
+        let max_ms = max.trim().parse::<u64>().map_err(|_| invalid())?;
+        if min_ms == 0 || min_ms > max_ms {
+            return Err(invalid());
+        }
+        Ok(Self { min_ms, max_ms })
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> Duration {
+        Duration::from_millis(rng.random_range(self.min_ms..=self.max_ms))
+    }
+}
+
+pub fn header(mode: Mode, deadline: Duration) -> (HeaderName, HeaderValue) {
+    let ms = deadline.as_millis() as u64;
+    match mode {
+        Mode::Envoy => (HeaderName::from_static("x-envoy-upstream-rq-timeout-ms"), HeaderValue::from(ms)),
+        // grpc-timeout 단위 m = 밀리초
+        Mode::Grpc => (HeaderName::from_static("grpc-timeout"), HeaderValue::from_str(&format!("{}m", ms)).unwrap()),
+    }
+}
+
+// 데드라인 초과 응답인지 (504, grpc 모드면 grpc-status 4 도)
+pub fn is_exceeded(mode: Mode, status: StatusCode, grpc_status: Option<u32>) -> bool {
+    match mode {
+        Mode::Envoy => status == StatusCode::GATEWAY_TIMEOUT,
+        Mode::Grpc => grpc_status == Some(4) || status == StatusCode::GATEWAY_TIMEOUT,
+    }
+}
+
+// 데드라인을 넘긴 요청이 올바른 상태로 돌아왔는지, 데드라인 전에 잘린 요청은 없는지
+pub fn summarize(records: &[RequestRecord]) -> String {
+    let with_deadline: Vec<&RequestRecord> = records.iter().filter(|r| r.deadline.is_some()).collect();
+    let over: Vec<&&RequestRecord> = with_deadline.iter().filter(|r| r.deadline.is_some_and(|d| r.latency > d + GRACE)).collect();
+    let exceeded = over.len();
+    let enforced = over.iter().filter(|r| r.deadline_exceeded).count();
+    let early = with_deadline.iter().filter(|r| r.deadline_exceeded && r.deadline.is_some_and(|d| r.latency < d)).count();
+
+    let mut deadlines: Vec<Duration> = with_deadline.iter().filter_map(|r| r.deadline).collect();
+    deadlines.sort();
+    format!(
+        "Deadlines: {} sent (min {} max {}) | over deadline {}: enforced {}, not enforced {} | cut before deadline {}",
+        with_deadline.len(),
+        format_ms(deadlines.first().copied().unwrap_or_default()),
+        format_ms(deadlines.last().copied().unwrap_or_default()),
+        exceeded, enforced, exceeded - enforced, early
+    )
+}
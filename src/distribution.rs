@@ -0,0 +1,140 @@
+use std::fs;
+
+use crate::stats::{format_ms, UpstreamStats};
+
+// 업스트림 간 요청 분배의 고른 정도
+pub struct Fairness {
+    // Jain 공정성 지수 (1 이면 완전히 고름, 1/n 이면 한 곳에 몰림)
+    pub jain: f64,
+    // 가장 많이 받은 업스트림과 가장 적게 받은 업스트림의 비
+    pub max_min_ratio: f64,
+    // 요청 수의 변동 계수 (표준편차 / 평균)
+    pub cv: f64,
+}
+
+// 업스트림마다 요청이 하나 이상 있으므로 0 으로 나누지 않는다
+pub fn fairness(stats: &[UpstreamStats]) -> Option<Fairness> {
+    if stats.is_empty() {
+        return None;
+    }
+    let counts: Vec<f64> = stats.iter().map(|s| s.count as f64).collect();
+    let n = counts.len() as f64;
+    let sum: f64 = counts.iter().sum();
+    let squares: f64 = counts.iter().map(|c| c * c).sum();
+    let mean = sum / n;
+    let variance = counts.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / n;
+    let (min, max) = counts.iter().fold((f64::MAX, 0.0f64), |(min, max), c| (min.min(*c), max.max(*c)));
+    Some(Fairness {
+        jain: sum * sum / (n * squares),
+        max_min_ratio: max / min,
+        cv: variance.sqrt() / mean,
+    })
+}
+
+impl Fairness {
+    pub fn describe(&self) -> String {
+        format!("Jain index {:.3}, max/min {:.2}, CV {:.3}", self.jain, self.max_min_ratio, self.cv)
+    }
+}
+
+fn share(stats: &UpstreamStats, total: usize) -> f64 {
+    stats.count as f64 * 100.0 / total.max(1) as f64
+}
+
+// 업스트림별 분배 표와 공정성 지표 (빈 줄 뒤에 metric,value)
+pub fn csv(stats: &[UpstreamStats]) -> String {
+    let total: usize = stats.iter().map(|s| s.count).sum();
+    let expected = 100.0 / stats.len().max(1) as f64;
+    let mut csv = String::from("upstream,requests,share_pct,expected_pct,deviation_pct,failed,p50_ms,p99_ms\n");
+    for s in stats {
+        let share = share(s, total);
+        csv.push_str(&format!(
+            "{},{},{:.3},{:.3},{:.3},{},{:.3},{:.3}\n",
+            csv_field(&s.name),
+            s.count,
+            share,
+            expected,
+            share - expected,
+            s.failed,
+            s.p50.as_secs_f64() * 1000.0,
+            s.p99.as_secs_f64() * 1000.0
+        ));
+    }
+    if let Some(fairness) = fairness(stats) {
+        csv.push_str(&format!("\nmetric,value\njain_index,{:.4}\nmax_min_ratio,{:.4}\ncv,{:.4}\n", fairness.jain, fairness.max_min_ratio, fairness.cv));
+    }
+    csv
+}
+
+// 업스트림별 요청 수 가로 막대 그래프. 점선은 고르게 나눴을 때의 몫
+pub fn svg(stats: &[UpstreamStats], title: &str) -> String {
+    const LABEL: f64 = 160.0;
+    const CHART: f64 = 400.0;
+    const ROW: f64 = 28.0;
+    const TOP: f64 = 56.0;
+
+    let total: usize = stats.iter().map(|s| s.count).sum();
+    let max = stats.iter().map(|s| s.count).max().unwrap_or(0).max(1) as f64;
+    let width = LABEL + CHART + 160.0;
+    let height = TOP + ROW * stats.len() as f64 + 16.0;
+    let subtitle = fairness(stats).map(|f| f.describe()).unwrap_or_default();
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" font-family=\"sans-serif\" font-size=\"12\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+         <text x=\"8\" y=\"20\" font-size=\"14\" font-weight=\"bold\">{}</text>\n\
+         <text x=\"8\" y=\"38\" fill=\"#555\">{} requests | {}</text>\n",
+        escape(title),
+        total,
+        subtitle
+    );
+    for (i, s) in stats.iter().enumerate() {
+        let y = TOP + ROW * i as f64;
+        let bar = CHART * s.count as f64 / max;
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n\
+             <rect x=\"{LABEL}\" y=\"{y}\" width=\"{bar:.1}\" height=\"{}\" fill=\"#4a90d9\"/>\n\
+             <text x=\"{}\" y=\"{}\">{} ({:.1}%, p50 {})</text>\n",
+            LABEL - 8.0,
+            y + ROW / 2.0 + 2.0,
+            escape(&s.name),
+            ROW - 8.0,
+            LABEL + bar + 6.0,
+            y + ROW / 2.0 + 2.0,
+            s.count,
+            share(s, total),
+            format_ms(s.p50)
+        ));
+    }
+    if !stats.is_empty() {
+        let even = LABEL + CHART * (total as f64 / stats.len() as f64) / max;
+        svg.push_str(&format!(
+            "<line x1=\"{even:.1}\" y1=\"{}\" x2=\"{even:.1}\" y2=\"{}\" stroke=\"#d0021b\" stroke-dasharray=\"4 3\"/>\n",
+            TOP - 4.0,
+            height - 12.0
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// 쉼표나 따옴표가 들어간 업스트림 이름은 따옴표로 감싼다
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// base.csv 와 base.svg 로 저장
+pub fn write(stats: &[UpstreamStats], base: &str, title: &str) -> Result<(String, String), String> {
+    let (csv_path, svg_path) = (format!("{}.csv", base), format!("{}.svg", base));
+    fs::write(&csv_path, csv(stats)).map_err(|e| format!("{}: {}", csv_path, e))?;
+    fs::write(&svg_path, svg(stats, title)).map_err(|e| format!("{}: {}", svg_path, e))?;
+    Ok((csv_path, svg_path))
+}
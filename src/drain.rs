@@ -0,0 +1,44 @@
+use std::{error::Error, time::{Duration, Instant}};
+
+use reqwest::StatusCode;
+
+// Envoy 드레인(핫 리스타트, 리스너 제거) 중 나타나는 응답 신호
+pub fn from_response(status: StatusCode, connection_close: bool, body: Option<&[u8]>) -> Option<&'static str> {
+    if status == StatusCode::SERVICE_UNAVAILABLE && body.is_some_and(|b| String::from_utf8_lossy(b).to_ascii_lowercase().contains("drain")) {
+        Some("503 DRAINING")
+    } else if connection_close {
+        Some("Connection: close")
+    } else {
+        None
+    }
+}
+
+// HTTP/2 연결 종료 (GOAWAY) 는 전송 에러로 나타난다
+// 상대가 NO_ERROR 로 보낸 GOAWAY 만 드레인으로 본다. 프로토콜 오류로 h2 가 스스로 만든 GOAWAY 는 일반 전송 에러
+pub fn from_error(err: &(dyn Error + 'static)) -> Option<&'static str> {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if let Some(h2) = e.downcast_ref::<h2::Error>() {
+            return (h2.is_go_away() && h2.is_remote() && h2.reason() == Some(h2::Reason::NO_ERROR)).then_some("GOAWAY");
+        }
+        current = e.source();
+    }
+    None
+}
+
+// 드레인 신호가 이어지는 구간. 마지막 신호 이후에 보낸 요청이 신호 없이 성공하면 끝난다
+// 응답 순서가 뒤바뀔 수 있으므로 시각은 모두 요청 전송 시각 기준
+pub struct DrainWindow {
+    pub started: Instant,
+    pub last_signal: Instant,
+    pub signals: usize,
+    pub reason: &'static str,
+}
+
+// 실행 동안 관측한 드레인 구간 요약
+#[derive(Default)]
+pub struct DrainSummary {
+    pub windows: usize,
+    pub total: Duration,
+    pub signals: usize,
+}
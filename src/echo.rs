@@ -0,0 +1,507 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use chrono::Local;
+use h2::{server::SendResponse, RecvStream};
+use hyper::http;
+use rand::Rng;
+use serde_json::{json, Value};
+use socket2::SockRef;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    runtime::Runtime,
+    task::JoinSet,
+};
+
+// 기본 리슨 주소 (test-server 와 같은 포트)
+const DEFAULT_LISTEN: &str = "127.0.0.1:24420";
+// 요청마다 지연을 바꾸는 요청 헤더 (slow_header 옵션으로 least-request 검증에 쓴다)
+const DELAY_HEADER: &str = "x-echo-delay-ms";
+// 헤더를 이만큼 받아도 끝나지 않으면 연결을 닫는다
+const MAX_HEAD_BYTES: usize = 1024 * 1024;
+// HTTP/2 prior knowledge (h2c) 연결이 보내는 첫 바이트
+const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+// 실행 중에 바꿀 수 있는 장애 주입 설정. 시작 플래그 (--error-rate), 제어 경로 (CHAOS_PATH), TUI chaos 명령이 같은 키를 쓴다
+#[derive(Clone)]
+pub struct Chaos {
+    // 실패 응답 비율 (%) 과 상태 코드
+    error_rate: f64,
+    error_status: u16,
+    // 응답 전 지연 (ms, 최소-최대 균등 분포) 과 지연을 거는 요청 비율 (%, 100 미만이면 꼬리 지연)
+    delay_ms: (u64, u64),
+    delay_percent: f64,
+    // 응답 없이 연결을 RST 로 끊는 비율 (%)
+    reset_rate: f64,
+}
+
+// 설정 키 (chaos 명령 도움말에도 쓴다)
+pub const CHAOS_KEYS: [&str; 5] = ["error_rate", "error_status", "delay_ms", "delay_percent", "reset_rate"];
+// 장애 주입 설정을 보고 바꾸는 제어 경로 (GET: 지금 설정, POST: JSON 으로 일부 키만 바꾼다)
+pub const CHAOS_PATH: &str = "/_echo/chaos";
+
+impl Default for Chaos {
+    fn default() -> Self {
+        Self { error_rate: 0.0, error_status: 503, delay_ms: (0, 0), delay_percent: 100.0, reset_rate: 0.0 }
+    }
+}
+
+fn parse_percent(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent),
+        _ => Err(format!("invalid percentage '{}' (0-100)", value)),
+    }
+}
+
+fn parse_delay(value: &str) -> Result<(u64, u64), String> {
+    let invalid = || format!("invalid delay '{}' (ms or min-max)", value);
+    let (min, max) = value.split_once('-').unwrap_or((value, value));
+    let min = min.trim().parse::<u64>().map_err(|_| invalid())?;
+    let max = max.trim().parse::<u64>().map_err(|_| invalid())?;
+    if min > max {
+        return Err(invalid());
+    }
+    Ok((min, max))
+}
+
+impl Chaos {
+    pub fn apply(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "error_rate" => self.error_rate = parse_percent(value)?,
+            "error_status" => {
+                self.error_status = match value.trim().parse::<u16>() {
+                    Ok(status @ 100..600) => status,
+                    _ => return Err(format!("invalid status '{}'", value)),
+                }
+            }
+            "delay_ms" => self.delay_ms = parse_delay(value)?,
+            "delay_percent" => self.delay_percent = parse_percent(value)?,
+            "reset_rate" => self.reset_rate = parse_percent(value)?,
+            _ => return Err(format!("unknown chaos setting '{}' ({})", key, CHAOS_KEYS.join("/"))),
+        }
+        Ok(())
+    }
+
+    // 제어 경로의 JSON ({"error_rate": 10, "delay_ms": "50-200"} 처럼 일부 키만). 틀린 키가 있으면 아무것도 바꾸지 않는다
+    fn update(&mut self, body: &[u8]) -> Result<(), String> {
+        let fields = match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Object(fields)) => fields,
+            Ok(_) => return Err(String::from("expected a JSON object of chaos settings")),
+            Err(e) => return Err(format!("invalid JSON: {}", e)),
+        };
+        let mut next = self.clone();
+        for (key, value) in &fields {
+            let text = match value {
+                Value::String(text) => text.clone(),
+                value => value.to_string(),
+            };
+            next.apply(key, &text)?;
+        }
+        *self = next;
+        Ok(())
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "error_rate": self.error_rate,
+            "error_status": self.error_status,
+            "delay_ms": format!("{}-{}", self.delay_ms.0, self.delay_ms.1),
+            "delay_percent": self.delay_percent,
+            "reset_rate": self.reset_rate,
+        })
+    }
+
+    fn describe(&self) -> String {
+        let delay = match self.delay_ms {
+            (0, 0) => String::from("no delay"),
+            (min, max) if min == max => format!("delay {}ms", min),
+            (min, max) => format!("delay {}-{}ms", min, max),
+        };
+        let delay = if self.delay_percent < 100.0 && self.delay_ms != (0, 0) { format!("{} on {}% of requests", delay, self.delay_percent) } else { delay };
+        format!("{}, {}% {} errors, {}% resets", delay, self.error_rate, self.error_status, self.reset_rate)
+    }
+
+    // 요청 하나에 대한 결정 (지연, 상태 코드, 연결 끊기)
+    fn roll(&self) -> (u64, u16, bool) {
+        let mut rng = rand::rng();
+        let delayed = self.delay_percent >= 100.0 || rng.random_bool(self.delay_percent / 100.0);
+        let delay = if delayed { rng.random_range(self.delay_ms.0..=self.delay_ms.1) } else { 0 };
+        let status = if self.error_rate > 0.0 && rng.random_bool(self.error_rate / 100.0) { self.error_status } else { 200 };
+        (delay, status, self.reset_rate > 0.0 && rng.random_bool(self.reset_rate / 100.0))
+    }
+}
+
+// 업스트림 하나 (리슨 주소마다 따로 두어 장애를 한 곳에만 걸 수 있다)
+struct Upstream {
+    // 응답 헤더 이름과 값 (클라이언트 upstream_header 기본값과 같은 이름)
+    id_header: String,
+    id: String,
+    chaos: Mutex<Chaos>,
+    log: bool,
+}
+
+// 받은 요청 하나
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    close: bool,
+}
+
+// 보낼 응답 (HTTP/1.1 과 h2 가 같이 쓴다. 본문은 JSON)
+struct Response {
+    status: u16,
+    body: String,
+    headers: Vec<(String, String)>,
+}
+
+// 응답 대신 할 일
+enum Reply {
+    Send(Response),
+    // 응답 없이 RST 로 끊는다 (h2 는 스트림만 RST_STREAM 으로 끊는다)
+    Reset,
+}
+
+// 명령줄 설정 (리슨 주소마다 같은 설정으로 시작한다)
+struct Options {
+    listen: Vec<SocketAddr>,
+    id: String,
+    id_header: String,
+    log: bool,
+    chaos: Chaos,
+}
+
+fn usage(program: &str) -> String {
+    format!(
+        "usage: {} echo-server [--listen <addr>]... [--id <name>] [--id-header <name>] [--delay-ms N|min-max] [--delay-percent %] [--error-rate %] [--error-status N] [--reset-rate %] [--log on|off] (HTTP/1.1 and h2c prior knowledge; no TLS)",
+        program
+    )
+}
+
+// 인자를 설정으로 바꾼다. --listen 은 여러 번 줄 수 있다
+fn parse(args: &[String]) -> Result<Options, Vec<String>> {
+    let mut options = Options { listen: Vec::new(), id: String::from("echo"), id_header: String::from("x-upstream-host"), log: true, chaos: Chaos::default() };
+    let mut errors = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.strip_prefix("--") else {
+            errors.push(format!("unexpected argument '{}'", arg));
+            continue;
+        };
+        let Some(value) = args.next() else {
+            errors.push(format!("--{}: missing value", flag));
+            break;
+        };
+        let result = match flag {
+            "listen" => value.parse().map(|addr| options.listen.push(addr)).map_err(|_| format!("invalid address '{}' (ip:port)", value)),
+            "id" if value.trim().is_empty() => Err(String::from("server id must not be empty")),
+            "id" => {
+                options.id = value.trim().to_owned();
+                Ok(())
+            }
+            "id-header" => reqwest::header::HeaderName::from_bytes(value.trim().as_bytes())
+                .map(|name| options.id_header = name.to_string())
+                .map_err(|_| format!("invalid header name '{}'", value)),
+            "log" => match value.as_str() {
+                "on" | "off" => {
+                    options.log = value == "on";
+                    Ok(())
+                }
+                _ => Err(format!("invalid value '{}' (on/off)", value)),
+            },
+            flag => options.chaos.apply(&flag.replace('-', "_"), value).map_err(|e| if e.starts_with("unknown") { String::from("unknown option") } else { e }),
+        };
+        if let Err(e) = result {
+            errors.push(format!("--{}: {}", flag, e));
+        }
+    }
+    if options.listen.is_empty() {
+        options.listen.push(DEFAULT_LISTEN.parse().expect("valid default address"));
+    }
+    if errors.is_empty() { Ok(options) } else { Err(errors) }
+}
+
+// 헤더 끝 위치 (빈 줄 다음)
+fn head_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|w| w == b"\r\n\r\n").map(|i| i + 4)
+}
+
+// chunked 본문을 풀어 (본문, 소비한 바이트 수). 아직 다 받지 못했으면 None
+fn dechunk(buf: &[u8]) -> Result<Option<(Vec<u8>, usize)>, String> {
+    let mut body = Vec::new();
+    let mut at = 0;
+    loop {
+        let Some(line) = buf[at..].windows(2).position(|w| w == b"\r\n") else {
+            return Ok(None);
+        };
+        let size = String::from_utf8_lossy(&buf[at..at + line]);
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or_default().trim(), 16).map_err(|_| format!("invalid chunk size '{}'", size))?;
+        at += line + 2;
+        if size == 0 {
+            // 트레일러는 읽고 버린다
+            return Ok(head_end(&buf[at - 2..]).map(|end| (body, at - 2 + end)));
+        }
+        if buf.len() < at + size + 2 {
+            return Ok(None);
+        }
+        body.extend_from_slice(&buf[at..at + size]);
+        at += size + 2;
+    }
+}
+
+// 버퍼 앞에 요청 하나가 다 들어왔으면 꺼낸다 (keep-alive 로 이어 오는 요청은 남겨 둔다)
+fn take_request(buf: &mut Vec<u8>) -> Result<Option<Request>, String> {
+    let Some(end) = head_end(buf) else {
+        return if buf.len() > MAX_HEAD_BYTES { Err(String::from("request head too large")) } else { Ok(None) };
+    };
+    let head = String::from_utf8_lossy(&buf[..end - 4]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut start = lines.next().unwrap_or_default().split(' ');
+    let (method, path, version) = (start.next().unwrap_or_default().to_owned(), start.next().unwrap_or("/").to_owned(), start.next().unwrap_or_default());
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_owned()))
+        .collect();
+    let header = |name: &str| headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.to_ascii_lowercase());
+    let close = match header("connection") {
+        Some(value) => value.contains("close"),
+        None => version == "HTTP/1.0",
+    };
+
+    let (body, used) = if header("transfer-encoding").is_some_and(|v| v.contains("chunked")) {
+        match dechunk(&buf[end..])? {
+            Some(chunked) => chunked,
+            None => return Ok(None),
+        }
+    } else {
+        let length = header("content-length").map(|v| v.parse::<usize>().map_err(|_| format!("invalid content-length '{}'", v))).transpose()?.unwrap_or(0);
+        if buf.len() < end + length {
+            return Ok(None);
+        }
+        (buf[end..end + length].to_vec(), length)
+    };
+    buf.drain(..end + used);
+    Ok(Some(Request { method, path, headers, body, close }))
+}
+
+fn reason(status: u16) -> &'static str {
+    reqwest::StatusCode::from_u16(status).ok().and_then(|s| s.canonical_reason()).unwrap_or("Unknown")
+}
+
+// HTTP/1.1 응답 바이트
+fn http1(response: &Response, request: &Request) -> Vec<u8> {
+    let mut head = format!("HTTP/1.1 {} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n", response.status, reason(response.status), response.body.len());
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if request.close {
+        head.push_str("connection: close\r\n");
+    }
+    head.push_str("\r\n");
+    let mut bytes = head.into_bytes();
+    if request.method != "HEAD" {
+        bytes.extend_from_slice(response.body.as_bytes());
+    }
+    bytes
+}
+
+// 제어 경로 요청. 장애 주입 없이 바로 답한다
+fn control(upstream: &Upstream, request: &Request) -> Response {
+    let mut chaos = upstream.chaos.lock().unwrap();
+    let (status, body) = match request.method.as_str() {
+        "GET" | "HEAD" => (200, chaos.to_json().to_string()),
+        "POST" | "PUT" => match chaos.update(&request.body) {
+            Ok(()) => {
+                println!("[{}] {} chaos updated: {}", Local::now().format("%H:%M:%S%.6f"), upstream.id, chaos.describe());
+                (200, chaos.to_json().to_string())
+            }
+            Err(e) => (400, json!({ "error": e }).to_string()),
+        },
+        _ => (405, json!({ "error": "use GET to read or POST to update chaos settings" }).to_string()),
+    };
+    Response { status, body, headers: Vec::new() }
+}
+
+// 요청 하나에 대한 응답. 받은 헤더와 본문을 JSON 으로 돌려준다
+async fn respond(upstream: &Upstream, request: &Request) -> Reply {
+    if request.path.split('?').next() == Some(CHAOS_PATH) {
+        return Reply::Send(control(upstream, request));
+    }
+    let (delay, status, reset) = upstream.chaos.lock().unwrap().roll();
+    let delay = request.headers.iter().find(|(name, _)| name == DELAY_HEADER).and_then(|(_, value)| value.parse::<u64>().ok()).unwrap_or(delay);
+    if delay > 0 {
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+    }
+
+    let headers: BTreeMap<&str, &str> = request.headers.iter().map(|(n, v)| (n.as_str(), v.as_str())).collect();
+    if upstream.log {
+        let my_id = headers.get("my_id").copied().unwrap_or("unknown");
+        let outcome = if reset { String::from("reset") } else { status.to_string() };
+        println!("[{}] {} {} {} {} -> {} ({}ms)", Local::now().format("%H:%M:%S%.6f"), upstream.id, my_id, request.method, request.path, outcome, delay);
+    }
+    if reset {
+        return Reply::Reset;
+    }
+    let body = json!({
+        "server": upstream.id,
+        "method": request.method,
+        "path": request.path,
+        "headers": headers,
+        "body": String::from_utf8_lossy(&request.body),
+        "body_bytes": request.body.len(),
+        "delay_ms": delay,
+    })
+    .to_string();
+    Reply::Send(Response { status, body, headers: vec![(upstream.id_header.clone(), upstream.id.clone())] })
+}
+
+// 첫 바이트가 h2 프리페이스면 h2c 로, 아니면 HTTP/1.1 로 처리한다
+async fn serve_connection(upstream: Arc<Upstream>, stream: TcpStream) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut preface = [0u8; H2_PREFACE.len()];
+    loop {
+        let read = stream.peek(&mut preface).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        if !H2_PREFACE.starts_with(&preface[..read]) {
+            return serve_http1(upstream, stream).await;
+        }
+        if read == H2_PREFACE.len() {
+            return serve_h2(upstream, stream).await;
+        }
+        // 프리페이스가 나뉘어 왔으면 나머지를 기다린다
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+}
+
+async fn serve_h2(upstream: Arc<Upstream>, stream: TcpStream) -> io::Result<()> {
+    let mut connection = h2::server::handshake(stream).await.map_err(io::Error::other)?;
+    while let Some(accepted) = connection.accept().await {
+        let Ok((request, send)) = accepted else {
+            break;
+        };
+        tokio::spawn(serve_h2_stream(upstream.clone(), request, send));
+    }
+    Ok(())
+}
+
+// h2 스트림 하나. 본문을 다 받은 뒤 HTTP/1.1 과 같은 응답을 보낸다
+async fn serve_h2_stream(upstream: Arc<Upstream>, request: http::Request<RecvStream>, mut send: SendResponse<Bytes>) -> Result<(), h2::Error> {
+    let (parts, mut stream) = request.into_parts();
+    let mut body = Vec::new();
+    while let Some(chunk) = stream.data().await {
+        let chunk = chunk?;
+        stream.flow_control().release_capacity(chunk.len())?;
+        body.extend_from_slice(&chunk);
+    }
+    let request = Request {
+        method: parts.method.to_string(),
+        path: parts.uri.path_and_query().map(|path| path.to_string()).unwrap_or_else(|| String::from("/")),
+        headers: parts.headers.iter().map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned())).collect(),
+        body,
+        close: false,
+    };
+    let response = match respond(&upstream, &request).await {
+        Reply::Send(response) => response,
+        Reply::Reset => {
+            send.send_reset(h2::Reason::INTERNAL_ERROR);
+            return Ok(());
+        }
+    };
+    let mut head = http::Response::builder().status(response.status).header("content-type", "application/json").header("content-length", response.body.len());
+    for (name, value) in &response.headers {
+        head = head.header(name.as_str(), value.as_str());
+    }
+    let head = head.body(()).map_err(|_| h2::Error::from(h2::Reason::INTERNAL_ERROR))?;
+    let end_of_stream = request.method == "HEAD" || response.body.is_empty();
+    let mut sender = send.send_response(head, end_of_stream)?;
+    if !end_of_stream {
+        sender.send_data(Bytes::from(response.body), true)?;
+    }
+    Ok(())
+}
+
+async fn serve_http1(upstream: Arc<Upstream>, mut stream: TcpStream) -> io::Result<()> {
+    let mut pending = Vec::new();
+    let mut chunk = vec![0u8; 16 * 1024];
+    loop {
+        loop {
+            let request = match take_request(&mut pending) {
+                Ok(Some(request)) => request,
+                Ok(None) => break,
+                Err(e) => {
+                    let body = format!("{}\n", e);
+                    stream.write_all(format!("HTTP/1.1 400 Bad Request\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body).as_bytes()).await?;
+                    return Ok(());
+                }
+            };
+            match respond(&upstream, &request).await {
+                Reply::Send(response) => stream.write_all(&http1(&response, &request)).await?,
+                Reply::Reset => {
+                    // linger 0 으로 닫으면 FIN 대신 RST 가 나간다
+                    SockRef::from(&stream).set_linger(Some(Duration::ZERO))?;
+                    return Ok(());
+                }
+            }
+            if request.close {
+                return Ok(());
+            }
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        pending.extend_from_slice(&chunk[..read]);
+    }
+}
+
+async fn serve(listener: TcpListener, upstream: Arc<Upstream>) {
+    loop {
+        if let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_connection(upstream.clone(), stream));
+        }
+    }
+}
+
+// echo-server 하위 명령: Envoy 뒤에 둘 업스트림. 요청 헤더와 본문을 JSON 으로 돌려주고 서버 id 헤더를 붙인다
+// HTTP/1.1 과 h2c (prior knowledge) 를 같은 포트에서 받는다. TLS 는 없다
+// 리슨 주소를 여러 개 주면 주소마다 다른 id (id:포트) 의 업스트림이 뜬다. Ctrl-C 로 끝낸다
+pub fn run(program: &str, args: &[String]) -> i32 {
+    let options = match parse(args) {
+        Ok(options) => options,
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            eprintln!("{}", usage(program));
+            return 2;
+        }
+    };
+
+    let rt = Runtime::new().expect("Failed to create Tokio runtime");
+    let mut servers = JoinSet::new();
+    for addr in &options.listen {
+        let listener = match rt.block_on(TcpListener::bind(addr)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Failed to listen on {}: {}", addr, e);
+                return 1;
+            }
+        };
+        let id = if options.listen.len() > 1 { format!("{}:{}", options.id, addr.port()) } else { options.id.clone() };
+        println!("Echo server '{}' listening on {} ({}: {}, {}; chaos control at {})", id, addr, options.id_header, id, options.chaos.describe(), CHAOS_PATH);
+        let upstream = Upstream { id_header: options.id_header.clone(), id, chaos: Mutex::new(options.chaos.clone()), log: options.log };
+        servers.spawn_on(serve(listener, Arc::new(upstream)), rt.handle());
+    }
+    rt.block_on(servers.join_next());
+    0
+}
@@ -0,0 +1,665 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    runtime::Runtime,
+    sync::{OwnedSemaphorePermit, Semaphore},
+    task::JoinSet,
+};
+
+use crate::{
+    alpn, als, bench, canary, compare, config::RunConfig, control, deadline, envoy_stats, error_code, events::{Bus, Event, Tick}, fault, graphql, header_matrix, hosts, idempotency, idle, least_request, longpoll,
+    markers::{self, MarkerKind},
+    ping, priority, ratelimit, routes, share_alert, sinks, stats, tcp, transcode, udp,
+    utils::*,
+    AppState,
+};
+
+// 헤지 지연 갱신 주기(요청 수)와 백분위 계산에 필요한 최소 표본 수
+const HEDGE_UPDATE_EVERY: usize = 20;
+const HEDGE_MIN_SAMPLES: usize = 20;
+// 동시 요청 허가를 기다리는 동안 중지와 설정 변경을 확인하는 간격
+const STOP_CHECK: Duration = Duration::from_millis(50);
+
+// 실행 시작: 시드를 정하고 통계를 비운 뒤 작업 스레드가 요청을 보내기 시작하게 한다
+// 이번 실행의 구독 작업은 bus 를 구독해 두고, 작업 스레드가 런타임에 띄운다
+pub fn start_run(state: &mut AppState, bus: &Bus, mut config: RunConfig) {
+    // 시드를 정하지 않았으면 새로 정해 두고 요약에 남겨 재현할 수 있게 한다
+    config.seed.get_or_insert_with(rand::random);
+    if config.benchmark {
+        bench::preset(&mut config);
+    }
+    let start_log = format!("Process Start: {}", config.describe());
+    state.add_log(&start_log);
+    state.config = config;
+    state.tee_logs();
+    state.reset_stats();
+    state.hedge_delay = if state.config.hedge_percentile > 0 { Some(Duration::from_millis(state.config.hedge_delay_ms)) } else { None };
+    // 이번 실행의 구독 작업은 첫 RunStarted 에서 설정을 받고, 지난 실행의 구독 작업은 이 RunStarted 를 받고 끝난다
+    state.subscribers.clear();
+    if state.config.share_alert_pct > 0.0 {
+        let interval = Duration::from_secs(state.config.share_alert_interval_s);
+        let watch = share_alert::ShareWatch::new(interval, state.config.share_alert_pct, Instant::now());
+        let events = bus.subscribe("upstream share alert");
+        state.subscribers.push(Box::pin(share_alert::run(watch, events, bus.reporter())));
+    }
+    let (sinks, errors) = sinks::build(&state.config, state.echo_logs);
+    for error in errors {
+        state.add_log(&format!("Sink disabled: {}", error));
+    }
+    if let Some(line) = sinks::describe(&sinks) {
+        state.add_log(&line);
+    }
+    if !sinks.is_empty() {
+        let events = bus.subscribe("result sinks");
+        state.subscribers.push(Box::pin(sinks::run(sinks, events, bus.reporter())));
+    }
+    let started = Arc::new(state.config.clone());
+    bus.publish(Event::RunStarted(started));
+    state.add_marker(MarkerKind::Start, &start_log);
+    state.running = true;
+}
+
+// 요청 루프. 실행 상태를 보고 요청을 보내고, 실행이 끝나면 보고를 남긴다
+// until_idle: 실행(큐 포함)이 끝나 대기 상태가 되면 돌아온다 (헤드리스 실행)
+// bus: 이 워크스페이스의 이벤트 버스 (요청 작업과 진행 상황, 실행 결과를 내보낸다)
+pub fn run(app_state_clone: Arc<Mutex<AppState>>, bus: Bus, rt: Arc<Runtime>, until_idle: bool) {
+    let mut iter = 0;
+    let mut template: Option<Arc<RequestTemplate>> = None;
+    // A/B 비교 실행의 B 대상 템플릿
+    let mut compare_template: Option<Arc<RequestTemplate>> = None;
+    let mut last_marker = Instant::now();
+    // least-request 보고 구간 (직전 구간은 지연 요청이 끝난 뒤 보고)
+    let mut run_started = Instant::now();
+    let mut window_start = Instant::now();
+    let mut previous_window = None;
+    // admin 으로 켠 fault 설정 (실행이 끝나면 해제)
+    let mut armed_fault: Option<(String, fault::FaultConfig)> = None;
+    // 실행 시작 때 읽은 Envoy 카운터 (admin 주소가 있을 때, 요약에서 차이를 보고)
+    let mut envoy_start: Option<envoy_stats::Snapshot> = None;
+    // 초당 요청 수 제한 (작업자가 늘어나도 함께 사용)
+    let mut bucket: Option<Arc<ratelimit::TokenBucket>> = None;
+    // 지금 버킷으로 보낸 요청 수와 속도 모드가 아닐 때 앞 요청의 예정 시각 (클라이언트 대기의 기준)
+    let mut bucket_sent = 0;
+    let mut schedule = Instant::now();
+    // 버킷을 만든 설정 (실행 중에 바뀌면 다시 만든다)
+    let mut bucket_rate = (0.0, 0);
+    // 속도 모드의 주기 보고 (목표 대비 실제 초당 요청 수)
+    let mut rate_report = ratelimit::RateReport::new(Instant::now());
+    // 동시 요청 수 상한. 요청 작업이 허가를 들고 있다가 끝나면 돌려준다 (상한이 0이면 None)
+    let mut pool: Option<Arc<Semaphore>> = None;
+    let mut pool_size = 0;
+    // 이번 실행에서 띄운 요청 작업. 중지하면 남은 작업을 취소한다
+    let mut tasks: JoinSet<()> = JoinSet::new();
+    // 실행이 끝난 뒤 접근 로그를 기다리는 기한
+    let mut als_deadline: Option<Instant> = None;
+    // 버스에 진행 상황을 마지막으로 내보낸 시각
+    let mut last_tick = Instant::now();
+
+    loop {
+        // 상태 확인
+        while tasks.try_join_next().is_some() {}
+        let (running, config, pending_markers, pending_posts, subscribers, tick) = {
+            let mut state = app_state_clone.lock().unwrap();
+            if state.closed {
+                // 구독 작업이 남은 이벤트를 처리하고 끝나도록 버스를 닫는다
+                state.als_listener = None;
+                state.control_listener = None;
+                drop(state);
+                bus.close();
+                return;
+            }
+            let queues = state.conn_events();
+            state.log_conn_events(&queues);
+            state.follow_reports(&bus);
+            let tick = (state.running && last_tick.elapsed() >= Duration::from_secs(1)).then(|| Tick { sent: state.sent, completed: state.completed() });
+            (
+                state.running,
+                state.config.clone(),
+                std::mem::take(&mut state.pending_markers),
+                std::mem::take(&mut state.pending_posts),
+                std::mem::take(&mut state.subscribers),
+                tick,
+            )
+        };
+        if let Some(tick) = tick {
+            last_tick = Instant::now();
+            bus.publish(Event::IntervalTick(tick));
+        }
+
+        // 이번 실행의 구독 작업 (결과 싱크, 업스트림 비율 경보)
+        for subscriber in subscribers {
+            rt.spawn(subscriber);
+        }
+
+        // 대기 중인 마커를 웹훅으로 전송
+        for marker in pending_markers {
+            let (url, token, state) = (config.annotation_url.clone(), config.annotation_token.clone(), app_state_clone.clone());
+            rt.spawn(async move {
+                if let Err(e) = markers::post_annotation(&url, &token, &marker).await {
+                    state.lock().unwrap().add_log(&format!("Failed to post marker to webhook: {}", e));
+                }
+            });
+        }
+
+        // 명령이 보낸 HTTP 전송
+        for post in pending_posts {
+            let state = app_state_clone.clone();
+            rt.spawn(async move {
+                if let Err(e) = sinks::post(&post).await {
+                    state.lock().unwrap().add_log(&format!("Failed to send to {}: {}", post.sink, e));
+                }
+            });
+        }
+        
+        let cloned_app_state = app_state_clone.clone();
+
+        // 연속 프로브 모드는 중지할 때까지 반복
+        let probing = config.probe_interval_ms > 0;
+        // 실행 시간이 정해져 있으면 반복 횟수 대신 경과 시간으로 판단
+        let remaining = if config.duration_s > 0 {
+            iter == 0 || run_started.elapsed() < Duration::from_secs(config.duration_s)
+        } else {
+            iter < config.iteration
+        };
+        if running && (probing || remaining) {
+            // 실행 시작 시 요청 템플릿을 한 번만 생성
+            if iter == 0 {
+                let compare_config = (!config.compare_url.is_empty()).then(|| RunConfig { dst_url: config.compare_url.clone(), ..config.clone() });
+                let built = RequestTemplate::new(&config).and_then(|t| Ok((t, compare_config.as_ref().map(RequestTemplate::new).transpose()?)));
+                match built {
+                    Ok((t, compare)) => {
+                        template = Some(Arc::new(t));
+                        compare_template = compare.map(Arc::new);
+                        let mut state = app_state_clone.lock().unwrap();
+                        state.routes = template.as_ref().unwrap().routes().to_vec();
+                        state.templates = template.clone().map(|t| (t, compare_template.clone()));
+                    }
+                    Err(e) => {
+                        let mut state = app_state_clone.lock().unwrap();
+                        state.running = false;
+                        state.add_log(&format!("Process Aborted: {}", e));
+                        state.add_marker(MarkerKind::Stop, "Process Aborted");
+                        continue;
+                    }
+                }
+                // Envoy 접근 로그 수신기는 첫 요청 전에 띄운다
+                if let Err(e) = als::ensure(&rt, &app_state_clone, &config.als_listen) {
+                    let mut state = app_state_clone.lock().unwrap();
+                    state.running = false;
+                    state.add_log(&format!("Process Aborted: {}", e));
+                    state.add_marker(MarkerKind::Stop, "Process Aborted");
+                    continue;
+                }
+                // 제어 API 도 첫 요청 전에 띄워 관찰자가 처음부터 볼 수 있게 한다
+                if let Err(e) = control::ensure(&rt, &app_state_clone, &bus, &config.control_listen) {
+                    let mut state = app_state_clone.lock().unwrap();
+                    state.running = false;
+                    state.add_log(&format!("Process Aborted: {}", e));
+                    state.add_marker(MarkerKind::Stop, "Process Aborted");
+                    continue;
+                }
+                // Envoy 카운터는 읽지 못해도 실행은 계속한다
+                envoy_start = None;
+                if !config.admin_url.trim().is_empty() {
+                    match rt.block_on(envoy_stats::snapshot(config.admin_url.trim())) {
+                        Ok(snapshot) => envoy_start = Some(snapshot),
+                        Err(e) => app_state_clone.lock().unwrap().add_log(&format!("Envoy stats unavailable: {}", e)),
+                    }
+                }
+                // admin 방식 fault 주입은 첫 요청 전에 켜 둔다
+                if let Ok(fault) = config.fault()
+                    && fault.mode == fault::Mode::Admin
+                {
+                    let result = rt.block_on(fault::set_runtime(&config.admin_url, &fault, true));
+                    let mut state = app_state_clone.lock().unwrap();
+                    match result {
+                        Ok(()) => {
+                            state.add_log("Fault injection enabled via admin runtime");
+                            armed_fault = Some((config.admin_url.clone(), fault));
+                        }
+                        Err(e) => {
+                            state.running = false;
+                            state.add_log(&format!("Process Aborted: failed to enable fault injection: {}", e));
+                            state.add_marker(MarkerKind::Stop, "Process Aborted");
+                            continue;
+                        }
+                    }
+                }
+                // 벤치마크는 연결을 미리 맺어 둔 뒤 시간을 잰다
+                if config.benchmark
+                    && let Some(template) = template.clone()
+                    && !template.clients().is_empty()
+                {
+                    let result = rt.block_on(bench::warm(&template));
+                    let mut state = app_state_clone.lock().unwrap();
+                    match result {
+                        Ok((warmed, took)) => state.add_log(&format!("Benchmark: warmed {} connection(s) in {}", warmed, stats::format_ms(took))),
+                        Err(e) => {
+                            state.running = false;
+                            state.add_log(&format!("Process Aborted: failed to warm connections: {}", e));
+                            state.add_marker(MarkerKind::Stop, "Process Aborted");
+                            continue;
+                        }
+                    }
+                }
+                // 카나리는 부하 전 기준 지연을 잰 뒤 실행 동안 따로 돈다
+                if let Some(template) = template.clone().filter(|t| t.canary.is_some()) {
+                    let canary = template.canary.as_ref().unwrap();
+                    let baseline = rt.block_on(canary.baseline());
+                    let mut latency: Vec<Duration> = baseline.iter().filter(|s| s.error.is_none()).map(|s| s.latency).collect();
+                    latency.sort();
+                    let mut state = app_state_clone.lock().unwrap();
+                    state.add_log(&format!(
+                        "Canary {} every {}: idle baseline {}/{} ok, p50 {}",
+                        canary.url,
+                        stats::format_ms(Duration::from_secs_f64(1.0 / config.canary_rps)),
+                        latency.len(),
+                        baseline.len(),
+                        stats::format_ms(stats::percentile(&latency, 50.0))
+                    ));
+                    state.canaries.extend(baseline);
+                    drop(state);
+                    rt.spawn(canary::run(template, app_state_clone.clone()));
+                }
+                if config.ping_interval_ms > 0
+                    && let Some(template) = template.clone()
+                {
+                    rt.spawn(ping::sample(template, Duration::from_millis(config.ping_interval_ms), app_state_clone.clone()));
+                }
+                last_marker = Instant::now();
+                run_started = Instant::now();
+                window_start = run_started;
+                previous_window = None;
+                rate_report = ratelimit::RateReport::new(run_started);
+            }
+
+            // 주기 마커
+            if config.marker_interval_s > 0 && last_marker.elapsed() >= Duration::from_secs(config.marker_interval_s) {
+                last_marker = Instant::now();
+                app_state_clone.lock().unwrap().add_marker(MarkerKind::Interval, &format!("Iteration {}", iter));
+            }
+
+            // 헤지 지연은 완료된 요청의 지연 백분위로 주기적으로 갱신
+            if config.hedge_percentile > 0 && iter > 0 && iter % HEDGE_UPDATE_EVERY == 0 {
+                let mut state = app_state_clone.lock().unwrap();
+                if state.records.len() >= HEDGE_MIN_SAMPLES {
+                    let mut latency: Vec<Duration> = state.records.iter().map(|r| r.latency).collect();
+                    latency.sort();
+                    state.hedge_delay = Some(stats::percentile(&latency, config.hedge_percentile as f64));
+                }
+            }
+
+            // least-request 구간 보고
+            let slow_mode = template.as_ref().is_some_and(|t| t.slow_percent > 0);
+            if slow_mode && config.shift_window_s > 0 && window_start.elapsed() >= Duration::from_secs(config.shift_window_s) {
+                let now = Instant::now();
+                if let Some(from) = previous_window {
+                    let mut state = app_state_clone.lock().unwrap();
+                    let shift = least_request::analyze(&state.records, from, window_start);
+                    state.add_log(&format!("Least-request window: {}", shift.describe()));
+                }
+                previous_window = Some(window_start);
+                window_start = now;
+            }
+
+            // 실행 중에 바뀐 초당 요청 수 반영
+            // UDP 모드는 초당 패킷 수 옵션이 있으면 그 값을 쓴다
+            let rps = if template.as_ref().is_some_and(|t| t.udp.is_some()) && config.udp_pps > 0.0 { config.udp_pps } else { config.rps };
+            if iter == 0 || bucket_rate != (rps, config.rps_burst) {
+                let ramp = Duration::from_secs(config.ramp_up_s);
+                bucket = (rps > 0.0).then(|| Arc::new(ratelimit::TokenBucket::new(rps, config.rps_burst).ramped(run_started, ramp)));
+                bucket_rate = (rps, config.rps_burst);
+                // 속도가 바뀌면 일정도 지금부터 다시 센다
+                bucket_sent = 0;
+                schedule = Instant::now();
+            }
+            if let Some(bucket) = bucket.as_ref().filter(|_| !probing) {
+                let mut state = app_state_clone.lock().unwrap();
+                let (sent, completed, limited) = (state.sent, state.completed(), state.rate_limited);
+                if let Some(line) = rate_report.tick(bucket, Instant::now(), sent, completed, limited) {
+                    state.add_log(&line);
+                }
+            }
+
+            // 실행 중에 바뀐 동시 요청 수 반영
+            if iter == 0 || pool_size != config.concurrency {
+                pool = resize_pool(&rt, if iter == 0 { None } else { pool.take() }, pool_size, config.concurrency);
+                pool_size = config.concurrency;
+            }
+
+            // 일정상 이 요청을 보냈어야 하는 시각 (속도 모드는 토큰이 생기는 시각, 아니면 앞 요청 + 간격)
+            // 동시 요청 상한이나 속도 제한에 막혀 밀린 시간은 여기서부터 클라이언트 대기로 잡힌다
+            let delay_ms = if probing { config.probe_interval_ms } else { config.delay_ms };
+            let scheduled_at = match &bucket {
+                Some(bucket) if !probing => bucket.due(bucket_sent),
+                _ => schedule + Duration::from_millis(delay_ms),
+            };
+
+            // 동시 요청 상한에 닿았으면 작업 하나가 끝날 때까지 대기 (A/B 비교는 두 요청이 허가 하나를 같이 쓴다)
+            let permit = match &pool {
+                Some(pool) => {
+                    let deadline = (!probing && config.duration_s > 0).then(|| run_started + Duration::from_secs(config.duration_s));
+                    match acquire(&rt, pool, &app_state_clone, pool_size, deadline) {
+                        Some(permit) => Some(permit),
+                        // 중지됐거나 시간이 다 됐거나 동시 요청 수가 바뀌었으면 루프 처음에서 다시 본다
+                        None => continue,
+                    }
+                }
+                None => None,
+            };
+
+            // 로그 추가
+            match &bucket {
+                Some(bucket) if !probing => bucket.acquire(),
+                // 로그 생성 간격. 일정보다 밀렸으면 기다리지 않는다
+                _ => thread::sleep(scheduled_at.saturating_duration_since(Instant::now())),
+            }
+            bucket_sent += 1;
+            schedule = scheduled_at;
+            let template = template.clone().expect("request template not built");
+            let compare = compare_template.clone();
+            let request = template.next_request(iter);
+            let task: Pin<Box<dyn Future<Output = ()> + Send>> = if template.tcp.is_some() {
+                Box::pin(tcp::send(template, request.0, scheduled_at, cloned_app_state, bus.clone()))
+            } else if template.udp.is_some() {
+                Box::pin(udp::send(template, iter, request.0, scheduled_at, cloned_app_state, bus.clone()))
+            } else if template.idle.is_some() {
+                Box::pin(idle::hold(template, request.0, scheduled_at, cloned_app_state, bus.clone()))
+            } else {
+                Box::pin(send_request(template, compare, iter, request, scheduled_at, cloned_app_state, bus.clone()))
+            };
+            tasks.spawn_on(
+                async move {
+                    task.await;
+                    drop(permit);
+                },
+                rt.handle(),
+            );
+
+            iter += 1;
+            app_state_clone.lock().unwrap().sent += if compare_template.is_some() { 2 } else { 1 };
+        }
+        else if running {
+            let mut state = app_state_clone.lock().unwrap();
+            // 아직 응답을 기다리는 요청이 있으면 요약 전에 잠시 대기 (A/B 비교는 요청마다 기록 두 개)
+            let expected = if compare_template.is_some() { iter * 2 } else { iter };
+            if state.completed() < expected {
+                drop(state);
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+            // Envoy 가 버퍼에 모아 보내는 접근 로그를 잠시 기다린다
+            if state.als_listener.is_some() && state.proxy_logs.len() < state.records.len() {
+                let deadline = *als_deadline.get_or_insert_with(|| Instant::now() + als::FLUSH_GRACE);
+                if Instant::now() < deadline {
+                    drop(state);
+                    thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+            }
+            als_deadline = None;
+            state.running = !state.running;
+            state.add_log("Process Done");
+            state.add_marker(MarkerKind::Stop, "Process Done");
+            state.end_drain(Instant::now());
+            // 오래된 결과를 구간 집계로 합쳤으면 실행 전체 줄이 기준이고, 요청별 결과로 낸 보고는 남은 창만 본 것이다
+            let summary = match state.history.summarize(&state.records, &config.percentiles) {
+                Some(whole_run) => {
+                    state.add_log(&whole_run);
+                    let window = stats::summarize(&state.records, &config.percentiles);
+                    state.add_report(&window);
+                    whole_run
+                }
+                None => {
+                    let summary = stats::summarize(&state.records, &config.percentiles);
+                    state.add_log(&summary);
+                    if let Some(errors) = error_code::summarize(&state.records) {
+                        state.add_log(&errors);
+                    }
+                    summary
+                }
+            };
+            if let Some(bucket) = bucket.as_ref().filter(|_| !probing) {
+                let log = ratelimit::summarize(bucket, run_started + Duration::from_secs(config.ramp_up_s), &state.records, state.rate_limited);
+                state.add_report(&log);
+            }
+            if state.als_listener.is_some() {
+                for line in als::summarize(&state.records, &state.proxy_logs, &config.percentiles) {
+                    state.add_report(&line);
+                }
+            }
+            if config.benchmark {
+                for line in bench::summarize(&state.records) {
+                    state.add_report(&line);
+                }
+            }
+            if let Some(start) = envoy_start.take() {
+                // admin 을 읽는 동안 잠금을 풀어 둔다
+                drop(state);
+                let end = rt.block_on(envoy_stats::snapshot(config.admin_url.trim()));
+                state = app_state_clone.lock().unwrap();
+                match end {
+                    Ok(end) => {
+                        let completed = state.completed();
+                        for line in envoy_stats::summarize(&start, &end, completed, &state.records) {
+                            state.add_report(&line);
+                        }
+                    }
+                    Err(e) => state.add_log(&format!("Envoy stats unavailable: {}", e)),
+                }
+            }
+            if let Some(seed) = config.seed {
+                state.add_log(&format!("Seed: {} (set the seed option to reproduce this run)", seed));
+            }
+            if let Some(queue) = state.queue_run.as_mut() {
+                queue.finish_stage(&summary);
+            }
+            if state.drain_summary.windows > 0 {
+                let drain = &state.drain_summary;
+                let log = format!("Drain windows: {} (total {}, {} signals)", drain.windows, stats::format_ms(drain.total), drain.signals);
+                state.add_log(&log);
+            }
+            if compare_template.is_some() {
+                for line in compare::report(&state.records) {
+                    state.add_report(&line);
+                }
+            }
+            for upstream in stats::by_upstream(&state.records) {
+                state.add_report(&format!(
+                    "Upstream {}: {} requests ({} failed) | {}",
+                    upstream.name, upstream.count, upstream.failed, stats::format_percentiles(&upstream.latency, &config.percentiles)
+                ));
+            }
+            if let Ok(offer) = alpn::Offer::parse(&config.alpn)
+                && let Some(log) = alpn::summarize(offer, &state.records)
+            {
+                state.add_report(&log);
+            }
+            if (config.host_concurrency > 0 || config.host_rps > 0.0)
+                && let Some(template) = &template
+            {
+                for line in hosts::summarize(template.url(), &state.routes, &state.records) {
+                    state.add_report(&line);
+                }
+            }
+            for result in routes::matrix(&state.routes, &state.records) {
+                let verdict = if result.failed == 0 { "PASS" } else { "FAIL" };
+                let mut log = format!("Route {} {}: {} passed, {} failed", verdict, result.path, result.passed, result.failed);
+                if let Some(reason) = result.last_failure {
+                    log.push_str(&format!(" (last: {})", reason));
+                }
+                state.add_report(&log);
+            }
+            let injected_delay = (config.fault_mode != "off" && config.fault_delay_ms > 0).then(|| Duration::from_millis(config.fault_delay_ms));
+            if let Some(log) = stats::attribution_summary(&state.records, injected_delay) {
+                state.add_report(&log);
+            }
+            for line in ping::summarize(&state.pings, &state.records, &config.percentiles) {
+                state.add_report(&line);
+            }
+            if let Some(canary) = template.as_ref().and_then(|t| t.canary.as_ref()) {
+                for line in canary::summarize(canary, &state.canaries, &config.percentiles) {
+                    state.add_report(&line);
+                }
+            }
+            if config.grpc_transcode {
+                for line in transcode::summarize(&state.records) {
+                    state.add_report(&line);
+                }
+            }
+            if !config.graphql_query.trim().is_empty() {
+                let log = graphql::summarize(&state.records);
+                state.add_report(&log);
+            }
+            if config.long_poll_s > 0 {
+                for line in longpoll::summarize(&state.records, Duration::from_secs(config.long_poll_s)) {
+                    state.add_report(&line);
+                }
+            }
+            if template.as_ref().is_some_and(|t| t.tcp.is_some()) {
+                for line in tcp::summarize(&state.records, &config.percentiles) {
+                    state.add_report(&line);
+                }
+            }
+            if let Some(target) = template.as_ref().and_then(|t| t.udp.as_ref()) {
+                for line in udp::summarize(target, &state.records, &config.percentiles) {
+                    state.add_report(&line);
+                }
+            }
+            if let Some(probe) = template.as_ref().and_then(|t| t.idle.as_ref()) {
+                for line in idle::summarize(probe, &state.records) {
+                    state.add_report(&line);
+                }
+            }
+            if let Some(sweep) = template.as_ref().and_then(|t| t.priority.as_ref()) {
+                for line in priority::summarize(sweep, &state.records, &config.percentiles) {
+                    state.add_report(&line);
+                }
+            }
+            if let Some(matrix) = template.as_ref().and_then(|t| t.header_matrix.as_ref()) {
+                for line in header_matrix::summarize(matrix, &state.records) {
+                    state.add_report(&line);
+                }
+            }
+            if !config.idempotency_header.trim().is_empty() {
+                for line in idempotency::summarize(&state.records) {
+                    state.add_report(&line);
+                }
+            }
+            if config.deadline_mode != "off" {
+                let log = deadline::summarize(&state.records);
+                state.add_report(&log);
+            }
+            if config.hedge_percentile > 0 {
+                let log = stats::hedge_summary(&state.records);
+                state.add_report(&log);
+            }
+            if let Ok(fault) = config.fault()
+                && fault.mode != fault::Mode::Off
+            {
+                for line in fault.verify(&state.records) {
+                    state.add_report(&line);
+                }
+            }
+            if template.as_ref().is_some_and(|t| t.slow_percent > 0) {
+                let shift = least_request::analyze(&state.records, run_started, Instant::now());
+                state.add_report(&format!("Least-request overall: {}", shift.describe()));
+            }
+            let events = vec![state.export(), state.notify("run finished")];
+            drop(state);
+            bus.publish_all(events);
+        }
+        else {
+            // 중지한 실행의 응답을 기다리는 요청은 취소 (중지 뒤에 결과가 더 쌓이지 않도록)
+            if !tasks.is_empty() {
+                let cancelled = tasks.len();
+                rt.block_on(tasks.shutdown());
+                app_state_clone.lock().unwrap().add_log(&format!("Cancelled {} in-flight request(s)", cancelled));
+            }
+            // 끝난 실행의 admin fault 설정 해제
+            if let Some((admin_url, fault)) = armed_fault.take() {
+                let result = rt.block_on(fault::set_runtime(&admin_url, &fault, false));
+                let mut state = app_state_clone.lock().unwrap();
+                match result {
+                    Ok(()) => state.add_log("Fault injection disabled via admin runtime"),
+                    Err(e) => state.add_log(&format!("Failed to disable fault injection: {}", e)),
+                }
+            }
+            iter = 0;
+            template = None;
+            compare_template = None;
+            // 큐 실행 중이면 다음 단계 시작, 마지막 단계였으면 전체 보고
+            let mut state = app_state_clone.lock().unwrap();
+            if let Some(mut queue) = state.queue_run.take() {
+                match queue.advance() {
+                    Some(config) => {
+                        state.add_log(&format!("Queue: starting stage {}/{} {}", queue.index + 1, queue.stages.len(), queue.current().name));
+                        state.queue_run = Some(queue);
+                        start_run(&mut state, &bus, config);
+                    }
+                    None => {
+                        for line in queue.report() {
+                            state.add_log(&line);
+                        }
+                        let event = state.notify("queue finished");
+                        bus.publish(event);
+                    }
+                }
+                continue;
+            }
+            drop(state);
+            if until_idle {
+                return;
+            }
+            // 작업 스레드가 너무 CPU를 점유하지 않도록 짧은 대기
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+// 작업 하나가 끝나 허가가 날 때까지 대기. 그 사이 실행이 멈추거나 deadline 이 지나거나
+// 동시 요청 수 설정이 바뀌면 None
+fn acquire(rt: &Runtime, pool: &Arc<Semaphore>, state: &Mutex<AppState>, size: usize, deadline: Option<Instant>) -> Option<OwnedSemaphorePermit> {
+    let interrupted = async {
+        loop {
+            tokio::time::sleep(STOP_CHECK).await;
+            let state = state.lock().unwrap();
+            if !state.running || state.config.concurrency != size || deadline.is_some_and(|d| Instant::now() >= d) {
+                return;
+            }
+        }
+    };
+    rt.block_on(async {
+        tokio::select! {
+            permit = pool.clone().acquire_owned() => permit.ok(),
+            () = interrupted => None,
+        }
+    })
+}
+
+// 동시 요청 수 상한이 바뀌면 허가 수를 맞춘다. 줄일 때는 돌아오는 허가를 받아 버리는 작업을 띄워 진행 중인 요청을 끊지 않는다
+fn resize_pool(rt: &Runtime, pool: Option<Arc<Semaphore>>, from: usize, to: usize) -> Option<Arc<Semaphore>> {
+    if to == 0 {
+        return None;
+    }
+    let Some(pool) = pool.filter(|_| from > 0) else {
+        return Some(Arc::new(Semaphore::new(to)));
+    };
+    if to > from {
+        pool.add_permits(to - from);
+    } else if to < from {
+        let shrink = pool.clone();
+        rt.spawn(async move {
+            if let Ok(permits) = shrink.acquire_many_owned((from - to) as u32).await {
+                permits.forget();
+            }
+        });
+    }
+    Some(pool)
+}
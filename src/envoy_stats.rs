@@ -0,0 +1,88 @@
+use std::{collections::{BTreeMap, HashMap}, time::Duration};
+
+use reqwest::{Client, Url};
+
+use crate::stats::RequestRecord;
+
+// 클러스터 카운터 (접미어, 요약에 쓸 이름)
+const CLUSTER_COUNTERS: [(&str, &str); 5] = [
+    ("upstream_rq_total", "requests"),
+    ("upstream_rq_retry", "retries"),
+    ("upstream_rq_pending_overflow", "pending overflow"),
+    ("upstream_cx_overflow", "cx overflow"),
+    ("upstream_rq_retry_overflow", "retry overflow"),
+];
+
+// 실행 시작/끝에 읽은 Envoy 카운터 (admin /stats 의 "이름: 값" 줄)
+pub struct Snapshot(HashMap<String, u64>);
+
+// admin /stats?usedonly 에서 숫자 값만 읽는다 (히스토그램 줄은 건너뜀)
+pub async fn snapshot(admin_url: &str) -> Result<Snapshot, String> {
+    let mut url = Url::parse(admin_url).map_err(|e| format!("invalid admin URL '{}': {}", admin_url, e))?.join("stats").map_err(|e| e.to_string())?;
+    url.set_query(Some("usedonly"));
+    let client = Client::builder().timeout(Duration::from_secs(5)).build().map_err(|e| e.to_string())?;
+    let response = client.get(url).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    let counters = text
+        .lines()
+        .filter_map(|line| line.split_once(": "))
+        .filter_map(|(name, value)| Some((name.to_owned(), value.trim().parse().ok()?)))
+        .collect();
+    Ok(Snapshot(counters))
+}
+
+impl Snapshot {
+    // 끝 값 - 시작 값 (시작에 없던 카운터는 0 에서 시작, Envoy 재시작으로 줄었으면 0)
+    fn delta(&self, end: &Snapshot, name: &str) -> u64 {
+        end.0.get(name).copied().unwrap_or(0).saturating_sub(self.0.get(name).copied().unwrap_or(0))
+    }
+
+    // "http.<stat_prefix>.<suffix>" 의 합 (admin 자체 요청은 제외)
+    fn downstream(&self, end: &Snapshot, suffix: &str) -> u64 {
+        end.0
+            .keys()
+            .filter(|name| name.starts_with("http.") && !name.starts_with("http.admin.") && name.ends_with(&format!(".{}", suffix)))
+            .map(|name| self.delta(end, name))
+            .sum()
+    }
+}
+
+// Envoy 가 센 요청 수와 클라이언트가 센 요청 수를 나란히 보고
+// 다른 클라이언트의 트래픽도 Envoy 카운터에 섞이므로 차이가 나면 표시만 한다
+pub fn summarize(start: &Snapshot, end: &Snapshot, completed: usize, records: &[RequestRecord]) -> Vec<String> {
+    // 클러스터 이름에 점이 있을 수 있어 알려진 접미어로 자른다
+    let mut clusters: BTreeMap<&str, [u64; CLUSTER_COUNTERS.len()]> = BTreeMap::new();
+    for name in end.0.keys() {
+        let Some(rest) = name.strip_prefix("cluster.") else { continue };
+        for (i, (suffix, _)) in CLUSTER_COUNTERS.iter().enumerate() {
+            if let Some(cluster) = rest.strip_suffix(suffix).and_then(|cluster| cluster.strip_suffix('.')) {
+                clusters.entry(cluster).or_default()[i] = start.delta(end, name);
+            }
+        }
+    }
+    clusters.retain(|_, counts| counts.iter().any(|&count| count > 0));
+    let total = |i: usize| clusters.values().map(|counts| counts[i]).sum::<u64>();
+
+    let seen = start.downstream(end, "downstream_rq_total");
+    let server_errors = start.downstream(end, "downstream_rq_5xx");
+    let client_errors = records.iter().filter(|r| r.status.is_some_and(|status| status >= 500)).count();
+    let mut summary = format!(
+        "Envoy stats delta: {} downstream requests (client completed {}), {} upstream requests, {} retries, {} overflows, {} 5xx (client saw {})",
+        seen, completed, total(0), total(1), total(2) + total(3) + total(4), server_errors, client_errors
+    );
+    let completed = completed as u64;
+    if seen > completed {
+        summary.push_str(&format!(" [mismatch: Envoy saw {} more]", seen - completed));
+    } else if seen < completed {
+        summary.push_str(&format!(" [mismatch: Envoy saw {} fewer]", completed - seen));
+    }
+    let mut lines = vec![summary];
+    for (cluster, counts) in clusters {
+        let detail: Vec<String> = CLUSTER_COUNTERS.iter().zip(counts).filter(|&(_, count)| count > 0).map(|((_, label), count)| format!("{} +{}", label, count)).collect();
+        lines.push(format!("Envoy cluster {}: {}", cluster, detail.join(", ")));
+    }
+    lines
+}
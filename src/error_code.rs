@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{stats::RequestRecord, transport::TimeoutKind};
+
+// 실패 원인을 스크립트가 영문 메시지를 해석하지 않고 쓸 수 있도록 고정한 코드 (로그, 내보내기에 기록)
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ErrorCode {
+    // TCP 연결 실패 (연결 타임아웃 포함)
+    Connect,
+    // TLS 핸드셰이크 실패 (TLS 타임아웃 포함)
+    Tls,
+    // 요청 타임아웃 (응답 헤더나 본문을 기다리다 초과)
+    Timeout,
+    // 연결 후 요청 전송이나 응답 헤더 수신 중 실패
+    Send,
+    // 응답 헤더를 받은 뒤 본문 수신 중 실패
+    Receive,
+    // 성공 기준에 맞지 않는 상태코드
+    Status4xx,
+    Status5xx,
+    Status,
+    // 응답은 받았지만 검증 실패 (트랜스코딩, GraphQL errors, 지연 기준 초과)
+    Assert,
+}
+
+impl ErrorCode {
+    // 내보내기 CSV 의 열 순서
+    pub const ALL: [ErrorCode; 9] = [
+        ErrorCode::Connect,
+        ErrorCode::Tls,
+        ErrorCode::Timeout,
+        ErrorCode::Send,
+        ErrorCode::Receive,
+        ErrorCode::Status4xx,
+        ErrorCode::Status5xx,
+        ErrorCode::Status,
+        ErrorCode::Assert,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Connect => "E_CONNECT",
+            ErrorCode::Tls => "E_TLS",
+            ErrorCode::Timeout => "E_TIMEOUT",
+            ErrorCode::Send => "E_SEND",
+            ErrorCode::Receive => "E_RECEIVE",
+            ErrorCode::Status4xx => "E_STATUS_4XX",
+            ErrorCode::Status5xx => "E_STATUS_5XX",
+            ErrorCode::Status => "E_STATUS",
+            ErrorCode::Assert => "E_ASSERT",
+        }
+    }
+
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+
+    // 응답을 받지 못한 요청. 타임아웃은 어느 단계에서 났는지를 우선한다
+    pub fn from_send_error(timeout: Option<TimeoutKind>, tls: bool, connect: bool) -> Self {
+        match timeout {
+            Some(TimeoutKind::Connect) => ErrorCode::Connect,
+            Some(TimeoutKind::Tls) => ErrorCode::Tls,
+            Some(TimeoutKind::Request) => ErrorCode::Timeout,
+            None if tls => ErrorCode::Tls,
+            None if connect => ErrorCode::Connect,
+            None => ErrorCode::Send,
+        }
+    }
+
+    // 성공 기준에 맞지 않는 상태코드
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            400..=499 => ErrorCode::Status4xx,
+            500..=599 => ErrorCode::Status5xx,
+            _ => ErrorCode::Status,
+        }
+    }
+}
+
+// JSON (제어 API) 에는 E_SEND 같은 코드 문자열로 쓴다
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Self::parse(&code).ok_or_else(|| de::Error::custom(format!("unknown error code '{}'", code)))
+    }
+}
+
+// 실패한 요청의 코드별 개수
+pub fn counts<'a>(records: impl IntoIterator<Item = &'a RequestRecord>) -> BTreeMap<ErrorCode, usize> {
+    let mut counts = BTreeMap::new();
+    for code in records.into_iter().filter_map(|r| r.error) {
+        *counts.entry(code).or_default() += 1;
+    }
+    counts
+}
+
+// 실행 종료 보고 ("Errors: E_CONNECT 2, E_STATUS_5XX 5")
+pub fn summarize(records: &[RequestRecord]) -> Option<String> {
+    describe(&counts(records.iter().filter(|r| r.primary())))
+}
+
+pub fn describe(counts: &BTreeMap<ErrorCode, usize>) -> Option<String> {
+    if counts.is_empty() {
+        return None;
+    }
+    let counts: Vec<String> = counts.iter().map(|(code, count)| format!("{} {}", code.as_str(), count)).collect();
+    Some(format!("Errors: {}", counts.join(", ")))
+}
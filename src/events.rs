@@ -0,0 +1,173 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::{
+    broadcast::{
+        self,
+        error::{RecvError, TryRecvError},
+    },
+    mpsc,
+};
+
+use crate::{als::ProxyLogs, config::RunConfig, markers::RunMarker, retention::History, stats::RequestRecord};
+
+// 실행 중에 일어난 일. 작업 스레드, 요청 작업, 명령이 버스에 내보내고 화면, 싱크, 경보, 제어 API 가 각자 구독한다
+#[derive(Clone)]
+pub enum Event {
+    // 실행 중에 set 명령으로 바뀐 설정
+    ConfigChanged(Arc<RunConfig>),
+    RunStarted(Arc<RunConfig>),
+    RequestFinished(Arc<RequestRecord>),
+    // 실행 중 1초마다 진행 상황
+    IntervalTick(Tick),
+    // 알릴 일 (첫 실패, 중단, 실행/큐 종료) 과 그 시점의 요약
+    Notice { title: String, summary: String },
+    // 실행이 끝나거나 중지됐을 때의 결과 (요청별 결과는 AppState 와 같은 Arc 를 나눠 쓴다)
+    RunCompleted(Arc<RunResults>),
+}
+
+#[derive(Clone, Copy)]
+pub struct Tick {
+    pub sent: usize,
+    pub completed: usize,
+}
+
+pub struct RunResults {
+    pub config: RunConfig,
+    pub records: Arc<Vec<RequestRecord>>,
+    // 구간 집계로 합친 오래된 결과
+    pub history: History,
+    // Envoy 접근 로그 (als_listen)
+    pub proxy_logs: ProxyLogs,
+    // 로그와 남긴 시각 (결과 파일 옆에 재생 파일로 남긴다)
+    pub logs: Vec<(i64, String)>,
+    // 실행 마커 (내보내기 파일에 함께 남긴다)
+    pub markers: Vec<RunMarker>,
+}
+
+// 구독자 하나가 이만큼 밀리면 가장 오래된 이벤트부터 버린다 (버린 수는 로그로 남긴다)
+const CAPACITY: usize = 1 << 16;
+// 작업 스레드가 옮기기 전에 쌓아 둘 수 있는 구독 작업의 로그 수 (넘치면 버린다)
+const REPORT_CAPACITY: usize = 1024;
+
+// 구독 작업이 AppState 를 잠그는 대신 남기는 것. 작업 스레드가 루프마다 AppState 로 옮긴다
+pub enum Report {
+    Log(String),
+    // 로그와 함께 상태 표시줄에 띄우는 경보
+    Alert(String),
+}
+
+// 구독자 모두가 크기가 정해진 broadcast 채널 하나를 나눠 받는다. 복제한 핸들끼리 같은 채널을 쓴다
+// AppState 밖에 두어 내보낼 때 AppState 를 잠그지 않는다
+#[derive(Clone)]
+pub struct Bus {
+    events: Arc<Mutex<Option<broadcast::Sender<Event>>>>,
+    reports: mpsc::Sender<Report>,
+    pending: Arc<Mutex<mpsc::Receiver<Report>>>,
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        let (reports, pending) = mpsc::channel(REPORT_CAPACITY);
+        Self { events: Arc::new(Mutex::new(Some(broadcast::channel(CAPACITY).0))), reports, pending: Arc::new(Mutex::new(pending)) }
+    }
+}
+
+impl Bus {
+    // name: 밀려서 이벤트를 놓쳤을 때 로그에 남길 구독자 이름
+    pub fn subscribe(&self, name: &'static str) -> Subscription {
+        // 닫힌 버스를 구독하면 곧바로 끝나는 채널을 준다
+        let events = match self.events.lock().unwrap().as_ref() {
+            Some(tx) => tx.subscribe(),
+            None => broadcast::channel(1).1,
+        };
+        Subscription { name, events, reporter: self.reporter(), lagged: 0 }
+    }
+
+    pub fn reporter(&self) -> Reporter {
+        Reporter { tx: self.reports.clone() }
+    }
+
+    pub fn publish(&self, event: Event) {
+        // 구독자가 없으면 그냥 버려진다
+        if let Some(tx) = self.events.lock().unwrap().as_ref() {
+            let _ = tx.send(event);
+        }
+    }
+
+    pub fn publish_all(&self, events: Vec<Event>) {
+        for event in events {
+            self.publish(event);
+        }
+    }
+
+    // 구독 작업이 남긴 로그와 경보를 꺼낸다
+    pub fn take_reports(&self) -> Vec<Report> {
+        let mut pending = self.pending.lock().unwrap();
+        std::iter::from_fn(|| pending.try_recv().ok()).collect()
+    }
+
+    // 채널을 닫는다 (구독 작업은 남은 이벤트를 처리하고 끝난다)
+    pub fn close(&self) {
+        *self.events.lock().unwrap() = None;
+    }
+}
+
+// 구독 작업이 로그와 경보를 남기는 핸들
+#[derive(Clone)]
+pub struct Reporter {
+    tx: mpsc::Sender<Report>,
+}
+
+impl Reporter {
+    pub fn log(&self, line: String) {
+        let _ = self.tx.try_send(Report::Log(line));
+    }
+
+    pub fn alert(&self, alert: String) {
+        let _ = self.tx.try_send(Report::Alert(alert));
+    }
+}
+
+// 버스 구독 하나. 밀려서 놓친 이벤트는 세어 두고 로그로 알린다
+pub struct Subscription {
+    name: &'static str,
+    events: broadcast::Receiver<Event>,
+    reporter: Reporter,
+    lagged: u64,
+}
+
+impl Subscription {
+    // 다음 이벤트. 버스가 닫히고 남은 이벤트도 다 받으면 None
+    pub async fn recv(&mut self) -> Option<Event> {
+        loop {
+            match self.events.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(missed)) => self.lag(missed),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    // 기다리지 않고 쌓인 이벤트만 꺼낸다 (화면)
+    pub fn try_recv(&mut self) -> Option<Event> {
+        loop {
+            match self.events.try_recv() {
+                Ok(event) => return Some(event),
+                Err(TryRecvError::Lagged(missed)) => self.lag(missed),
+                Err(_) => return None,
+            }
+        }
+    }
+
+    fn lag(&mut self, missed: u64) {
+        self.lagged += missed;
+        self.reporter.log(format!("Event bus: {} fell behind and dropped {} event(s) ({} so far)", self.name, missed, self.lagged));
+    }
+}
+
+// 실행을 시작할 때 만든 구독 작업. start_run 에는 런타임이 없어 작업 스레드가 띄운다
+pub type Subscriber = Pin<Box<dyn Future<Output = ()> + Send>>;
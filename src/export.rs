@@ -0,0 +1,258 @@
+use std::{collections::BTreeMap, fs, time::{Duration, Instant}};
+
+use chrono::{Local, SecondsFormat, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{als::ProxyLogs, error_code::{self, ErrorCode}, markers::RunMarker, probe, retention::History, stats::{percentile, percentile_label, RequestRecord}};
+
+// 구간 하나의 집계 (요청 전송 시각 기준)
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    // 구간 시작 (UTC 밀리초)과 실행 시작으로부터의 초
+    pub time_ms: i64,
+    pub offset_s: u64,
+    // 구간 길이 (오래되어 합친 구간은 내보내기 간격보다 길 수 있다)
+    pub span: Duration,
+    pub requests: usize,
+    pub errors: usize,
+    // 에러 코드별 실패 수
+    pub error_codes: BTreeMap<ErrorCode, usize>,
+    pub rps: f64,
+    // 설정한 백분위 지연 (ms)
+    pub latency_ms: Vec<f64>,
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+// 실패가 있었던 코드만 담은 객체 ({"E_CONNECT": 2})
+fn error_code_fields(counts: &BTreeMap<ErrorCode, usize>) -> Value {
+    Value::Object(counts.iter().map(|(code, count)| (code.as_str().to_owned(), json!(count))).collect())
+}
+
+// 백분위 지연 필드 (p99.9 -> "p99.9_ms")
+fn latency_fields(map: &mut Map<String, Value>, percentiles: &[f64], values: &[f64]) {
+    for (p, value) in percentiles.iter().zip(values) {
+        map.insert(format!("{}_ms", percentile_label(*p)), json!(value));
+    }
+}
+
+// 첫 요청 전송 시각부터 interval 간격으로 나눈 집계. 요청이 없는 구간도 0 으로 남긴다
+pub fn snapshots(records: &[RequestRecord], interval: Duration, percentiles: &[f64]) -> Vec<Snapshot> {
+    let Some(origin) = records.iter().map(|r| r.sent_at).min() else {
+        return Vec::new();
+    };
+    let records: Vec<&RequestRecord> = records.iter().collect();
+    snapshots_from(origin, &records, interval, percentiles)
+}
+
+// origin 부터 interval 간격으로 나눈 집계 (origin 보다 먼저 보낸 요청은 첫 구간에)
+pub fn snapshots_from(origin: Instant, records: &[&RequestRecord], interval: Duration, percentiles: &[f64]) -> Vec<Snapshot> {
+    if records.is_empty() {
+        return Vec::new();
+    }
+    let interval = interval.max(Duration::from_millis(1));
+    let slot = |r: &RequestRecord| (r.sent_at.saturating_duration_since(origin).as_secs_f64() / interval.as_secs_f64()) as usize;
+    let count = records.iter().map(|r| slot(r)).max().unwrap_or(0) + 1;
+
+    let mut buckets: Vec<Vec<&RequestRecord>> = (0..count).map(|_| Vec::new()).collect();
+    for record in records {
+        buckets[slot(record)].push(record);
+    }
+
+    let start_ms = probe::wall_time(origin).timestamp_millis();
+    buckets
+        .into_iter()
+        .enumerate()
+        .map(|(i, bucket)| {
+            let mut latency: Vec<Duration> = bucket.iter().map(|r| r.latency).collect();
+            latency.sort();
+            let offset = interval * i as u32;
+            Snapshot {
+                time_ms: start_ms + offset.as_millis() as i64,
+                offset_s: offset.as_secs(),
+                span: interval,
+                requests: bucket.len(),
+                errors: bucket.iter().filter(|r| !r.success).count(),
+                error_codes: error_code::counts(bucket.iter().copied()),
+                rps: bucket.len() as f64 / interval.as_secs_f64(),
+                latency_ms: percentiles.iter().map(|p| ms(percentile(&latency, *p))).collect(),
+            }
+        })
+        .collect()
+}
+
+// 실행 전체 요약 (합친 구간이 있으면 히스토그램으로 어림한 백분위)
+fn summary(records: &[RequestRecord], history: &History, percentiles: &[f64]) -> Map<String, Value> {
+    let mut summary = Map::new();
+    match history.totals(records) {
+        Some(totals) => {
+            summary.insert("requests".to_owned(), json!(totals.requests));
+            summary.insert("errors".to_owned(), json!(totals.errors));
+            summary.insert("error_codes".to_owned(), error_code_fields(&totals.error_codes));
+            let values: Vec<f64> = percentiles.iter().map(|p| ms(totals.latency.percentile(*p))).collect();
+            latency_fields(&mut summary, percentiles, &values);
+            summary.insert("downsampled".to_owned(), json!(history.folded()));
+        }
+        None => {
+            let mut latency: Vec<Duration> = records.iter().map(|r| r.latency).collect();
+            latency.sort();
+            summary.insert("requests".to_owned(), json!(records.len()));
+            summary.insert("errors".to_owned(), json!(records.iter().filter(|r| !r.success).count()));
+            summary.insert("error_codes".to_owned(), error_code_fields(&error_code::counts(records)));
+            let values: Vec<f64> = percentiles.iter().map(|p| ms(percentile(&latency, *p))).collect();
+            latency_fields(&mut summary, percentiles, &values);
+        }
+    }
+    summary
+}
+
+// 쉼표, 따옴표, 줄바꿈이 들어간 값은 따옴표로 감싼다
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+// 마커 하나 (CSV 에서는 마커 열만 채운 줄로 들어간다)
+fn marker_fields(marker: &RunMarker) -> Map<String, Value> {
+    let mut map = Map::new();
+    let time = Local.timestamp_millis_opt(marker.time_ms).single().map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, false));
+    map.insert("timestamp".to_owned(), json!(time));
+    map.insert("marker".to_owned(), json!(marker.kind.as_str()));
+    map.insert("marker_text".to_owned(), json!(marker.text));
+    map
+}
+
+// 요청 하나의 결과 (보낸 시각 순)
+fn result_fields(record: &RequestRecord, proxy_logs: &ProxyLogs) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert("timestamp".to_owned(), json!(probe::wall_time(record.sent_at).to_rfc3339_opts(SecondsFormat::Millis, false)));
+    map.insert("id".to_owned(), json!(record.id));
+    map.insert("status".to_owned(), json!(record.status));
+    map.insert("success".to_owned(), json!(record.success));
+    map.insert("latency_ms".to_owned(), json!(ms(record.latency)));
+    map.insert("queue_delay_ms".to_owned(), json!(ms(record.queue_delay)));
+    map.insert("upstream".to_owned(), json!(record.upstream));
+    map.insert("error".to_owned(), json!(record.error.map(|e| e.as_str())));
+    map.insert("detail".to_owned(), json!(record.validation));
+    // Envoy 접근 로그가 있으면 프록시가 잰 지연과 응답 플래그
+    let proxy = proxy_logs.get(&record.id);
+    map.insert("proxy_latency_ms".to_owned(), json!(proxy.and_then(|p| p.latency).map(ms)));
+    map.insert("response_flags".to_owned(), json!(proxy.map(|p| p.flags())));
+    map
+}
+
+// 요청별 결과 보고서. .csv 면 요청마다 한 줄, 그 외에는 요약과 요청 목록을 JSON 으로 저장한다
+// 보관 기간 (raw_window_s) 이 지나 구간 집계로 합친 요청은 목록에 없고 요약에만 들어간다. 저장한 요청 수를 돌려준다
+// 실행 마커 (시작, 종료, 드레인 등) 는 JSON 에서는 markers 목록으로, CSV 에서는 시각 순으로 끼운 마커 줄로 남긴다
+pub fn write_results(path: &str, target: &str, records: &[RequestRecord], history: &History, proxy_logs: &ProxyLogs, markers: &[RunMarker], percentiles: &[f64]) -> Result<usize, String> {
+    let mut ordered: Vec<&RequestRecord> = records.iter().collect();
+    ordered.sort_by_key(|r| r.sent_at);
+    let results: Vec<Map<String, Value>> = ordered.iter().map(|r| result_fields(r, proxy_logs)).collect();
+    let content = if path.to_ascii_lowercase().ends_with(".csv") {
+        const COLUMNS: [&str; 13] = ["timestamp", "id", "status", "success", "latency_ms", "queue_delay_ms", "upstream", "error", "detail", "proxy_latency_ms", "response_flags", "marker", "marker_text"];
+        let mut rows: Vec<(i64, &Map<String, Value>)> = ordered.iter().map(|r| probe::wall_time(r.sent_at).timestamp_millis()).zip(&results).collect();
+        let marker_rows: Vec<Map<String, Value>> = markers.iter().map(marker_fields).collect();
+        rows.extend(markers.iter().map(|m| m.time_ms).zip(&marker_rows));
+        rows.sort_by_key(|(at, _)| *at);
+        let mut csv = COLUMNS.join(",") + "\n";
+        for (_, result) in rows {
+            let row: Vec<String> = COLUMNS
+                .iter()
+                .map(|column| match result.get(*column).unwrap_or(&Value::Null) {
+                    Value::Null => String::new(),
+                    Value::String(text) => csv_field(text),
+                    Value::Number(n) if column.ends_with("_ms") => format!("{:.3}", n.as_f64().unwrap_or_default()),
+                    value => value.to_string(),
+                })
+                .collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    } else {
+        let report = json!({
+            "target": target,
+            "percentiles": percentiles,
+            "summary": summary(records, history, percentiles),
+            "markers": markers,
+            "requests": results,
+        });
+        serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?
+    };
+    fs::write(path, content).map_err(|e| e.to_string())?;
+    Ok(results.len())
+}
+
+// 확장자가 .csv 면 구간 집계만 CSV 로, 그 외에는 요약과 구간 집계를 JSON 으로 저장
+// 오래되어 구간 집계로 합친 결과도 포함한다 (합친 구간의 지연은 히스토그램으로 어림한 값)
+// 실행 마커는 JSON 에서는 markers 목록으로, CSV 에서는 구간 사이에 시각 순으로 끼운 마커 줄로 남긴다
+pub fn write(path: &str, target: &str, records: &[RequestRecord], history: &History, markers: &[RunMarker], interval: Duration, percentiles: &[f64]) -> Result<usize, String> {
+    let snapshots = history.snapshots(records, interval, percentiles);
+    let content = if path.to_ascii_lowercase().ends_with(".csv") {
+        let mut csv = String::from("time_ms,offset_s,requests,errors,rps");
+        for p in percentiles {
+            csv.push_str(&format!(",{}_ms", percentile_label(*p)));
+        }
+        // 코드별 열은 실패가 없어도 항상 같은 순서로 둔다
+        for code in ErrorCode::ALL {
+            csv.push_str(&format!(",{}", code.as_str()));
+        }
+        csv.push_str(",marker,marker_text\n");
+        let mut rows: Vec<(i64, String)> = snapshots
+            .iter()
+            .map(|s| {
+                let mut row = format!("{},{},{},{},{:.3}", s.time_ms, s.offset_s, s.requests, s.errors, s.rps);
+                for value in &s.latency_ms {
+                    row.push_str(&format!(",{:.3}", value));
+                }
+                for code in ErrorCode::ALL {
+                    row.push_str(&format!(",{}", s.error_codes.get(&code).copied().unwrap_or(0)));
+                }
+                (s.time_ms, row + ",,")
+            })
+            .collect();
+        // 마커 줄은 시각, 실행 시작으로부터의 초, 마커 열만 채운다
+        let origin = snapshots.first().map_or(0, |s| s.time_ms);
+        let blanks = ",".repeat(3 + percentiles.len() + ErrorCode::ALL.len());
+        rows.extend(markers.iter().map(|m| (m.time_ms, format!("{},{}{},{},{}", m.time_ms, (m.time_ms - origin).max(0) / 1000, blanks, m.kind.as_str(), csv_field(&m.text)))));
+        rows.sort_by_key(|(at, _)| *at);
+        for (_, row) in rows {
+            csv.push_str(&row);
+            csv.push('\n');
+        }
+        csv
+    } else {
+        let summary = summary(records, history, percentiles);
+        let intervals: Vec<Value> = snapshots
+            .iter()
+            .map(|s| {
+                let mut map = Map::new();
+                map.insert("time_ms".to_owned(), json!(s.time_ms));
+                map.insert("offset_s".to_owned(), json!(s.offset_s));
+                map.insert("requests".to_owned(), json!(s.requests));
+                map.insert("errors".to_owned(), json!(s.errors));
+                map.insert("error_codes".to_owned(), error_code_fields(&s.error_codes));
+                map.insert("rps".to_owned(), json!(s.rps));
+                latency_fields(&mut map, percentiles, &s.latency_ms);
+                Value::Object(map)
+            })
+            .collect();
+        let report = json!({
+            "target": target,
+            "interval_s": interval.as_secs_f64(),
+            "percentiles": percentiles,
+            "summary": summary,
+            "markers": markers,
+            "intervals": intervals,
+        });
+        serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?
+    };
+    fs::write(path, content).map_err(|e| e.to_string())?;
+    Ok(snapshots.len())
+}
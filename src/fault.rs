@@ -0,0 +1,102 @@
+use reqwest::{header::{HeaderName, HeaderValue}, Client, Url};
+
+use crate::stats::RequestRecord;
+
+// Envoy fault 필터 주입 방식
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Off,
+    // x-envoy-fault-* 요청 헤더 (fault 필터에 header_delay/header_abort 설정 필요)
+    Headers,
+    // admin /runtime_modify 로 fault.http.* 런타임 값 변경
+    Admin,
+}
+
+pub fn parse_mode(value: &str) -> Result<Mode, String> {
+    match value {
+        "" | "off" => Ok(Mode::Off),
+        "headers" => Ok(Mode::Headers),
+        "admin" => Ok(Mode::Admin),
+        _ => Err(format!("unknown fault mode '{}' (off/headers/admin)", value)),
+    }
+}
+
+#[derive(Clone)]
+pub struct FaultConfig {
+    pub mode: Mode,
+    pub delay_ms: u64,
+    pub delay_percent: u64,
+    // 0 이면 abort 주입 안 함
+    pub abort_status: u16,
+    pub abort_percent: u64,
+}
+
+impl FaultConfig {
+    // 모든 요청에 붙일 헤더. 실제 주입 여부는 Envoy 가 비율에 따라 결정
+    pub fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let mut headers = Vec::new();
+        if self.mode != Mode::Headers {
+            return headers;
+        }
+        let mut push = |name: &'static str, value: u64| headers.push((HeaderName::from_static(name), HeaderValue::from(value)));
+        if self.delay_ms > 0 {
+            push("x-envoy-fault-delay-request", self.delay_ms);
+            push("x-envoy-fault-delay-request-percentage", self.delay_percent);
+        }
+        if self.abort_status > 0 {
+            push("x-envoy-fault-abort-request", self.abort_status as u64);
+            push("x-envoy-fault-abort-request-percentage", self.abort_percent);
+        }
+        headers
+    }
+
+    // admin 런타임 키. 해제할 때는 값을 비워 오버라이드를 지운다
+    fn runtime_values(&self, enable: bool) -> Vec<(&'static str, String)> {
+        let value = |v: u64| if enable { v.to_string() } else { String::new() };
+        let mut values = Vec::new();
+        if self.delay_ms > 0 {
+            values.push(("fault.http.delay.fixed_delay_percent", value(self.delay_percent)));
+            values.push(("fault.http.delay.fixed_duration_ms", value(self.delay_ms)));
+        }
+        if self.abort_status > 0 {
+            values.push(("fault.http.abort.abort_percent", value(self.abort_percent)));
+            values.push(("fault.http.abort.http_status", value(self.abort_status as u64)));
+        }
+        values
+    }
+
+    // 관측된 주입 비율과 설정 비율 비교
+    pub fn verify(&self, records: &[RequestRecord]) -> Vec<String> {
+        let total = records.len().max(1) as f64;
+        let rate = |count: usize| count as f64 * 100.0 / total;
+        let mut lines = Vec::new();
+
+        if self.delay_ms > 0 {
+            let delayed = records.iter().filter(|r| r.latency.as_millis() >= self.delay_ms as u128).count();
+            lines.push(format!(
+                "Fault delay {}ms: observed {:.1}% ({} of {}), configured {}%",
+                self.delay_ms, rate(delayed), delayed, records.len(), self.delay_percent
+            ));
+        }
+        if self.abort_status > 0 {
+            let aborted = records.iter().filter(|r| r.status == Some(self.abort_status)).count();
+            lines.push(format!(
+                "Fault abort HTTP {}: observed {:.1}% ({} of {}), configured {}%",
+                self.abort_status, rate(aborted), aborted, records.len(), self.abort_percent
+            ));
+        }
+        lines
+    }
+}
+
+// Envoy admin 의 런타임 값 변경 (POST /runtime_modify?key=value)
+pub async fn set_runtime(admin_url: &str, fault: &FaultConfig, enable: bool) -> Result<(), String> {
+    let mut url = Url::parse(admin_url).map_err(|e| format!("invalid admin URL '{}': {}", admin_url, e))?.join("runtime_modify").map_err(|e| e.to_string())?;
+    url.query_pairs_mut().extend_pairs(fault.runtime_values(enable));
+
+    let response = Client::new().post(url).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::utils::RequestTemplate;
+
+// h2 라이브러리는 상대가 알린 한도를 스스로 지키고 받은 SETTINGS 도 감추므로
+// 한도를 넘겨 보거나 SETTINGS 를 그대로 봐야 하는 실험은 프레임을 직접 주고받는다
+
+// 클라이언트 연결 프리페이스와 빈 SETTINGS 프레임
+pub const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\x00\x00\x00\x04\x00\x00\x00\x00\x00";
+
+pub const HEADERS: u8 = 0x1;
+pub const RST_STREAM: u8 = 0x3;
+pub const SETTINGS: u8 = 0x4;
+pub const PING: u8 = 0x6;
+pub const GOAWAY: u8 = 0x7;
+
+pub const FLAG_ACK: u8 = 0x1;
+pub const FLAG_END_HEADERS: u8 = 0x4;
+
+pub const REFUSED_STREAM: u32 = 0x7;
+
+// 받은 프레임을 이만큼보다 크게 읽지 않는다 (SETTINGS_MAX_FRAME_SIZE 최댓값)
+const MAX_PAYLOAD: usize = 16_777_215;
+
+pub struct Frame {
+    pub kind: u8,
+    pub flags: u8,
+    pub stream: u32,
+    pub payload: Vec<u8>,
+}
+
+pub async fn read<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Frame> {
+    let mut header = [0u8; 9];
+    reader.read_exact(&mut header).await?;
+    let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+    if len > MAX_PAYLOAD {
+        return Err(std::io::Error::other("frame too large"));
+    }
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).await?;
+    let stream = u32::from_be_bytes([header[5], header[6], header[7], header[8]]) & 0x7fff_ffff;
+    Ok(Frame { kind: header[3], flags: header[4], stream, payload })
+}
+
+pub async fn write<W: AsyncWrite + Unpin>(writer: &mut W, kind: u8, flags: u8, stream: u32, payload: &[u8]) -> std::io::Result<()> {
+    let mut frame = Vec::with_capacity(9 + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes()[1..]);
+    frame.extend_from_slice(&[kind, flags]);
+    frame.extend_from_slice(&stream.to_be_bytes());
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame).await
+}
+
+// SETTINGS 페이로드의 (식별자, 값) 목록
+pub fn settings(payload: &[u8]) -> Vec<(u16, u32)> {
+    payload.chunks_exact(6).map(|s| (u16::from_be_bytes([s[0], s[1]]), u32::from_be_bytes([s[2], s[3], s[4], s[5]]))).collect()
+}
+
+// RST_STREAM, GOAWAY 의 에러 코드 (GOAWAY 는 마지막 스트림 id 뒤)
+pub fn error_code(frame: &Frame) -> Option<u32> {
+    let at = if frame.kind == GOAWAY { 4 } else { 0 };
+    let code = frame.payload.get(at..at + 4)?;
+    Some(u32::from_be_bytes([code[0], code[1], code[2], code[3]]))
+}
+
+// HPACK 정수 (prefix 비트 수, 첫 바이트의 나머지 비트)
+fn hpack_int(out: &mut Vec<u8>, mut value: usize, prefix: u8, pattern: u8) {
+    let max = (1usize << prefix) - 1;
+    if value < max {
+        out.push(pattern | value as u8);
+        return;
+    }
+    out.push(pattern | max as u8);
+    value -= max;
+    while value >= 0x80 {
+        out.push((value % 0x80) as u8 | 0x80);
+        value /= 0x80;
+    }
+    out.push(value as u8);
+}
+
+// 정적 테이블의 이름 + 허프만 없는 리터럴 값 (색인하지 않음)
+fn hpack_literal(out: &mut Vec<u8>, name_index: usize, value: &str) {
+    hpack_int(out, name_index, 4, 0x00);
+    hpack_int(out, value.len(), 7, 0x00);
+    out.extend_from_slice(value.as_bytes());
+}
+
+// 대상 URL 로 보내는 GET 요청의 헤더 블록 (동적 테이블을 쓰지 않음)
+pub fn request_headers(template: &RequestTemplate) -> Vec<u8> {
+    let url = template.url();
+    let mut block = Vec::new();
+    // :method GET, :scheme http/https
+    block.push(0x82);
+    block.push(if url.scheme() == "https" { 0x87 } else { 0x86 });
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_owned(),
+    };
+    hpack_literal(&mut block, 4, &path);
+    let authority = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_owned(),
+    };
+    hpack_literal(&mut block, 1, &authority);
+    block
+}
+
+// 에러 코드 이름 (RFC 9113 7절)
+pub fn error_name(code: u32) -> String {
+    let name = match code {
+        0x0 => "NO_ERROR",
+        0x1 => "PROTOCOL_ERROR",
+        0x2 => "INTERNAL_ERROR",
+        0x3 => "FLOW_CONTROL_ERROR",
+        0x4 => "SETTINGS_TIMEOUT",
+        0x5 => "STREAM_CLOSED",
+        0x6 => "FRAME_SIZE_ERROR",
+        0x7 => "REFUSED_STREAM",
+        0x8 => "CANCEL",
+        0x9 => "COMPRESSION_ERROR",
+        0xa => "CONNECT_ERROR",
+        0xb => "ENHANCE_YOUR_CALM",
+        0xc => "INADEQUATE_SECURITY",
+        0xd => "HTTP_1_1_REQUIRED",
+        _ => return format!("error 0x{:x}", code),
+    };
+    String::from(name)
+}
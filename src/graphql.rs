@@ -0,0 +1,78 @@
+use std::fs;
+
+use bytes::Bytes;
+use serde_json::{json, Value};
+
+use crate::stats::RequestRecord;
+
+// GraphQL 요청 템플릿. 변수 템플릿의 {{id}}, {{index}}, {{random}} 은 요청마다 치환
+pub struct QueryTemplate {
+    query: String,
+    variables: Option<String>,
+}
+
+// 직접 입력하거나 "@파일경로"
+fn read_value(value: &str, what: &str) -> Result<String, String> {
+    match value.strip_prefix('@') {
+        Some(path) => fs::read_to_string(path.trim()).map_err(|e| format!("Failed to read GraphQL {} '{}': {}", what, path.trim(), e)),
+        None => Ok(value.to_owned()),
+    }
+}
+
+impl QueryTemplate {
+    // 쿼리가 비어 있으면 GraphQL 모드를 쓰지 않음
+    pub fn new(query: &str, variables: &str) -> Result<Option<Self>, String> {
+        if query.trim().is_empty() {
+            return Ok(None);
+        }
+        let template = Self {
+            query: read_value(query.trim(), "query")?,
+            variables: match variables.trim() {
+                "" => None,
+                value => Some(read_value(value, "variables")?),
+            },
+        };
+        // 치환 결과가 JSON 이 되는지 미리 확인
+        template.variables("check", 0, 0)?;
+        Ok(Some(template))
+    }
+
+    fn variables(&self, id: &str, index: usize, random: u32) -> Result<Value, String> {
+        let Some(variables) = &self.variables else {
+            return Ok(Value::Null);
+        };
+        let text = variables.replace("{{id}}", id).replace("{{index}}", &index.to_string()).replace("{{random}}", &random.to_string());
+        serde_json::from_str(&text).map_err(|e| format!("GraphQL variables are not valid JSON after substitution: {}", e))
+    }
+
+    // 요청 하나의 본문 {"query": ..., "variables": ...}
+    pub fn body(&self, id: &str, index: usize, random: u32) -> Bytes {
+        let variables = self.variables(id, index, random).unwrap_or(Value::Null);
+        Bytes::from(json!({ "query": self.query, "variables": variables }).to_string())
+    }
+}
+
+// 응답 검증: JSON 이어야 하고 errors[] 가 있으면 실패
+pub fn validate(body: &[u8]) -> Result<(), String> {
+    let response: Value = serde_json::from_slice(body).map_err(|_| String::from("response is not JSON"))?;
+    if let Some(errors) = response.get("errors").and_then(Value::as_array)
+        && let Some(first) = errors.first()
+    {
+        let message = first.get("message").and_then(Value::as_str).unwrap_or("(no message)");
+        return Err(format!("{} error(s), first: {}", errors.len(), message));
+    }
+    if response.get("data").is_none_or(Value::is_null) {
+        return Err(String::from("no data"));
+    }
+    Ok(())
+}
+
+pub fn summarize(records: &[RequestRecord]) -> String {
+    let responses = records.iter().filter(|r| r.status.is_some()).count();
+    let failed: Vec<&RequestRecord> = records.iter().filter(|r| r.validation.is_some()).collect();
+    let mut summary = format!("GraphQL: {} responses, {} with errors", responses, failed.len());
+    if let Some(reason) = failed.last().and_then(|r| r.validation.as_deref()) {
+        summary.push_str(&format!(" (last: {})", reason));
+    }
+    summary
+}
@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use reqwest::header::{HeaderName, HeaderValue};
+
+use crate::{compare::Side, stats::RequestRecord};
+
+// 헤더 값의 모든 조합을 돌려 가며 보내는 라우팅 매트릭스 ("x-version: v1|v2; x-region: us|eu")
+pub struct HeaderMatrix {
+    pub headers: Vec<(HeaderName, Vec<HeaderValue>)>,
+}
+
+impl HeaderMatrix {
+    pub fn combinations(&self) -> usize {
+        self.headers.iter().map(|(_, values)| values.len()).product()
+    }
+
+    // index 번째 요청이 쓸 조합 번호
+    pub fn pick(&self, index: usize) -> usize {
+        index % self.combinations()
+    }
+
+    // 조합 번호를 헤더마다 값 하나로 푼다 (마지막 헤더가 가장 빨리 바뀐다)
+    pub fn values(&self, combination: usize) -> Vec<(&HeaderName, &HeaderValue)> {
+        let mut rest = combination;
+        let mut values: Vec<_> = self
+            .headers
+            .iter()
+            .rev()
+            .map(|(name, values)| {
+                let value = &values[rest % values.len()];
+                rest /= values.len();
+                (name, value)
+            })
+            .collect();
+        values.reverse();
+        values
+    }
+
+    // "x-version=v1 x-region=us"
+    pub fn label(&self, combination: usize) -> String {
+        self.values(combination)
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, String::from_utf8_lossy(value.as_bytes())))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+// 헤더마다 "name: v1|v2" 를 ; 로 구분. 비어 있으면 None
+pub fn parse(value: &str) -> Result<Option<HeaderMatrix>, String> {
+    let mut headers = Vec::new();
+    for entry in value.split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (name, values) = entry.split_once(':').ok_or_else(|| format!("invalid header matrix entry '{}' (expected 'name: v1|v2')", entry))?;
+        let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| format!("invalid header matrix name '{}'", name.trim()))?;
+        let values = values
+            .split('|')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| HeaderValue::from_str(v).map_err(|_| format!("invalid header matrix value '{}'", v)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.is_empty() {
+            return Err(format!("header matrix entry '{}' needs at least one value", name));
+        }
+        if headers.iter().any(|(existing, _)| *existing == name) {
+            return Err(format!("header '{}' appears twice in the header matrix", name));
+        }
+        headers.push((name, values));
+    }
+    Ok((!headers.is_empty()).then_some(HeaderMatrix { headers }))
+}
+
+// 같은 조합이 여러 대상으로 갔으면 "a x3, b x2 (mixed)"
+fn tally(values: impl Iterator<Item = Option<String>>) -> String {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value.unwrap_or_else(|| String::from("(unknown)"))).or_default() += 1;
+    }
+    let mut text = counts.iter().map(|(name, count)| format!("{} x{}", name, count)).collect::<Vec<_>>().join(", ");
+    if counts.len() > 1 {
+        text.push_str(" (mixed)");
+    }
+    text
+}
+
+// 조합마다 응답한 업스트림과 클러스터 (라우팅 진리표)
+// 한 조합이 여러 업스트림으로 갔으면 헤더 매칭으로 경로가 정해지지 않은 것이다
+pub fn summarize(matrix: &HeaderMatrix, records: &[RequestRecord]) -> Vec<String> {
+    (0..matrix.combinations())
+        .map(|combination| {
+            // A/B 비교의 B 요청은 다른 대상이므로 제외
+            let group: Vec<&RequestRecord> = records.iter().filter(|r| r.combination == Some(combination) && r.side != Some(Side::B)).collect();
+            let label = matrix.label(combination);
+            if group.is_empty() {
+                return format!("Matrix [{}]: not sent (run at least {} requests)", label, matrix.combinations());
+            }
+            let failed = group.iter().filter(|r| !r.success).count();
+            let mut log = format!("Matrix [{}]: {} requests ({} failed) -> upstream {}", label, group.len(), failed, tally(group.iter().map(|r| r.upstream.clone())));
+            if group.iter().any(|r| r.cluster.is_some()) {
+                log.push_str(&format!(", cluster {}", tally(group.iter().map(|r| r.cluster.clone()))));
+            }
+            if let Some(status) = group.iter().rev().find_map(|r| r.status.filter(|_| !r.success)) {
+                log.push_str(&format!(" (last failure HTTP {})", status));
+            }
+            log
+        })
+        .collect()
+}
@@ -0,0 +1,74 @@
+use crossterm::event::KeyCode;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::utils::input_handling;
+
+// "name: value" 한 줄을 헤더로
+pub fn parse(line: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let Some((name, value)) = line.split_once(':') else {
+        return Err(format!("invalid header '{}' (expected 'name: value')", line.trim()));
+    };
+    let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| format!("invalid header name '{}'", name.trim()))?;
+    let value = HeaderValue::from_str(value.trim()).map_err(|_| format!("invalid value for header '{}'", name))?;
+    Ok((name, value))
+}
+
+// 사용자 헤더를 생성된 헤더 위에 덮어쓴다. 같은 이름을 여러 줄 적으면 모두 보낸다
+pub fn merge(headers: &mut HeaderMap, custom: &[String]) -> Result<(), String> {
+    let custom = custom.iter().map(|line| parse(line)).collect::<Result<Vec<_>, _>>()?;
+    for (name, _) in &custom {
+        headers.remove(name);
+    }
+    for (name, value) in custom {
+        headers.append(name, value);
+    }
+    Ok(())
+}
+
+// 사용자 헤더 편집 창 (H). 줄마다 "name: value"
+pub struct Editor {
+    pub index: usize,
+    // 선택한 줄을 고치는 중
+    pub editing: bool,
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Self { index: 0, editing: false }
+    }
+
+    // a: 추가, Enter: 편집, d: 삭제, j/k: 이동. 창을 닫아야 하면 true
+    pub fn key(&mut self, lines: &mut Vec<String>, key: KeyCode) -> bool {
+        if self.editing {
+            match key {
+                KeyCode::Enter | KeyCode::Esc => {
+                    self.editing = false;
+                    // 비워 둔 줄은 지운다
+                    if lines.get(self.index).is_some_and(|line| line.trim().is_empty()) {
+                        lines.remove(self.index);
+                        self.index = self.index.min(lines.len().saturating_sub(1));
+                    }
+                }
+                key => input_handling(&mut lines[self.index], key),
+            }
+            return false;
+        }
+        match key {
+            KeyCode::Char('a') => {
+                lines.push(String::new());
+                self.index = lines.len() - 1;
+                self.editing = true;
+            }
+            KeyCode::Enter if !lines.is_empty() => self.editing = true,
+            KeyCode::Char('d') | KeyCode::Delete if !lines.is_empty() => {
+                lines.remove(self.index);
+                self.index = self.index.min(lines.len().saturating_sub(1));
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.index + 1 < lines.len() => self.index += 1,
+            KeyCode::Up | KeyCode::Char('k') => self.index = self.index.saturating_sub(1),
+            KeyCode::Esc | KeyCode::Char('H') | KeyCode::Char('q') => return true,
+            _ => {}
+        }
+        false
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{config::RunConfig, headers, load_generator::LoadGenerator};
+
+// 자주 쓰는 옵션의 짧은 이름 (나머지는 --option-key 형태로 옵션 키를 그대로 쓴다)
+fn option_key(flag: &str) -> String {
+    match flag {
+        "url" => String::from("dst_url"),
+        "iterations" => String::from("iteration"),
+        "header-size" => String::from("header_size_kb"),
+        "output" => String::from("results_file"),
+        "control" => String::from("control_listen"),
+        flag => flag.replace('-', "_"),
+    }
+}
+
+fn usage(program: &str) -> String {
+    format!(
+        "usage: {} run [--config <config.json>] --url <url> [--iterations N] [--delay-ms N] [--header-size KB] [--protocol queryString|headerKey] [--alpn h2] [--header 'name: value' ...] [--output results.json|.csv] [--control ip:port] [--<option-key> <value> ...]",
+        program
+    )
+}
+
+// 인자를 설정으로 바꾼다. --config 파일을 먼저 읽고 나머지 옵션을 순서대로 덮어쓴다 (calibrate 하위 명령도 같이 쓴다)
+pub fn parse(args: &[String]) -> Result<RunConfig, Vec<String>> {
+    let mut config = RunConfig::default();
+    let mut errors = Vec::new();
+    if let Some(at) = args.iter().position(|a| a == "--config") {
+        let Some(path) = args.get(at + 1) else {
+            return Err(vec![String::from("--config: missing file")]);
+        };
+        let text = std::fs::read_to_string(path).map_err(|e| vec![format!("{}: {}", path, e)])?;
+        config = RunConfig::from_json(&text).map_err(|errors| errors.iter().map(|e| format!("{}: {}: {}", path, e.field, e.reason)).collect::<Vec<_>>())?;
+    }
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.strip_prefix("--") else {
+            errors.push(format!("unexpected argument '{}'", arg));
+            continue;
+        };
+        let Some(value) = args.next() else {
+            errors.push(format!("--{}: missing value", flag));
+            break;
+        };
+        if flag == "config" {
+            continue;
+        }
+        // --header 는 curl -H 처럼 여러 번 줄 수 있다
+        if flag == "header" {
+            match headers::parse(value) {
+                Ok(_) => config.custom_headers.push(value.trim().to_owned()),
+                Err(e) => errors.push(format!("--{}: {}", flag, e)),
+            }
+            continue;
+        }
+        if let Err(e) = config.apply_option(&option_key(flag), value) {
+            errors.push(format!("--{}: {}", flag, e));
+        }
+    }
+    if errors.is_empty() { Ok(config) } else { Err(errors) }
+}
+
+// run 하위 명령: TUI 없이 같은 요청 루프를 실행하고 로그를 표준 출력으로 보낸다
+// 종료 코드: 0 모든 요청 성공, 1 실패한 요청이 있거나 실행이 시작되지 못함, 2 인자/설정 오류
+pub fn run(program: &str, args: &[String]) -> i32 {
+    let config = match parse(args) {
+        Ok(config) if config.dst_url.is_empty() => {
+            eprintln!("--url is required");
+            eprintln!("{}", usage(program));
+            return 2;
+        }
+        Ok(config) => config,
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            eprintln!("{}", usage(program));
+            return 2;
+        }
+    };
+
+    let report = LoadGenerator::new(config).echo_logs(true).run();
+    let (completed, failed) = (report.completed, report.failed);
+    if completed == 0 || failed > 0 {
+        eprintln!("{} of {} requests failed", failed, completed);
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,78 @@
+use std::{collections::{BTreeMap, HashMap}, time::Duration};
+
+use reqwest::Url;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::{compare::Side, ratelimit::TokenBucket, routes::Route, stats::{format_ms, RequestRecord}};
+
+// 대상 호스트 식별자 (host:port)
+pub fn host_key(url: &Url) -> String {
+    format!("{}:{}", url.host_str().unwrap_or_default(), url.port_or_known_default().unwrap_or_default())
+}
+
+// 호스트 하나의 동시 요청 수와 초당 요청 수 상한
+struct HostLimit {
+    slots: Option<Semaphore>,
+    bucket: Option<TokenBucket>,
+}
+
+// 여러 호스트를 대상으로 할 때 호스트별 상한. 느린 호스트의 요청은 그 호스트 몫으로만 대기해
+// 다른 호스트로 가는 요청을 막지 않는다
+pub struct HostLimits {
+    hosts: HashMap<String, HostLimit>,
+}
+
+impl HostLimits {
+    // 상한이 모두 0 이면 None
+    pub fn new<'a>(urls: impl Iterator<Item = &'a Url>, concurrency: usize, rps: f64) -> Option<Self> {
+        if concurrency == 0 && rps <= 0.0 {
+            return None;
+        }
+        let hosts = urls
+            .map(|url| {
+                let limit = HostLimit {
+                    slots: (concurrency > 0).then(|| Semaphore::new(concurrency)),
+                    bucket: (rps > 0.0).then(|| TokenBucket::new(rps, 1)),
+                };
+                (host_key(url), limit)
+            })
+            .collect();
+        Some(Self { hosts })
+    }
+
+    // 호스트 상한 안에서 보낼 수 있을 때까지 대기. 돌려받은 허가는 응답을 받을 때까지 들고 있는다
+    pub async fn acquire(&self, url: &Url) -> Option<SemaphorePermit<'_>> {
+        let limit = self.hosts.get(&host_key(url))?;
+        let permit = match &limit.slots {
+            Some(slots) => slots.acquire().await.ok(),
+            None => None,
+        };
+        if let Some(bucket) = &limit.bucket {
+            bucket.acquire_async().await;
+        }
+        permit
+    }
+}
+
+// 호스트별 요청 수, 실패 수, 상한 때문에 기다린 시간 (클라이언트 대기)
+pub fn summarize(dst_url: &Url, routes: &[Route], records: &[RequestRecord]) -> Vec<String> {
+    let mut hosts: BTreeMap<String, (usize, usize, Vec<Duration>)> = BTreeMap::new();
+    // A/B 비교의 B 요청은 비교 URL 로 가므로 제외
+    for record in records.iter().filter(|r| r.side != Some(Side::B)) {
+        let url = record.route.and_then(|route| routes.get(route)).map_or(dst_url, |route| &route.url);
+        let entry = hosts.entry(host_key(url)).or_default();
+        entry.0 += 1;
+        if !record.success {
+            entry.1 += 1;
+        }
+        entry.2.push(record.queue_delay);
+    }
+    hosts
+        .into_iter()
+        .map(|(host, (count, failed, waits))| {
+            let avg = waits.iter().sum::<Duration>() / count as u32;
+            let max = waits.iter().max().copied().unwrap_or_default();
+            format!("Host {}: {} requests ({} failed) | limit wait avg {} max {}", host, count, failed, format_ms(avg), format_ms(max))
+        })
+        .collect()
+}
@@ -0,0 +1,89 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{compare::Side, stats::RequestRecord};
+
+// 중복 요청을 처리 중이거나 이미 처리했다며 거절할 때 쓰는 상태코드
+fn is_conflict(status: u16) -> bool {
+    matches!(status, 409 | 422 | 425)
+}
+
+// 같은 키를 쓴 요청 묶음의 판정
+enum Outcome {
+    // 모든 요청이 같은 업스트림에서 같은 상태코드와 본문을 받음 (프록시/업스트림이 중복을 걸러냄)
+    Deduped,
+    // 첫 요청 뒤의 중복이 모두 409/422/425 로 거절됨
+    Rejected,
+    // 요청마다 다른 응답을 받음 (중복이 그대로 처리됨)
+    Diverged,
+    // 응답을 받지 못한 요청이 있어 판정 불가
+    Incomplete,
+}
+
+fn judge(group: &[&RequestRecord]) -> Outcome {
+    if group.iter().any(|r| r.status.is_none()) {
+        return Outcome::Incomplete;
+    }
+    let first = group[0];
+    if group.iter().all(|r| r.status == first.status && r.body_hash == first.body_hash && r.upstream == first.upstream) {
+        Outcome::Deduped
+    } else if group[1..].iter().all(|r| r.status.is_some_and(is_conflict)) {
+        Outcome::Rejected
+    } else {
+        Outcome::Diverged
+    }
+}
+
+// 멱등성 키 보고: 키마다 중복 요청이 같은 응답을 받았는지, 거절됐는지, 각각 처리됐는지
+// 한 키의 요청이 여러 업스트림으로 갔으면 키 기반 라우팅이 고정되지 않은 것이다
+pub fn summarize(records: &[RequestRecord]) -> Vec<String> {
+    let mut groups: BTreeMap<&str, Vec<&RequestRecord>> = BTreeMap::new();
+    // A/B 비교의 B 요청은 다른 대상이므로 제외
+    for record in records.iter().filter(|r| r.side != Some(Side::B)) {
+        if let Some(key) = &record.idempotency_key {
+            groups.entry(key).or_default().push(record);
+        }
+    }
+    // 실행이 중간에 끝나 요청이 하나뿐인 키는 비교할 수 없다
+    groups.retain(|_, group| group.len() > 1);
+    if groups.is_empty() {
+        return Vec::new();
+    }
+
+    let (mut deduped, mut rejected, mut diverged, mut incomplete, mut split) = (0, 0, 0, 0, 0);
+    let mut examples = Vec::new();
+    for (key, group) in groups.iter_mut() {
+        // 먼저 보낸 요청이 원본, 나머지가 중복
+        group.sort_by_key(|r| r.sent_at);
+        let upstreams: BTreeSet<&str> = group.iter().filter_map(|r| r.upstream.as_deref()).collect();
+        if upstreams.len() > 1 {
+            split += 1;
+        }
+        match judge(group) {
+            Outcome::Deduped => deduped += 1,
+            Outcome::Rejected => rejected += 1,
+            Outcome::Incomplete => incomplete += 1,
+            Outcome::Diverged => {
+                diverged += 1;
+                if examples.len() < 3 {
+                    let statuses: Vec<String> = group.iter().map(|r| r.status.map_or(String::from("-"), |s| s.to_string())).collect();
+                    let upstreams: Vec<&str> = upstreams.into_iter().collect();
+                    examples.push(format!("Idempotency key {} diverged: status {} | upstreams {}", key, statuses.join("/"), upstreams.join(", ")));
+                }
+            }
+        }
+    }
+
+    let requests: usize = groups.values().map(Vec::len).sum();
+    let mut lines = vec![format!(
+        "Idempotency: {} keys ({} requests) | deduped {}, duplicates rejected {}, diverged {}, incomplete {} | keys split across upstreams {}",
+        groups.len(),
+        requests,
+        deduped,
+        rejected,
+        diverged,
+        incomplete,
+        split
+    )];
+    lines.extend(examples);
+    lines
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use h2::{Ping, PingPong};
+use http_body_util::{BodyExt, Full};
+use hyper::{header::HOST, Request};
+use hyper_util::rt::TokioIo;
+use tokio::time::timeout;
+
+use crate::{
+    config::RunConfig,
+    conn,
+    error_code::ErrorCode,
+    events::Bus,
+    stats::{self, format_ms, RequestRecord},
+    utils::RequestTemplate,
+    AppState,
+};
+
+// 유휴 연결을 붙잡아 두는 동안 보내는 keepalive
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Keepalive {
+    // 아무것도 보내지 않음 (HTTP/1.1)
+    None,
+    // HTTP/2 PING 프레임
+    H2Ping,
+    // TCP keepalive 프로브 (HTTP/1.1)
+    Tcp,
+}
+
+impl Keepalive {
+    // "off" 면 유휴 연결 실험을 하지 않는다
+    pub fn parse(value: &str) -> Result<Option<Self>, String> {
+        match value {
+            "off" => Ok(None),
+            "none" => Ok(Some(Keepalive::None)),
+            "h2-ping" => Ok(Some(Keepalive::H2Ping)),
+            "tcp" => Ok(Some(Keepalive::Tcp)),
+            _ => Err(format!("unknown idle keepalive '{}' (off/none/h2-ping/tcp)", value)),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Keepalive::None => "none",
+            Keepalive::H2Ping => "h2-ping",
+            Keepalive::Tcp => "tcp",
+        }
+    }
+}
+
+// 유휴 연결 실험: 반복마다 연결을 하나 열어 요청 하나를 보낸 뒤 유휴 상태로 두고 Envoy 가 언제 끊는지 잰다
+pub struct IdleProbe {
+    keepalive: Keepalive,
+    interval: Duration,
+    // 이 시간이 지나도 끊기지 않으면 관찰을 멈춘다
+    limit: Duration,
+}
+
+impl IdleProbe {
+    pub fn new(config: &RunConfig) -> Result<Option<Self>, String> {
+        let Some(keepalive) = Keepalive::parse(&config.idle_keepalive)? else {
+            return Ok(None);
+        };
+        if !config.compare_url.is_empty() || !config.routes_file.trim().is_empty() {
+            return Err(String::from("idle connection probe cannot be combined with A/B compare or a routes file"));
+        }
+        Ok(Some(Self { keepalive, interval: Duration::from_secs(config.idle_keepalive_s), limit: Duration::from_secs(config.idle_limit_s) }))
+    }
+}
+
+// 유휴 연결 하나가 끝난 방식
+#[derive(Clone)]
+pub enum Close {
+    // 상대가 연결을 닫음 (이유)
+    Peer(String),
+    // 관찰 시간 상한까지 열려 있음
+    Limit,
+    // 실행을 중지함
+    Stopped,
+}
+
+// 유휴 연결 하나의 결과 (첫 응답 이후 끊길 때까지의 시간, 응답받은 keepalive 수)
+#[derive(Clone)]
+pub struct IdleOutcome {
+    pub lifetime: Duration,
+    pub close: Close,
+    pub answered: usize,
+}
+
+// 연결이 끊기거나, 상한에 닿거나, 실행이 중지될 때까지 대기. H2 PING 이 있으면 주기마다 보낸다
+async fn watch(closed: impl Future<Output = String>, probe: &IdleProbe, state: &Mutex<AppState>, mut pings: Option<PingPong>) -> IdleOutcome {
+    let idle_since = Instant::now();
+    let mut next_ping = idle_since + probe.interval;
+    let mut answered = 0;
+    let mut closed = std::pin::pin!(closed);
+    let close = loop {
+        tokio::select! {
+            reason = &mut closed => break Close::Peer(reason),
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+        }
+        if idle_since.elapsed() >= probe.limit {
+            break Close::Limit;
+        }
+        if !state.lock().unwrap().running {
+            break Close::Stopped;
+        }
+        if let Some(pings) = pings.as_mut()
+            && Instant::now() >= next_ping
+        {
+            next_ping += probe.interval;
+            match timeout(probe.interval, pings.ping(Ping::opaque())).await {
+                Ok(Ok(_)) => answered += 1,
+                Ok(Err(e)) => break Close::Peer(conn::describe_h2_error(&e)),
+                // 응답 없는 PING 은 세지 않고 다음 주기에 다시 보낸다
+                Err(_) => {}
+            }
+        }
+    };
+    IdleOutcome { lifetime: idle_since.elapsed(), close, answered }
+}
+
+// HTTP/1.1 연결 (keepalive 없음 또는 TCP keepalive)
+async fn hold_h1(template: &RequestTemplate, probe: &IdleProbe, state: &Mutex<AppState>) -> Result<(u16, SocketAddr, IdleOutcome), String> {
+    let keepalive = (probe.keepalive == Keepalive::Tcp).then_some(probe.interval);
+    let (io, remote, _) = conn::open(template, &["http/1.1"], keepalive).await?;
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(io)).await.map_err(|e| e.to_string())?;
+    let mut connection = tokio::spawn(connection);
+
+    let url = template.url();
+    let request = Request::get(url.path()).header(HOST, url.host_str().unwrap_or_default()).body(Full::new(Bytes::new())).map_err(|e| e.to_string())?;
+    let status = match timeout(template.request_timeout, async {
+        let response = sender.send_request(request).await?;
+        let status = response.status().as_u16();
+        response.into_body().collect().await?;
+        Ok::<_, hyper::Error>(status)
+    })
+    .await
+    {
+        Ok(Ok(status)) => status,
+        Ok(Err(e)) => return Err(format!("request failed: {}", e)),
+        Err(_) => return Err(String::from("request timed out")),
+    };
+
+    let closed = async {
+        match (&mut connection).await {
+            Ok(Ok(())) => String::from("connection closed"),
+            Ok(Err(e)) => e.to_string(),
+            Err(e) => e.to_string(),
+        }
+    };
+    let outcome = watch(closed, probe, state, None).await;
+    connection.abort();
+    Ok((status, remote, outcome))
+}
+
+// HTTP/2 연결 (주기마다 PING)
+async fn hold_h2(template: &RequestTemplate, probe: &IdleProbe, state: &Mutex<AppState>) -> Result<(u16, SocketAddr, IdleOutcome), String> {
+    let (mut send, mut connection, remote) = conn::h2(template).await?;
+    let pings = connection.ping_pong();
+    let mut connection = tokio::spawn(connection);
+
+    let status = match timeout(template.request_timeout, async {
+        let (response, _) = send.send_request(conn::request(template), true)?;
+        let response = response.await?;
+        let status = response.status().as_u16();
+        let mut body = response.into_body();
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk?;
+            let _ = body.flow_control().release_capacity(chunk.len());
+        }
+        Ok::<_, h2::Error>(status)
+    })
+    .await
+    {
+        Ok(Ok(status)) => status,
+        Ok(Err(e)) => return Err(format!("request failed: {}", conn::describe_h2_error(&e))),
+        Err(_) => return Err(String::from("request timed out")),
+    };
+
+    let closed = async {
+        match (&mut connection).await {
+            Ok(Ok(())) => String::from("connection closed"),
+            Ok(Err(e)) => conn::describe_h2_error(&e),
+            Err(e) => e.to_string(),
+        }
+    };
+    let outcome = watch(closed, probe, state, pings).await;
+    connection.abort();
+    Ok((status, remote, outcome))
+}
+
+// 작업 스레드가 반복마다 띄우는 유휴 연결
+pub async fn hold(template: Arc<RequestTemplate>, id: String, scheduled_at: Instant, state: Arc<Mutex<AppState>>, bus: Bus) {
+    let Some(probe) = &template.idle else {
+        return;
+    };
+    let sent_at = Instant::now();
+    let mut record = RequestRecord::new(id, sent_at);
+    record.queue_delay = sent_at.duration_since(scheduled_at);
+    let result = match probe.keepalive {
+        Keepalive::H2Ping => hold_h2(&template, probe, &state).await,
+        Keepalive::None | Keepalive::Tcp => hold_h1(&template, probe, &state).await,
+    };
+    record.latency = sent_at.elapsed();
+
+    let log = match result {
+        Ok((status, remote, outcome)) => {
+            record.status = Some(status);
+            record.remote = Some(remote);
+            record.success = template.success.accepts(reqwest::StatusCode::from_u16(status).unwrap_or_default(), None);
+            if !record.success {
+                record.error = Some(ErrorCode::from_status(status));
+            }
+            let ending = match &outcome.close {
+                Close::Peer(reason) => format!("closed by peer after {}s ({})", secs(outcome.lifetime), reason),
+                Close::Limit => format!("still open after {}s (limit)", secs(outcome.lifetime)),
+                Close::Stopped => format!("stopped after {}s", secs(outcome.lifetime)),
+            };
+            let mut log = format!("Idle connection {} HTTP {}, {}", record.id, status, ending);
+            if probe.keepalive == Keepalive::H2Ping {
+                log.push_str(&format!(" [{} pings answered]", outcome.answered));
+            }
+            log.push_str(&format!(" [{}]", remote));
+            record.idle = Some(outcome);
+            log
+        }
+        Err(e) => {
+            record.error = Some(ErrorCode::Send);
+            format!("Idle connection {} Failed: {} [{}]", record.id, e, ErrorCode::Send.as_str())
+        }
+    };
+    let events = {
+        let mut app_state = state.lock().unwrap();
+        app_state.add_log(&log);
+        app_state.add_record(record)
+    };
+    bus.publish_all(events);
+}
+
+fn secs(d: Duration) -> String {
+    format!("{:.1}", d.as_secs_f64())
+}
+
+// 실행 종료 보고: 상대가 닫기까지 걸린 시간 분포와 닫힌 이유
+pub fn summarize(probe: &IdleProbe, records: &[RequestRecord]) -> Vec<String> {
+    if records.is_empty() {
+        return Vec::new();
+    }
+    let outcomes: Vec<&IdleOutcome> = records.iter().filter_map(|r| r.idle.as_ref()).collect();
+    let mut closed: Vec<Duration> = Vec::new();
+    let mut reasons: BTreeMap<&str, usize> = BTreeMap::new();
+    for outcome in &outcomes {
+        if let Close::Peer(reason) = &outcome.close {
+            closed.push(outcome.lifetime);
+            *reasons.entry(reason).or_default() += 1;
+        }
+    }
+    closed.sort();
+    let count = |f: fn(&Close) -> bool| outcomes.iter().filter(|o| f(&o.close)).count();
+
+    let keepalive = match probe.keepalive {
+        Keepalive::None => String::from("no keepalive"),
+        keepalive => format!("keepalive {} every {}s", keepalive.as_str(), probe.interval.as_secs()),
+    };
+    let mut lines = vec![format!(
+        "Idle: {} connections ({}, limit {}s) | closed by peer {}, open at limit {}, stopped {}, failed {}",
+        records.len(),
+        keepalive,
+        probe.limit.as_secs(),
+        closed.len(),
+        count(|c| matches!(c, Close::Limit)),
+        count(|c| matches!(c, Close::Stopped)),
+        records.len() - outcomes.len(),
+    )];
+    if !closed.is_empty() {
+        let reasons: Vec<String> = reasons.iter().map(|(reason, n)| format!("{} x{}", reason, n)).collect();
+        lines.push(format!(
+            "Idle close after: min {} p50 {} max {} | {}",
+            format_ms(closed[0]),
+            format_ms(stats::percentile(&closed, 50.0)),
+            format_ms(closed[closed.len() - 1]),
+            reasons.join(", ")
+        ));
+    }
+    // Envoy 의 idle_timeout 은 활성 스트림 기준이라 PING 에 응답하더라도 타이머가 초기화되지 않는다
+    if probe.keepalive == Keepalive::H2Ping && !outcomes.is_empty() {
+        let answered: usize = outcomes.iter().map(|o| o.answered).sum();
+        lines.push(format!("Idle pings answered: {} over {} connections", answered, outcomes.len()));
+    }
+    lines
+}
@@ -0,0 +1,56 @@
+use std::{collections::HashMap, net::SocketAddr};
+
+use crate::{
+    als::{ProxyLog, ProxyLogs},
+    compare::Side,
+    stats::RequestRecord,
+};
+
+// 로그 한 줄이 가리키는 요청 id 와 비교 실행의 대상
+// "[12:00:00.000000] [B] Request abc Succeded ..." 형식 (Response ... Failed 도 같은 위치에 id)
+pub fn request_ref(log: &str) -> Option<(&str, Option<Side>)> {
+    let (_, rest) = log.split_once("] ")?;
+    let (side, rest) = match rest.split_at_checked(4) {
+        Some(("[A] ", rest)) => (Some(Side::A), rest),
+        Some(("[B] ", rest)) => (Some(Side::B), rest),
+        _ => (None, rest),
+    };
+    let rest = rest.strip_prefix("Request ").or_else(|| rest.strip_prefix("Response "))?;
+    let id = rest.split(' ').next().filter(|id| !id.is_empty())?;
+    Some((id, side))
+}
+
+// 인스펙터에서 다시 보낸 요청 (result 가 None 이면 응답 대기 중)
+pub struct Resend {
+    pub id: String,
+    pub side: Option<Side>,
+    pub result: Option<RequestRecord>,
+}
+
+// 로그에서 고른 요청 하나의 상세 창
+pub struct Inspector {
+    pub log: String,
+    pub record: RequestRecord,
+    // 요청이 도착한 주소에서 받은 HTTP/2 SETTINGS
+    pub settings: Option<Result<String, String>>,
+    // Envoy 접근 로그 (als_listen)
+    pub proxy: Option<ProxyLog>,
+    // 다시 보낸 결과 (None: 보내지 않음, Some(None): 응답 대기 중)
+    pub resend: Option<Option<RequestRecord>>,
+}
+
+impl Inspector {
+    // 요청 결과 줄이 아니거나 기록이 지워졌으면 None
+    pub fn open(log: &str, records: &[RequestRecord], h2_settings: &HashMap<SocketAddr, Option<Result<String, String>>>, proxy_logs: &ProxyLogs) -> Option<Self> {
+        let (id, side) = request_ref(log)?;
+        let record = records.iter().rev().find(|r| r.id == id && r.side == side)?;
+        let settings = record.remote.and_then(|remote| h2_settings.get(&remote).cloned().flatten());
+        let proxy = proxy_logs.get(&record.id).cloned();
+        Some(Self { log: log.to_owned(), record: record.clone(), settings, proxy, resend: None })
+    }
+
+    // 이 요청을 다시 보낸 결과만 가져온다
+    pub fn sync(&mut self, resend: Option<&Resend>) {
+        self.resend = resend.filter(|r| r.id == self.record.id && r.side == self.record.side).map(|r| r.result.clone());
+    }
+}
@@ -0,0 +1,55 @@
+use std::{collections::BTreeSet, time::Instant};
+
+use crate::stats::RequestRecord;
+
+// least-request 검증: 지연 헤더를 붙인 요청이 처리 중인 업스트림(느려진 호스트)으로
+// 일반 요청이 덜 가는지 확인한다. 균등 분배일 때의 기대 비율과 실제 비율을 비교
+pub struct Shift {
+    // 느려진 호스트가 있던 동안 보낸 일반 요청 수
+    pub exposed: usize,
+    // 그중 느려진 호스트로 간 요청 수
+    pub to_slowed: usize,
+    // 균등 분배였다면 느려진 호스트로 갔을 기대 요청 수
+    pub expected: f64,
+}
+
+impl Shift {
+    pub fn describe(&self) -> String {
+        if self.exposed == 0 {
+            return String::from("no requests were sent while a host was slowed");
+        }
+        let observed = self.to_slowed as f64 * 100.0 / self.exposed as f64;
+        let expected = self.expected * 100.0 / self.exposed as f64;
+        format!(
+            "slowed hosts got {:.1}% of {} requests (uniform {:.1}%, shift {:+.1}pp)",
+            observed, self.exposed, expected, observed - expected
+        )
+    }
+}
+
+// from..to 사이에 보낸 일반 요청 분석. 업스트림을 식별한 응답만 사용
+pub fn analyze(records: &[RequestRecord], from: Instant, to: Instant) -> Shift {
+    let upstreams: BTreeSet<&str> = records.iter().filter_map(|r| r.upstream.as_deref()).collect();
+    let slow: Vec<(&str, Instant, Instant)> = records
+        .iter()
+        .filter(|r| r.slowed)
+        .filter_map(|r| r.upstream.as_deref().map(|u| (u, r.sent_at, r.sent_at + r.latency)))
+        .collect();
+
+    let mut shift = Shift { exposed: 0, to_slowed: 0, expected: 0.0 };
+    for record in records.iter().filter(|r| !r.slowed && r.sent_at >= from && r.sent_at < to) {
+        let Some(upstream) = record.upstream.as_deref() else {
+            continue;
+        };
+        let slowed: BTreeSet<&str> = slow.iter().filter(|(_, start, end)| *start <= record.sent_at && record.sent_at < *end).map(|(u, _, _)| *u).collect();
+        if slowed.is_empty() {
+            continue;
+        }
+        shift.exposed += 1;
+        shift.expected += slowed.len() as f64 / upstreams.len() as f64;
+        if slowed.contains(upstream) {
+            shift.to_slowed += 1;
+        }
+    }
+    shift
+}
@@ -0,0 +1,1702 @@
+mod alpn;
+mod als;
+mod bench;
+mod calibrate;
+mod canary;
+mod commands;
+mod compare;
+mod config;
+mod control;
+mod conn_log;
+mod conn;
+mod deadline;
+mod distribution;
+mod drain;
+mod echo;
+mod engine;
+mod envoy_stats;
+mod events;
+mod error_code;
+mod export;
+mod fault;
+mod frames;
+mod graphql;
+mod header_matrix;
+mod headers;
+mod headless;
+mod hosts;
+mod idempotency;
+mod idle;
+mod inspector;
+mod least_request;
+mod load_generator;
+mod logview;
+mod longpoll;
+mod markers;
+mod metrics;
+mod mismatch;
+mod notify;
+mod payload;
+mod ping;
+mod preflight;
+mod priority;
+mod probe;
+mod profiles;
+mod proxy_protocol;
+mod query;
+mod queue;
+mod ratelimit;
+mod replay;
+mod retention;
+mod routes;
+mod session;
+mod settings;
+mod share_alert;
+mod sinks;
+mod sni;
+mod stats;
+mod streams;
+mod success;
+mod tcp;
+mod terminal;
+mod timing;
+mod tls;
+mod transcode;
+mod transport;
+mod udp;
+mod utils;
+mod ui;
+mod wizard;
+
+// 단순 주석 추가 테스트
+use std::{collections::HashMap, fs::{self, File}, io::{self, LineWriter, Write}, net::SocketAddr, path::Path, sync::{mpsc, Arc, Mutex}, thread, time::{Duration, Instant}};
+use tokio::runtime::Runtime;
+use chrono::{DateTime, Local};
+use color_eyre::eyre;
+use crossterm::{
+    event::{self, Event, KeyCode},
+};
+
+
+use ratatui::Terminal;
+use config::{CONFIG_FILE, OPTIONS, PROTOCOLS};
+use markers::{MarkerKind, RunMarker};
+use utils::*;
+use ui::ui;
+
+// 라이브러리 API: TTY 없이 실행하고 결과를 받는다 (통합 테스트도 이 면만 쓴다)
+pub use config::RunConfig;
+pub use load_generator::{LoadGenerator, Report};
+pub use stats::RequestRecord;
+
+// 동시에 열 수 있는 워크스페이스 탭 수
+const MAX_WORKSPACES: usize = 9;
+
+// 작업 스레드와 공유할 상태
+pub struct AppState {
+    running: bool,
+    // 실행값
+    config: RunConfig,
+    // 로그 (마지막 줄들만 남긴다. logged 는 지금까지 남긴 줄 수)
+    logs: Vec<String>,
+    logged: usize,
+    // 로그마다 남긴 시각 (UTC 밀리초, 재생에서 커서 시점까지의 로그를 고를 때)
+    log_times: Vec<i64>,
+    // replay 명령으로 연 재생 (화면이 가져간다)
+    pending_replay: Option<replay::Replay>,
+    // 요청별 결과와 구간 집계로 합친 오래된 결과 (요청별 결과는 실행 결과 이벤트와 나눠 쓴다)
+    records: Arc<Vec<stats::RequestRecord>>,
+    history: retention::History,
+    // 이번 실행에서 순환하는 경로 목록
+    routes: Vec<routes::Route>,
+    // 요청 id -> 응답한 업스트림 (헤더가 없으면 None)
+    upstreams: HashMap<String, Option<String>>,
+    // 실행 마커 (웹훅 전송 대기 포함)
+    markers: Vec<RunMarker>,
+    pending_markers: Vec<RunMarker>,
+    // 이번 실행의 실패 횟수
+    failures: usize,
+    // 헤지 지연 (헤지를 사용하지 않으면 None)
+    hedge_delay: Option<Duration>,
+    // 진행 중인 드레인 구간과 이번 실행의 드레인 요약
+    drain: Option<drain::DrainWindow>,
+    drain_summary: drain::DrainSummary,
+    // 연속 프로브 모드의 진행 중인 장애 구간과 요약
+    disruption: Option<probe::DisruptionWindow>,
+    disruption_summary: probe::DisruptionSummary,
+    // 작업 스레드가 띄울 이번 실행의 구독 작업 (결과 싱크, 업스트림 비율 경보)
+    subscribers: Vec<events::Subscriber>,
+    // 작업 스레드가 보낼 HTTP 전송 (chaos 명령)
+    pending_posts: Vec<sinks::Post>,
+    // 시작 전 사전 점검 (결과 확인 후 실행)
+    preflight: Option<PreflightState>,
+    // 실행 큐에 쌓아 둔 단계와 진행 중인 큐 실행
+    queue: Vec<queue::Stage>,
+    queue_run: Option<queue::QueueRun>,
+    // 워크스페이스 탭을 닫으면 작업 스레드 종료
+    closed: bool,
+    // 마지막 실행의 요청 템플릿 (대상, 비교 대상). 인스펙터에서 요청을 다시 보낼 때 사용
+    templates: Option<(Arc<RequestTemplate>, Option<Arc<RequestTemplate>>)>,
+    // 인스펙터에서 마지막으로 다시 보낸 요청
+    resend: Option<inspector::Resend>,
+    // 마지막 업스트림 비율 경보 (상태 표시줄)
+    alert: Option<String>,
+    // h2 PING 으로 잰 연결 수준 RTT 표본
+    pings: Vec<ping::PingSample>,
+    // 카나리 요청 결과 (부하 전 기준 표본 포함)
+    canaries: Vec<canary::CanarySample>,
+    // 주소별로 받은 HTTP/2 SETTINGS 요약 (None: 받는 중)
+    h2_settings: HashMap<SocketAddr, Option<Result<String, String>>>,
+    // 헤드리스 실행이면 로그를 표준 출력으로도 내보낸다
+    echo_logs: bool,
+    // 이번 실행에서 보낸 요청 수 (응답을 기다리는 요청 포함, A/B 비교는 대상마다 하나)
+    sent: usize,
+    // 이번 실행에서 429 로 거절된 요청 수 (속도 모드 보고)
+    rate_limited: usize,
+    // Envoy 가 보낸 요청별 접근 로그와 수신기 (als_listen)
+    proxy_logs: als::ProxyLogs,
+    als_listener: Option<als::Listener>,
+    // 읽기 전용 제어 API 수신기 (control_listen)
+    control_listener: Option<control::Listener>,
+    // 로그를 이어 쓰는 파일 (log_dir, 연 폴더)
+    log_file: Option<(String, LineWriter<File>)>,
+}
+
+pub struct PreflightState {
+    config: RunConfig,
+    // 점검이 끝나기 전에는 None
+    results: Option<Vec<preflight::CheckResult>>,
+}
+
+impl AppState {
+    fn new() -> Self {
+        Self {
+            running: false,
+            config: RunConfig::default(),
+            logs: Vec::new(),
+            logged: 0,
+            log_times: Vec::new(),
+            pending_replay: None,
+            records: Arc::default(),
+            history: retention::History::default(),
+            routes: Vec::new(),
+            upstreams: HashMap::new(),
+            markers: Vec::new(),
+            pending_markers: Vec::new(),
+            failures: 0,
+            hedge_delay: None,
+            drain: None,
+            drain_summary: drain::DrainSummary::default(),
+            disruption: None,
+            disruption_summary: probe::DisruptionSummary::default(),
+            subscribers: Vec::new(),
+            pending_posts: Vec::new(),
+            preflight: None,
+            queue: Vec::new(),
+            queue_run: None,
+            closed: false,
+            templates: None,
+            resend: None,
+            alert: None,
+            pings: Vec::new(),
+            canaries: Vec::new(),
+            h2_settings: HashMap::new(),
+            echo_logs: false,
+            sent: 0,
+            rate_limited: 0,
+            proxy_logs: als::ProxyLogs::default(),
+            als_listener: None,
+            control_listener: None,
+            log_file: None,
+        }
+    }
+
+    pub fn add_log(&mut self, log: &str) {
+        self.add_log_at(Local::now(), log);
+    }
+
+    // 구독 작업이 버스에 남긴 로그와 경보를 옮긴다
+    pub fn follow_reports(&mut self, bus: &events::Bus) {
+        for report in bus.take_reports() {
+            match report {
+                events::Report::Log(line) => self.add_log(&line),
+                events::Report::Alert(alert) => {
+                    self.add_log(&alert);
+                    self.alert = Some(format!("[{}] {}", Local::now().format("%H:%M:%S"), alert));
+                }
+            }
+        }
+    }
+
+    // 실행 종료 보고 중 요청별 결과로 계산한 줄. 오래된 결과를 구간 집계로 합쳤으면 남은 창만 본 것이라 표시한다
+    pub fn add_report(&mut self, log: &str) {
+        match self.history.folded() {
+            0 => self.add_log(log),
+            _ => self.add_log(&format!("(last window only) {}", log)),
+        }
+    }
+
+    // 일어난 시각을 따로 아는 로그 (전송 계층이 쌓아 둔 연결 이벤트)
+    fn add_log_at(&mut self, at: DateTime<Local>, log: &str) {
+        let timestamp = at.format("%H:%M:%S%.6f").to_string();
+        let line = format!("[{}] {}", timestamp, log);
+        if self.echo_logs {
+            println!("{}", line);
+        }
+        if let Some((_, file)) = self.log_file.as_mut()
+            && let Err(e) = writeln!(file, "{}", line)
+        {
+            self.log_file = None;
+            self.add_log(&format!("Log file disabled: {}", e));
+        }
+        self.logs.push(line);
+        self.log_times.push(at.timestamp_millis());
+        self.logged += 1;
+
+        if self.logs.len() > 3000 {
+            let excess = self.logs.len() - 3000;
+            self.logs.drain(0..excess);
+            self.log_times.drain(0..excess);
+        }
+    }
+
+    // log_dir 이 정해져 있으면 시각을 붙인 파일을 열고 지금까지의 로그부터 쓴다. 같은 폴더면 이어 쓴다
+    fn tee_logs(&mut self) {
+        let dir = self.config.log_dir.clone();
+        if dir.is_empty() {
+            self.log_file = None;
+            return;
+        }
+        if self.log_file.as_ref().is_some_and(|(open, _)| *open == dir) {
+            return;
+        }
+        let path = Path::new(&dir).join(format!("envoy-lb-client-{}.log", Local::now().format("%Y%m%d-%H%M%S")));
+        let opened = fs::create_dir_all(&dir).and_then(|()| File::options().create(true).append(true).open(&path)).and_then(|file| {
+            let mut file = LineWriter::new(file);
+            for line in &self.logs {
+                writeln!(file, "{}", line)?;
+            }
+            Ok(file)
+        });
+        match opened {
+            Ok(file) => {
+                self.log_file = Some((dir, file));
+                self.add_log(&format!("Logging to {}", path.display()));
+            }
+            Err(e) => {
+                self.log_file = None;
+                self.add_log(&format!("Log file disabled: {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    // 마지막 실행의 템플릿(대상, 비교 대상)이 쌓는 연결 이벤트
+    fn conn_events(&self) -> Vec<conn_log::ConnEvents> {
+        self.templates.iter().flat_map(|(target, compare)| [Some(target), compare.as_ref()]).flatten().filter_map(|t| t.conn_events.clone()).collect()
+    }
+
+    // 쌓인 연결 이벤트를 일어난 순서대로 로그에 옮긴다
+    fn log_conn_events(&mut self, queues: &[conn_log::ConnEvents]) {
+        let mut events: Vec<_> = queues.iter().flat_map(|queue| queue.drain()).collect();
+        events.sort_by_key(|(at, _)| *at);
+        for (at, event) in events {
+            self.add_log_at(at, &event);
+        }
+    }
+
+    pub fn add_marker(&mut self, kind: MarkerKind, text: &str) {
+        let marker = RunMarker::new(kind, text);
+        self.add_log(&format!("Marker [{}] {} (t={})", kind.as_str(), text, marker.time_ms));
+
+        if !self.config.markers_file.is_empty()
+            && let Err(e) = markers::append_to_file(&self.config.markers_file, &marker)
+        {
+            self.add_log(&format!("Failed to write marker file: {}", e));
+        }
+        if !self.config.annotation_url.is_empty() {
+            self.pending_markers.push(marker.clone());
+        }
+        self.markers.push(marker);
+    }
+
+    // 알림 이벤트와 그 시점의 요약. 부르는 쪽이 잠금을 푼 뒤 버스에 내보낸다
+    pub fn notify(&self, title: &str) -> events::Event {
+        let summary = stats::summarize(&self.records, &self.config.percentiles);
+        events::Event::Notice { title: title.to_owned(), summary }
+    }
+
+    // 실행이 끝나거나 중지됐을 때의 결과 이벤트 (싱크마다 요약과 구간별 집계를 쓴다)
+    pub fn export(&self) -> events::Event {
+        let results = events::RunResults {
+            config: self.config.clone(),
+            records: self.records.clone(),
+            history: self.history.clone(),
+            proxy_logs: self.proxy_logs.clone(),
+            logs: self.timed_logs(),
+            markers: self.markers.clone(),
+        };
+        events::Event::RunCompleted(Arc::new(results))
+    }
+
+    // 지금까지의 요청별 결과와 요약을 저장 (results_file 이 없으면 시각을 붙인 JSON 파일)
+    pub fn export_results(&mut self) {
+        if self.records.is_empty() && self.history.totals(&self.records).is_none() {
+            self.add_log("No results to export yet");
+            return;
+        }
+        let path = match self.config.results_file.as_str() {
+            "" => format!("results-{}.json", Local::now().format("%Y%m%d-%H%M%S")),
+            path => path.to_owned(),
+        };
+        let log = match export::write_results(&path, &self.config.dst_url, &self.records, &self.history, &self.proxy_logs, &self.markers, &self.config.percentiles) {
+            Ok(count) => format!("Exported {} request result(s) to {}", count, path),
+            Err(e) => format!("Failed to write results file: {}", e),
+        };
+        self.add_log(&log);
+        let interval = Duration::from_secs(self.config.export_interval_s);
+        let log = match replay::save(&path, &self.records, &self.history, &self.timed_logs(), interval, &self.config.percentiles) {
+            Ok(replay) => format!("Saved the replay to {} (open it with ':replay {}')", replay, replay),
+            Err(e) => format!("Failed to save the replay: {}", e),
+        };
+        self.add_log(&log);
+    }
+
+    // 로그와 남긴 시각
+    fn timed_logs(&self) -> Vec<(i64, String)> {
+        self.log_times.iter().copied().zip(self.logs.iter().cloned()).collect()
+    }
+
+    // 업스트림 분배와 공정성 지표를 CSV, SVG 로 저장 (파일 이름에 시각을 붙여 덮어쓰지 않음)
+    pub fn export_upstreams(&mut self) {
+        let upstreams = stats::by_upstream(&self.records);
+        if upstreams.is_empty() {
+            self.add_log("No upstream data to export (check the upstream header option)");
+            return;
+        }
+        let base = format!("upstreams-{}", Local::now().format("%Y%m%d-%H%M%S"));
+        let title = format!("Upstream distribution: {}", self.config.dst_url);
+        let log = match distribution::write(&upstreams, &base, &title) {
+            Ok((csv, svg)) => {
+                let fairness = distribution::fairness(&upstreams).map(|f| f.describe()).unwrap_or_default();
+                format!("Exported upstream distribution to {} and {} ({})", csv, svg, fairness)
+            }
+            Err(e) => format!("Failed to export upstream distribution: {}", e),
+        };
+        self.add_log(&log);
+    }
+
+    // 요청 결과와 집계 초기화 (새 실행 시작, 세션 초기화)
+    fn reset_stats(&mut self) {
+        // 지난 실행의 결과를 아직 구독자가 들고 있을 수 있으므로 비우지 않고 바꾼다
+        self.records = Arc::default();
+        self.history = retention::History::new(self.config.raw_window_s, self.config.memory_budget_mb, Duration::from_secs(self.config.export_interval_s));
+        self.upstreams.clear();
+        self.proxy_logs.clear();
+        self.routes.clear();
+        self.failures = 0;
+        self.drain = None;
+        self.drain_summary = drain::DrainSummary::default();
+        self.disruption = None;
+        self.disruption_summary = probe::DisruptionSummary::default();
+        self.markers.clear();
+        self.resend = None;
+        self.alert = None;
+        self.pings.clear();
+        self.canaries.clear();
+        self.h2_settings.clear();
+        self.sent = 0;
+        self.rate_limited = 0;
+    }
+
+    // 로그와 집계를 비워 다음 실험을 깨끗한 상태에서 시작 (실행 중에는 불가)
+    pub fn clear_session(&mut self) {
+        if self.running || self.preflight.is_some() {
+            self.add_log("Stop the run before clearing logs and statistics");
+            return;
+        }
+        self.logs.clear();
+        self.log_times.clear();
+        self.reset_stats();
+        self.add_log("Logs and statistics cleared");
+    }
+
+    // 보낸 요청 수. 비교 실행은 요청마다 A, B 두 개를 보내므로 지표 창과 제어 API 에는 A 쪽만 센다
+    fn sent_primary(&self) -> usize {
+        if self.config.compare_url.is_empty() { self.sent } else { self.sent / 2 }
+    }
+
+    // 이번 실행에서 끝난 요청 수 (구간 집계로 합친 결과 포함)
+    fn completed(&self) -> usize {
+        self.records.len() + self.history.folded()
+    }
+
+    // 요청 결과 기록. 첫 실패 알림과 실패 횟수 기준 중단을 함께 처리
+    // 돌려준 이벤트 (RequestFinished, 알림) 는 부르는 쪽이 잠금을 푼 뒤 버스에 내보낸다
+    pub fn add_record(&mut self, record: stats::RequestRecord) -> Vec<events::Event> {
+        self.track_drain(&record);
+        if self.config.probe_interval_ms > 0 {
+            self.track_disruption(&record);
+        }
+        let mut events = vec![events::Event::RequestFinished(Arc::new(record.clone()))];
+        let success = record.success;
+        if record.status == Some(429) {
+            self.rate_limited += 1;
+        }
+        // 비교 실행의 B 는 같은 요청 id 를 쓰므로 A 쪽 업스트림만 남긴다
+        if record.primary() {
+            self.upstreams.insert(record.id.clone(), record.upstream.clone());
+        }
+        let records = Arc::make_mut(&mut self.records);
+        records.push(record);
+        for record in self.history.retain(records).iter().filter(|r| r.primary()) {
+            self.upstreams.remove(&record.id);
+            self.proxy_logs.forget(&record.id);
+        }
+        if success || !self.running {
+            return events;
+        }
+
+        self.failures += 1;
+        if self.failures == 1 {
+            events.push(self.notify("encountered its first error"));
+        }
+        if self.config.abort_after_failures > 0 && self.failures >= self.config.abort_after_failures {
+            self.running = false;
+            self.add_log(&format!("Process Aborted: {} failures reached", self.failures));
+            self.add_marker(MarkerKind::Stop, "Process Aborted");
+            events.push(self.notify(&format!("aborted after {} failures", self.failures)));
+        }
+        events
+    }
+}
+
+impl AppState {
+    // 드레인 신호가 처음 보이면 구간 시작, 마지막 신호 이후에 보낸 요청이 신호 없이 성공하면 구간 종료
+    fn track_drain(&mut self, record: &stats::RequestRecord) {
+        match (record.drain, self.drain.as_mut()) {
+            (Some(_), Some(window)) => {
+                window.signals += 1;
+                window.started = window.started.min(record.sent_at);
+                window.last_signal = window.last_signal.max(record.sent_at);
+            }
+            (Some(reason), None) => {
+                self.drain = Some(drain::DrainWindow { started: record.sent_at, last_signal: record.sent_at, signals: 1, reason });
+                self.add_marker(MarkerKind::DrainStart, &format!("Drain detected ({})", reason));
+            }
+            (None, Some(window)) if record.success && record.sent_at > window.last_signal => self.end_drain(record.sent_at),
+            _ => {}
+        }
+    }
+
+    pub fn end_drain(&mut self, ended: Instant) {
+        if let Some(window) = self.drain.take() {
+            let duration = ended.saturating_duration_since(window.started);
+            self.drain_summary.windows += 1;
+            self.drain_summary.total += duration;
+            self.drain_summary.signals += window.signals;
+            self.add_marker(
+                MarkerKind::DrainEnd,
+                &format!("Drain window ended after {} ({} signals, first: {})", stats::format_ms(duration), window.signals, window.reason),
+            );
+        }
+    }
+}
+
+impl AppState {
+    // 실패가 처음 보이면 장애 구간 시작, 마지막 실패 이후에 보낸 요청이 성공하면 구간 종료
+    fn track_disruption(&mut self, record: &stats::RequestRecord) {
+        match (record.success, self.disruption.as_mut()) {
+            (false, Some(window)) => {
+                window.errors += 1;
+                window.started = window.started.min(record.sent_at);
+                window.last_error = window.last_error.max(record.sent_at);
+            }
+            (false, None) => {
+                self.disruption = Some(probe::DisruptionWindow::new(record.sent_at));
+                self.add_marker(MarkerKind::DisruptionStart, &format!("Disruption started at {}", probe::format_time(record.sent_at)));
+            }
+            (true, Some(window)) if record.sent_at > window.last_error => self.end_disruption(record.sent_at),
+            _ => {}
+        }
+    }
+
+    pub fn end_disruption(&mut self, ended: Instant) {
+        if let Some(window) = self.disruption.take() {
+            let duration = ended.saturating_duration_since(window.started);
+            self.disruption_summary.windows += 1;
+            self.disruption_summary.longest = self.disruption_summary.longest.max(duration);
+            self.disruption_summary.errors += window.errors;
+            self.add_marker(
+                MarkerKind::DisruptionEnd,
+                &format!(
+                    "Disruption {} ~ {}: {} with {} errors",
+                    probe::format_time(window.started), probe::format_time(ended), stats::format_ms(duration), window.errors
+                ),
+            );
+        }
+    }
+}
+
+// 애플리케이션 상태
+#[derive(PartialEq, Eq)]
+enum InputMode {
+    Normal,
+    EditingDstUrl,
+    EditingDelay,
+    EditingHeaderSize,
+    EditingIteration,
+    EditingConcurrency,
+    EditingOption,
+    // 로그 검색어 입력 (/)
+    SearchingLog,
+}
+
+// 부하 모델 (지연 입력창에서 ←/→ 로 바꾼다)
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LoadModel {
+    // 요청마다 정해진 간격
+    Delay,
+    // 목표 초당 요청 수 (응답 지연과 상관없이 일정한 속도로 보낸다)
+    Rate,
+}
+
+// 옵션 창의 한 항목
+pub struct OptionField {
+    key: &'static str,
+    label: &'static str,
+    value: String,
+}
+
+struct App {
+    // 입력 필드
+    dst_url: String,
+    delay_ms: String,
+    header_size_kb: String,
+    iteration: String,
+    concurrency: String,
+    // 지연 입력창이 받는 값 (Rate 면 rps 입력)
+    load_model: LoadModel,
+    rps: String,
+    // 요청 값을 싣는 위치 (0 = queryString, 1 = headerKey). HTTP 버전은 alpn 옵션으로 고른다
+    protocol_index: usize,
+    protocols: Vec<&'static str>,
+    // 현재 입력 모드
+    input_mode: InputMode,
+    // 로그 메시지
+    logs: Vec<String>,
+    // 로그 스크롤 위치
+    log_scroll: usize,
+    // 로그 영역에 보일 줄 (f) 과 검색어 (/)
+    log_filter: logview::LogFilter,
+    log_search: String,
+    // 실행 중 여부
+    running: bool,
+    // 포커스된 항목 (0: 주소입력창, 1: 지연시간, 2: 헤더 크기, 3: 반복 횟수, 4: 동시 요청 수, 5: HTTP 프로토콜, 6: 실행 버튼, 7: 로그 영역)
+    focused_item: usize,
+    // 사전 점검 창 (None: 닫힘, Some(None): 점검 중)
+    preflight: Option<Option<Vec<preflight::CheckResult>>>,
+    // 고급 옵션 창
+    options: Vec<OptionField>,
+    option_index: usize,
+    show_options: bool,
+    // 모든 요청에 붙이는 사용자 헤더 ("name: value") 와 편집 창
+    custom_headers: Vec<String>,
+    header_editor: Option<headers::Editor>,
+    // 명령 팔레트 입력 (None: 닫힘)
+    command: Option<String>,
+    // 업스트림별 지연 비교
+    upstream_stats: Vec<stats::UpstreamStats>,
+    // 경로별 통과/실패
+    route_results: Vec<routes::RouteResult>,
+    // 최근 요청의 (프록시 구간, 업스트림 처리 시간) ms
+    attribution: Vec<(u64, u64)>,
+    // 최근 PING RTT 와 요청 지연 (us)
+    pings: (Vec<u64>, Vec<u64>),
+    // 보낸/성공/실패 수, 초당 처리량, 지연 백분위
+    metrics: Option<metrics::LiveMetrics>,
+    // A/B 비교 실행의 대상별 지표 (A, B)
+    compare: Option<(compare::SideStats, compare::SideStats)>,
+    // 끝난 실행 재생 (None: 실시간 화면)
+    replay: Option<replay::Replay>,
+    // 로그에서 고른 요청의 상세 창
+    inspector: Option<inspector::Inspector>,
+    // 마지막 업스트림 비율 경보
+    alert: Option<String>,
+    // 관찰 모드면 붙은 곳과 연결 상태 (입력과 실행 조작은 막는다)
+    observing: Option<String>,
+    // 첫 실행 안내 (설정 파일이 없을 때)
+    wizard: Option<wizard::Wizard>,
+    // 마지막으로 불러오거나 저장한 프로필 이름
+    profile: Option<String>,
+    // 색 없이 글자 표시로 그리는 화면 (--no-color, NO_COLOR, plain_ui 옵션)
+    plain: bool,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            dst_url: String::from(""),
+            delay_ms: String::from("100"),
+            header_size_kb: String::from("1"),
+            iteration: String::from("1"),
+            concurrency: String::from("0"),
+            load_model: LoadModel::Delay,
+            rps: String::from("100"),
+            protocol_index: 0,
+            protocols: PROTOCOLS.to_vec(),
+            input_mode: InputMode::Normal,
+            logs: Vec::new(),
+            log_scroll: 0,
+            log_filter: logview::LogFilter::All,
+            log_search: String::new(),
+            running: false,
+            focused_item: 0,
+            preflight: None,
+            options: OPTIONS
+                .iter()
+                .map(|(key, label, default)| OptionField { key, label, value: default.to_string() })
+                .collect(),
+            option_index: 0,
+            show_options: false,
+            custom_headers: Vec::new(),
+            header_editor: None,
+            command: None,
+            upstream_stats: Vec::new(),
+            route_results: Vec::new(),
+            attribution: Vec::new(),
+            pings: (Vec::new(), Vec::new()),
+            metrics: None,
+            compare: None,
+            replay: None,
+            inspector: None,
+            alert: None,
+            observing: None,
+            wizard: None,
+            profile: None,
+            plain: false,
+        }
+    }
+}
+
+impl App {
+    // 실행 중 set 명령으로 바뀐 부하 값을 입력창과 옵션 창에 옮긴다 (다음 실행도 같은 값으로 시작)
+    fn follow(&mut self, config: &RunConfig) {
+        self.delay_ms = config.delay_ms.to_string();
+        self.concurrency = config.concurrency.to_string();
+        if config.rps > 0.0 {
+            self.rps = config.rps.to_string();
+            self.load_model = LoadModel::Rate;
+        } else {
+            self.load_model = LoadModel::Delay;
+        }
+        for option in self.options.iter_mut().filter(|option| option.key == "rps_burst") {
+            option.value = config.rps_burst.to_string();
+        }
+    }
+
+    // 옵션 창의 plain_ui 값 (읽을 수 없는 값이면 꺼짐)
+    fn plain_ui(&self) -> bool {
+        let mut config = RunConfig::default();
+        self.options.iter().any(|option| option.key == "plain_ui" && config.apply_option(option.key, &option.value).is_ok() && config.plain_ui)
+    }
+
+    // 숫자 입력창이 비었거나 읽을 수 없어 기본값을 쓰게 되면 그 값
+    fn fallback(&self, index: usize) -> Option<String> {
+        let defaults = RunConfig::default();
+        match index {
+            1 if self.load_model == LoadModel::Rate && !self.rps.parse::<f64>().is_ok_and(|rps| rps > 0.0) => {
+                Some(format!("{} ms delay", self.delay_ms.parse::<u64>().unwrap_or(defaults.delay_ms)))
+            }
+            1 if self.load_model == LoadModel::Delay && self.delay_ms.parse::<u64>().is_err() => Some(format!("{} ms", defaults.delay_ms)),
+            2 if self.header_size_kb.parse::<usize>().is_err() => Some(format!("{} KB", defaults.header_size_kb)),
+            3 if self.iteration.parse::<usize>().is_err() => Some(defaults.iteration.to_string()),
+            4 if self.concurrency.parse::<usize>().is_err() => Some(defaults.concurrency.to_string()),
+            _ => None,
+        }
+    }
+
+    // 기본값으로 바뀌는 입력창마다 경고 (실행 로그와 상태 표시줄)
+    fn input_warnings(&self) -> Vec<String> {
+        let load = match self.load_model {
+            LoadModel::Delay => (1, "Delay", &self.delay_ms),
+            LoadModel::Rate => (1, "Target RPS", &self.rps),
+        };
+        [load, (2, "Header size", &self.header_size_kb), (3, "Iteration", &self.iteration), (4, "Concurrency", &self.concurrency)]
+            .into_iter()
+            .filter_map(|(index, label, text)| {
+                let value = self.fallback(index)?;
+                Some(if text.is_empty() {
+                    format!("{} is empty, using {}", label, value)
+                } else {
+                    format!("{} '{}' is not a valid number, using {}", label, text, value)
+                })
+            })
+            .collect()
+    }
+
+    // 숫자 입력창 한 단계 (지연과 RPS 는 10 씩)
+    fn step_size(&self, index: usize) -> u64 {
+        if index == 1 { 10 } else { 1 }
+    }
+
+    // 편집 모드에 들어가지 않고 숫자 입력창을 steps 단계만큼 바꾼다. 비었거나 읽을 수 없으면 실제로 쓰일 값에서 시작한다
+    fn adjust(&mut self, index: usize, steps: i64) {
+        let defaults = RunConfig::default();
+        let delta = self.step_size(index) as i64 * steps;
+        let bump = |text: &mut String, fallback: u64| {
+            *text = text.parse::<u64>().unwrap_or(fallback).saturating_add_signed(delta).to_string();
+        };
+        match index {
+            1 if self.load_model == LoadModel::Rate => {
+                let rps = self.rps.parse::<f64>().ok().filter(|rps| rps.is_finite()).unwrap_or(0.0);
+                self.rps = (rps + delta as f64).max(0.0).to_string();
+            }
+            1 => bump(&mut self.delay_ms, defaults.delay_ms),
+            2 => bump(&mut self.header_size_kb, defaults.header_size_kb as u64),
+            3 => bump(&mut self.iteration, defaults.iteration as u64),
+            4 => bump(&mut self.concurrency, defaults.concurrency as u64),
+            _ => {}
+        }
+    }
+
+    // 로그 영역에서 고른 줄 (스크롤 위치의 맨 아래 줄)
+    fn selected_log(&self) -> Option<&String> {
+        self.logs.len().checked_sub(self.log_scroll + 1).map(|i| &self.logs[i])
+    }
+}
+
+// 실행 파일 진입점 (하위 명령을 고르고 TUI 를 띄운다)
+pub fn run_cli() -> Result<(), io::Error> {
+    // --validate-config <파일>: TUI 를 띄우지 않고 설정 파일만 검증
+    let mut args: Vec<String> = std::env::args().collect();
+    // --no-color (어느 위치든) 또는 NO_COLOR 환경 변수: 색 없이 글자 표시로 그린다
+    let no_color = args.iter().skip(1).any(|arg| arg == "--no-color") || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    args.retain(|arg| arg != "--no-color");
+    if args.get(1).map(String::as_str) == Some("--validate-config") {
+        let Some(path) = args.get(2) else {
+            eprintln!("usage: {} --validate-config <config.json>", args[0]);
+            std::process::exit(2);
+        };
+        std::process::exit(validate_config(path));
+    }
+    // calibrate [옵션...]: 더미 서버로 생성기 자체의 상한과 요청당 부담을 잰다
+    if args.get(1).map(String::as_str) == Some("calibrate") {
+        std::process::exit(calibrate::run(&args[0], &args[2..]));
+    }
+    // echo-server [옵션...]: 종단 간 시험용 업스트림 (헤더/본문 에코, 서버 id 헤더, 지연/실패 비율)
+    if args.get(1).map(String::as_str) == Some("echo-server") {
+        std::process::exit(echo::run(&args[0], &args[2..]));
+    }
+    // --profile <이름>: 저장한 프로필로 첫 탭의 입력창을 채운다 (없는 프로필이면 TUI 를 띄우지 않는다)
+    let profile = match args.get(1).map(String::as_str) {
+        Some("--profile") => {
+            let Some(name) = args.get(2) else {
+                eprintln!("usage: {} --profile <name>", args[0]);
+                std::process::exit(2);
+            };
+            match profiles::load(name) {
+                Ok(config) => Some((name.clone(), config)),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        _ => None,
+    };
+    // run [옵션...]: TUI 없이 실행하고 결과를 종료 코드로 알린다
+    if args.get(1).map(String::as_str) == Some("run") {
+        std::process::exit(headless::run(&args[0], &args[2..]));
+    }
+    // attach <ip:port>: 다른 곳에서 control_listen 으로 띄운 실행을 읽기 전용으로 본다
+    let observe = match args.get(1).map(String::as_str) {
+        Some("attach") => match args.get(2) {
+            Some(addr) if args.len() == 3 => Some(addr.clone()),
+            _ => {
+                eprintln!("usage: {} attach <ip:port>", args[0]);
+                std::process::exit(2);
+            }
+        },
+        _ => None,
+    };
+
+    // 터미널 설정 (화면을 닫을 때와 패닉 때 복원된다)
+    let mut screen = terminal::Screen::enter()?;
+
+    // 애플리케이션 상태 생성
+    let app = App::default();
+    let res = run_app(&mut screen.terminal, app, profile, observe, no_color);
+    drop(screen);
+
+    match res {
+        Ok(Some(warning)) => eprintln!("{}", warning),
+        Ok(None) => {}
+        Err(err) => println!("{:?}", err),
+    }
+
+    Ok(())
+}
+
+// 틀린 필드를 모두 출력하고, 맞으면 기본값을 채운 실제 설정을 JSON 으로 출력한다 (종료 코드: 0 정상, 1 오류)
+fn validate_config(path: &str) -> i32 {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return 1;
+        }
+    };
+    match RunConfig::from_json(&text) {
+        Ok(config) => {
+            eprintln!("{}: OK", path);
+            println!("{}", serde_json::to_string_pretty(&config).unwrap_or_default());
+            0
+        }
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{}: {}: {}", path, error.field, error.reason);
+            }
+            eprintln!("{}: {} invalid field(s)", path, errors.len());
+            1
+        }
+    }
+}
+
+fn run_app<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    app: App,
+    profile: Option<(String, RunConfig)>,
+    observe: Option<String>,
+    no_color: bool,
+) -> eyre::Result<Option<String>> {
+    // 이벤트 처리를 위한 설정
+    let (tx, rx) = mpsc::channel();
+    let tick_rate = Duration::from_millis(100);
+
+    // 요청 전송과 사전 점검에 함께 쓰는 런타임
+    let rt = Arc::new(Runtime::new().expect("Failed to create runtime"));
+    
+    // 워크스페이스 탭 (탭마다 실행 상태와 작업 스레드를 따로 둔다)
+    let (mut workspaces, mut active) = match observe {
+        Some(addr) => (vec![Workspace::observer(app, &addr, &rt)], 0),
+        None => open_session(app, profile, &rt),
+    };
+
+    let platform = terminal::Platform::current();
+    thread::spawn(move || {
+        let mut last_tick = Instant::now();
+        loop {
+            let timeout = tick_rate
+                .checked_sub(last_tick.elapsed())
+                .unwrap_or_else(|| Duration::from_secs(0));
+
+            if event::poll(timeout).unwrap()
+                && let Event::Key(key) = event::read().unwrap()
+                && let Some(input) = terminal::translate(&key, platform)
+            {
+                tx.send(input).unwrap();
+            }
+
+            if last_tick.elapsed() >= tick_rate {
+                last_tick = Instant::now();
+            }
+        }
+    });
+
+    // 메인 루프
+    loop {
+        for workspace in workspaces.iter_mut() {
+            workspace.follow_events();
+        }
+        let plain = no_color || workspaces[active].app.plain_ui();
+        let tabs = workspace_titles(&workspaces, plain);
+        let Workspace { app, state: app_state, bus, live, panels, changed, observer, .. } = &mut workspaces[active];
+        app.plain = plain;
+
+        // 작업 스레드에서 로그 업데이트 가져오기 (관찰 모드면 제어 API 에서 읽어 온 상태)
+        if let Some(observer) = observer {
+            follow_observer(app, observer);
+        } else {
+            let mut state = app_state.lock().unwrap();
+            if let Some(replay) = state.pending_replay.take() {
+                app.replay = Some(replay);
+                app.log_scroll = 0;
+            }
+            // 재생 중이면 커서 시점까지의 기록과 로그로 그린다 (커서가 움직였을 때만 다시 계산한다)
+            let moved = app.replay.as_mut().is_some_and(replay::Replay::take_moved);
+            let records = match &app.replay {
+                Some(replay) => &replay.view().records,
+                None => &state.records,
+            };
+            app.logs = match &app.replay {
+                Some(replay) => replay.view().logs.clone(),
+                None => state.logs.clone(),
+            };
+            if app.log_filter != logview::LogFilter::All || !app.log_search.is_empty() {
+                logview::retain(&mut app.logs, app.log_filter, &app.log_search);
+            }
+            app.running = state.running;
+            app.preflight = state.preflight.as_ref().map(|p| p.results.clone());
+            // 기록으로 그리는 창은 새 이벤트가 왔거나 재생 커서가 움직였을 때만 다시 채운다
+            // 실행 중에는 이벤트로 쌓은 집계를 쓰고, 재생 중에는 커서 시점까지의 기록으로 새로 모은다
+            if std::mem::take(changed) || moved {
+                let mut replayed = app.replay.as_ref().map(|_| metrics::Panels::from_records(records));
+                let panels = replayed.as_mut().unwrap_or(panels);
+                app.upstream_stats = panels.upstreams();
+                app.route_results = panels.routes(&state.routes);
+                app.attribution = panels.attribution();
+                app.compare = panels.compare();
+                let pings = match &app.replay {
+                    Some(replay) => &replay.view().pings,
+                    None => &state.pings,
+                };
+                app.pings = ping::series(pings, records, metrics::ATTRIBUTION_POINTS);
+                if app.replay.is_some() {
+                    app.metrics = metrics::live(records, &retention::History::default(), 0, None, &state.config.percentiles);
+                }
+            }
+            if app.replay.is_none() {
+                app.metrics = live.live(state.sent_primary(), state.history.folded(), state.running.then(Instant::now), &state.config.percentiles);
+            }
+            app.alert = state.alert.clone();
+            if let Some(inspector) = app.inspector.as_mut() {
+                inspector.sync(state.resend.as_ref());
+            }
+        }
+        
+        // UI 그리기
+        terminal.draw(|f| ui(f, app, &tabs, active))?;
+
+        // 이벤트 처리 (Ctrl+C 는 열린 창과 입력 모드에 상관없이 끝낸다)
+        let key = match rx.try_recv() {
+            Ok(terminal::Input::Interrupt) => return quit(&workspaces, active),
+            Ok(terminal::Input::Key(key)) => Ok(key),
+            Err(e) => Err(e),
+        };
+        match key {
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => { return Ok(None) }
+            Ok(key) if observer.is_some() => {
+                if observer_key_handling(app, key) {
+                    return Ok(None);
+                }
+            }
+            Ok(key) if app.wizard.is_some() => wizard_key_handling(app, key, app_state),
+            Ok(key) if app.preflight.is_some() => preflight_key_handling(key, app_state, bus),
+            Ok(key) if app.show_options => options_key_handling(app, key),
+            Ok(key) if let Some(editor) = app.header_editor.as_mut() => {
+                if editor.key(&mut app.custom_headers, key) {
+                    app.header_editor = None;
+                }
+            }
+            Ok(key) if app.command.is_some() => command_key_handling(app, key, app_state, bus),
+            Ok(key) if app.inspector.is_some() => inspector_key_handling(app, key, app_state, &rt),
+            Ok(key) if app.replay.is_some() => {
+                replay_key_handling(app, key);
+                *changed |= app.replay.is_none();
+            }
+            Ok(key @ (KeyCode::Char('t') | KeyCode::Char('w') | KeyCode::Char('[') | KeyCode::Char(']'))) if app.input_mode == InputMode::Normal => {
+                workspace_key_handling(&mut workspaces, &mut active, key, &rt);
+            }
+            Ok(key) => {
+                match key {
+                    KeyCode::Char('q') if app.input_mode == InputMode::Normal => return quit(&workspaces, active),
+                    KeyCode::Tab => {
+                        app.focused_item = (app.focused_item + 1) % 8; // 로그 영역까지 포함하여 8개 항목
+                        if let 0..=5 = app.focused_item {
+                            app.input_mode = InputMode::Normal;
+                        }
+                    }
+                    KeyCode::BackTab => {
+                        app.focused_item = (app.focused_item + 7) % 8; // 로그 영역까지 포함하여 8개 항목
+                        if let 0..=5 = app.focused_item {
+                            app.input_mode = InputMode::Normal;
+                        }
+                    }
+                    // 검색어 입력을 끝내면 걸러진 줄에 남고, Esc 는 검색어를 지운다
+                    KeyCode::Enter if app.input_mode == InputMode::SearchingLog => app.input_mode = InputMode::Normal,
+                    KeyCode::Esc if app.input_mode == InputMode::SearchingLog => {
+                        app.log_search.clear();
+                        app.input_mode = InputMode::Normal;
+                    }
+                    KeyCode::Enter => match app.focused_item {
+                        0 => app.input_mode = InputMode::EditingDstUrl,
+                        1 => app.input_mode = InputMode::EditingDelay,
+                        2 => app.input_mode = InputMode::EditingHeaderSize,
+                        3 => app.input_mode = InputMode::EditingIteration,
+                        4 => app.input_mode = InputMode::EditingConcurrency,
+                        5 => app.protocol_index = (app.protocol_index + 1) % app.protocols.len(),
+                        6 => {
+                            toggle_run(app, app_state, bus, &rt);
+
+                            // 새 로그가 추가되면 자동으로 스크롤을 최신 로그로 이동 (focused_item이 로그 영역일 때만)
+                            if app.focused_item == 7 {
+                                app.log_scroll = 0;
+                            }
+                        }
+                        7 => open_inspector(app, app_state),
+                        _ => {}
+                    },
+                    KeyCode::Esc => app.input_mode = InputMode::Normal,
+                    // 입력 모드에 따라 다른 키 처리
+                    key => match app.input_mode {
+                        InputMode::EditingDstUrl => input_handling(&mut app.dst_url, key),
+                        InputMode::EditingDelay if app.load_model == LoadModel::Rate => input_handling_num(&mut app.rps, key),
+                        InputMode::EditingDelay => input_handling_num(&mut app.delay_ms, key),
+                        InputMode::EditingHeaderSize => input_handling_num(&mut app.header_size_kb, key),
+                        InputMode::EditingIteration => input_handling_num(&mut app.iteration, key),
+                        InputMode::EditingConcurrency => input_handling_num(&mut app.concurrency, key),
+                        InputMode::EditingOption => {}
+                        InputMode::SearchingLog => {
+                            input_handling(&mut app.log_search, key);
+                            app.log_scroll = 0;
+                        }
+                        InputMode::Normal if key == KeyCode::Char('o') => app.show_options = true,
+                        InputMode::Normal if key == KeyCode::Char('H') => app.header_editor = Some(headers::Editor::new()),
+                        InputMode::Normal if key == KeyCode::Char(':') => app.command = Some(String::new()),
+                        InputMode::Normal if key == KeyCode::Char('r') => start_replay(app, app_state),
+                        InputMode::Normal if key == KeyCode::Char('e') => app_state.lock().unwrap().export_upstreams(),
+                        InputMode::Normal if key == KeyCode::Char('x') => app_state.lock().unwrap().export_results(),
+                        InputMode::Normal if key == KeyCode::Char('p') => switch_profile(app, app_state),
+                        InputMode::Normal if key == KeyCode::Char('P') => save_profile(app, app_state),
+                        InputMode::Normal if key == KeyCode::Char('c') => {
+                            app_state.lock().unwrap().clear_session();
+                            *live = metrics::Tracker::default();
+                            *panels = metrics::Panels::default();
+                            *changed = true;
+                            app.log_scroll = 0;
+                        }
+                        InputMode::Normal => match app.focused_item {
+                            1..=4 if let Some(steps) = step_key(key) => app.adjust(app.focused_item, steps),
+                            1 if matches!(key, KeyCode::Right | KeyCode::Char('l') | KeyCode::Left | KeyCode::Char('h')) => {
+                                app.load_model = if app.load_model == LoadModel::Delay { LoadModel::Rate } else { LoadModel::Delay };
+                            }
+                            5 => {
+                                if matches!(key, KeyCode::Right | KeyCode::Char('l')) {
+                                    app.protocol_index = (app.protocol_index + 1) % app.protocols.len();
+                                } else if matches!(key, KeyCode::Left | KeyCode::Char('h')) {
+                                    app.protocol_index = (app.protocol_index + app.protocols.len() - 1) % app.protocols.len();
+                                }
+                            }
+                            6 => {
+                                if matches!(key, KeyCode::Char(' ')) {
+                                    toggle_run(app, app_state, bus, &rt);
+                                }
+                            }
+                            7 => {
+                                // 로그 영역 스크롤 처리
+                                if matches!(key, KeyCode::Down | KeyCode::Char('j')) {
+                                    if app.log_scroll < app.logs.len().saturating_sub(1) {
+                                        app.log_scroll += 1;
+                                    }
+                                } else if matches!(key, KeyCode::Up | KeyCode::Char('k')) {
+                                    app.log_scroll = app.log_scroll.saturating_sub(1);
+                                } else if matches!(key, KeyCode::PageDown) {
+                                    app.log_scroll = (app.log_scroll + 10).min(app.logs.len().saturating_sub(1));
+                                } else if matches!(key, KeyCode::PageUp) {
+                                    app.log_scroll = app.log_scroll.saturating_sub(10);
+                                } else if matches!(key, KeyCode::Home) {
+                                    app.log_scroll = 0;
+                                } else if matches!(key, KeyCode::End) {
+                                    app.log_scroll = app.logs.len().saturating_sub(1);
+                                } else if matches!(key, KeyCode::Char('f')) {
+                                    app.log_filter = app.log_filter.next();
+                                    app.log_scroll = 0;
+                                } else if matches!(key, KeyCode::Char('/')) {
+                                    app.input_mode = InputMode::SearchingLog;
+                                }
+                            }
+                            _ => {}
+                        },
+                    },
+                }
+            }
+        }
+    }
+}
+
+// 모든 탭의 작업 중지 및 종료
+// 세션을 저장하지 못해도 종료는 막지 않고, 화면을 닫은 뒤 터미널에 남길 경고로 돌려준다
+fn quit(workspaces: &[Workspace], active: usize) -> eyre::Result<Option<String>> {
+    // 관찰 모드는 남의 실행이므로 세션을 남기지 않는다
+    if workspaces.iter().any(|workspace| workspace.observer.is_some()) {
+        return Ok(None);
+    }
+    for workspace in workspaces {
+        workspace.state.lock().unwrap().running = false;
+    }
+    // 다음 시작 때 같은 화면으로 열 수 있도록 세션 저장
+    let tabs = workspaces.iter().map(|workspace| (&workspace.app, workspace.state.lock().unwrap().logs.clone()));
+    Ok(session::save(tabs, active).err().map(|e| format!("Session not saved: {}", e)))
+}
+
+// 숫자 입력창 조정 키. = 와 - 는 한 단계, Shift 를 누른 같은 키(+ 와 _)는 10 단계
+fn step_key(key: KeyCode) -> Option<i64> {
+    match key {
+        KeyCode::Char('=') => Some(1),
+        KeyCode::Char('-') => Some(-1),
+        KeyCode::Char('+') => Some(10),
+        KeyCode::Char('_') => Some(-10),
+        _ => None,
+    }
+}
+
+// 워크스페이스 탭 하나: 입력값/옵션 화면과 실행 상태, 작업 스레드
+struct Workspace {
+    app: App,
+    state: Arc<Mutex<AppState>>,
+    // 이 탭의 이벤트 버스와 화면 쪽 구독
+    bus: events::Bus,
+    events: events::Subscription,
+    // RequestFinished 로 쌓는 실시간 지표와 기록으로 그리는 창의 집계
+    live: metrics::Tracker,
+    panels: metrics::Panels,
+    // 지난 프레임 뒤로 기록이 바뀌었는지 (기록으로 계산하는 창은 이때만 다시 계산한다)
+    changed: bool,
+    // 관찰 모드 탭이면 읽어 오는 곳 (작업 스레드 없이 화면만 그린다)
+    observer: Option<control::Observer>,
+}
+
+impl Workspace {
+    fn new(app: App, rt: &Arc<Runtime>) -> Self {
+        let bus = events::Bus::default();
+        let events = bus.subscribe("screen");
+        let state = Arc::new(Mutex::new(AppState::new()));
+        spawn_worker(state.clone(), bus.clone(), rt.clone());
+        Self { app, state, bus, events, live: metrics::Tracker::default(), panels: metrics::Panels::default(), changed: true, observer: None }
+    }
+
+    fn observer(mut app: App, addr: &str, rt: &Runtime) -> Self {
+        let bus = events::Bus::default();
+        let events = bus.subscribe("screen");
+        app.focused_item = 7;
+        let state = Arc::new(Mutex::new(AppState::new()));
+        Self { app, state, bus, events, live: metrics::Tracker::default(), panels: metrics::Panels::default(), changed: true, observer: Some(control::Observer::attach(rt, addr)) }
+    }
+
+    // 쌓인 이벤트를 화면에 반영
+    fn follow_events(&mut self) {
+        while let Some(event) = self.events.try_recv() {
+            match event {
+                events::Event::ConfigChanged(config) => self.app.follow(&config),
+                events::Event::RunStarted(_) => {
+                    self.live = metrics::Tracker::default();
+                    self.panels = metrics::Panels::default();
+                }
+                events::Event::RequestFinished(record) => {
+                    self.live.add(&record);
+                    self.panels.add(record);
+                }
+                _ => {}
+            }
+            self.changed = true;
+        }
+    }
+}
+
+// 프로필을 고르지 않았으면 지난 세션의 탭을 그대로 연다. 세션이 없으면 새 탭 하나에 설정을 불러온다
+fn open_session(app: App, profile: Option<(String, RunConfig)>, rt: &Arc<Runtime>) -> (Vec<Workspace>, usize) {
+    let restored = match profile {
+        Some(_) => Ok(None),
+        None => session::load(),
+    };
+    let (tabs, active) = match restored {
+        Ok(Some(restored)) => restored,
+        Ok(None) => {
+            let mut workspace = Workspace::new(app, rt);
+            open_config(&mut workspace, profile);
+            return (vec![workspace], 0);
+        }
+        Err(e) => {
+            let mut workspace = Workspace::new(app, rt);
+            workspace.state.lock().unwrap().add_log(&format!("Session not restored: {}", e));
+            open_config(&mut workspace, None);
+            return (vec![workspace], 0);
+        }
+    };
+    let workspaces: Vec<Workspace> = tabs
+        .into_iter()
+        .map(|(app, logs)| {
+            let workspace = Workspace::new(app, rt);
+            let mut state = workspace.state.lock().unwrap();
+            state.logged = logs.len();
+            // 세션에서 되살린 로그는 남긴 시각을 몰라 재생의 어느 시점에서도 보이게 둔다
+            state.log_times = vec![0; logs.len()];
+            state.logs = logs;
+            drop(state);
+            workspace
+        })
+        .collect();
+    let path = session::path().map(|path| path.display().to_string()).unwrap_or_default();
+    workspaces[active].state.lock().unwrap().add_log(&format!(
+        "Session restored from {} ({} workspace(s), secret options {} are not saved)",
+        path,
+        workspaces.len(),
+        config::SECRET_OPTIONS.join("/")
+    ));
+    (workspaces, active)
+}
+
+// 시작할 때 고른 프로필이나 설정 파일이 있으면 첫 탭에 불러오고, 둘 다 없으면 첫 실행 안내를 띄운다
+fn open_config(workspace: &mut Workspace, profile: Option<(String, RunConfig)>) {
+    let mut state = workspace.state.lock().unwrap();
+    if let Some((name, config)) = profile {
+        apply_config(&mut workspace.app, &config);
+        state.add_log(&format!("Profile '{}' loaded", name));
+        workspace.app.profile = Some(name);
+        return;
+    }
+    let text = match std::fs::read_to_string(CONFIG_FILE) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            workspace.app.wizard = Some(wizard::Wizard::new());
+            return;
+        }
+        Err(e) => {
+            state.add_log(&format!("Failed to read {}: {}", CONFIG_FILE, e));
+            return;
+        }
+    };
+    match RunConfig::from_json(&text) {
+        Ok(config) => {
+            apply_config(&mut workspace.app, &config);
+            state.add_log(&format!("Config loaded from {}", CONFIG_FILE));
+        }
+        Err(errors) => {
+            for error in &errors {
+                state.add_log(&format!("Config {}: {}: {}", CONFIG_FILE, error.field, error.reason));
+            }
+            state.add_log(&format!("Config {} not loaded ({} invalid field(s))", CONFIG_FILE, errors.len()));
+        }
+    }
+}
+
+// 설정을 입력창과 옵션 창에 채운다
+fn apply_config(app: &mut App, config: &RunConfig) {
+    app.dst_url = config.dst_url.clone();
+    app.delay_ms = config.delay_ms.to_string();
+    app.load_model = if config.rps > 0.0 { LoadModel::Rate } else { LoadModel::Delay };
+    if config.rps > 0.0 {
+        app.rps = config.rps.to_string();
+    }
+    app.header_size_kb = config.header_size_kb.to_string();
+    app.iteration = config.iteration.to_string();
+    app.concurrency = config.concurrency.to_string();
+    app.protocol_index = app.protocols.iter().position(|p| *p == config.protocol).unwrap_or(0);
+    app.custom_headers = config.custom_headers.clone();
+    for option in app.options.iter_mut() {
+        option.value = config.option_value(option.key);
+    }
+}
+
+// 저장한 프로필을 이름순으로 돌아가며 입력창에 불러온다 (p)
+fn switch_profile(app: &mut App, app_state: &Arc<Mutex<AppState>>) {
+    let mut state = app_state.lock().unwrap();
+    let names = match profiles::names() {
+        Ok(names) if names.is_empty() => {
+            state.add_log("No profiles yet (press P to save the current inputs, or ':profile save <name>')");
+            return;
+        }
+        Ok(names) => names,
+        Err(e) => {
+            state.add_log(&format!("Profile: {}", e));
+            return;
+        }
+    };
+    let next = match app.profile.as_ref().and_then(|current| names.iter().position(|name| name == current)) {
+        Some(index) => (index + 1) % names.len(),
+        None => 0,
+    };
+    match profiles::load(&names[next]) {
+        Ok(config) => {
+            apply_config(app, &config);
+            state.add_log(&format!("Profile '{}' loaded ({}/{}): {} | {}", names[next], next + 1, names.len(), config.dst_url, config.describe()));
+            app.profile = Some(names[next].clone());
+        }
+        Err(e) => state.add_log(&format!("Profile: {}", e)),
+    }
+}
+
+// 지금 입력값과 옵션을 지금 프로필로 저장한다 (P, 프로필이 없으면 default)
+fn save_profile(app: &mut App, app_state: &Arc<Mutex<AppState>>) {
+    let mut state = app_state.lock().unwrap();
+    let name = app.profile.clone().unwrap_or_else(|| String::from(profiles::DEFAULT_PROFILE));
+    match build_config(app).and_then(|config| profiles::save(&name, &config)) {
+        Ok(path) => {
+            state.add_log(&format!("Profile '{}' saved to {}", name, path.display()));
+            app.profile = Some(name);
+        }
+        Err(e) => state.add_log(&format!("Profile: {}", e)),
+    }
+}
+
+// 첫 실행 안내의 키 처리. 끝나면 답을 입력창에 채우고 원하면 설정 파일로 저장한다
+fn wizard_key_handling(app: &mut App, key: KeyCode, app_state: &Arc<Mutex<AppState>>) {
+    let Some(outcome) = app.wizard.as_mut().and_then(|wizard| wizard.key(key)) else {
+        return;
+    };
+    let config = app.wizard.take().map(|wizard| wizard.config).unwrap_or_default();
+    let mut state = app_state.lock().unwrap();
+    match outcome {
+        wizard::Outcome::Done(save) => {
+            apply_config(app, &config);
+            state.add_log(&format!("Setup: {} | {} (press Enter on Start to run)", config.dst_url, config.describe()));
+            if save {
+                match config.save(CONFIG_FILE) {
+                    Ok(()) => state.add_log(&format!("Config saved to {}", CONFIG_FILE)),
+                    Err(e) => state.add_log(&format!("Failed to save config to {}: {}", CONFIG_FILE, e)),
+                }
+            }
+        }
+        wizard::Outcome::Skipped => state.add_log("Setup skipped (press 'o' for options, ':config save' to keep settings)"),
+    }
+}
+
+// 탭 제목: 번호와 대상 호스트, 실행 중이면 * (색 없는 화면이면 [RUNNING])
+fn workspace_titles(workspaces: &[Workspace], plain: bool) -> Vec<String> {
+    workspaces
+        .iter()
+        .enumerate()
+        .map(|(i, workspace)| {
+            let url = reqwest::Url::parse(&workspace.app.dst_url).ok();
+            let host = url.as_ref().and_then(|url| url.host_str()).unwrap_or("new");
+            let running = match (workspace.state.lock().unwrap().running, plain) {
+                (false, _) => "",
+                (true, false) => " *",
+                (true, true) => " [RUNNING]",
+            };
+            format!("{} {}{}", i + 1, host, running)
+        })
+        .collect()
+}
+
+// 탭 키 처리 (t: 새 탭, w: 탭 닫기, [ ]: 탭 이동)
+fn workspace_key_handling(workspaces: &mut Vec<Workspace>, active: &mut usize, key: KeyCode, rt: &Arc<Runtime>) {
+    match key {
+        KeyCode::Char('t') if workspaces.len() < MAX_WORKSPACES => {
+            workspaces.push(Workspace::new(App::default(), rt));
+            *active = workspaces.len() - 1;
+        }
+        KeyCode::Char('t') => workspaces[*active].state.lock().unwrap().add_log(&format!("At most {} workspaces can be open", MAX_WORKSPACES)),
+        KeyCode::Char('w') => {
+            let mut state = workspaces[*active].state.lock().unwrap();
+            if workspaces.len() == 1 {
+                state.add_log("Cannot close the last workspace");
+                return;
+            }
+            if state.running || state.preflight.is_some() || state.queue_run.is_some() {
+                state.add_log("Stop the run before closing this workspace");
+                return;
+            }
+            state.closed = true;
+            drop(state);
+            workspaces.remove(*active);
+            *active = (*active).min(workspaces.len() - 1);
+        }
+        KeyCode::Char('[') => *active = (*active + workspaces.len() - 1) % workspaces.len(),
+        KeyCode::Char(']') => *active = (*active + 1) % workspaces.len(),
+        _ => {}
+    }
+}
+
+// 워크스페이스 하나의 작업 스레드
+fn spawn_worker(app_state_clone: Arc<Mutex<AppState>>, bus: events::Bus, rt: Arc<Runtime>) {
+    thread::spawn(move || engine::run(app_state_clone, bus, rt, false));
+}
+
+// 입력값으로 실행 설정을 만들어 실행하거나, 실행 중이면 중지
+fn toggle_run(app: &App, app_state: &Arc<Mutex<AppState>>, bus: &events::Bus, rt: &Runtime) {
+    let mut state = app_state.lock().unwrap();
+
+    if !state.running {
+        for warning in app.input_warnings() {
+            state.add_log(&format!("Warning: {}", warning));
+        }
+        let config = match build_config(app) {
+            Ok(config) => config,
+            Err(e) => {
+                state.add_log(&format!("Invalid {}", e));
+                return;
+            }
+        };
+
+        if !config.preflight {
+            engine::start_run(&mut state, bus, config);
+            return;
+        }
+
+        // 사전 점검은 런타임에서 돌리고 결과는 점검 창에 표시
+        let template = match RequestTemplate::new(&config) {
+            Ok(template) => template,
+            Err(e) => {
+                state.add_log(&format!("Pre-flight failed: {}", e));
+                return;
+            }
+        };
+        let (mismatch, sni_names, stream_limit) = (config.mismatch_probes, sni::parse_list(&config.sni_list), config.stream_limit_max);
+        state.preflight = Some(PreflightState { config, results: None });
+        let app_state = app_state.clone();
+        // 사전 점검이 맺은 연결의 이벤트도 남긴다
+        let conn_events: Vec<_> = template.conn_events.iter().cloned().collect();
+        rt.spawn(async move {
+            let results = preflight::run(template, mismatch, sni_names, stream_limit).await;
+            let mut state = app_state.lock().unwrap();
+            state.log_conn_events(&conn_events);
+            for line in sni::log_lines(&results) {
+                state.add_log(&line);
+            }
+            if let Some(line) = streams::log_line(&results) {
+                state.add_log(&line);
+            }
+            if let Some(preflight) = state.preflight.as_mut() {
+                preflight.results = Some(results);
+            }
+        });
+    } else {
+        state.running = false;
+        state.add_log("Process Stopped by user");
+        state.add_marker(MarkerKind::Stop, "Process Stopped by user");
+        bus.publish(state.export());
+        // 큐 실행도 함께 중지하고 지금까지의 결과 보고
+        if let Some(mut queue) = state.queue_run.take() {
+            queue.finish_stage("Stopped by user");
+            for line in queue.report() {
+                state.add_log(&line);
+            }
+        }
+        if state.config.probe_interval_ms > 0 {
+            state.end_disruption(Instant::now());
+            let summary = &state.disruption_summary;
+            let log = format!(
+                "Probe: {} disruption window(s) (longest {}, {} errors)",
+                summary.windows, stats::format_ms(summary.longest), summary.errors
+            );
+            state.add_log(&log);
+        }
+    }
+}
+
+// 입력값과 옵션으로 실행 설정 생성
+fn build_config(app: &App) -> Result<RunConfig, String> {
+    let defaults = RunConfig::default();
+    let mut config = RunConfig {
+        dst_url: app.dst_url.clone(),
+        delay_ms: app.delay_ms.parse::<u64>().unwrap_or(defaults.delay_ms),
+        header_size_kb: app.header_size_kb.parse::<usize>().unwrap_or(defaults.header_size_kb),
+        protocol: app.protocols[app.protocol_index].to_owned(),
+        iteration: app.iteration.parse::<usize>().unwrap_or(defaults.iteration),
+        concurrency: app.concurrency.parse::<usize>().unwrap_or(defaults.concurrency),
+        rps: match app.load_model {
+            LoadModel::Rate => app.rps.parse::<f64>().unwrap_or(0.0),
+            LoadModel::Delay => 0.0,
+        },
+        ..defaults
+    };
+    for option in &app.options {
+        config.apply_option(option.key, &option.value)?;
+    }
+    config.apply_option("custom_headers", &app.custom_headers.join("\n"))?;
+    Ok(config)
+}
+
+// 사전 점검 창이 열려 있을 때의 키 처리 (Enter: 실행, Esc: 취소)
+fn preflight_key_handling(key: KeyCode, app_state: &Arc<Mutex<AppState>>, bus: &events::Bus) {
+    let mut state = app_state.lock().unwrap();
+    match key {
+        KeyCode::Enter if state.preflight.as_ref().is_some_and(|p| p.results.is_some()) => {
+            let preflight = state.preflight.take().unwrap();
+            let failed = preflight.results.iter().flatten().filter(|r| r.ok == Some(false)).count();
+            if failed > 0 {
+                state.add_log(&format!("Pre-flight: {} check(s) failed, starting anyway", failed));
+            }
+            engine::start_run(&mut state, bus, preflight.config);
+        }
+        KeyCode::Esc | KeyCode::Char('q') => {
+            state.preflight = None;
+            state.add_log("Pre-flight cancelled");
+        }
+        _ => {}
+    }
+}
+
+// 옵션 창이 열려 있을 때의 키 처리
+fn options_key_handling(app: &mut App, key: KeyCode) {
+    if app.input_mode == InputMode::EditingOption {
+        match key {
+            KeyCode::Enter | KeyCode::Esc => app.input_mode = InputMode::Normal,
+            key => input_handling(&mut app.options[app.option_index].value, key),
+        }
+        return;
+    }
+
+    match key {
+        KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => app.option_index = (app.option_index + 1) % app.options.len(),
+        KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => app.option_index = (app.option_index + app.options.len() - 1) % app.options.len(),
+        KeyCode::Enter => app.input_mode = InputMode::EditingOption,
+        // on/off 옵션은 편집하지 않고 바로 뒤집는다
+        KeyCode::Char(' ') => {
+            let value = &mut app.options[app.option_index].value;
+            match value.as_str() {
+                "on" => *value = String::from("off"),
+                "off" => *value = String::from("on"),
+                _ => {}
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('o') | KeyCode::Char('q') => app.show_options = false,
+        _ => {}
+    }
+}
+
+// 끝난 실행을 내보내기 간격 단위로 재생
+fn start_replay(app: &mut App, app_state: &Arc<Mutex<AppState>>) {
+    let mut state = app_state.lock().unwrap();
+    if state.running {
+        state.add_log("Replay is available after the run finishes");
+        return;
+    }
+    let interval = Duration::from_secs(state.config.export_interval_s);
+    match replay::Replay::new(&state.records, &state.history, &state.pings, state.timed_logs(), interval, &state.config.percentiles) {
+        Some(replay) => {
+            app.replay = Some(replay);
+            app.log_scroll = 0;
+        }
+        None => state.add_log("No finished run to replay"),
+    }
+}
+
+// 재생 중의 키 처리 (←/→: 구간 이동, Home/End: 처음/끝, Esc: 실시간 화면으로)
+fn replay_key_handling(app: &mut App, key: KeyCode) {
+    let Some(replay) = app.replay.as_mut() else {
+        return;
+    };
+    match key {
+        KeyCode::Left | KeyCode::Char('h') => replay.step(-1),
+        KeyCode::Right | KeyCode::Char('l') => replay.step(1),
+        KeyCode::PageUp => replay.step(-10),
+        KeyCode::PageDown => replay.step(10),
+        KeyCode::Home => replay.jump(false),
+        KeyCode::End => replay.jump(true),
+        KeyCode::Esc | KeyCode::Char('r') | KeyCode::Char('q') => app.replay = None,
+        _ => {}
+    }
+    app.log_scroll = 0;
+}
+
+// 고른 로그 줄의 요청을 인스펙터로 연다
+fn open_inspector(app: &mut App, app_state: &Arc<Mutex<AppState>>) {
+    let Some(log) = app.selected_log() else {
+        return;
+    };
+    let mut state = app_state.lock().unwrap();
+    app.inspector = inspector::Inspector::open(log, &state.records, &state.h2_settings, &state.proxy_logs);
+    if app.inspector.is_none() {
+        state.add_log("The selected log line is not a request result");
+    }
+}
+
+// 인스펙터가 열려 있을 때의 키 처리 (r: 다시 보내기, Esc, Enter, q: 닫기)
+fn inspector_key_handling(app: &mut App, key: KeyCode, app_state: &Arc<Mutex<AppState>>, rt: &Arc<Runtime>) {
+    match key {
+        KeyCode::Char('r') => {
+            if let Some(inspector) = &app.inspector {
+                resend_request(inspector.record.clone(), app_state, rt);
+            }
+        }
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => app.inspector = None,
+        _ => {}
+    }
+}
+
+// 인스펙터의 요청을 마지막 실행의 템플릿으로 한 번 더 보내고 결과를 인스펙터에 나란히 표시
+fn resend_request(record: stats::RequestRecord, app_state: &Arc<Mutex<AppState>>, rt: &Arc<Runtime>) {
+    let mut state = app_state.lock().unwrap();
+    if state.resend.as_ref().is_some_and(|r| r.result.is_none()) {
+        return;
+    }
+    let template = match (&state.templates, record.side) {
+        (Some((_, Some(compare))), Some(compare::Side::B)) => Some(compare.clone()),
+        (Some((template, _)), _) => Some(template.clone()),
+        (None, _) => None,
+    };
+    let Some(template) = template else {
+        state.add_log("Cannot replay the request: no run template (start a run first)");
+        return;
+    };
+    state.resend = Some(inspector::Resend { id: record.id.clone(), side: record.side, result: None });
+    drop(state);
+
+    let app_state = app_state.clone();
+    rt.spawn(async move {
+        let (result, log) = resend(&template, &record).await;
+        let mut state = app_state.lock().unwrap();
+        state.add_log(&format!("Inspector replay: {}", log));
+        if let Some(resend) = state.resend.as_mut()
+            && resend.id == record.id
+            && resend.side == record.side
+        {
+            resend.result = Some(result);
+        }
+    });
+}
+
+// 관찰 모드에서 읽어 온 상태를 화면에 반영
+fn follow_observer(app: &mut App, observer: &control::Observer) {
+    let mut observed = observer.observed.lock().unwrap();
+    if let Some(config) = observed.config.take() {
+        apply_config(app, &config);
+    }
+    app.logs = observed.logs.clone();
+    if app.log_filter != logview::LogFilter::All {
+        logview::retain(&mut app.logs, app.log_filter, "");
+    }
+    app.running = observed.running;
+    app.metrics = observed.metrics.clone();
+    app.observing = Some(match &observed.error {
+        Some(e) => format!("Observing {} (read-only): {}", observer.addr, e),
+        None => format!("Observing {} (read-only)", observer.addr),
+    });
+}
+
+// 관찰 모드의 키 처리. 로그 보기만 된다 (j/k/PageUp/PageDown/Home/End: 스크롤, f: 필터, q/Esc: 끝내기)
+fn observer_key_handling(app: &mut App, key: KeyCode) -> bool {
+    let last = app.logs.len().saturating_sub(1);
+    match key {
+        KeyCode::Char('q') | KeyCode::Esc => return true,
+        KeyCode::Down | KeyCode::Char('j') => app.log_scroll = (app.log_scroll + 1).min(last),
+        KeyCode::Up | KeyCode::Char('k') => app.log_scroll = app.log_scroll.saturating_sub(1),
+        KeyCode::PageDown => app.log_scroll = (app.log_scroll + 10).min(last),
+        KeyCode::PageUp => app.log_scroll = app.log_scroll.saturating_sub(10),
+        KeyCode::Home => app.log_scroll = 0,
+        KeyCode::End => app.log_scroll = last,
+        KeyCode::Char('f') => {
+            app.log_filter = app.log_filter.next();
+            app.log_scroll = 0;
+        }
+        _ => {}
+    }
+    false
+}
+
+// 명령 팔레트가 열려 있을 때의 키 처리 (Enter: 실행, Esc: 닫기)
+fn command_key_handling(app: &mut App, key: KeyCode, app_state: &Arc<Mutex<AppState>>, bus: &events::Bus) {
+    match key {
+        KeyCode::Enter => {
+            let input = app.command.take().unwrap_or_default();
+            match commands::run(&mut app_state.lock().unwrap(), bus, &input, build_config(app)) {
+                Some(commands::ProfileChange::Loaded(name, config)) => {
+                    apply_config(app, &config);
+                    app.profile = Some(name);
+                }
+                Some(commands::ProfileChange::Saved(name)) => app.profile = Some(name),
+                Some(commands::ProfileChange::Deleted(name)) if app.profile.as_ref() == Some(&name) => app.profile = None,
+                _ => {}
+            }
+        }
+        KeyCode::Esc => app.command = None,
+        key => {
+            if let Some(input) = app.command.as_mut() {
+                input_handling(input, key);
+            }
+        }
+    }
+}
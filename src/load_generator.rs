@@ -0,0 +1,79 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use tokio::runtime::Runtime;
+
+use crate::{config::RunConfig, engine, events::Bus, stats::RequestRecord, AppState};
+
+// 실행이 끝난 뒤 싱크 내보내기, 웹훅 전송 같은 남은 작업을 기다리는 최대 시간
+const SETTLE: Duration = Duration::from_secs(5);
+
+// TTY 없이 실행 하나를 돌리는 라이브러리 진입점 (헤드리스 실행, calibrate, 통합 테스트가 같은 요청 루프를 쓴다)
+pub struct LoadGenerator {
+    config: RunConfig,
+    // 로그를 표준 출력으로도 내보낸다 (헤드리스 실행)
+    echo_logs: bool,
+}
+
+// 끝난 실행의 결과
+pub struct Report {
+    // 끝난 요청 수와 실패 수 (구간 집계로 합친 오래된 결과 포함)
+    pub completed: usize,
+    pub failed: usize,
+    // 요청별 결과 (구간 집계로 합친 결과는 빠진다)
+    pub records: Vec<RequestRecord>,
+    pub logs: Vec<String>,
+}
+
+impl LoadGenerator {
+    pub fn new(config: RunConfig) -> Self {
+        Self { config, echo_logs: false }
+    }
+
+    pub fn echo_logs(mut self, echo_logs: bool) -> Self {
+        self.echo_logs = echo_logs;
+        self
+    }
+
+    // 실행(큐 포함)이 끝나고 남은 작업이 정리될 때까지 막는다
+    pub fn run(self) -> Report {
+        let rt = Arc::new(Runtime::new().expect("Failed to create Tokio runtime"));
+        let state = Arc::new(Mutex::new(AppState::new()));
+        let bus = Bus::default();
+        {
+            let mut state = state.lock().unwrap();
+            state.echo_logs = self.echo_logs;
+            engine::start_run(&mut state, &bus, self.config);
+        }
+        engine::run(state.clone(), bus.clone(), rt.clone(), true);
+        // ALS 수신기는 계속 떠 있으므로 남은 작업을 기다리기 전에 닫는다
+        // 버스도 닫아 구독 작업이 남은 이벤트를 처리하고 끝나게 한다
+        // 풀에 남은 연결도 닫아 닫힘 이벤트까지 남긴다
+        let queues = {
+            let mut state = state.lock().unwrap();
+            state.als_listener = None;
+            state.control_listener = None;
+            bus.close();
+            let queues = state.conn_events();
+            state.templates = None;
+            queues
+        };
+
+        let settle = Instant::now();
+        while rt.metrics().num_alive_tasks() > 0 && settle.elapsed() < SETTLE {
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        let mut state = state.lock().unwrap();
+        state.log_conn_events(&queues);
+        state.follow_reports(&bus);
+        let (completed, failed) = match state.history.totals(&state.records) {
+            Some(totals) => (totals.requests, totals.errors),
+            None => (state.records.len(), state.records.iter().filter(|r| !r.success).count()),
+        };
+        Report { completed, failed, records: Arc::unwrap_or_clone(std::mem::take(&mut state.records)), logs: std::mem::take(&mut state.logs) }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{conn_log, inspector};
+
+// 로그 영역에 보일 줄 (f 로 돌아가며 바꾼다)
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFilter {
+    #[default]
+    All,
+    // 실패한 요청과 에러/중단 로그
+    Failures,
+    // 성공한 요청
+    Successes,
+    Connections,
+}
+
+impl LogFilter {
+    pub fn next(self) -> Self {
+        match self {
+            LogFilter::All => LogFilter::Failures,
+            LogFilter::Failures => LogFilter::Successes,
+            LogFilter::Successes => LogFilter::Connections,
+            LogFilter::Connections => LogFilter::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogFilter::All => "all",
+            LogFilter::Failures => "failures",
+            LogFilter::Successes => "successes",
+            LogFilter::Connections => "connection events",
+        }
+    }
+
+    // label() 의 반대 (모르는 이름이면 All)
+    pub fn from_label(label: &str) -> Self {
+        let mut filter = LogFilter::All.next();
+        while filter != LogFilter::All && filter.label() != label {
+            filter = filter.next();
+        }
+        filter
+    }
+
+    // 실패한 요청 줄에는 항상 [E_...] 에러 코드가 붙는다
+    fn accepts(self, log: &str) -> bool {
+        let request = inspector::request_ref(log).is_some();
+        match self {
+            LogFilter::All => true,
+            LogFilter::Failures if request => log.contains(" [E_"),
+            LogFilter::Failures => {
+                let message = log.to_ascii_lowercase();
+                ["fail", "error", "abort"].iter().any(|word| message.contains(word))
+            }
+            LogFilter::Successes => request && !log.contains(" [E_"),
+            LogFilter::Connections => conn_log::is_event(log),
+        }
+    }
+}
+
+// 필터와 검색어(대소문자 무시)에 맞는 줄만 남긴다
+pub fn retain(logs: &mut Vec<String>, filter: LogFilter, pattern: &str) {
+    let pattern = pattern.to_ascii_lowercase();
+    logs.retain(|log| filter.accepts(log) && (pattern.is_empty() || log.to_ascii_lowercase().contains(&pattern)));
+}
+
+// 검색어와 맞는 구간 (바이트 위치). ASCII 만 소문자로 바꾸므로 위치가 원래 줄과 같다
+pub fn matches(log: &str, pattern: &str) -> Vec<(usize, usize)> {
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+    let (log, pattern) = (log.to_ascii_lowercase(), pattern.to_ascii_lowercase());
+    log.match_indices(&pattern).map(|(start, found)| (start, start + found.len())).collect()
+}
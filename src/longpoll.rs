@@ -0,0 +1,78 @@
+use std::{collections::BTreeMap, time::{Duration, Instant}};
+
+use crate::stats::{format_ms, percentile, RequestRecord};
+
+// 기대 유지 시간의 95% 전에 끝난 요청은 일찍 끊긴 것으로 본다 (업스트림 응답 시각의 오차 허용)
+pub fn ended_early(latency: Duration, hold: Duration) -> bool {
+    latency < hold.mul_f64(0.95)
+}
+
+// 일찍 끊긴 원인: 상태코드 (408 stream idle timeout, 504 upstream timeout 등), 클라이언트 타임아웃, 연결 오류
+fn cause(record: &RequestRecord) -> String {
+    match (record.status, record.timeout) {
+        (Some(status), _) => format!("HTTP {}", status),
+        (None, Some(kind)) => kind.to_string(),
+        (None, None) => String::from("connection error"),
+    }
+}
+
+// 동시에 열려 있던 요청 수의 최대값과 그 시각
+fn peak_open<'a>(records: impl Iterator<Item = &'a RequestRecord>) -> (usize, Option<Instant>) {
+    let mut events: Vec<(Instant, i32)> = records.flat_map(|r| [(r.sent_at, 1), (r.sent_at + r.latency, -1)]).collect();
+    // 같은 시각이면 끝난 요청을 먼저 뺀다
+    events.sort();
+    let mut open = 0i32;
+    let mut peak = (0, None);
+    for (at, delta) in events {
+        open += delta;
+        if open as usize > peak.0 {
+            peak = (open as usize, Some(at));
+        }
+    }
+    peak
+}
+
+// 롱폴링 보고: 유지 시간을 채운 요청, 일찍 끊긴 요청의 원인과 시점, 동시에 유지한 요청 수
+pub fn summarize(records: &[RequestRecord], hold: Duration) -> Vec<String> {
+    let early: Vec<&RequestRecord> = records.iter().filter(|r| ended_early(r.latency, hold)).collect();
+    let held = records.len() - early.len();
+    let (peak, _) = peak_open(records.iter());
+    let (peak_held, _) = peak_open(records.iter().filter(|r| !ended_early(r.latency, hold)));
+    let mut lines = vec![format!(
+        "Long-poll: {} requests, {} held for {}s, {} ended early | peak concurrent open {}, held {}",
+        records.len(),
+        held,
+        hold.as_secs(),
+        early.len(),
+        peak,
+        peak_held
+    )];
+    if early.is_empty() {
+        return lines;
+    }
+
+    let mut causes: BTreeMap<String, usize> = BTreeMap::new();
+    for record in &early {
+        *causes.entry(cause(record)).or_default() += 1;
+    }
+    let causes: Vec<String> = causes.into_iter().map(|(cause, count)| format!("{} x{}", cause, count)).collect();
+    lines.push(format!("Long-poll early ends: {}", causes.join(", ")));
+
+    // 끊긴 시점이 한 값에 몰려 있으면 Envoy 의 stream idle timeout 등 고정 타임아웃일 가능성이 높다
+    let mut latency: Vec<Duration> = early.iter().map(|r| r.latency).collect();
+    latency.sort();
+    let (min, median, max) = (latency[0], percentile(&latency, 50.0), latency[latency.len() - 1]);
+    let mut log = format!("Long-poll early end after: min {}, p50 {}, max {}", format_ms(min), format_ms(median), format_ms(max));
+    if latency.len() > 1 && max - min <= median.mul_f64(0.1) {
+        log.push_str(&format!(" (consistent with a ~{:.0}s timeout, check stream_idle_timeout / route timeout)", median.as_secs_f64()));
+    }
+    lines.push(log);
+
+    // 첫 번째로 일찍 끊긴 요청이 끝난 시점에 열려 있던 요청 수 (동시 유지 한계 추정)
+    if let Some(first) = early.iter().min_by_key(|r| r.sent_at + r.latency) {
+        let at = first.sent_at + first.latency;
+        let open = records.iter().filter(|r| r.sent_at <= at && r.sent_at + r.latency >= at).count();
+        lines.push(format!("Long-poll first early end: {} open at the time ({})", open, cause(first)));
+    }
+    lines
+}
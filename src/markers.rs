@@ -0,0 +1,77 @@
+use std::{fs::OpenOptions, io::Write};
+
+use chrono::Utc;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::json;
+
+// 외부 대시보드와 실행 구간을 맞추기 위한 마커
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkerKind {
+    Start,
+    Stop,
+    Interval,
+    #[serde(rename = "drain_start")]
+    DrainStart,
+    #[serde(rename = "drain_end")]
+    DrainEnd,
+    #[serde(rename = "disruption_start")]
+    DisruptionStart,
+    #[serde(rename = "disruption_end")]
+    DisruptionEnd,
+}
+
+impl MarkerKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarkerKind::Start => "start",
+            MarkerKind::Stop => "stop",
+            MarkerKind::Interval => "interval",
+            MarkerKind::DrainStart => "drain_start",
+            MarkerKind::DrainEnd => "drain_end",
+            MarkerKind::DisruptionStart => "disruption_start",
+            MarkerKind::DisruptionEnd => "disruption_end",
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct RunMarker {
+    pub kind: MarkerKind,
+    // UTC 밀리초 타임스탬프
+    pub time_ms: i64,
+    pub text: String,
+}
+
+impl RunMarker {
+    pub fn new(kind: MarkerKind, text: &str) -> Self {
+        Self { kind, time_ms: Utc::now().timestamp_millis(), text: text.to_owned() }
+    }
+}
+
+// 마커 파일에 한 줄씩 JSON 으로 기록
+pub fn append_to_file(path: &str, marker: &RunMarker) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(marker)?)
+}
+
+// Grafana annotation API (POST /api/annotations) 형식으로 전송
+pub async fn post_annotation(url: &str, token: &str, marker: &RunMarker) -> Result<(), String> {
+    let body = json!({
+        "time": marker.time_ms,
+        "tags": ["envoy-lb-client", marker.kind.as_str()],
+        "text": marker.text,
+    });
+
+    let mut request = Client::new().post(url).json(&body);
+    if !token.is_empty() {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    Ok(())
+}
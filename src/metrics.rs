@@ -0,0 +1,303 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compare::{Side, SideStats},
+    error_code::ErrorCode,
+    retention::{Bucket, History},
+    routes::{self, Route, RouteResult},
+    stats::{self, RequestRecord, UpstreamStats},
+};
+
+// 초당 처리량 차트에 남기는 최근 초 수
+const RPS_SECONDS: usize = 120;
+// 지연 분해 차트에 표시할 최근 요청 수
+pub const ATTRIBUTION_POINTS: usize = 200;
+
+// 실시간 지표 창에 보이는 값 (제어 API 로 관찰자에게도 보낸다)
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LiveMetrics {
+    pub sent: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    // 최근 1초 동안 끝난 요청 수
+    pub rps: u64,
+    // 설정한 백분위와 그 지연
+    pub latency: Vec<(f64, Duration)>,
+    // 응답 본문 평균 크기 (본문을 받은 응답이 없으면 None)
+    pub avg_size: Option<u64>,
+    // 상태 코드 계열별 수 (2xx, 3xx, 4xx, 5xx, 응답 없음)
+    pub classes: [usize; 5],
+    // 초마다 끝난 요청 수 (오래된 것부터, 마지막은 진행 중인 초)
+    pub rps_series: Vec<u64>,
+    // 오래되어 구간 집계로 합친 요청 수 (백분위는 히스토그램으로 어림한 값)
+    pub downsampled: usize,
+}
+
+// 상태 코드 계열 번호 (2xx, 3xx, 4xx, 5xx, 응답 없음)
+pub fn status_class(status: Option<u16>) -> usize {
+    match status {
+        Some(status @ 200..600) => (status / 100 - 2) as usize,
+        _ => 4,
+    }
+}
+
+fn finished_at(record: &RequestRecord) -> Instant {
+    record.sent_at + record.latency
+}
+
+// now 는 실행 중이면 현재 시각, 끝났거나 재생 중이면 마지막 응답 시각
+// history 는 구간 집계로 합친 오래된 결과 (재생 중에는 비어 있는 집계를 넘긴다)
+pub fn live(records: &[RequestRecord], history: &History, sent: usize, now: Option<Instant>, percentiles: &[f64]) -> Option<LiveMetrics> {
+    // 비교 실행이면 A 쪽만 센다
+    let primary: Vec<RequestRecord>;
+    let records = if records.iter().all(RequestRecord::primary) {
+        records
+    } else {
+        primary = records.iter().filter(|r| r.primary()).cloned().collect();
+        &primary
+    };
+    let first = records.iter().map(|r| r.sent_at).chain(history.origin()).min()?;
+    let now = now.unwrap_or_else(|| records.iter().map(finished_at).max().unwrap_or(first));
+
+    let totals = history.totals(records);
+    let (completed, succeeded, sizes, classes, latency) = match &totals {
+        Some(totals) => (
+            totals.requests,
+            totals.requests - totals.errors,
+            (totals.body_bytes, totals.bodies),
+            totals.classes,
+            percentiles.iter().map(|p| (*p, totals.latency.percentile(*p))).collect(),
+        ),
+        None => {
+            let mut latency: Vec<Duration> = records.iter().map(|r| r.latency).collect();
+            latency.sort();
+            let sizes: Vec<u64> = records.iter().filter_map(|r| r.response_size).collect();
+            let mut classes = [0; 5];
+            for record in records {
+                classes[status_class(record.status)] += 1;
+            }
+            (
+                records.len(),
+                records.iter().filter(|r| r.success).count(),
+                (sizes.iter().sum(), sizes.len()),
+                classes,
+                percentiles.iter().map(|p| (*p, stats::percentile(&latency, *p))).collect(),
+            )
+        }
+    };
+
+    let seconds = now.saturating_duration_since(first).as_secs() as usize + 1;
+    let mut rps_series = vec![0; seconds.min(RPS_SECONDS)];
+    let skipped = seconds - rps_series.len();
+    for record in records {
+        let second = finished_at(record).saturating_duration_since(first).as_secs() as usize;
+        if let Some(count) = second.checked_sub(skipped).and_then(|i| rps_series.get_mut(i)) {
+            *count += 1;
+        }
+    }
+    for (at, count) in history.per_second(first + Duration::from_secs(skipped as u64)) {
+        if let Some(slot) = (at.saturating_duration_since(first).as_secs() as usize).checked_sub(skipped).and_then(|i| rps_series.get_mut(i)) {
+            *slot += count;
+        }
+    }
+    let rps = records.iter().filter(|r| now.saturating_duration_since(finished_at(r)) < Duration::from_secs(1)).count() as u64;
+
+    Some(LiveMetrics {
+        sent: sent.max(completed),
+        succeeded,
+        failed: completed - succeeded,
+        rps,
+        latency,
+        avg_size: (sizes.1 > 0).then(|| sizes.0 / sizes.1 as u64),
+        classes,
+        rps_series,
+        downsampled: history.folded(),
+    })
+}
+
+// 화면용 실시간 지표를 RequestFinished 이벤트마다 조금씩 쌓는다 (매 프레임 전체 기록을 다시 훑지 않는다)
+// 백분위는 히스토그램으로 어림한 값이고, 초당 처리량은 처음 받은 요청의 전송 시각부터 센다
+#[derive(Default)]
+pub struct Tracker {
+    first: Option<Instant>,
+    last: Option<Instant>,
+    totals: Bucket,
+    // 초마다 끝난 요청 수. 앞쪽 skipped 초는 버렸다
+    per_second: VecDeque<u64>,
+    skipped: usize,
+    // 최근 1초 남짓 동안 끝난 시각
+    recent: VecDeque<Instant>,
+}
+
+impl Tracker {
+    // 비교 실행의 B 쪽 결과는 세지 않는다
+    pub fn add(&mut self, record: &RequestRecord) {
+        if !record.primary() {
+            return;
+        }
+        let first = *self.first.get_or_insert(record.sent_at);
+        let finished = finished_at(record);
+        self.last = self.last.max(Some(finished));
+        self.totals.add(record);
+
+        let second = finished.saturating_duration_since(first).as_secs() as usize;
+        if let Some(index) = second.checked_sub(self.skipped) {
+            if self.per_second.len() <= index {
+                self.per_second.resize(index + 1, 0);
+            }
+            self.per_second[index] += 1;
+        }
+        while self.per_second.len() > RPS_SECONDS {
+            self.per_second.pop_front();
+            self.skipped += 1;
+        }
+
+        self.recent.push_back(finished);
+        self.prune(self.last.unwrap_or(finished));
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while self.recent.front().is_some_and(|at| now.saturating_duration_since(*at) >= Duration::from_secs(1)) {
+            self.recent.pop_front();
+        }
+    }
+
+    // 실패 코드별 개수 (지금까지 받은 요청 전체)
+    pub fn error_codes(&self) -> &BTreeMap<ErrorCode, usize> {
+        &self.totals.error_codes
+    }
+
+    // now 는 실행 중이면 현재 시각 (None 이면 마지막 응답 시각), downsampled 는 구간 집계로 합친 요청 수
+    pub fn live(&mut self, sent: usize, downsampled: usize, now: Option<Instant>, percentiles: &[f64]) -> Option<LiveMetrics> {
+        let first = self.first?;
+        let now = now.or(self.last).unwrap_or(first);
+        self.prune(now);
+
+        let seconds = now.saturating_duration_since(first).as_secs() as usize + 1;
+        let mut rps_series = vec![0; seconds.min(RPS_SECONDS)];
+        let skipped = seconds - rps_series.len();
+        for (i, count) in self.per_second.iter().enumerate() {
+            if let Some(slot) = (self.skipped + i).checked_sub(skipped).and_then(|i| rps_series.get_mut(i)) {
+                *slot += count;
+            }
+        }
+
+        let totals = &self.totals;
+        Some(LiveMetrics {
+            sent: sent.max(totals.requests),
+            succeeded: totals.requests - totals.errors,
+            failed: totals.errors,
+            rps: self.recent.iter().filter(|at| now.saturating_duration_since(**at) < Duration::from_secs(1)).count() as u64,
+            latency: percentiles.iter().map(|p| (*p, totals.latency.percentile(*p))).collect(),
+            avg_size: (totals.bodies > 0).then(|| totals.body_bytes / totals.bodies as u64),
+            classes: totals.classes,
+            rps_series,
+            downsampled,
+        })
+    }
+}
+
+// 기록으로 그리는 창 (업스트림, 경로, 지연 분해, A/B 비교) 의 집계. RequestFinished 이벤트마다 조금씩 쌓는다
+// 지연 백분위는 히스토그램으로 어림한 값
+#[derive(Default)]
+pub struct Panels {
+    // 업스트림 이름 -> 집계 (비교 실행이면 A 쪽만)
+    upstreams: BTreeMap<String, Bucket>,
+    // A/B 비교 실행의 대상별 (전체, 성공한 요청) 집계. 비교 실행이 아니면 비어 있다
+    sides: [(Bucket, Bucket); 2],
+    // 최근 요청의 (프록시 구간, 업스트림 처리 시간) ms
+    attribution: VecDeque<(u64, u64)>,
+    // 경로별 통과/실패. 경로 목록은 AppState 에 있어 경로 요청은 쌓아 두었다가 routes 에서 판정한다
+    routes: Vec<RouteResult>,
+    unchecked: Vec<Arc<RequestRecord>>,
+}
+
+impl Panels {
+    // 재생처럼 이벤트 없이 기록만 있을 때
+    pub fn from_records(records: &[RequestRecord]) -> Self {
+        let mut panels = Self::default();
+        for record in records {
+            panels.add(Arc::new(record.clone()));
+        }
+        panels
+    }
+
+    pub fn add(&mut self, record: Arc<RequestRecord>) {
+        if record.primary()
+            && let Some(upstream) = &record.upstream
+        {
+            self.upstreams.entry(upstream.clone()).or_default().add(&record);
+        }
+        if let Some(side) = record.side {
+            let (all, succeeded) = &mut self.sides[side as usize];
+            all.add(&record);
+            if record.success {
+                succeeded.add(&record);
+            }
+        }
+        if let Some(upstream) = record.upstream_time {
+            self.attribution.push_back((record.latency.saturating_sub(upstream).as_millis() as u64, upstream.as_millis() as u64));
+            if self.attribution.len() > ATTRIBUTION_POINTS {
+                self.attribution.pop_front();
+            }
+        }
+        if record.route.is_some() {
+            self.unchecked.push(record);
+        }
+    }
+
+    // 업스트림별 통계 (이름순, 보고용 지연 목록은 비워 둔다)
+    pub fn upstreams(&self) -> Vec<UpstreamStats> {
+        self.upstreams
+            .iter()
+            .map(|(name, bucket)| UpstreamStats {
+                name: name.clone(),
+                count: bucket.requests,
+                failed: bucket.errors,
+                p50: bucket.latency.percentile(50.0),
+                p99: bucket.latency.percentile(99.0),
+                latency: Vec::new(),
+            })
+            .collect()
+    }
+
+    pub fn attribution(&self) -> Vec<(u64, u64)> {
+        self.attribution.iter().copied().collect()
+    }
+
+    // 실시간 비교 패널용 (A, B). 비교 실행이 아니면 None
+    pub fn compare(&self) -> Option<(SideStats, SideStats)> {
+        if self.sides.iter().all(|(all, _)| all.requests == 0) {
+            return None;
+        }
+        let side = |side: Side| {
+            let (all, succeeded) = &self.sides[side as usize];
+            SideStats {
+                count: all.requests,
+                failed: all.errors,
+                avg: succeeded.latency_sum / succeeded.requests.max(1) as u32,
+                p50: succeeded.latency.percentile(50.0),
+                p90: succeeded.latency.percentile(90.0),
+                p99: succeeded.latency.percentile(99.0),
+            }
+        };
+        Some((side(Side::A), side(Side::B)))
+    }
+
+    // 쌓아 둔 경로 요청을 판정한 경로별 통과/실패 (경로 목록이 바뀌면 처음부터 센다)
+    pub fn routes(&mut self, routes: &[Route]) -> Vec<RouteResult> {
+        if self.routes.len() != routes.len() || self.routes.iter().zip(routes).any(|(result, route)| result.path != route.path) {
+            self.routes = routes::empty(routes);
+        }
+        for record in self.unchecked.drain(..) {
+            routes::tally(&mut self.routes, routes, &record);
+        }
+        self.routes.clone()
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+use tokio_native_tls::{native_tls, TlsConnector};
+
+use crate::{preflight::CheckResult, utils::RequestTemplate};
+
+// 잘못된 프로토콜을 보낸 뒤 반응을 기다리는 최대 시간 (요청 타임아웃이 더 짧으면 그 값)
+const MAX_WAIT: Duration = Duration::from_secs(5);
+
+// HTTP/2 연결 프리페이스와 빈 SETTINGS 프레임
+const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\x00\x00\x00\x04\x00\x00\x00\x00\x00";
+
+pub const NAMES: [&str; 2] = ["Wrong protocol", "h2 preface"];
+
+// 잘못된 프로토콜을 받은 리스너의 반응
+enum Reaction {
+    // HTTP 응답 (첫 줄)
+    Http(String),
+    // HTTP/2 프레임 (SETTINGS, GOAWAY 등 프레임 종류)
+    Http2(u8),
+    // TLS alert (설명 코드), ServerHello
+    TlsAlert(u8),
+    TlsHello,
+    Reset,
+    Closed,
+    // 기다리는 동안 아무 응답도, 연결 종료도 없음
+    Silent,
+    Other(String),
+}
+
+impl Reaction {
+    fn describe(&self) -> String {
+        match self {
+            Reaction::Http(line) => format!("HTTP response '{}'", line),
+            Reaction::Http2(0x04) => String::from("HTTP/2 SETTINGS"),
+            Reaction::Http2(0x07) => String::from("HTTP/2 GOAWAY"),
+            Reaction::Http2(kind) => format!("HTTP/2 frame type {}", kind),
+            Reaction::TlsAlert(code) => format!("TLS alert {}", code),
+            Reaction::TlsHello => String::from("TLS ServerHello"),
+            Reaction::Reset => String::from("connection reset"),
+            Reaction::Closed => String::from("connection closed without response"),
+            Reaction::Silent => String::from("no response"),
+            Reaction::Other(e) => e.clone(),
+        }
+    }
+}
+
+// 첫 응답 바이트로 반응 분류
+fn classify(bytes: &[u8]) -> Reaction {
+    if bytes.starts_with(b"HTTP/") {
+        let line = bytes.split(|b| *b == b'\r' || *b == b'\n').next().unwrap_or_default();
+        return Reaction::Http(String::from_utf8_lossy(line).into_owned());
+    }
+    match bytes {
+        [0x15, 0x03, _, _, _, _, code, ..] => Reaction::TlsAlert(*code),
+        [0x16, 0x03, ..] => Reaction::TlsHello,
+        // 프레임 헤더: 길이(3) 종류(1) 플래그(1) 스트림(4)
+        [_, _, _, kind @ (0x04 | 0x07), _, _, _, _, _, ..] => Reaction::Http2(*kind),
+        // 텍스트면 첫 줄 앞부분을 함께 보여 준다
+        _ => match std::str::from_utf8(bytes).ok().and_then(|text| text.lines().next()) {
+            Some(line) => Reaction::Other(format!("unrecognized response ({} bytes, '{}')", bytes.len(), line.chars().take(40).collect::<String>())),
+            None => Reaction::Other(format!("unrecognized response ({} bytes)", bytes.len())),
+        },
+    }
+}
+
+// 보낸 뒤 첫 응답을 읽어 분류
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S, bytes: &[u8], wait: Duration) -> Reaction {
+    if let Err(e) = stream.write_all(bytes).await {
+        return Reaction::Other(format!("write failed: {}", e));
+    }
+    let mut buf = [0u8; 512];
+    match timeout(wait, stream.read(&mut buf)).await {
+        Err(_) => Reaction::Silent,
+        Ok(Ok(0)) => Reaction::Closed,
+        Ok(Ok(n)) => classify(&buf[..n]),
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::ConnectionReset => Reaction::Reset,
+        Ok(Err(e)) => Reaction::Other(format!("read failed: {}", e)),
+    }
+}
+
+// SNI 를 담은 최소한의 TLS 1.2 ClientHello 레코드
+// random: ClientHello 의 random 필드 (실행 시드를 따른다)
+fn client_hello(host: &str, random: [u8; 32]) -> Vec<u8> {
+    let mut extensions = Vec::new();
+    if host.parse::<std::net::IpAddr>().is_err() {
+        let name = host.as_bytes();
+        let list_len = name.len() + 3;
+        extensions.extend_from_slice(&[0x00, 0x00]);
+        extensions.extend_from_slice(&((list_len + 2) as u16).to_be_bytes());
+        extensions.extend_from_slice(&(list_len as u16).to_be_bytes());
+        extensions.push(0x00);
+        extensions.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        extensions.extend_from_slice(name);
+    }
+
+    let mut hello = vec![0x03, 0x03];
+    hello.extend_from_slice(&random);
+    // 세션 id 없음, 암호 스위트 2개 (TLS_AES_128_GCM_SHA256, ECDHE_RSA_AES_128_GCM_SHA256), 압축 없음
+    hello.extend_from_slice(&[0x00, 0x00, 0x04, 0x13, 0x01, 0xc0, 0x2f, 0x01, 0x00]);
+    hello.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+    hello.extend_from_slice(&extensions);
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(hello.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&hello);
+    let mut record = vec![0x16, 0x03, 0x01];
+    record.extend_from_slice(&(handshake.len() as u16).to_be_bytes());
+    record.extend_from_slice(&handshake);
+    record
+}
+
+fn check(name: &'static str, started: Instant, connected: Result<(Reaction, &'static str), String>, wait: Duration) -> CheckResult {
+    // 거절(응답, 종료, 리셋)은 정상 반응이고, 응답 없이 연결을 붙잡고 있는 경우만 실패로 본다
+    let result = connected.and_then(|(reaction, verdict)| match reaction {
+        Reaction::Silent => Err(format!("no response within {}s (listener holds the connection)", wait.as_secs_f64())),
+        reaction => Ok(format!("{} -> {}", reaction.describe(), verdict)),
+    });
+    CheckResult::new(name, started, result)
+}
+
+async fn connect(addr: SocketAddr, template: &RequestTemplate) -> Result<TcpStream, String> {
+    match timeout(template.connect_timeout, TcpStream::connect(addr)).await {
+        Ok(Ok(stream)) => Ok(stream),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(String::from("connect timed out")),
+    }
+}
+
+// 평문 리스너에는 TLS ClientHello 를, TLS 리스너에는 평문 HTTP 요청을 보낸다
+async fn wrong_protocol(addr: SocketAddr, template: &RequestTemplate, wait: Duration) -> CheckResult {
+    let started = Instant::now();
+    let url = template.url();
+    let host = url.host_str().unwrap_or_default();
+    let tls = url.scheme() == "https";
+    let result = async {
+        let mut stream = connect(addr, template).await?;
+        Ok(if tls {
+            let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", url.path(), host);
+            let reaction = exchange(&mut stream, request.as_bytes(), wait).await;
+            let verdict = match reaction {
+                Reaction::Http(_) => "plaintext accepted on the TLS listener (raw_buffer filter chain matched)",
+                Reaction::TlsAlert(_) => "rejected by the TLS transport socket",
+                _ => "rejected (no filter chain for plaintext)",
+            };
+            (reaction, verdict)
+        } else {
+            let reaction = exchange(&mut stream, &client_hello(host, template.random()), wait).await;
+            let verdict = match reaction {
+                Reaction::TlsHello => "TLS accepted on the plaintext listener (tls filter chain matched)",
+                Reaction::Http(_) => "rejected by the HTTP codec",
+                _ => "rejected (no filter chain for TLS)",
+            };
+            (reaction, verdict)
+        })
+    }
+    .await;
+    check(NAMES[0], started, result, wait)
+}
+
+// http/1.1 리스너가 HTTP/2 프리페이스(prior knowledge)를 받았을 때의 반응. TLS 대상은 ALPN 없이 핸드셰이크한 뒤 보낸다
+async fn h2_preface(addr: SocketAddr, template: &RequestTemplate, wait: Duration) -> CheckResult {
+    let started = Instant::now();
+    let url = template.url();
+    let host = url.host_str().unwrap_or_default();
+    let result = async {
+        let mut stream = connect(addr, template).await?;
+        let reaction = if url.scheme() == "https" {
+            let connector = native_tls::TlsConnector::builder().danger_accept_invalid_certs(true).build().map_err(|e| e.to_string())?;
+            let mut stream = match timeout(template.tls_timeout, TlsConnector::from(connector).connect(host, stream)).await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(e)) => return Err(format!("TLS handshake failed: {}", e)),
+                Err(_) => return Err(String::from("TLS handshake timed out")),
+            };
+            exchange(&mut stream, H2_PREFACE, wait).await
+        } else {
+            exchange(&mut stream, H2_PREFACE, wait).await
+        };
+        let verdict = match reaction {
+            Reaction::Http2(_) => "HTTP/2 prior knowledge accepted (http2 or auto codec)",
+            Reaction::Http(_) => "rejected by an HTTP/1.1-only codec",
+            _ => "rejected",
+        };
+        Ok((reaction, verdict))
+    }
+    .await;
+    check(NAMES[1], started, result, wait)
+}
+
+// 리스너 필터 체인 매칭 검증용 프로토콜 불일치 프로브 (사전 점검에 이어서 실행)
+pub async fn run(template: &RequestTemplate, addr: SocketAddr) -> Vec<CheckResult> {
+    let wait = template.request_timeout.min(MAX_WAIT);
+    vec![wrong_protocol(addr, template, wait).await, h2_preface(addr, template, wait).await]
+}
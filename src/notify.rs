@@ -0,0 +1,4 @@
+// 알림 본문: 제목 + 요약을 코드 블록으로 (Slack incoming webhook 호환 형식으로 보낸다)
+pub fn message(title: &str, target: &str, summary: &str) -> String {
+    format!("*envoy-lb-client* {} (`{}`)\n```{}```", title, target, summary)
+}
@@ -0,0 +1,298 @@
+use std::fs;
+
+use bytes::Bytes;
+use rand::{distr::Alphanumeric, seq::IndexedRandom, Rng};
+use reqwest::header::{HeaderName, HeaderValue, CONTENT_ENCODING, CONTENT_TYPE};
+
+// 사전 파일 읽기. 한 줄에 항목 하나 (토큰, JWT, 쿠키 등), 빈 줄과 # 주석은 무시
+pub fn load_dictionary(path: &str) -> Result<Vec<String>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read payload dictionary '{}': {}", path, e))?;
+    let entries: Vec<String> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+        .collect();
+    if entries.is_empty() {
+        return Err(format!("payload dictionary '{}' has no entries", path));
+    }
+    Ok(entries)
+}
+
+// 사전 항목을 무작위로 골라 "; " 로 이어 붙인 size kb 크기의 값 (마지막 항목은 크기에 맞춰 자름)
+pub fn from_dictionary(rng: &mut impl Rng, entries: &[String], size_kb: usize) -> String {
+    let size = size_kb * 1024;
+    let mut content = String::with_capacity(size + 64);
+    while content.len() < size {
+        if !content.is_empty() {
+            content.push_str("; ");
+        }
+        content.push_str(entries.choose(rng).expect("dictionary is not empty"));
+    }
+    // 멀티바이트 문자 중간에서 자르지 않도록 경계를 맞춘다
+    let mut end = size.min(content.len());
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    content.truncate(end);
+    content
+}
+
+// 무작위 헤더/쿼리스트링 값에 쓸 문자 (Envoy 헤더 검증 시험)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    Alphanumeric,
+    // 공백을 뺀 출력 가능한 ASCII 전체 (0x21-0x7e)
+    Visible,
+    // 영숫자 사이에 %XX 퍼센트 인코딩 (%00, %2F 같은 값 포함)
+    Percent,
+    // 2-4 바이트 UTF-8 문자 (헤더에는 obs-text 바이트로 그대로 실린다)
+    Utf8,
+}
+
+pub fn parse_charset(value: &str) -> Result<Charset, String> {
+    match value {
+        "alnum" => Ok(Charset::Alphanumeric),
+        "visible" => Ok(Charset::Visible),
+        "percent" => Ok(Charset::Percent),
+        "utf8" => Ok(Charset::Utf8),
+        _ => Err(format!("unknown header charset '{}' (alnum/visible/percent/utf8)", value)),
+    }
+}
+
+// 멀티바이트 문자를 고를 범위: Latin-1 보충 (2바이트), 한글 음절 (3바이트), 이모지 (4바이트)
+const UTF8_RANGES: [(u32, u32); 3] = [(0xa1, 0xff), (0xac00, 0xd7a3), (0x1f600, 0x1f64f)];
+
+// size kb 크기의 무작위 값. UTF-8 은 문자 경계에서 자르므로 조금 짧을 수 있다
+pub fn random_value(rng: &mut impl Rng, charset: Charset, size_kb: usize) -> String {
+    let size = size_kb * 1024;
+    if charset == Charset::Alphanumeric {
+        return rng.sample_iter(&Alphanumeric).take(size).map(char::from).collect();
+    }
+    let mut content = String::with_capacity(size + 4);
+    while content.len() < size {
+        match charset {
+            Charset::Visible => content.push(char::from(rng.random_range(0x21u8..=0x7e))),
+            Charset::Percent if content.len() + 3 <= size && rng.random_bool(0.5) => content.push_str(&format!("%{:02X}", rng.random::<u8>())),
+            Charset::Utf8 => {
+                let (low, high) = UTF8_RANGES[rng.random_range(0..UTF8_RANGES.len())];
+                content.push(char::from_u32(rng.random_range(low..=high)).expect("ranges hold valid scalar values"));
+            }
+            _ => content.push(char::from(rng.sample(Alphanumeric))),
+        }
+    }
+    let mut end = size;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    content.truncate(end);
+    content
+}
+
+// JSON 요청 본문: 직접 입력하거나 "@파일경로". 비어 있으면 "{}"
+pub fn json_body(value: &str) -> Result<Bytes, String> {
+    let text = match value.strip_prefix('@') {
+        Some(path) => fs::read_to_string(path.trim()).map_err(|e| format!("Failed to read JSON body '{}': {}", path.trim(), e))?,
+        None if value.is_empty() => String::from("{}"),
+        None => value.to_owned(),
+    };
+    serde_json::from_str::<serde_json::Value>(&text).map_err(|e| format!("invalid JSON body: {}", e))?;
+    Ok(Bytes::from(text))
+}
+
+// 정해진 크기로 만드는 일반 요청 본문 (Envoy 버퍼 한계 시험)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BodyType {
+    // 영숫자 텍스트
+    Text,
+    // 작은 객체를 이어 붙인 JSON 배열
+    Json,
+    // 무작위 바이트
+    Binary,
+}
+
+pub fn parse_body_type(value: &str) -> Result<BodyType, String> {
+    match value {
+        "text" => Ok(BodyType::Text),
+        "json" => Ok(BodyType::Json),
+        "binary" => Ok(BodyType::Binary),
+        _ => Err(format!("unknown body type '{}' (text/json/binary)", value)),
+    }
+}
+
+// size_kb 크기의 본문과 Content-Type
+pub fn sized_body(rng: &mut impl Rng, kind: BodyType, size_kb: usize) -> Result<(Bytes, HeaderValue), String> {
+    if size_kb > MAX_BODY_KB {
+        return Err(format!("body size {}kb is over the limit ({}kb)", size_kb, MAX_BODY_KB));
+    }
+    let size = size_kb * 1024;
+    let text = |rng: &mut dyn rand::RngCore, len: usize| rng.sample_iter(&Alphanumeric).take(len).map(char::from).collect::<String>();
+    Ok(match kind {
+        BodyType::Text => (Bytes::from(text(rng, size)), HeaderValue::from_static("text/plain")),
+        BodyType::Binary => {
+            let mut body = vec![0u8; size];
+            rng.fill(&mut body[..]);
+            (Bytes::from(body), HeaderValue::from_static("application/octet-stream"))
+        }
+        BodyType::Json => {
+            // {"index":N,"value":"..."} 를 이어 붙이고 마지막 값의 길이로 크기를 맞춘다 (마지막 객체가 들어갈 자리를 남겨 둔다)
+            let mut json = String::with_capacity(size + 64);
+            json.push('[');
+            let mut index = 0;
+            while json.len() + 128 < size {
+                if index > 0 {
+                    json.push(',');
+                }
+                json.push_str(&format!("{{\"index\":{},\"value\":\"{}\"}}", index, text(rng, 32)));
+                index += 1;
+            }
+            let prefix = if index > 0 { "," } else { "" };
+            let fixed = format!("{}{{\"index\":{},\"value\":\"\"}}]", prefix, index).len();
+            let last = text(rng, size.saturating_sub(json.len() + fixed));
+            json.push_str(&format!("{}{{\"index\":{},\"value\":\"{}\"}}]", prefix, index, last));
+            (Bytes::from(json), HeaderValue::from_static("application/json"))
+        }
+    })
+}
+
+// 위험 페이로드 크기 상한 (kb, nested-json 은 깊이). 테스트 장비가 먼저 죽지 않도록 제한
+const MAX_BODY_KB: usize = 64 * 1024;
+const MAX_BOMB_KB: usize = 256 * 1024;
+const MAX_NESTING_DEPTH: usize = 1_000_000;
+const MAX_HEADER_KB: usize = 16 * 1024;
+
+// 디코더/트랜스코더 한계 시험용 비정상 입력 (위험 페이로드 옵션을 켜야 사용 가능)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Pathological {
+    // 같은 바이트를 반복한 본문 (압축률이 매우 높음)
+    Compressible,
+    // 크게 풀리는 gzip 본문 (Content-Encoding: gzip, decompressor 한계)
+    GzipBomb,
+    // 깊게 중첩된 JSON 본문 (JSON 트랜스코더, 파서 한계)
+    NestedJson,
+    // 아주 긴 헤더 한 줄
+    LongHeader,
+}
+
+pub fn parse_pathological(value: &str) -> Result<Option<Pathological>, String> {
+    match value {
+        "" | "off" => Ok(None),
+        "compressible" => Ok(Some(Pathological::Compressible)),
+        "gzip-bomb" => Ok(Some(Pathological::GzipBomb)),
+        "nested-json" => Ok(Some(Pathological::NestedJson)),
+        "long-header" => Ok(Some(Pathological::LongHeader)),
+        _ => Err(format!("unknown pathological payload '{}' (off/compressible/gzip-bomb/nested-json/long-header)", value)),
+    }
+}
+
+// 요청 본문과 함께 붙일 헤더
+pub struct Generated {
+    pub body: Bytes,
+    pub headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+pub fn generate(kind: Pathological, size: usize) -> Result<Generated, String> {
+    let limit = match kind {
+        Pathological::Compressible => MAX_BODY_KB,
+        Pathological::GzipBomb => MAX_BOMB_KB,
+        Pathological::NestedJson => MAX_NESTING_DEPTH,
+        Pathological::LongHeader => MAX_HEADER_KB,
+    };
+    if size == 0 || size > limit {
+        return Err(format!("pathological payload size {} is out of range (1-{})", size, limit));
+    }
+
+    Ok(match kind {
+        Pathological::Compressible => Generated { body: Bytes::from(vec![b'A'; size * 1024]), headers: vec![(CONTENT_TYPE, HeaderValue::from_static("text/plain"))] },
+        Pathological::GzipBomb => Generated {
+            body: Bytes::from(gzip_zeros(size * 1024)),
+            headers: vec![(CONTENT_ENCODING, HeaderValue::from_static("gzip")), (CONTENT_TYPE, HeaderValue::from_static("application/octet-stream"))],
+        },
+        Pathological::NestedJson => {
+            let mut json = String::with_capacity(size * 6 + 2);
+            json.push_str(&"{\"a\":".repeat(size));
+            json.push_str("null");
+            json.push_str(&"}".repeat(size));
+            Generated { body: Bytes::from(json), headers: vec![(CONTENT_TYPE, HeaderValue::from_static("application/json"))] }
+        }
+        Pathological::LongHeader => Generated {
+            body: Bytes::new(),
+            headers: vec![(HeaderName::from_static("x-long-header"), HeaderValue::from_str(&"a".repeat(size * 1024)).unwrap())],
+        },
+    })
+}
+
+// size 바이트의 0 을 압축한 gzip. 고정 허프만 블록에 거리 1, 길이 258 반복 참조만 써서 약 160:1 로 줄어든다
+fn gzip_zeros(size: usize) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    // BFINAL=1, BTYPE=01 (고정 허프만)
+    bits.push(1, 1);
+    bits.push(1, 2);
+    // 리터럴 0 (코드 00110000) 하나 뒤에 "직전 바이트 258개 복사"(길이 코드 285 = 11000101, 거리 코드 0 = 00000) 반복
+    bits.push_code(0b0011_0000, 8);
+    let matches = (size - 1) / 258;
+    for _ in 0..matches {
+        bits.push_code(0b1100_0101, 8);
+        bits.push_code(0, 5);
+    }
+    for _ in 0..(size - 1) % 258 {
+        bits.push_code(0b0011_0000, 8);
+    }
+    // 블록 끝 (코드 256 = 0000000)
+    bits.push_code(0, 7);
+
+    let mut gzip = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+    gzip.extend(bits.finish());
+    gzip.extend(crc32_zeros(size).to_le_bytes());
+    gzip.extend((size as u32).to_le_bytes());
+    gzip
+}
+
+// deflate 비트 스트림 (하위 비트부터 채움)
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u8,
+    used: u8,
+}
+
+impl BitWriter {
+    // 값의 하위 비트부터 기록 (블록 헤더, 추가 비트)
+    fn push(&mut self, value: u32, count: u8) {
+        for i in 0..count {
+            self.current |= (((value >> i) & 1) as u8) << self.used;
+            self.used += 1;
+            if self.used == 8 {
+                self.bytes.push(self.current);
+                self.current = 0;
+                self.used = 0;
+            }
+        }
+    }
+
+    // 허프만 코드는 상위 비트부터 기록
+    fn push_code(&mut self, code: u32, len: u8) {
+        for i in (0..len).rev() {
+            self.push((code >> i) & 1, 1);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.used > 0 {
+            self.bytes.push(self.current);
+        }
+        self.bytes
+    }
+}
+
+// size 바이트의 0 에 대한 CRC-32
+fn crc32_zeros(size: usize) -> u32 {
+    let table: Vec<u32> = (0..256u32)
+        .map(|n| (0..8).fold(n, |c, _| if c & 1 == 1 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 }))
+        .collect();
+    let mut crc = 0xFFFF_FFFFu32;
+    for _ in 0..size {
+        crc = table[(crc & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
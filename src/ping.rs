@@ -0,0 +1,89 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use h2::Ping;
+use tokio::time::timeout;
+
+use crate::{
+    conn,
+    stats::{self, format_ms, RequestRecord},
+    utils::RequestTemplate,
+    AppState,
+};
+
+// 연결 수준 RTT 표본 (PING 을 보낸 시각, 응답까지 걸린 시간)
+#[derive(Clone)]
+pub struct PingSample {
+    pub at: Instant,
+    pub rtt: Duration,
+}
+
+// 실행 동안 별도 h2 연결 하나로 주기마다 PING 을 보내 RTT 를 잰다
+// 요청 지연과 달리 프록시/업스트림 처리 시간이 들어가지 않으므로 네트워크 흔들림만 보인다
+pub async fn sample(template: Arc<RequestTemplate>, interval: Duration, state: Arc<Mutex<AppState>>) {
+    let (_send, mut connection, remote) = match conn::h2(&template).await {
+        Ok(connected) => connected,
+        Err(e) => {
+            state.lock().unwrap().add_log(&format!("PING sampler failed: {}", e));
+            return;
+        }
+    };
+    let Some(mut pings) = connection.ping_pong() else {
+        return;
+    };
+    let connection = tokio::spawn(connection);
+    state.lock().unwrap().add_log(&format!("PING sampler connected [{}], every {}", remote, format_ms(interval)));
+
+    while state.lock().unwrap().running {
+        let at = Instant::now();
+        match timeout(template.request_timeout, pings.ping(Ping::opaque())).await {
+            Ok(Ok(_)) => state.lock().unwrap().pings.push(PingSample { at, rtt: at.elapsed() }),
+            Ok(Err(e)) => {
+                state.lock().unwrap().add_log(&format!("PING sampler stopped: {}", conn::describe_h2_error(&e)));
+                break;
+            }
+            Err(_) => {
+                state.lock().unwrap().add_log(&format!("PING sampler stopped: no PING ACK within {}", format_ms(template.request_timeout)));
+                break;
+            }
+        }
+        tokio::time::sleep(interval.saturating_sub(at.elapsed())).await;
+    }
+    connection.abort();
+}
+
+// 차트용 최근 PING RTT 와 요청 지연 (us)
+pub fn series(samples: &[PingSample], records: &[RequestRecord], last: usize) -> (Vec<u64>, Vec<u64>) {
+    if samples.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let rtt = samples[samples.len().saturating_sub(last)..].iter().map(|s| s.rtt.as_micros() as u64).collect();
+    let latency = records[records.len().saturating_sub(last)..].iter().map(|r| r.latency.as_micros() as u64).collect();
+    (rtt, latency)
+}
+
+// 실행 종료 보고: PING RTT 분포와 흔들림(연속 표본 차의 평균), 요청 지연 중 RTT 를 뺀 처리 시간
+pub fn summarize(samples: &[PingSample], records: &[RequestRecord], percentiles: &[f64]) -> Vec<String> {
+    if samples.is_empty() {
+        return Vec::new();
+    }
+    let mut rtt: Vec<Duration> = samples.iter().map(|s| s.rtt).collect();
+    let jitter = rtt.windows(2).map(|w| w[0].abs_diff(w[1])).sum::<Duration>() / (rtt.len() as u32 - 1).max(1);
+    rtt.sort();
+    let mut lines = vec![format!("PING RTT: {} samples | {} | jitter {}", rtt.len(), stats::format_percentiles(&rtt, percentiles), format_ms(jitter))];
+
+    let mut latency: Vec<Duration> = records.iter().filter(|r| r.status.is_some()).map(|r| r.latency).collect();
+    if !latency.is_empty() {
+        latency.sort();
+        let (latency_p50, rtt_p50) = (stats::percentile(&latency, 50.0), stats::percentile(&rtt, 50.0));
+        lines.push(format!(
+            "PING vs request p50: request {} - RTT {} = {} proxy/upstream processing",
+            format_ms(latency_p50),
+            format_ms(rtt_p50),
+            format_ms(latency_p50.saturating_sub(rtt_p50))
+        ));
+    }
+    lines
+}
@@ -0,0 +1,132 @@
+use std::time::{Duration, Instant};
+
+use reqwest::Url;
+use tokio::{net::TcpStream, time::timeout};
+use tokio_native_tls::TlsConnector;
+
+use crate::{mismatch, sni, streams, transport, utils::{probe_request, RequestTemplate}};
+
+// 사전 점검 한 단계의 결과
+#[derive(Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    // None 이면 앞 단계 실패로 건너뜀
+    pub ok: Option<bool>,
+    pub detail: String,
+    pub elapsed: Duration,
+}
+
+impl CheckResult {
+    pub fn new(name: &'static str, started: Instant, result: Result<String, String>) -> Self {
+        let (ok, detail) = match result {
+            Ok(detail) => (Some(true), detail),
+            Err(detail) => (Some(false), detail),
+        };
+        Self { name, ok, detail, elapsed: started.elapsed() }
+    }
+
+    pub fn skipped(name: &'static str) -> Self {
+        Self { name, ok: None, detail: String::from("skipped"), elapsed: Duration::ZERO }
+    }
+}
+
+// 실행 전 점검: DNS -> TCP 연결 -> TLS 핸드셰이크 -> 단일 요청
+// 각 단계는 실행 설정의 타임아웃을 따르고, 실패한 단계 이후는 건너뛴다
+// mismatch 가 켜져 있으면 이어서 프로토콜 불일치 프로브를, SNI 목록이 있으면 SNI 매트릭스를 돌린다 (DNS 가 실패했으면 건너뜀)
+// stream_limit 이 0 이 아니면 그 수까지 동시 스트림을 늘려 보는 한도 프로브도 돌린다
+pub async fn run(template: RequestTemplate, mismatch: bool, sni_names: Vec<String>, stream_limit: usize) -> Vec<CheckResult> {
+    let names = ["DNS resolve", "TCP connect", "TLS handshake", "Probe request"];
+    let mut results = Vec::new();
+
+    let url: &Url = template.url();
+    let host = url.host_str().unwrap_or_default().to_owned();
+    let port = url.port_or_known_default().unwrap_or(80);
+
+    // DNS
+    let started = Instant::now();
+    let addrs = match timeout(template.connect_timeout, tokio::net::lookup_host((host.as_str(), port))).await {
+        Ok(Ok(addrs)) => Ok(addrs.collect::<Vec<_>>()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(String::from("timed out")),
+    };
+    let addrs = match addrs.map(|a| template.family.order(a)) {
+        Ok(addrs) if !addrs.is_empty() => {
+            let list = addrs.iter().map(|a| a.ip().to_string()).collect::<Vec<_>>().join(", ");
+            results.push(CheckResult::new(names[0], started, Ok(list)));
+            addrs
+        }
+        Ok(_) => {
+            results.push(CheckResult::new(names[0], started, Err(String::from("no address for the selected address family"))));
+            Vec::new()
+        }
+        Err(e) => {
+            results.push(CheckResult::new(names[0], started, Err(e)));
+            Vec::new()
+        }
+    };
+
+    // TCP
+    let mut stream = None;
+    if !addrs.is_empty() {
+        let started = Instant::now();
+        let result = match timeout(template.connect_timeout, TcpStream::connect(addrs[0])).await {
+            Ok(Ok(s)) => {
+                let detail = format!("connected to {} ({})", addrs[0], transport::family_name(&addrs[0]));
+                stream = Some(s);
+                Ok(detail)
+            }
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(String::from("timed out")),
+        };
+        results.push(CheckResult::new(names[1], started, result));
+    }
+
+    // TLS
+    if let Some(stream) = stream {
+        let started = Instant::now();
+        if url.scheme() == "https" {
+            let result = match template.tls.connector().build() {
+                Ok(connector) => match timeout(template.tls_timeout, TlsConnector::from(connector).connect(&host, stream)).await {
+                    Ok(Ok(_)) => Ok(String::from("handshake completed")),
+                    Ok(Err(e)) => Err(e.to_string()),
+                    Err(_) => Err(String::from("timed out")),
+                },
+                Err(e) => Err(e.to_string()),
+            };
+            results.push(CheckResult::new(names[2], started, result));
+        } else {
+            results.push(CheckResult::new(names[2], started, Ok(String::from("not required (plain HTTP)"))));
+        }
+    }
+
+    // 단일 요청
+    if results.len() == 3 && results.iter().all(|r| r.ok == Some(true)) {
+        let started = Instant::now();
+        let result = probe_request(&template).await;
+        results.push(CheckResult::new(names[3], started, result));
+    }
+
+    for name in names.iter().skip(results.len()) {
+        results.push(CheckResult::skipped(name));
+    }
+    if mismatch {
+        match addrs.first() {
+            Some(addr) => results.extend(mismatch::run(&template, *addr).await),
+            None => results.extend(mismatch::NAMES.map(CheckResult::skipped)),
+        }
+    }
+    if !sni_names.is_empty() {
+        match addrs.first() {
+            Some(addr) if url.scheme() == "https" => results.extend(sni::run(&template, *addr, &sni_names).await),
+            Some(_) => results.push(CheckResult::new(sni::NAME, Instant::now(), Err(String::from("SNI matrix requires an https target")))),
+            None => results.push(CheckResult::skipped(sni::NAME)),
+        }
+    }
+    if stream_limit > 0 {
+        match addrs.first() {
+            Some(_) => results.push(streams::run(&template, stream_limit).await),
+            None => results.push(CheckResult::skipped(streams::NAME)),
+        }
+    }
+    results
+}
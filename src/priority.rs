@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use reqwest::header::{HeaderName, HeaderValue};
+
+use crate::{compare::Side, stats::{format_ms, format_percentiles, RequestRecord}};
+
+// 요청마다 순서대로 돌려 가며 붙이는 우선순위 헤더
+pub struct PrioritySweep {
+    pub name: HeaderName,
+    pub values: Vec<HeaderValue>,
+}
+
+impl PrioritySweep {
+    // index 번째 요청이 쓸 값의 번호
+    pub fn pick(&self, index: usize) -> usize {
+        index % self.values.len()
+    }
+}
+
+// "x-priority: high|low" 형식. 비어 있으면 None
+pub fn parse(value: &str) -> Result<Option<PrioritySweep>, String> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    let (name, values) = value.split_once(':').ok_or_else(|| format!("invalid priority header '{}' (expected 'name: v1|v2')", value))?;
+    let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| format!("invalid priority header name '{}'", name.trim()))?;
+    let values = values
+        .split('|')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| HeaderValue::from_str(v).map_err(|_| format!("invalid priority value '{}'", v)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if values.is_empty() {
+        return Err(String::from("priority header needs at least one value"));
+    }
+    Ok(Some(PrioritySweep { name, values }))
+}
+
+// 우선순위별 요청 수, 실패율, 503(부하 차단) 수, 지연 백분위
+// 낮은 우선순위만 실패나 지연이 늘면 우선순위 기반 라우팅/차단이 동작하는 것이다
+pub fn summarize(sweep: &PrioritySweep, records: &[RequestRecord], percentiles: &[f64]) -> Vec<String> {
+    sweep
+        .values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            // A/B 비교의 B 요청은 다른 대상이므로 제외
+            let group: Vec<&RequestRecord> = records.iter().filter(|r| r.priority == Some(i) && r.side != Some(Side::B)).collect();
+            let failed = group.iter().filter(|r| !r.success).count();
+            let shed = group.iter().filter(|r| r.status == Some(503)).count();
+            let mut latency: Vec<Duration> = group.iter().map(|r| r.latency).collect();
+            latency.sort();
+            format!(
+                "Priority {}: {} requests ({} failed, {:.1}%, 503 x{}) | {} max {}",
+                String::from_utf8_lossy(value.as_bytes()),
+                group.len(),
+                failed,
+                failed as f64 * 100.0 / group.len().max(1) as f64,
+                shed,
+                format_percentiles(&latency, percentiles),
+                format_ms(latency.last().copied().unwrap_or_default())
+            )
+        })
+        .collect()
+}
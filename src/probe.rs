@@ -0,0 +1,34 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+// 저율 연속 프로브 모드에서 관측한 장애 구간 (LDS/CDS 갱신, 리스너 워밍업 영향 측정)
+// 첫 실패부터 마지막 실패 이후에 보낸 요청이 성공할 때까지. 시각은 요청 전송 시각 기준
+pub struct DisruptionWindow {
+    pub started: Instant,
+    pub last_error: Instant,
+    pub errors: usize,
+}
+
+impl DisruptionWindow {
+    pub fn new(sent_at: Instant) -> Self {
+        Self { started: sent_at, last_error: sent_at, errors: 1 }
+    }
+}
+
+// 전송 시각(Instant)을 로그용 벽시계 시각으로 변환
+pub fn wall_time(at: Instant) -> DateTime<Local> {
+    Local::now() - at.elapsed()
+}
+
+pub fn format_time(at: Instant) -> String {
+    wall_time(at).format("%H:%M:%S%.3f").to_string()
+}
+
+// 실행 동안의 장애 구간 요약
+#[derive(Default)]
+pub struct DisruptionSummary {
+    pub windows: usize,
+    pub longest: Duration,
+    pub errors: usize,
+}
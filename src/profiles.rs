@@ -0,0 +1,76 @@
+use std::{env, fs, path::PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::config::RunConfig;
+
+// 이름을 정하지 않고 저장할 때의 프로필 이름
+pub const DEFAULT_PROFILE: &str = "default";
+
+// 프로필 파일: 이름별 실행 설정 (설정 파일과 같은 필드) 을 모은 JSON. $XDG_CONFIG_HOME 이 없으면 ~/.config 아래
+pub fn path() -> Result<PathBuf, String> {
+    let base = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME").ok_or_else(|| String::from("HOME is not set"))?).join(".config"),
+    };
+    Ok(base.join("envoy-lb-client").join("profiles.json"))
+}
+
+// 파일이 아직 없으면 빈 목록
+fn read() -> Result<(PathBuf, Map<String, Value>), String> {
+    let path = path()?;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((path, Map::new())),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    match serde_json::from_str(&text) {
+        Ok(Value::Object(profiles)) => Ok((path, profiles)),
+        Ok(_) => Err(format!("{}: expected a JSON object of profiles", path.display())),
+        Err(e) => Err(format!("{}: invalid JSON: {}", path.display(), e)),
+    }
+}
+
+fn write(path: &PathBuf, profiles: &Map<String, Value>) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(profiles).map_err(|e| e.to_string())?;
+    fs::write(path, json + "\n").map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// 이름순 프로필 목록
+pub fn names() -> Result<Vec<String>, String> {
+    Ok(read()?.1.keys().cloned().collect())
+}
+
+// 설정 파일과 같은 검증으로 읽는다. 틀린 필드가 있으면 모두 모아 알린다
+pub fn load(name: &str) -> Result<RunConfig, String> {
+    let (path, profiles) = read()?;
+    let Some(profile) = profiles.get(name) else {
+        return Err(format!("profile '{}' not found in {}", name, path.display()));
+    };
+    RunConfig::from_json(&profile.to_string()).map_err(|errors| {
+        let fields: Vec<String> = errors.iter().map(|e| format!("{}: {}", e.field, e.reason)).collect();
+        format!("profile '{}': {}", name, fields.join(", "))
+    })
+}
+
+// 같은 이름이 있으면 덮어쓴다. 저장한 파일 경로를 돌려준다
+pub fn save(name: &str, config: &RunConfig) -> Result<PathBuf, String> {
+    if name.trim().is_empty() {
+        return Err(String::from("profile name must not be empty"));
+    }
+    let (path, mut profiles) = read()?;
+    profiles.insert(name.trim().to_owned(), serde_json::to_value(config).map_err(|e| e.to_string())?);
+    write(&path, &profiles)?;
+    Ok(path)
+}
+
+pub fn delete(name: &str) -> Result<(), String> {
+    let (path, mut profiles) = read()?;
+    if profiles.remove(name).is_none() {
+        return Err(format!("profile '{}' not found in {}", name, path.display()));
+    }
+    write(&path, &profiles)
+}
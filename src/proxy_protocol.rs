@@ -0,0 +1,169 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use rand::Rng;
+
+// HAProxy PROXY protocol 헤더 (새 연결마다 첫 바이트로 전송)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    V1,
+    V2,
+}
+
+const V2_SIGNATURE: [u8; 12] = [0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A];
+
+// 헤더에 넣을 클라이언트 주소
+#[derive(Clone, Copy)]
+pub enum Source {
+    // 실제 로컬 주소
+    Real,
+    Fixed(SocketAddr),
+    // 연결마다 임의 주소 (목적지가 IPv4 면 10.0.0.0/8, IPv6 면 fd00::/8 대역)
+    Random,
+}
+
+pub fn parse_version(value: &str) -> Result<Option<Version>, String> {
+    match value {
+        "" | "off" => Ok(None),
+        "v1" => Ok(Some(Version::V1)),
+        "v2" => Ok(Some(Version::V2)),
+        _ => Err(format!("unknown PROXY protocol version '{}' (off/v1/v2)", value)),
+    }
+}
+
+pub fn parse_source(value: &str) -> Result<Source, String> {
+    match value {
+        "" => Ok(Source::Real),
+        "random" => Ok(Source::Random),
+        addr => addr.parse::<SocketAddr>().map(Source::Fixed).map_err(|_| format!("invalid PROXY source address '{}' (ip:port or random)", addr)),
+    }
+}
+
+impl Source {
+    // dst: 연결한 목적지 (임의 주소는 같은 주소 체계로 만든다)
+    // rng: 실행 시드로 초기화한 난수 생성기 (Random 일 때만 쓴다)
+    pub fn resolve(&self, local: SocketAddr, dst: SocketAddr, rng: &mut impl Rng) -> SocketAddr {
+        match self {
+            Source::Real => local,
+            Source::Fixed(addr) => *addr,
+            Source::Random => {
+                let ip = match dst {
+                    SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::new(10, rng.random(), rng.random(), rng.random_range(1..255))),
+                    SocketAddr::V6(_) => {
+                        let mut octets: [u8; 16] = rng.random();
+                        octets[0] = 0xfd;
+                        IpAddr::V6(Ipv6Addr::from(octets))
+                    }
+                };
+                SocketAddr::new(ip, rng.random_range(1024..65535))
+            }
+        }
+    }
+}
+
+pub fn header(version: Version, src: SocketAddr, dst: SocketAddr) -> Result<Vec<u8>, String> {
+    if src.is_ipv4() != dst.is_ipv4() {
+        return Err(format!("PROXY source {} and destination {} must be the same address family", src, dst));
+    }
+
+    match version {
+        Version::V1 => {
+            let family = if src.is_ipv4() { "TCP4" } else { "TCP6" };
+            Ok(format!("PROXY {} {} {} {} {}\r\n", family, src.ip(), dst.ip(), src.port(), dst.port()).into_bytes())
+        }
+        Version::V2 => {
+            let mut buf = V2_SIGNATURE.to_vec();
+            // 버전 2, PROXY 커맨드
+            buf.push(0x21);
+            match (src.ip(), dst.ip()) {
+                (IpAddr::V4(s), IpAddr::V4(d)) => {
+                    buf.push(0x11); // TCP over IPv4
+                    buf.extend_from_slice(&12u16.to_be_bytes());
+                    buf.extend_from_slice(&s.octets());
+                    buf.extend_from_slice(&d.octets());
+                }
+                (IpAddr::V6(s), IpAddr::V6(d)) => {
+                    buf.push(0x21); // TCP over IPv6
+                    buf.extend_from_slice(&36u16.to_be_bytes());
+                    buf.extend_from_slice(&s.octets());
+                    buf.extend_from_slice(&d.octets());
+                }
+                _ => unreachable!(),
+            }
+            buf.extend_from_slice(&src.port().to_be_bytes());
+            buf.extend_from_slice(&dst.port().to_be_bytes());
+            Ok(buf)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn v1_lines() {
+        assert_eq!(header(Version::V1, addr("10.1.2.3:4000"), addr("192.168.0.1:80")).unwrap(), b"PROXY TCP4 10.1.2.3 192.168.0.1 4000 80\r\n");
+        assert_eq!(header(Version::V1, addr("[fd00::1]:4000"), addr("[::1]:443")).unwrap(), b"PROXY TCP6 fd00::1 ::1 4000 443\r\n");
+    }
+
+    #[test]
+    fn v2_ipv4_bytes() {
+        let buf = header(Version::V2, addr("10.1.2.3:4000"), addr("192.168.0.1:80")).unwrap();
+        let mut expected = V2_SIGNATURE.to_vec();
+        expected.extend_from_slice(&[0x21, 0x11, 0x00, 0x0C]);
+        expected.extend_from_slice(&[10, 1, 2, 3, 192, 168, 0, 1]);
+        expected.extend_from_slice(&[0x0F, 0xA0, 0x00, 0x50]);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn v2_ipv6_bytes() {
+        let buf = header(Version::V2, addr("[fd00::1]:4000"), addr("[::1]:443")).unwrap();
+        assert_eq!(&buf[..12], &V2_SIGNATURE);
+        assert_eq!(&buf[12..16], &[0x21, 0x21, 0x00, 0x24]);
+        // 헤더 16바이트 뒤 길이 필드만큼 주소가 온다
+        assert_eq!(buf.len(), 16 + 36);
+        assert_eq!(buf[16], 0xfd);
+        assert_eq!(buf[47], 1);
+        assert_eq!(&buf[48..], &[0x0F, 0xA0, 0x01, 0xBB]);
+    }
+
+    #[test]
+    fn mixed_families_rejected() {
+        assert!(header(Version::V1, addr("10.0.0.1:1"), addr("[::1]:80")).is_err());
+        assert!(header(Version::V2, addr("[::1]:1"), addr("10.0.0.1:80")).is_err());
+    }
+
+    #[test]
+    fn random_source_follows_destination() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let local = addr("127.0.0.1:50000");
+        for _ in 0..100 {
+            match Source::Random.resolve(local, addr("192.168.0.1:80"), &mut rng).ip() {
+                IpAddr::V4(ip) => assert_eq!(ip.octets()[0], 10),
+                ip => panic!("expected IPv4, got {}", ip),
+            }
+            let src = Source::Random.resolve(local, addr("[::1]:80"), &mut rng);
+            match src.ip() {
+                IpAddr::V6(ip) => assert_eq!(ip.octets()[0], 0xfd),
+                ip => panic!("expected IPv6, got {}", ip),
+            }
+            assert!(header(Version::V2, src, addr("[::1]:80")).is_ok());
+        }
+    }
+
+    #[test]
+    fn sources_parse() {
+        assert!(matches!(parse_source("").unwrap(), Source::Real));
+        assert!(matches!(parse_source("random").unwrap(), Source::Random));
+        assert!(matches!(parse_source("[::1]:9").unwrap(), Source::Fixed(a) if a == addr("[::1]:9")));
+        assert!(parse_source("nope").is_err());
+        assert!(parse_version("v3").is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use rand::Rng;
+use reqwest::Url;
+
+// 요청마다 치환하는 값
+const PLACEHOLDERS: [&str; 5] = ["id", "iter", "index", "uuid", "random"];
+
+// 요청마다 붙이는 쿼리 파라미터 템플릿 ("user={{uuid}}&n={{iter}}")
+// 적은 그대로 (퍼센트 인코딩 포함) 붙이고 {{...}} 만 치환한다
+pub struct QueryTemplate {
+    text: String,
+}
+
+impl QueryTemplate {
+    // 비어 있으면 템플릿을 쓰지 않음
+    pub fn new(value: &str) -> Result<Option<Self>, String> {
+        let text = value.trim().trim_start_matches('?');
+        if text.is_empty() {
+            return Ok(None);
+        }
+        if text.contains(['#', ' ']) {
+            return Err(format!("query parameters '{}' must not contain spaces or '#'", text));
+        }
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                return Err(format!("unclosed placeholder in query parameters '{}'", text));
+            };
+            let name = &rest[start + 2..start + end];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(format!("unknown placeholder '{{{{{}}}}}' in query parameters ({{{{{}}}}})", name, PLACEHOLDERS.join("}} {{")));
+            }
+            rest = &rest[start + end + 2..];
+        }
+        Ok(Some(Self { text: text.to_owned() }))
+    }
+
+    // 요청 하나의 쿼리. {{iter}} 와 {{index}} 는 실행 내 요청 순번
+    pub fn render(&self, id: &str, index: usize, rng: &mut impl Rng) -> String {
+        let mut query = self.text.replace("{{id}}", id).replace("{{iter}}", &index.to_string()).replace("{{index}}", &index.to_string());
+        // 실행 시드로 재현되도록 필요한 값만 난수에서 뽑는다
+        while query.contains("{{uuid}}") {
+            query = query.replacen("{{uuid}}", &uuid(rng), 1);
+        }
+        while query.contains("{{random}}") {
+            query = query.replacen("{{random}}", &rng.random::<u32>().to_string(), 1);
+        }
+        query
+    }
+}
+
+// 대상 URL 의 쿼리 뒤에 이어 붙인다
+pub fn append(url: &Url, query: &str) -> Url {
+    let mut url = url.clone();
+    let joined = match url.query() {
+        Some(existing) if !existing.is_empty() => format!("{}&{}", existing, query),
+        _ => query.to_owned(),
+    };
+    url.set_query(Some(&joined));
+    url
+}
+
+// 난수로 만든 UUID v4
+fn uuid(rng: &mut impl Rng) -> String {
+    let mut bytes: [u8; 16] = rng.random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
@@ -0,0 +1,60 @@
+use std::time::Instant;
+
+use crate::config::RunConfig;
+
+// 실행 큐에 넣은 단계 하나 (추가할 때의 입력값/옵션 그대로)
+#[derive(Clone)]
+pub struct Stage {
+    pub name: String,
+    pub config: RunConfig,
+}
+
+// 진행 중인 큐 실행. 단계가 끝날 때마다 결과를 모아 마지막에 한 번에 보고
+pub struct QueueRun {
+    pub stages: Vec<Stage>,
+    pub index: usize,
+    started_at: Instant,
+    stage_started: Instant,
+    reports: Vec<String>,
+}
+
+impl QueueRun {
+    pub fn new(stages: Vec<Stage>) -> Self {
+        let now = Instant::now();
+        Self { stages, index: 0, started_at: now, stage_started: now, reports: Vec::new() }
+    }
+
+    pub fn current(&self) -> &Stage {
+        &self.stages[self.index]
+    }
+
+    // 현재 단계의 결과 기록 (단계마다 한 번만)
+    pub fn finish_stage(&mut self, outcome: &str) {
+        if self.reports.len() > self.index {
+            return;
+        }
+        let line = format!(
+            "Stage {}/{} {} ({:.1}s): {}",
+            self.index + 1, self.stages.len(), self.current().name, self.stage_started.elapsed().as_secs_f64(), outcome
+        );
+        self.reports.push(line);
+    }
+
+    // 다음 단계로 이동. 결과 없이 끝난 단계(실패 기준 중단 등)는 중단으로 기록
+    pub fn advance(&mut self) -> Option<RunConfig> {
+        self.finish_stage("Aborted");
+        self.index += 1;
+        self.stage_started = Instant::now();
+        self.stages.get(self.index).map(|stage| stage.config.clone())
+    }
+
+    // 단계별로 나눈 전체 보고
+    pub fn report(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Queue report: {} of {} stage(s) in {:.1}s",
+            self.reports.len(), self.stages.len(), self.started_at.elapsed().as_secs_f64()
+        )];
+        lines.extend(self.reports.iter().cloned());
+        lines
+    }
+}
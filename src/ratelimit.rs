@@ -0,0 +1,170 @@
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::stats::RequestRecord;
+
+// 램프업 중에는 속도가 계속 오르므로 이 간격보다 길게 자지 않고 다시 계산한다
+const RAMP_STEP: Duration = Duration::from_millis(10);
+// 속도 모드에서 실제 초당 요청 수를 남기는 간격
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+// 여러 작업자가 함께 쓰는 토큰 버킷. 요청 하나에 토큰 하나
+// 각자 sleep 하는 대신 전체 초당 요청 수를 맞춘다
+pub struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    // 램프업 시작 시각과 길이 (이 동안 속도를 0 에서 rate 까지 선형으로 올린다)
+    ramp: Option<(Instant, Duration)>,
+    created: Instant,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rps: f64, burst: u64) -> Self {
+        let burst = burst.max(1) as f64;
+        let created = Instant::now();
+        // 시작 직후 버스트가 몰리지 않도록 토큰 하나로 시작
+        Self { rate: rps, burst, ramp: None, created, state: Mutex::new(BucketState { tokens: 1.0, refilled_at: created }) }
+    }
+
+    // 실행 시작부터 ramp 동안 속도를 올린다. 실행 중에 버킷을 다시 만들어도 같은 시작 시각을 넘기면 이어서 오른다
+    pub fn ramped(self, started: Instant, ramp: Duration) -> Self {
+        Self { ramp: (!ramp.is_zero()).then_some((started, ramp)), ..self }
+    }
+
+    // 목표 속도
+    pub fn target(&self) -> f64 {
+        self.rate
+    }
+
+    // at 시점의 속도 (램프업 중이면 목표의 일부)
+    pub fn rate_at(&self, at: Instant) -> f64 {
+        match self.ramp {
+            Some((started, ramp)) => self.rate * (at.saturating_duration_since(started).as_secs_f64() / ramp.as_secs_f64()).min(1.0),
+            None => self.rate,
+        }
+    }
+
+    pub fn ramping(&self, at: Instant) -> bool {
+        self.ramp.is_some_and(|(started, ramp)| at.saturating_duration_since(started) < ramp)
+    }
+
+    // 버킷을 만든 뒤 n 번째 (0 부터) 토큰이 일정대로라면 생기는 시각
+    // 버스트로 쌓이는 토큰은 빼고 순수한 속도만으로 계산한다 (밀린 만큼이 클라이언트 대기가 된다)
+    pub fn due(&self, n: u64) -> Instant {
+        let Some((started, ramp)) = self.ramp else {
+            return self.created + Duration::from_secs_f64(n as f64 / self.rate);
+        };
+        // 램프업 시작부터 쌓인 토큰 수와 그 역함수
+        let ramp_s = ramp.as_secs_f64();
+        let ramp_tokens = self.rate * ramp_s / 2.0;
+        let accrued = |x: f64| if x <= ramp_s { self.rate * x * x / (2.0 * ramp_s) } else { ramp_tokens + self.rate * (x - ramp_s) };
+        let target = accrued(self.created.saturating_duration_since(started).as_secs_f64()) + n as f64;
+        let x = if target <= ramp_tokens { (2.0 * ramp_s * target / self.rate).sqrt() } else { ramp_s + (target - ramp_tokens) / self.rate };
+        started + Duration::from_secs_f64(x)
+    }
+
+    // 토큰을 얻을 때까지 대기
+    pub fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            thread::sleep(wait);
+        }
+    }
+
+    // 비동기 작업용. 작업 스레드를 막지 않고 기다린다
+    pub async fn acquire_async(&self) {
+        while let Err(wait) = self.try_acquire() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    // 토큰이 있으면 하나 쓰고, 없으면 다음 토큰까지 남은 시간
+    fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+        // 램프업 중에는 구간 양 끝 속도의 평균으로 채운다
+        let rate = self.rate_at(now);
+        state.tokens = (state.tokens + elapsed * (self.rate_at(state.refilled_at) + rate) / 2.0).min(self.burst);
+        state.refilled_at = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return Ok(());
+        }
+        if rate <= 0.0 {
+            return Err(RAMP_STEP);
+        }
+        let wait = Duration::from_secs_f64((1.0 - state.tokens) / rate);
+        Err(if self.ramping(now) { wait.min(RAMP_STEP) } else { wait })
+    }
+}
+
+// 속도 모드에서 목표와 실제 초당 요청 수를 주기적으로 비교한다 (Envoy 속도 제한 설정과 맞춰 보기 위해)
+pub struct RateReport {
+    at: Instant,
+    sent: usize,
+    completed: usize,
+    limited: usize,
+}
+
+impl RateReport {
+    pub fn new(at: Instant) -> Self {
+        Self { at, sent: 0, completed: 0, limited: 0 }
+    }
+
+    // 지난 보고 뒤로 REPORT_INTERVAL 이 지났으면 그 구간의 보낸/끝난/429 응답 속도
+    pub fn tick(&mut self, bucket: &TokenBucket, now: Instant, sent: usize, completed: usize, limited: usize) -> Option<String> {
+        let elapsed = now.duration_since(self.at);
+        if elapsed < REPORT_INTERVAL {
+            return None;
+        }
+        let per_second = |count: usize, previous: usize| count.saturating_sub(previous) as f64 / elapsed.as_secs_f64();
+        let target = if bucket.ramping(now) {
+            format!("{:.1} req/s (ramping to {:.1})", bucket.rate_at(now), bucket.target())
+        } else {
+            format!("{:.1} req/s", bucket.target())
+        };
+        let line = format!(
+            "Rate: target {} | sent {:.1}/s | completed {:.1}/s | 429 {:.1}/s",
+            target,
+            per_second(sent, self.sent),
+            per_second(completed, self.completed),
+            per_second(limited, self.limited)
+        );
+        *self = Self { at: now, sent, completed, limited };
+        Some(line)
+    }
+}
+
+// 실행 종료 보고: 램프업이 끝난 뒤 구간의 실제 보낸/끝난 속도와 429 응답 수
+pub fn summarize(bucket: &TokenBucket, ramp_end: Instant, records: &[RequestRecord], limited: usize) -> String {
+    let rate = |times: Vec<Instant>| {
+        let (Some(first), Some(last)) = (times.iter().min(), times.iter().max()) else {
+            return 0.0;
+        };
+        let span = last.duration_since(*first).as_secs_f64();
+        if span > 0.0 { (times.len() - 1) as f64 / span } else { 0.0 }
+    };
+    let steady: Vec<&RequestRecord> = records.iter().filter(|r| r.sent_at >= ramp_end).collect();
+    let sent = rate(steady.iter().map(|r| r.sent_at).collect());
+    let completed = rate(steady.iter().map(|r| r.sent_at + r.latency).collect());
+    let window = if bucket.ramp.is_some() { " after ramp-up" } else { "" };
+    format!(
+        "Rate: target {:.1} req/s | achieved{} {:.1} sent/s, {:.1} completed/s ({:.1}% of target) | {} rate limited (429)",
+        bucket.target(),
+        window,
+        sent,
+        completed,
+        sent / bucket.target() * 100.0,
+        limited
+    )
+}
@@ -0,0 +1,122 @@
+use std::{fs, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{export::Snapshot, ping::PingSample, probe, retention::History, stats::{format_ms, percentile_label, RequestRecord}};
+
+// 끝난 실행을 구간 단위로 되돌려 보는 재생 상태. 대시보드는 커서 시점까지 끝난 요청만으로 다시 그린다
+// 시각은 모두 UTC 밀리초로 다뤄 자정을 넘긴 실행도, 저장했다가 다시 연 재생도 같은 기준으로 자른다
+pub struct Replay {
+    percentiles: Vec<f64>,
+    snapshots: Vec<Snapshot>,
+    // 로그와 그 로그를 남긴 시각
+    logs: Vec<(i64, String)>,
+    // 요청별 결과와 PING 표본 (파일에서 연 재생은 구간 집계와 로그만 있다)
+    records: Vec<RequestRecord>,
+    pings: Vec<PingSample>,
+    cursor: usize,
+    // 커서 시점의 기록과 로그. 커서가 움직일 때만 다시 만든다
+    view: View,
+    moved: bool,
+}
+
+#[derive(Default)]
+pub struct View {
+    pub records: Vec<RequestRecord>,
+    pub pings: Vec<PingSample>,
+    pub logs: Vec<String>,
+}
+
+// 내보내기 옆에 저장하는 재생 파일
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    percentiles: Vec<f64>,
+    snapshots: Vec<Snapshot>,
+    logs: Vec<(i64, String)>,
+}
+
+impl Replay {
+    // 기록이 없으면 None. 처음에는 마지막 구간을 가리킨다
+    // 구간 집계로 합친 오래된 부분은 합친 구간 단위로 넘기고, 그 구간에서는 남아 있는 요청별 결과만 그린다
+    pub fn new(records: &[RequestRecord], history: &History, pings: &[PingSample], logs: Vec<(i64, String)>, interval: Duration, percentiles: &[f64]) -> Option<Self> {
+        let snapshots = history.snapshots(records, interval, percentiles);
+        Self::open(percentiles.to_vec(), snapshots, logs, records.to_vec(), pings.to_vec())
+    }
+
+    // save 로 저장한 재생을 연다
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let saved: Saved = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        Self::open(saved.percentiles, saved.snapshots, saved.logs, Vec::new(), Vec::new()).ok_or_else(|| String::from("no intervals to replay"))
+    }
+
+    fn open(percentiles: Vec<f64>, snapshots: Vec<Snapshot>, logs: Vec<(i64, String)>, records: Vec<RequestRecord>, pings: Vec<PingSample>) -> Option<Self> {
+        let cursor = snapshots.len().checked_sub(1)?;
+        let mut replay = Self { percentiles, snapshots, logs, records, pings, cursor, view: View::default(), moved: false };
+        replay.refresh();
+        Some(replay)
+    }
+
+    pub fn step(&mut self, delta: isize) {
+        self.cursor = self.cursor.saturating_add_signed(delta).min(self.snapshots.len() - 1);
+        self.refresh();
+    }
+
+    pub fn jump(&mut self, end: bool) {
+        self.cursor = if end { self.snapshots.len() - 1 } else { 0 };
+        self.refresh();
+    }
+
+    // 커서 시점까지 끝난 요청과 PING 표본, 남은 로그
+    pub fn view(&self) -> &View {
+        &self.view
+    }
+
+    // 지난번 뒤로 커서가 움직였는지 (기록으로 계산하는 창은 이때만 다시 계산한다)
+    pub fn take_moved(&mut self) -> bool {
+        std::mem::take(&mut self.moved)
+    }
+
+    // 커서 구간의 끝 시각 (UTC 밀리초)
+    fn until(&self) -> i64 {
+        let s = &self.snapshots[self.cursor];
+        s.time_ms + s.span.as_millis() as i64
+    }
+
+    fn refresh(&mut self) {
+        let until = self.until();
+        let done = |at| probe::wall_time(at).timestamp_millis() <= until;
+        self.view = View {
+            records: self.records.iter().filter(|r| done(r.sent_at + r.latency)).cloned().collect(),
+            pings: self.pings.iter().filter(|s| done(s.at + s.rtt)).cloned().collect(),
+            logs: self.logs.iter().filter(|(at, _)| *at <= until).map(|(_, line)| line.clone()).collect(),
+        };
+        self.moved = true;
+    }
+
+    // 커서 구간의 지표 (로그 제목에 표시)
+    pub fn describe(&self) -> String {
+        let s = &self.snapshots[self.cursor];
+        let latency: Vec<String> = self.percentiles.iter().zip(&s.latency_ms).map(|(p, ms)| format!("{} {}", percentile_label(*p), format_ms(Duration::from_secs_f64(ms / 1000.0)))).collect();
+        format!(
+            "Replay {}/{} t={}s: {} req, {} err, {:.1} rps, {} | ←/→ scrub, Home/End, Esc exit",
+            self.cursor + 1,
+            self.snapshots.len(),
+            s.offset_s,
+            s.requests,
+            s.errors,
+            s.rps,
+            latency.join(" ")
+        )
+    }
+}
+
+// 결과 파일 옆에 구간 집계와 로그를 재생 파일로 남기고 그 경로를 돌려준다 (results.json -> results.replay.json)
+pub fn save(results: &str, records: &[RequestRecord], history: &History, logs: &[(i64, String)], interval: Duration, percentiles: &[f64]) -> Result<String, String> {
+    let path = Path::new(results);
+    let path = path.with_file_name(format!("{}.replay.json", path.file_stem().and_then(|s| s.to_str()).unwrap_or("results")));
+    let saved = Saved { percentiles: percentiles.to_vec(), snapshots: history.snapshots(records, interval, percentiles), logs: logs.to_vec() };
+    let content = serde_json::to_string(&saved).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
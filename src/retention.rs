@@ -0,0 +1,336 @@
+use std::{
+    collections::BTreeMap,
+    mem,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error_code::{self, ErrorCode},
+    export::{self, Snapshot},
+    metrics, probe,
+    stats::{self, RequestRecord},
+};
+
+// 지연 히스토그램 칸의 배율 (칸마다 앞 칸보다 5% 크다. 집계한 백분위의 오차도 그 정도)
+const GROWTH: f64 = 1.05;
+// 구간 수가 이보다 많아지면 이웃한 두 구간을 합쳐 간격을 두 배로 늘린다
+const MAX_BUCKETS: usize = 512;
+// 예산을 넘으면 예산의 이 비율까지 한 번에 줄인다 (기록마다 앞쪽을 지우지 않도록)
+const LOW_WATER: f64 = 0.9;
+// 히스토그램 칸 하나의 대략적인 메모리 (BTreeMap 항목)
+const HISTOGRAM_ENTRY_BYTES: usize = 32;
+
+// 구간에 합쳐 둔 지연 분포 (칸 번호 -> 요청 수)
+#[derive(Clone, Default)]
+pub struct Histogram {
+    counts: BTreeMap<u32, u64>,
+    total: u64,
+}
+
+impl Histogram {
+    fn slot(latency: Duration) -> u32 {
+        ((latency.as_micros().max(1) as f64).ln() / GROWTH.ln()) as u32
+    }
+
+    pub fn add(&mut self, latency: Duration) {
+        *self.counts.entry(Self::slot(latency)).or_default() += 1;
+        self.total += 1;
+    }
+
+    pub fn merge(&mut self, other: &Histogram) {
+        for (slot, count) in &other.counts {
+            *self.counts.entry(*slot).or_default() += count;
+        }
+        self.total += other.total;
+    }
+
+    // 칸의 윗값으로 어림한 백분위 지연
+    pub fn percentile(&self, p: f64) -> Duration {
+        if self.total == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((p / 100.0) * self.total as f64).ceil().clamp(1.0, self.total as f64) as u64;
+        let mut seen = 0;
+        for (slot, count) in &self.counts {
+            seen += count;
+            if seen >= rank {
+                return Duration::from_micros(GROWTH.powi(*slot as i32 + 1) as u64);
+            }
+        }
+        Duration::ZERO
+    }
+}
+
+// 요청 전송 시각 기준 구간 하나의 집계
+#[derive(Clone, Default)]
+pub struct Bucket {
+    pub requests: usize,
+    pub errors: usize,
+    pub error_codes: BTreeMap<ErrorCode, usize>,
+    // 상태 코드 계열별 수 (metrics::status_class 순서)
+    pub classes: [usize; 5],
+    // 응답 본문 크기 합과 본문을 받은 응답 수
+    pub body_bytes: u64,
+    pub bodies: usize,
+    pub latency: Histogram,
+    pub latency_sum: Duration,
+}
+
+impl Bucket {
+    pub fn add(&mut self, record: &RequestRecord) {
+        self.requests += 1;
+        if !record.success {
+            self.errors += 1;
+        }
+        if let Some(code) = record.error {
+            *self.error_codes.entry(code).or_default() += 1;
+        }
+        self.classes[metrics::status_class(record.status)] += 1;
+        if let Some(size) = record.response_size {
+            self.body_bytes += size;
+            self.bodies += 1;
+        }
+        self.latency.add(record.latency);
+        self.latency_sum += record.latency;
+    }
+
+    fn merge(&mut self, other: &Bucket) {
+        self.requests += other.requests;
+        self.errors += other.errors;
+        for (code, count) in &other.error_codes {
+            *self.error_codes.entry(*code).or_default() += count;
+        }
+        for (class, count) in self.classes.iter_mut().zip(other.classes) {
+            *class += count;
+        }
+        self.body_bytes += other.body_bytes;
+        self.bodies += other.bodies;
+        self.latency.merge(&other.latency);
+        self.latency_sum += other.latency_sum;
+    }
+
+    fn footprint(&self) -> usize {
+        mem::size_of::<Self>() + (self.latency.counts.len() + self.error_codes.len()) * HISTOGRAM_ENTRY_BYTES
+    }
+}
+
+// 요청 결과 하나와 요청 id -> 업스트림 항목이 차지하는 대략적인 메모리
+fn footprint(record: &RequestRecord) -> usize {
+    let text = |s: &Option<String>| s.as_ref().map_or(0, String::len);
+    let keyed = record.id.len() + text(&record.upstream);
+    mem::size_of::<RequestRecord>()
+        + mem::size_of::<(String, Option<String>)>()
+        + keyed * 2
+        + text(&record.cluster)
+        + text(&record.validation)
+        + text(&record.query)
+        + text(&record.idempotency_key)
+        + text(&record.alpn)
+        + record.body.as_ref().map_or(0, |b| b.len())
+}
+
+// 장시간 실행에서 오래된 요청 결과를 구간 집계로 줄여 보관한다
+// 최근 raw_window_s 초의 결과와 메모리 예산 안에 들어가는 결과만 요청별로 남기고, 나머지는 전송 시각 구간마다 합친다
+#[derive(Clone, Default)]
+pub struct History {
+    window: Option<Duration>,
+    budget: Option<usize>,
+    // 첫 구간의 시작 (처음 합친 요청들의 가장 이른 전송 시각)
+    origin: Option<Instant>,
+    interval: Duration,
+    buckets: Vec<Bucket>,
+    // 합친 요청 수와 남아 있는 요청별 결과의 대략적인 메모리
+    folded: usize,
+    raw_bytes: usize,
+}
+
+impl History {
+    // window_s, budget_mb 가 0 이면 그 기준으로는 줄이지 않는다. interval 은 처음 구간 간격 (내보내기 간격)
+    pub fn new(window_s: u64, budget_mb: u64, interval: Duration) -> Self {
+        Self {
+            window: (window_s > 0).then(|| Duration::from_secs(window_s)),
+            budget: (budget_mb > 0).then(|| budget_mb as usize * 1024 * 1024),
+            interval: interval.max(Duration::from_secs(1)),
+            ..Self::default()
+        }
+    }
+
+    // 구간 집계로 합친 요청 수
+    pub fn folded(&self) -> usize {
+        self.folded
+    }
+
+    fn history_bytes(&self) -> usize {
+        self.buckets.iter().map(Bucket::footprint).sum()
+    }
+
+    // 요청 결과를 추가한 뒤 부른다. 창 밖이거나 예산을 넘은 앞쪽(먼저 끝난) 결과를 구간 집계로 옮기고, 옮긴 결과를 돌려준다
+    pub fn retain(&mut self, records: &mut Vec<RequestRecord>) -> Vec<RequestRecord> {
+        if let Some(record) = records.last() {
+            self.raw_bytes += footprint(record);
+        }
+        let mut count = 0;
+        if let Some(window) = self.window
+            && let Some(latest) = records.last().map(|r| r.sent_at)
+            && let Some(cutoff) = latest.checked_sub(window)
+            && records.first().is_some_and(|r| r.sent_at + window / 10 < cutoff)
+        {
+            count = records.iter().position(|r| r.sent_at >= cutoff).unwrap_or(records.len());
+        }
+        if let Some(budget) = self.budget
+            && self.raw_bytes + self.history_bytes() > budget
+        {
+            let target = (budget as f64 * LOW_WATER) as usize;
+            let mut bytes = self.raw_bytes + self.history_bytes();
+            let mut over = 0;
+            for record in records.iter() {
+                if bytes <= target {
+                    break;
+                }
+                bytes = bytes.saturating_sub(footprint(record));
+                over += 1;
+            }
+            count = count.max(over);
+        }
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let drained: Vec<RequestRecord> = records.drain(..count).collect();
+        let origin = *self.origin.get_or_insert_with(|| drained.iter().map(|r| r.sent_at).min().unwrap_or_else(Instant::now));
+        for record in &drained {
+            self.raw_bytes = self.raw_bytes.saturating_sub(footprint(record));
+            fold(&mut self.buckets, origin, self.interval, record);
+        }
+        self.folded += drained.len();
+        // 구간이 너무 많거나 집계가 예산의 절반을 넘으면 간격을 늘린다
+        while self.buckets.len() > MAX_BUCKETS || self.budget.is_some_and(|budget| self.buckets.len() > 1 && self.history_bytes() > budget / 2) {
+            self.buckets = self
+                .buckets
+                .chunks(2)
+                .map(|pair| {
+                    let mut merged = pair[0].clone();
+                    if let Some(next) = pair.get(1) {
+                        merged.merge(next);
+                    }
+                    merged
+                })
+                .collect();
+            self.interval *= 2;
+        }
+        drained
+    }
+
+    // 구간 집계에 남은 결과를 더한 실행 전체 집계 (합친 결과가 없으면 None: 요청별 결과로 정확히 계산하면 된다)
+    pub fn totals(&self, records: &[RequestRecord]) -> Option<Bucket> {
+        if self.folded == 0 {
+            return None;
+        }
+        let mut totals = Bucket::default();
+        for bucket in &self.buckets {
+            totals.merge(bucket);
+        }
+        for record in records {
+            totals.add(record);
+        }
+        Some(totals)
+    }
+
+    // 실행 전체의 구간별 집계. 합친 부분은 합친 구간 간격으로, 그 뒤는 interval 간격으로 남은 결과에서 계산한다
+    pub fn snapshots(&self, records: &[RequestRecord], interval: Duration, percentiles: &[f64]) -> Vec<Snapshot> {
+        let Some(origin) = self.origin() else {
+            return export::snapshots(records, interval, percentiles);
+        };
+        let end = origin + self.interval * self.buckets.len() as u32;
+        let mut buckets = self.buckets.clone();
+        let mut rest = Vec::new();
+        for record in records {
+            if record.sent_at < end {
+                fold(&mut buckets, origin, self.interval, record);
+            } else {
+                rest.push(record);
+            }
+        }
+
+        let start_ms = probe::wall_time(origin).timestamp_millis();
+        let mut snapshots: Vec<Snapshot> = buckets
+            .iter()
+            .enumerate()
+            .map(|(i, bucket)| {
+                let offset = self.interval * i as u32;
+                Snapshot {
+                    time_ms: start_ms + offset.as_millis() as i64,
+                    offset_s: offset.as_secs(),
+                    span: self.interval,
+                    requests: bucket.requests,
+                    errors: bucket.errors,
+                    error_codes: bucket.error_codes.clone(),
+                    rps: bucket.requests as f64 / self.interval.as_secs_f64(),
+                    latency_ms: percentiles.iter().map(|p| bucket.latency.percentile(*p).as_secs_f64() * 1000.0).collect(),
+                }
+            })
+            .collect();
+        let skipped = end.duration_since(origin).as_secs();
+        for mut snapshot in export::snapshots_from(end, &rest, interval, percentiles) {
+            snapshot.offset_s += skipped;
+            snapshots.push(snapshot);
+        }
+        snapshots
+    }
+
+    // 합친 구간의 시작 시각
+    pub fn origin(&self) -> Option<Instant> {
+        self.origin.filter(|_| self.folded > 0)
+    }
+
+    // from 이후 초마다 합친 요청 수 (구간 안에서는 고르게 나눈다)
+    pub fn per_second(&self, from: Instant) -> Vec<(Instant, u64)> {
+        let Some(origin) = self.origin() else {
+            return Vec::new();
+        };
+        let seconds = self.interval.as_secs().max(1);
+        let mut series = Vec::new();
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            let start = origin + self.interval * i as u32;
+            for s in 0..seconds {
+                let at = start + Duration::from_secs(s);
+                if at >= from {
+                    series.push((at, bucket.requests as u64 / seconds));
+                }
+            }
+        }
+        series
+    }
+
+    // 실행 종료 보고. 합친 결과가 없으면 None
+    pub fn summarize(&self, records: &[RequestRecord], percentiles: &[f64]) -> Option<String> {
+        let totals = self.totals(records)?;
+        let latency: Vec<String> = percentiles
+            .iter()
+            .map(|p| format!("{} {}", stats::percentile_label(*p), stats::format_ms(totals.latency.percentile(*p))))
+            .collect();
+        let mut log = format!(
+            "Whole run: {} completed ({} failed) | latency {} (±5%) | {} older result(s) downsampled into {} interval(s) of {}s; per-request reports cover the last {}",
+            totals.requests,
+            totals.errors,
+            latency.join(" "),
+            self.folded,
+            self.buckets.len(),
+            self.interval.as_secs(),
+            records.len()
+        );
+        if let Some(errors) = error_code::describe(&totals.error_codes) {
+            log.push_str(&format!(" | {}", errors));
+        }
+        Some(log)
+    }
+}
+
+// 전송 시각이 속한 구간에 더한다 (첫 구간보다 먼저 보낸 요청은 첫 구간에)
+fn fold(buckets: &mut Vec<Bucket>, origin: Instant, interval: Duration, record: &RequestRecord) {
+    let slot = (record.sent_at.saturating_duration_since(origin).as_secs_f64() / interval.as_secs_f64()) as usize;
+    if buckets.len() <= slot {
+        buckets.resize_with(slot + 1, Bucket::default);
+    }
+    buckets[slot].add(record);
+}
@@ -0,0 +1,104 @@
+use std::fs;
+
+use reqwest::Url;
+
+use crate::stats::RequestRecord;
+
+// 라우팅 테이블 적합성 테스트의 한 항목
+// 파일 한 줄: "<경로> <기대 상태코드|-> [기대 클러스터|-]"
+#[derive(Clone)]
+pub struct Route {
+    pub path: String,
+    pub url: Url,
+    pub expect_status: Option<u16>,
+    pub expect_cluster: Option<String>,
+}
+
+// 경로 목록 파일 읽기. 빈 줄과 # 주석은 무시
+pub fn load(path: &str, base: &Url) -> Result<Vec<Route>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read routes file '{}': {}", path, e))?;
+    let mut routes = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let route_path = fields.next().unwrap_or_default();
+        let invalid = |what: &str| format!("routes file line {}: invalid {} in '{}'", number + 1, what, line);
+
+        let url = base.join(route_path).map_err(|_| invalid("path"))?;
+        let expect_status = match fields.next() {
+            None | Some("-") => None,
+            Some(status) => Some(status.parse::<u16>().map_err(|_| invalid("status"))?),
+        };
+        let expect_cluster = fields.next().filter(|c| *c != "-").map(str::to_owned);
+        routes.push(Route { path: route_path.to_owned(), url, expect_status, expect_cluster });
+    }
+
+    if routes.is_empty() {
+        return Err(format!("routes file '{}' has no routes", path));
+    }
+    Ok(routes)
+}
+
+impl Route {
+    // 기대값과 다르면 이유를 돌려준다
+    pub fn check(&self, record: &RequestRecord) -> Result<(), String> {
+        let Some(status) = record.status else {
+            return Err(String::from("no response"));
+        };
+        if let Some(expected) = self.expect_status
+            && status != expected
+        {
+            return Err(format!("status {} != {}", status, expected));
+        }
+        if let Some(expected) = &self.expect_cluster
+            && record.cluster.as_deref() != Some(expected.as_str())
+        {
+            return Err(format!("cluster {} != {}", record.cluster.as_deref().unwrap_or("(none)"), expected));
+        }
+        Ok(())
+    }
+}
+
+// 경로별 통과/실패 집계
+#[derive(Clone)]
+pub struct RouteResult {
+    pub path: String,
+    pub passed: usize,
+    pub failed: usize,
+    // 마지막 실패 이유
+    pub last_failure: Option<String>,
+}
+
+pub fn matrix(routes: &[Route], records: &[RequestRecord]) -> Vec<RouteResult> {
+    let mut results = empty(routes);
+    for record in records {
+        tally(&mut results, routes, record);
+    }
+    results
+}
+
+// 아직 요청이 없는 경로별 집계
+pub fn empty(routes: &[Route]) -> Vec<RouteResult> {
+    routes.iter().map(|r| RouteResult { path: r.path.clone(), passed: 0, failed: 0, last_failure: None }).collect()
+}
+
+// 경로 요청 하나를 판정해 집계에 더한다 (경로 요청이 아니면 그대로)
+pub fn tally(results: &mut [RouteResult], routes: &[Route], record: &RequestRecord) {
+    let Some(index) = record.route else {
+        return;
+    };
+    let (Some(route), Some(result)) = (routes.get(index), results.get_mut(index)) else {
+        return;
+    };
+    match route.check(record) {
+        Ok(()) => result.passed += 1,
+        Err(reason) => {
+            result.failed += 1;
+            result.last_failure = Some(reason);
+        }
+    }
+}
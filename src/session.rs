@@ -0,0 +1,119 @@
+use std::{fs, path::PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use crate::{config::SECRET_OPTIONS, logview::LogFilter, profiles, App, LoadModel};
+
+// 탭마다 남기는 최근 로그 줄 수
+const SAVED_LOGS: usize = 200;
+
+// 세션 파일: 프로필 파일과 같은 폴더. q 로 정상 종료할 때 쓰고 다음 시작 때 읽는다
+pub fn path() -> Result<PathBuf, String> {
+    Ok(profiles::path()?.with_file_name("session.json"))
+}
+
+// 탭 하나의 입력값, 옵션, 포커스, 스크롤, 로그 필터와 최근 로그
+// 토큰, 인증 헤더 같은 비밀 옵션은 파일에 남기지 않는다 (되살린 탭에서는 비어 있다)
+fn capture(app: &App, logs: &[String]) -> Value {
+    let options: Map<String, Value> = app
+        .options
+        .iter()
+        .filter(|option| !SECRET_OPTIONS.contains(&option.key))
+        .map(|option| (option.key.to_owned(), Value::from(option.value.clone())))
+        .collect();
+    let logs = &logs[logs.len().saturating_sub(SAVED_LOGS)..];
+    json!({
+        "dst_url": app.dst_url,
+        "delay_ms": app.delay_ms,
+        "header_size_kb": app.header_size_kb,
+        "iteration": app.iteration,
+        "concurrency": app.concurrency,
+        "load_model": if app.load_model == LoadModel::Rate { "rate" } else { "delay" },
+        "rps": app.rps,
+        "protocol": app.protocols[app.protocol_index],
+        "options": options,
+        "option_index": app.option_index,
+        "custom_headers": app.custom_headers,
+        "focused_item": app.focused_item,
+        "log_scroll": app.log_scroll,
+        "log_filter": app.log_filter.label(),
+        "log_search": app.log_search,
+        "profile": app.profile,
+        "logs": logs,
+    })
+}
+
+// 저장된 값만 덮어쓰고 없는 값은 기본값 (이전 버전의 세션 파일도 읽힌다)
+fn restore(tab: &Value) -> Tab {
+    let mut app = App::default();
+    let text = |key: &str| tab.get(key).and_then(Value::as_str).map(str::to_owned);
+    let number = |key: &str| tab.get(key).and_then(Value::as_u64).map(|n| n as usize);
+    for (field, key) in [
+        (&mut app.dst_url, "dst_url"),
+        (&mut app.delay_ms, "delay_ms"),
+        (&mut app.header_size_kb, "header_size_kb"),
+        (&mut app.iteration, "iteration"),
+        (&mut app.concurrency, "concurrency"),
+        (&mut app.rps, "rps"),
+        (&mut app.log_search, "log_search"),
+    ] {
+        if let Some(value) = text(key) {
+            *field = value;
+        }
+    }
+    if text("load_model").as_deref() == Some("rate") {
+        app.load_model = LoadModel::Rate;
+    }
+    if let Some(protocol) = text("protocol") {
+        app.protocol_index = app.protocols.iter().position(|p| *p == protocol).unwrap_or(0);
+    }
+    if let Some(options) = tab.get("options").and_then(Value::as_object) {
+        for option in &mut app.options {
+            if let Some(value) = options.get(option.key).and_then(Value::as_str) {
+                option.value = value.to_owned();
+            }
+        }
+    }
+    app.option_index = number("option_index").unwrap_or(0).min(app.options.len().saturating_sub(1));
+    let lines = |key: &str| tab.get(key).and_then(Value::as_array).map(|lines| lines.iter().filter_map(Value::as_str).map(str::to_owned).collect::<Vec<_>>()).unwrap_or_default();
+    app.custom_headers = lines("custom_headers");
+    // 입력 모드는 저장하지 않으므로 편집 중이던 입력창도 Normal 로 연다
+    app.focused_item = number("focused_item").unwrap_or(0).min(7);
+    app.log_scroll = number("log_scroll").unwrap_or(0);
+    app.log_filter = LogFilter::from_label(&text("log_filter").unwrap_or_default());
+    app.profile = text("profile");
+    (app, lines("logs"))
+}
+
+// 저장된 탭 하나 (입력 화면, 로그)
+pub type Tab = (App, Vec<String>);
+
+// 탭마다 (입력 화면, 로그) 와 보던 탭 번호
+pub fn save<'a>(tabs: impl Iterator<Item = (&'a App, Vec<String>)>, active: usize) -> Result<PathBuf, String> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let tabs: Vec<Value> = tabs.map(|(app, logs)| capture(app, &logs)).collect();
+    let json = serde_json::to_string_pretty(&json!({ "active": active, "workspaces": tabs })).map_err(|e| e.to_string())?;
+    fs::write(&path, json + "\n").map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+// 세션 파일이 없으면 None
+pub fn load() -> Result<Option<(Vec<Tab>, usize)>, String> {
+    let path = path()?;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let session: Value = serde_json::from_str(&text).map_err(|e| format!("{}: invalid JSON: {}", path.display(), e))?;
+    let tabs: Vec<Tab> = session.get("workspaces").and_then(Value::as_array).map(|tabs| tabs.iter().map(restore).collect()).unwrap_or_default();
+    if tabs.is_empty() {
+        return Err(format!("{}: no workspaces saved", path.display()));
+    }
+    let tabs: Vec<Tab> = tabs.into_iter().take(crate::MAX_WORKSPACES).collect();
+    let active = (session.get("active").and_then(Value::as_u64).unwrap_or(0) as usize).min(tabs.len() - 1);
+    Ok(Some((tabs, active)))
+}
@@ -0,0 +1,81 @@
+use std::{
+    net::SocketAddr,
+    sync::Mutex,
+};
+
+use tokio::{io::AsyncWriteExt, time::timeout};
+
+use crate::{alpn, conn, frames, stats::RequestRecord, utils::RequestTemplate, AppState};
+
+// SETTINGS 식별자 이름 (RFC 9113 6.5.2, RFC 8441)
+fn name(id: u16) -> String {
+    let name = match id {
+        0x1 => "header_table_size",
+        0x2 => "enable_push",
+        0x3 => "max_concurrent_streams",
+        0x4 => "initial_window_size",
+        0x5 => "max_frame_size",
+        0x6 => "max_header_list_size",
+        0x8 => "enable_connect_protocol",
+        _ => return format!("0x{:x}", id),
+    };
+    String::from(name)
+}
+
+// 리스너 http2_protocol_options 확인용 요약. 동시 스트림, 프레임 크기, 헤더 목록 크기는 알리지 않았어도 기본값으로 표시
+fn describe(values: &[(u16, u32)]) -> String {
+    let value = |id: u16| values.iter().rev().find(|(v, _)| *v == id).map(|(_, value)| value.to_string());
+    let mut parts = vec![
+        format!("max_concurrent_streams {}", value(0x3).unwrap_or_else(|| String::from("unlimited"))),
+        format!("max_frame_size {}", value(0x5).unwrap_or_else(|| String::from("16384 (default)"))),
+        format!("max_header_list_size {}", value(0x6).unwrap_or_else(|| String::from("unlimited"))),
+    ];
+    for (id, value) in values.iter().filter(|(id, _)| ![0x3, 0x5, 0x6].contains(id)) {
+        parts.push(format!("{} {}", name(*id), value));
+    }
+    parts.join(" | ")
+}
+
+// 요청이 도착한 주소로 h2 연결을 하나 더 맺어 첫 SETTINGS 를 받는다
+// 요청 경로의 클라이언트는 받은 SETTINGS 를 보여 주지 않으므로 같은 리스너에 따로 묻는다
+async fn capture(template: &RequestTemplate, addr: SocketAddr) -> Result<String, String> {
+    let (mut io, negotiated) = conn::open_to(template, addr, &["h2"], None).await?;
+    conn::require_h2(template, negotiated.as_deref())?;
+    io.write_all(frames::PREFACE).await.map_err(|e| e.to_string())?;
+    let wait = async {
+        loop {
+            let frame = frames::read(&mut io).await.map_err(|e| format!("connection lost: {}", e))?;
+            if frame.kind == frames::SETTINGS && frame.flags & frames::FLAG_ACK == 0 {
+                return Ok::<_, String>(describe(&frames::settings(&frame.payload)));
+            }
+        }
+    };
+    match timeout(template.request_timeout, wait).await {
+        Ok(result) => result,
+        Err(_) => Err(String::from("no SETTINGS within the request timeout")),
+    }
+}
+
+// h2 로 응답한 요청의 주소마다 한 번씩 SETTINGS 를 받아 로그와 인스펙터에 남긴다
+pub async fn track(template: &RequestTemplate, record: &RequestRecord, state: &Mutex<AppState>) {
+    let h2 = record.alpn.as_deref() == Some("h2") || (template.url().scheme() == "http" && template.alpn == alpn::Offer::H2);
+    let Some(remote) = record.remote.filter(|_| h2 && record.status.is_some()) else {
+        return;
+    };
+    {
+        let mut state = state.lock().unwrap();
+        if state.h2_settings.contains_key(&remote) {
+            return;
+        }
+        // 받는 중에 같은 주소로 다시 묻지 않도록 먼저 자리를 잡는다
+        state.h2_settings.insert(remote, None);
+    }
+    let result = capture(template, remote).await;
+    let mut state = state.lock().unwrap();
+    let log = match &result {
+        Ok(settings) => format!("HTTP/2 SETTINGS from {}: {}", remote, settings),
+        Err(e) => format!("HTTP/2 SETTINGS from {} not captured: {}", remote, e),
+    };
+    state.add_log(&log);
+    state.h2_settings.insert(remote, Some(result));
+}
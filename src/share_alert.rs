@@ -0,0 +1,89 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    compare::Side,
+    events::{Event, Reporter, Subscription},
+};
+
+// 구간 요청 수가 이보다 적으면 비율이 흔들리기 쉬워 비교하지 않는다
+const MIN_REQUESTS: usize = 10;
+
+// 구간마다 업스트림별 트래픽 비율을 직전 구간과 비교해 급변을 알린다
+// (Envoy 뒤에서 호스트가 이젝션되거나 새로 추가되면 비율이 먼저 바뀐다)
+pub struct ShareWatch {
+    interval: Duration,
+    // 비율 변화 기준 (%p)
+    threshold: f64,
+    started: Instant,
+    counts: BTreeMap<String, usize>,
+    previous: Option<BTreeMap<String, usize>>,
+}
+
+fn share(counts: &BTreeMap<String, usize>, upstream: &str) -> f64 {
+    let total: usize = counts.values().sum();
+    counts.get(upstream).copied().unwrap_or(0) as f64 * 100.0 / total.max(1) as f64
+}
+
+impl ShareWatch {
+    pub fn new(interval: Duration, threshold: f64, now: Instant) -> Self {
+        Self { interval, threshold, started: now, counts: BTreeMap::new(), previous: None }
+    }
+
+    // 응답 하나 반영. 구간이 끝났으면 직전 구간과 비교해 기준을 넘은 업스트림마다 경보 문구를 돌려준다
+    pub fn record(&mut self, upstream: &str, now: Instant) -> Vec<String> {
+        let mut alerts = Vec::new();
+        if now.duration_since(self.started) >= self.interval {
+            let counts = std::mem::take(&mut self.counts);
+            if let Some(previous) = &self.previous
+                && previous.values().sum::<usize>() >= MIN_REQUESTS
+                && counts.values().sum::<usize>() >= MIN_REQUESTS
+            {
+                let names: BTreeSet<&String> = previous.keys().chain(counts.keys()).collect();
+                for name in names {
+                    let (before, after) = (share(previous, name), share(&counts, name));
+                    if (after - before).abs() > self.threshold {
+                        let hint = if after == 0.0 {
+                            " (no traffic, ejected?)"
+                        } else if before == 0.0 {
+                            " (new upstream)"
+                        } else {
+                            ""
+                        };
+                        alerts.push(format!("Upstream share alert: {} {:.1}% -> {:.1}% ({:+.1} pts){}", name, before, after, after - before, hint));
+                    }
+                }
+            }
+            self.previous = Some(counts);
+            self.started = now;
+        }
+        *self.counts.entry(upstream.to_owned()).or_default() += 1;
+        alerts
+    }
+}
+
+// 버스에서 응답을 받아 비율을 비교하고 경보를 로그와 상태 표시줄에 남긴다 (비교 실행의 B 는 제외)
+// 첫 RunStarted 는 이번 실행의 시작이고, 다음 실행이 시작되거나 버스가 닫히면 끝난다
+pub async fn run(mut watch: ShareWatch, mut events: Subscription, reporter: Reporter) {
+    let mut started = false;
+    while let Some(event) = events.recv().await {
+        let record = match event {
+            Event::RunStarted(_) if started => break,
+            Event::RunStarted(_) => {
+                started = true;
+                continue;
+            }
+            Event::RequestFinished(record) => record,
+            _ => continue,
+        };
+        let Some(upstream) = record.upstream.as_ref().filter(|_| record.side != Some(Side::B)) else {
+            continue;
+        };
+        // 이벤트가 늦게 도착해도 구간은 응답이 끝난 시각으로 나눈다
+        for alert in watch.record(upstream, record.sent_at + record.latency) {
+            reporter.alert(alert);
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::{net::UdpSocket, sync::Arc, time::Duration};
+
+use reqwest::Client;
+use serde_json::json;
+
+use crate::{
+    config::RunConfig,
+    events::{Event, Reporter, RunResults, Subscription, Tick},
+    export, notify, replay,
+    stats::{self, RequestRecord},
+};
+
+// StatsD 지표 이름과 Prometheus 지표 이름의 접두어
+const PREFIX: &str = "envoy_lb_client";
+
+// 실행 결과를 내보내는 곳. 여러 개를 함께 켤 수 있고, 구독 작업은 종류를 모른 채 같은 순서로 부른다
+pub trait ResultSink: Send {
+    // 실행 시작 로그에 남길 이름 (종류와 대상)
+    fn describe(&self) -> String;
+    // 요청 하나가 끝났을 때 (실시간으로 내보내는 싱크만)
+    fn record(&mut self, _record: &RequestRecord) {}
+    // 실행 중 1초마다
+    fn tick(&mut self, _tick: &Tick) {}
+    // 알릴 일이 생겼을 때 (첫 실패, 중단, 실행/큐 종료)
+    fn event(&mut self, _title: &str, _summary: &str, _config: &RunConfig) -> Option<Delivery> {
+        None
+    }
+    // 실행이 끝나거나 중지됐을 때
+    fn finish(&mut self, _run: &RunResults) -> Option<Delivery> {
+        None
+    }
+}
+
+// 싱크가 만든 결과. 파일처럼 바로 끝나는 일은 로그, HTTP 전송은 작업 스레드가 비동기로 보낸다
+pub enum Delivery {
+    Done(String),
+    Post(Post),
+}
+
+pub struct Post {
+    pub sink: String,
+    pub url: String,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+// 싱크 종류와 대상 ("file:out.json")
+fn parse_entry(entry: &str) -> Result<(&str, &str), String> {
+    let (kind, target) = entry.split_once(':').unwrap_or((entry, ""));
+    match kind {
+        "console" if target.is_empty() => Ok((kind, target)),
+        "console" => Err(String::from("console sink takes no target")),
+        "file" | "results" | "prometheus" | "statsd" | "webhook" if target.is_empty() => Err(format!("{} sink needs a target ({}:...)", kind, kind)),
+        "file" | "results" | "prometheus" | "statsd" | "webhook" => Ok((kind, target)),
+        _ => Err(format!("unknown sink '{}' (console/file/results/prometheus/statsd/webhook)", kind)),
+    }
+}
+
+// sinks 옵션 검증 (쉼표로 구분한 kind[:target] 목록)
+pub fn parse_list(value: &str) -> Result<(), String> {
+    value.split(',').map(str::trim).filter(|e| !e.is_empty()).try_for_each(|entry| parse_entry(entry).map(|_| ()))
+}
+
+// sinks 옵션과 기존 내보내기 파일, 알림 웹훅 옵션으로 이번 실행의 싱크를 만든다
+// 만들지 못한 싱크는 이유를 돌려주고 나머지로 실행한다. 콘솔 싱크는 TUI 화면을 깨뜨리므로 헤드리스 실행에서만 쓴다
+pub fn build(config: &RunConfig, headless: bool) -> (Vec<Box<dyn ResultSink>>, Vec<String>) {
+    let mut entries: Vec<(String, String)> = config
+        .sinks
+        .split(',')
+        .map(str::trim)
+        .filter_map(|entry| parse_entry(entry).ok())
+        .map(|(kind, target)| (kind.to_owned(), target.to_owned()))
+        .collect();
+    if !config.export_file.is_empty() {
+        entries.push((String::from("file"), config.export_file.clone()));
+    }
+    if !config.results_file.is_empty() {
+        entries.push((String::from("results"), config.results_file.clone()));
+    }
+    if !config.notify_url.is_empty() {
+        entries.push((String::from("webhook"), config.notify_url.clone()));
+    }
+
+    let mut sinks: Vec<Box<dyn ResultSink>> = Vec::new();
+    let mut errors = Vec::new();
+    for (kind, target) in entries {
+        match kind.as_str() {
+            "console" if !headless => errors.push(String::from("console sink is only available in headless runs (use the log pane)")),
+            "console" => sinks.push(Box::new(Console)),
+            "file" => sinks.push(Box::new(File { path: target })),
+            "results" => sinks.push(Box::new(Results { path: target })),
+            "prometheus" => sinks.push(Box::new(Prometheus { url: target })),
+            "webhook" => sinks.push(Box::new(Webhook { url: target })),
+            _ => match Statsd::connect(&target) {
+                Ok(sink) => sinks.push(Box::new(sink)),
+                Err(e) => errors.push(format!("statsd sink {}: {}", target, e)),
+            },
+        }
+    }
+    (sinks, errors)
+}
+
+// 이번 실행의 싱크를 버스 구독자로 돌린다. 첫 RunStarted 에서 설정을 받고, 다음 실행이 시작되거나 버스가 닫히면 끝난다
+// (큐의 마지막 단계가 끝난 뒤의 큐 종료 알림까지 받는다)
+pub async fn run(mut sinks: Vec<Box<dyn ResultSink>>, mut events: Subscription, reporter: Reporter) {
+    let mut config: Option<Arc<RunConfig>> = None;
+    while let Some(event) = events.recv().await {
+        let deliveries: Vec<Delivery> = match &event {
+            Event::RunStarted(_) if config.is_some() => break,
+            Event::RunStarted(started) | Event::ConfigChanged(started) => {
+                config = Some(started.clone());
+                continue;
+            }
+            Event::RequestFinished(record) => {
+                sinks.iter_mut().for_each(|sink| sink.record(record));
+                continue;
+            }
+            Event::IntervalTick(tick) => {
+                sinks.iter_mut().for_each(|sink| sink.tick(tick));
+                continue;
+            }
+            Event::Notice { title, summary } => match &config {
+                Some(config) => sinks.iter_mut().filter_map(|sink| sink.event(title, summary, config)).collect(),
+                None => continue,
+            },
+            Event::RunCompleted(results) => sinks.iter_mut().filter_map(|sink| sink.finish(results)).collect(),
+        };
+        for delivery in deliveries {
+            match delivery {
+                Delivery::Done(log) => reporter.log(log),
+                Delivery::Post(post) => {
+                    let reporter = reporter.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = self::post(&post).await {
+                            reporter.log(format!("Failed to send to {}: {}", post.sink, e));
+                        }
+                    });
+                }
+            }
+        }
+    }
+}
+
+pub async fn post(post: &Post) -> Result<(), String> {
+    let response = Client::new()
+        .post(&post.url)
+        .header(reqwest::header::CONTENT_TYPE, post.content_type)
+        .body(post.body.clone())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+// 요청마다 JSON 한 줄을 표준 출력으로
+struct Console;
+
+impl ResultSink for Console {
+    fn describe(&self) -> String {
+        String::from("console")
+    }
+
+    fn record(&mut self, record: &RequestRecord) {
+        let line = json!({
+            "id": record.id,
+            "status": record.status,
+            "success": record.success,
+            "latency_ms": record.latency.as_secs_f64() * 1000.0,
+            "upstream": record.upstream,
+            "error": record.error.map(|e| e.as_str()),
+            "response_bytes": record.response_size,
+        });
+        println!("{}", line);
+    }
+}
+
+// 요약과 구간별 집계 파일 (.json/.csv)
+struct File {
+    path: String,
+}
+
+impl ResultSink for File {
+    fn describe(&self) -> String {
+        format!("file {}", self.path)
+    }
+
+    fn finish(&mut self, run: &RunResults) -> Option<Delivery> {
+        let interval = Duration::from_secs(run.config.export_interval_s);
+        Some(Delivery::Done(match export::write(&self.path, &run.config.dst_url, &run.records, &run.history, &run.markers, interval, &run.config.percentiles) {
+            Ok(count) => format!("Exported {} interval(s) to {}", count, self.path),
+            Err(e) => format!("Failed to write export file: {}", e),
+        }))
+    }
+}
+
+// 요청별 결과와 요약 파일 (.json/.csv)
+struct Results {
+    path: String,
+}
+
+impl ResultSink for Results {
+    fn describe(&self) -> String {
+        format!("results {}", self.path)
+    }
+
+    fn finish(&mut self, run: &RunResults) -> Option<Delivery> {
+        let count = match export::write_results(&self.path, &run.config.dst_url, &run.records, &run.history, &run.proxy_logs, &run.markers, &run.config.percentiles) {
+            Ok(count) => count,
+            Err(e) => return Some(Delivery::Done(format!("Failed to write results file: {}", e))),
+        };
+        let interval = Duration::from_secs(run.config.export_interval_s);
+        let replay = match replay::save(&self.path, &run.records, &run.history, &run.logs, interval, &run.config.percentiles) {
+            Ok(replay) => format!("replay {}", replay),
+            Err(e) => format!("replay not saved: {}", e),
+        };
+        Some(Delivery::Done(format!("Exported {} request result(s) to {} ({})", count, self.path, replay)))
+    }
+}
+
+// 실행이 끝나면 Pushgateway 로 텍스트 형식 지표를 보낸다 (예: http://pushgateway:9091/metrics/job/envoy-lb-client)
+struct Prometheus {
+    url: String,
+}
+
+impl ResultSink for Prometheus {
+    fn describe(&self) -> String {
+        format!("prometheus {}", self.url)
+    }
+
+    fn finish(&mut self, run: &RunResults) -> Option<Delivery> {
+        let mut latency: Vec<Duration> = run.records.iter().map(|r| r.latency).collect();
+        latency.sort();
+        // 오래된 결과를 합쳤으면 실행 전체 집계로 (백분위는 히스토그램으로 어림한 값)
+        let totals = run.history.totals(&run.records);
+        let (requests, failed, sum) = match &totals {
+            Some(totals) => (totals.requests, totals.errors, totals.latency_sum),
+            None => (run.records.len(), run.records.iter().filter(|r| !r.success).count(), latency.iter().sum()),
+        };
+        let quantile = |p: f64| match &totals {
+            Some(totals) => totals.latency.percentile(p),
+            None => stats::percentile(&latency, p),
+        };
+        let mut body = format!("# TYPE {p}_requests_total counter\n{p}_requests_total {}\n", requests, p = PREFIX);
+        body.push_str(&format!("# TYPE {p}_errors_total counter\n{p}_errors_total {}\n", failed, p = PREFIX));
+        body.push_str(&format!("# TYPE {}_latency_seconds summary\n", PREFIX));
+        for p in &run.config.percentiles {
+            body.push_str(&format!("{}_latency_seconds{{quantile=\"{}\"}} {}\n", PREFIX, p / 100.0, quantile(*p).as_secs_f64()));
+        }
+        body.push_str(&format!("{}_latency_seconds_sum {}\n", PREFIX, sum.as_secs_f64()));
+        body.push_str(&format!("{}_latency_seconds_count {}\n", PREFIX, requests));
+        body.push_str(&format!("# TYPE {}_upstream_requests_total counter\n", PREFIX));
+        for upstream in stats::by_upstream(&run.records) {
+            body.push_str(&format!("{}_upstream_requests_total{{upstream=\"{}\"}} {}\n", PREFIX, upstream.name.replace('"', "'"), upstream.count));
+        }
+        Some(Delivery::Post(Post { sink: self.describe(), url: self.url.clone(), content_type: "text/plain; version=0.0.4", body }))
+    }
+}
+
+// 요청마다 UDP 로 카운터와 지연 타이머를 보낸다 (보내지 못한 패킷은 버린다)
+struct Statsd {
+    target: String,
+    socket: UdpSocket,
+}
+
+impl Statsd {
+    fn connect(target: &str) -> Result<Self, String> {
+        let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+        socket.connect(target).map_err(|e| e.to_string())?;
+        socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+        Ok(Self { target: target.to_owned(), socket })
+    }
+}
+
+impl ResultSink for Statsd {
+    fn describe(&self) -> String {
+        format!("statsd {}", self.target)
+    }
+
+    fn record(&mut self, record: &RequestRecord) {
+        let mut packet = format!("{p}.requests:1|c\n{p}.latency:{:.3}|ms", record.latency.as_secs_f64() * 1000.0, p = PREFIX);
+        if !record.success {
+            packet.push_str(&format!("\n{}.errors:1|c", PREFIX));
+        }
+        if let Some(status) = record.status {
+            packet.push_str(&format!("\n{}.status.{}:1|c", PREFIX, status));
+        }
+        let _ = self.socket.send(packet.as_bytes());
+    }
+
+    // 응답을 기다리는 요청 수
+    fn tick(&mut self, tick: &Tick) {
+        let packet = format!("{}.in_flight:{}|g", PREFIX, tick.sent.saturating_sub(tick.completed));
+        let _ = self.socket.send(packet.as_bytes());
+    }
+}
+
+// Slack 호환 웹훅 ({"text": ...}) 으로 알림과 요약
+struct Webhook {
+    url: String,
+}
+
+impl ResultSink for Webhook {
+    fn describe(&self) -> String {
+        format!("webhook {}", self.url)
+    }
+
+    fn event(&mut self, title: &str, summary: &str, config: &RunConfig) -> Option<Delivery> {
+        let message = notify::message(title, &config.dst_url, summary);
+        Some(Delivery::Post(Post { sink: self.describe(), url: self.url.clone(), content_type: "application/json", body: json!({ "text": message }).to_string() }))
+    }
+}
+
+// 실행 시작 로그에 남길 싱크 목록
+pub fn describe(sinks: &[Box<dyn ResultSink>]) -> Option<String> {
+    if sinks.is_empty() {
+        return None;
+    }
+    let names: Vec<String> = sinks.iter().map(|s| s.describe()).collect();
+    Some(format!("Sinks: {}", names.join(", ")))
+}
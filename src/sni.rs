@@ -0,0 +1,189 @@
+use std::{net::SocketAddr, time::Instant};
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::{header::HOST, Request};
+use hyper_util::rt::TokioIo;
+use tokio::{net::TcpStream, time::timeout};
+use tokio_native_tls::{native_tls, TlsConnector, TlsStream};
+
+use crate::{preflight::CheckResult, utils::RequestTemplate};
+
+pub const NAME: &str = "SNI";
+
+// 쉼표로 구분한 SNI 목록 ("a.example.com,b.example.com")
+pub fn parse_list(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_owned).collect()
+}
+
+// DER TLV 하나 (태그, 내용, 나머지)
+fn tlv(der: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = der.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let n = (first & 0x7f) as usize;
+        if n == 0 || n > 4 || rest.len() < n {
+            return None;
+        }
+        (rest[..n].iter().fold(0, |len, b| len << 8 | *b as usize), &rest[n..])
+    };
+    (rest.len() >= len).then(|| (tag, &rest[..len], &rest[len..]))
+}
+
+// SEQUENCE/SET 안의 TLV 들
+fn children(mut der: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut items = Vec::new();
+    while let Some((tag, value, rest)) = tlv(der) {
+        items.push((tag, value));
+        der = rest;
+    }
+    items
+}
+
+// 인증서의 subject CN 과 SAN dNSName 목록
+struct CertNames {
+    cn: Option<String>,
+    san: Vec<String>,
+}
+
+const OID_CN: &[u8] = &[0x55, 0x04, 0x03];
+const OID_SAN: &[u8] = &[0x55, 0x1d, 0x11];
+
+// Certificate ::= SEQUENCE { tbsCertificate, ... }
+// tbsCertificate ::= SEQUENCE { [0] version, serial, signature, issuer, validity, subject, spki, ..., [3] extensions }
+fn cert_names(der: &[u8]) -> Option<CertNames> {
+    let (_, cert, _) = tlv(der)?;
+    let (_, tbs, _) = tlv(cert)?;
+    let fields = children(tbs);
+    // version 이 없으면(v1) 한 칸 앞당겨진다
+    let skip = if fields.first()?.0 == 0xa0 { 1 } else { 0 };
+    let subject = fields.get(skip + 4)?.1;
+
+    let cn = children(subject)
+        .into_iter()
+        .flat_map(|(_, set)| children(set))
+        .filter_map(|(_, attribute)| match children(attribute).as_slice() {
+            [(0x06, oid), (_, value)] if *oid == OID_CN => Some(String::from_utf8_lossy(value).into_owned()),
+            _ => None,
+        })
+        .next_back();
+
+    let mut san = Vec::new();
+    if let Some((_, extensions)) = fields.iter().find(|(tag, _)| *tag == 0xa3)
+        && let Some((_, extensions, _)) = tlv(extensions)
+    {
+        for (_, extension) in children(extensions) {
+            let parts = children(extension);
+            if parts.first().is_some_and(|(tag, oid)| *tag == 0x06 && *oid == OID_SAN)
+                && let Some((_, names)) = parts.last()
+                && let Some((_, names, _)) = tlv(names)
+            {
+                // dNSName 은 [2] 태그
+                san.extend(children(names).into_iter().filter(|(tag, _)| *tag == 0x82).map(|(_, name)| String::from_utf8_lossy(name).into_owned()));
+            }
+        }
+    }
+    Some(CertNames { cn, san })
+}
+
+fn describe_cert(der: &[u8]) -> String {
+    match cert_names(der) {
+        Some(names) => {
+            let mut text = format!("CN={}", names.cn.as_deref().unwrap_or("-"));
+            if !names.san.is_empty() {
+                let shown: Vec<&str> = names.san.iter().take(3).map(String::as_str).collect();
+                let more = if names.san.len() > 3 { format!(" +{}", names.san.len() - 3) } else { String::new() };
+                text.push_str(&format!(" SAN {}{}", shown.join(","), more));
+            }
+            text
+        }
+        None => format!("unparsed certificate ({} bytes)", der.len()),
+    }
+}
+
+// SNI 하나로 핸드셰이크해 받은 인증서(DER)와 TLS 연결
+async fn handshake(addr: SocketAddr, template: &RequestTemplate, sni: Option<&str>) -> Result<(Vec<u8>, TlsStream<TcpStream>), String> {
+    let stream = match timeout(template.connect_timeout, TcpStream::connect(addr)).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(e)) => return Err(format!("connect failed: {}", e)),
+        Err(_) => return Err(String::from("connect timed out")),
+    };
+    // 어떤 인증서가 오는지 보려는 것이므로 검증은 하지 않는다
+    let connector = native_tls::TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .danger_accept_invalid_hostnames(true)
+        .use_sni(sni.is_some())
+        .build()
+        .map_err(|e| e.to_string())?;
+    let domain = sni.unwrap_or_else(|| template.url().host_str().unwrap_or_default());
+    let stream = match timeout(template.tls_timeout, TlsConnector::from(connector).connect(domain, stream)).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(e)) => return Err(format!("TLS handshake failed: {}", e)),
+        Err(_) => return Err(String::from("TLS handshake timed out")),
+    };
+    let der = stream
+        .get_ref()
+        .peer_certificate()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| String::from("no certificate presented"))?
+        .to_der()
+        .map_err(|e| e.to_string())?;
+    Ok((der, stream))
+}
+
+// 같은 연결로 Host 를 SNI 에 맞춘 요청을 보내 어느 라우트/업스트림이 받는지 확인
+async fn route(stream: TlsStream<TcpStream>, template: &RequestTemplate, host: &str) -> Result<String, String> {
+    let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream)).await.map_err(|e| e.to_string())?;
+    tokio::spawn(conn);
+    let path = template.url().path();
+    let request = Request::builder().uri(path).header(HOST, host).body(Full::new(Bytes::new())).map_err(|e| e.to_string())?;
+    let response = match timeout(template.request_timeout, sender.send_request(request)).await {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => return Err(format!("request failed: {}", e)),
+        Err(_) => return Err(String::from("request timed out")),
+    };
+    let mut text = format!("HTTP {}", response.status().as_u16());
+    if let Some(upstream) = template.upstream_of(response.headers()) {
+        text.push_str(&format!(" upstream {}", upstream));
+    }
+    Ok(text)
+}
+
+// SNI 없이 한 번(기본 필터 체인), 목록의 SNI 마다 한 번씩 같은 주소로 핸드셰이크해 받은 인증서와 라우트를 비교
+pub async fn run(template: &RequestTemplate, addr: SocketAddr, names: &[String]) -> Vec<CheckResult> {
+    let mut results = Vec::new();
+    let mut default_cert = None;
+    for sni in std::iter::once(None).chain(names.iter().map(|name| Some(name.as_str()))) {
+        let started = Instant::now();
+        let label = sni.unwrap_or("(no SNI)");
+        let result = match handshake(addr, template, sni).await {
+            Ok((der, stream)) => {
+                let mut text = format!("{} -> {}", label, describe_cert(&der));
+                match (&default_cert, sni) {
+                    (None, None) => default_cert = Some(der),
+                    (Some(default), Some(_)) if *default == der => text.push_str(" (default chain)"),
+                    _ => {}
+                }
+                let host = sni.unwrap_or_else(|| template.url().host_str().unwrap_or_default());
+                match route(stream, template, host).await {
+                    Ok(route) => Ok(format!("{} | {}", text, route)),
+                    Err(e) => Err(format!("{} | {}", text, e)),
+                }
+            }
+            Err(e) => Err(format!("{} -> {}", label, e)),
+        };
+        results.push(CheckResult::new(NAME, started, result));
+    }
+    results
+}
+
+// 점검 창을 닫은 뒤에도 볼 수 있도록 실행 로그에 남길 줄
+pub fn log_lines(results: &[CheckResult]) -> Vec<String> {
+    results
+        .iter()
+        .filter(|r| r.name == NAME)
+        .map(|r| format!("SNI matrix {}: {}", if r.ok == Some(true) { "OK" } else { "FAIL" }, r.detail))
+        .collect()
+}
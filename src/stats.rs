@@ -0,0 +1,273 @@
+use std::{collections::BTreeMap, net::SocketAddr, time::{Duration, Instant}};
+
+use bytes::Bytes;
+
+use crate::{compare::Side, error_code::ErrorCode, idle, timing::Timing, transport::TimeoutKind};
+
+// 요청 하나의 결과
+#[derive(Clone)]
+pub struct RequestRecord {
+    pub id: String,
+    // 응답 상태코드 (응답을 받지 못했으면 None)
+    pub status: Option<u16>,
+    // 응답 헤더로 식별한 업스트림과 클러스터
+    pub upstream: Option<String>,
+    pub cluster: Option<String>,
+    // 경로 목록을 순환할 때 사용한 경로 번호
+    pub route: Option<usize>,
+    // 실제 전송 시각
+    pub sent_at: Instant,
+    // 지연 헤더를 붙여 보낸 요청인지 (least-request 검증)
+    pub slowed: bool,
+    // 작업 스레드가 요청을 예약한 시각 ~ 실제 전송 시각 (클라이언트 측 대기)
+    pub queue_delay: Duration,
+    // 실제 전송 시각 ~ 응답 수신 완료 (서버 측 지연)
+    pub latency: Duration,
+    pub success: bool,
+    // 실패 원인이 타임아웃이면 어느 단계인지
+    pub timeout: Option<TimeoutKind>,
+    // Envoy 가 알려준 업스트림 처리 시간 (x-envoy-upstream-service-time)
+    pub upstream_time: Option<Duration>,
+    // 요청에 실어 보낸 데드라인과 데드라인 초과 응답(504, DEADLINE_EXCEEDED) 여부
+    pub deadline: Option<Duration>,
+    pub deadline_exceeded: bool,
+    // 헤지 요청을 보냈는지, 헤지 요청의 응답을 사용했는지
+    pub hedged: bool,
+    pub hedge_won: bool,
+    // 드레인 신호 (Connection: close, 503 DRAINING, GOAWAY)
+    pub drain: Option<&'static str>,
+    // 응답을 받은 연결의 목적지 주소 (주소 계열 집계용)
+    pub remote: Option<SocketAddr>,
+    // A/B 비교 실행에서 요청을 받은 대상 (비교 실행이 아니면 None)
+    pub side: Option<Side>,
+    // 응답의 gRPC 상태 (grpc-status 헤더, 트랜스코딩 모드에서는 오류 본문의 code 포함)
+    pub grpc_status: Option<u32>,
+    // 모드별 응답 검증에 실패한 이유 (트랜스코딩 매핑, JSON 본문)
+    pub validation: Option<String>,
+    // 응답을 받은 요청의 단계별 시간 (인스펙터 워터폴)
+    pub timing: Option<Timing>,
+    // 요청마다 만든 본문 (GraphQL 변수 치환, 인스펙터에서 다시 보낼 때 사용)
+    pub body: Option<Bytes>,
+    // 요청마다 만든 쿼리 파라미터 (쿼리 템플릿 치환)
+    pub query: Option<String>,
+    // 실패한 요청의 에러 코드 (스크립트용)
+    pub error: Option<ErrorCode>,
+    // 멱등성 키와 응답 본문 해시 (중복 요청 검증)
+    pub idempotency_key: Option<String>,
+    pub body_hash: Option<u64>,
+    // 우선순위 헤더 스윕에서 붙인 값 번호
+    pub priority: Option<usize>,
+    // 헤더 매트릭스에서 붙인 조합 번호
+    pub combination: Option<usize>,
+    // TCP/UDP 모드에서 보내고 받은 바이트 수
+    pub transferred: Option<(u64, u64)>,
+    // HTTP 응답 본문 크기 (바이트)
+    pub response_size: Option<u64>,
+    // TLS 응답에서 협상된 ALPN 프로토콜 (전송 계층에서 협상하지 않았으면 "none")
+    pub alpn: Option<String>,
+    // 유휴 연결 실험의 결과 (유휴 시간, 닫힌 방식)
+    pub idle: Option<idle::IdleOutcome>,
+}
+
+impl RequestRecord {
+    // HTTP 요청이 아닌 연결, 패킷(TCP/UDP 모드) 결과의 기본값
+    pub fn new(id: String, sent_at: Instant) -> Self {
+        Self {
+            id,
+            status: None,
+            upstream: None,
+            cluster: None,
+            route: None,
+            sent_at,
+            slowed: false,
+            queue_delay: Duration::ZERO,
+            latency: Duration::ZERO,
+            success: false,
+            timeout: None,
+            upstream_time: None,
+            deadline: None,
+            deadline_exceeded: false,
+            hedged: false,
+            hedge_won: false,
+            drain: None,
+            remote: None,
+            side: None,
+            grpc_status: None,
+            validation: None,
+            timing: None,
+            body: None,
+            query: None,
+            error: None,
+            idempotency_key: None,
+            body_hash: None,
+            priority: None,
+            combination: None,
+            transferred: None,
+            response_size: None,
+            alpn: None,
+            idle: None,
+        }
+    }
+
+    // 비교 실행의 B 쪽이 아닌 기록 (비교 보고 밖의 집계와 실시간 지표는 A 쪽만 센다)
+    pub fn primary(&self) -> bool {
+        self.side != Some(Side::B)
+    }
+}
+
+// 정렬된 값에서 백분위 값 계산
+pub fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub fn format_ms(d: Duration) -> String {
+    format!("{:.3}ms", d.as_secs_f64() * 1000.0)
+}
+
+// 전송량 표시 (1000 단위)
+pub fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..1_000 => format!("{}B", bytes),
+        1_000..1_000_000 => format!("{:.1}KB", bytes as f64 / 1_000.0),
+        _ => format!("{:.2}MB", bytes as f64 / 1_000_000.0),
+    }
+}
+
+// 보고에 쓰는 백분위 이름 (99.9 -> "p99.9")
+pub fn percentile_label(p: f64) -> String {
+    format!("p{}", p)
+}
+
+// 쉼표로 구분한 백분위 목록 ("50,99,99.9")
+pub fn parse_percentiles(value: &str) -> Result<Vec<f64>, String> {
+    let percentiles = value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| match s.trim_start_matches('p').parse::<f64>() {
+            Ok(p) if p > 0.0 && p <= 100.0 => Ok(p),
+            _ => Err(format!("'{}' is not a percentile between 0 and 100", s)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if percentiles.is_empty() {
+        return Err(String::from("at least one percentile is required"));
+    }
+    Ok(percentiles)
+}
+
+// 정렬된 지연의 백분위 목록 ("p50 1.000ms p99 2.000ms")
+pub fn format_percentiles(sorted: &[Duration], percentiles: &[f64]) -> String {
+    percentiles.iter().map(|p| format!("{} {}", percentile_label(*p), format_ms(percentile(sorted, *p)))).collect::<Vec<_>>().join(" ")
+}
+
+// 클라이언트 대기 시간과 서버 지연을 분리한 요약. 서버 지연은 설정한 백분위로 표시
+pub fn summarize(records: &[RequestRecord], percentiles: &[f64]) -> String {
+    let records: Vec<&RequestRecord> = records.iter().filter(|r| r.primary()).collect();
+    let mut queue: Vec<Duration> = records.iter().map(|r| r.queue_delay).collect();
+    let mut latency: Vec<Duration> = records.iter().map(|r| r.latency).collect();
+    queue.sort();
+    latency.sort();
+
+    let avg = |v: &[Duration]| if v.is_empty() { Duration::ZERO } else { v.iter().sum::<Duration>() / v.len() as u32 };
+    let failed = records.iter().filter(|r| !r.success).count();
+    let timeouts = |kind| records.iter().filter(|r| r.timeout == Some(kind)).count();
+    let ipv4 = records.iter().filter(|r| r.remote.is_some_and(|a| a.is_ipv4())).count();
+    let ipv6 = records.iter().filter(|r| r.remote.is_some_and(|a| a.is_ipv6())).count();
+
+    format!(
+        "Summary: {} completed ({} failed; timeouts connect {} / TLS {} / request {}) | IPv4 {} / IPv6 {} | client queue avg {} p99 {} max {} | server latency avg {} {} max {}",
+        records.len(), failed,
+        timeouts(TimeoutKind::Connect), timeouts(TimeoutKind::Tls), timeouts(TimeoutKind::Request),
+        ipv4, ipv6,
+        format_ms(avg(&queue)), format_ms(percentile(&queue, 99.0)), format_ms(queue.last().copied().unwrap_or_default()),
+        format_ms(avg(&latency)), format_percentiles(&latency, percentiles), format_ms(latency.last().copied().unwrap_or_default()),
+    )
+}
+
+// 업스트림 하나의 지연 통계
+#[derive(Clone)]
+pub struct UpstreamStats {
+    pub name: String,
+    pub count: usize,
+    pub failed: usize,
+    pub p50: Duration,
+    pub p99: Duration,
+    // 보고용 정렬된 지연
+    pub latency: Vec<Duration>,
+}
+
+// 응답 헤더로 식별된 업스트림별 통계 (이름순)
+pub fn by_upstream(records: &[RequestRecord]) -> Vec<UpstreamStats> {
+    let mut groups: BTreeMap<&str, Vec<&RequestRecord>> = BTreeMap::new();
+    for record in records.iter().filter(|r| r.primary()) {
+        if let Some(upstream) = &record.upstream {
+            groups.entry(upstream.as_str()).or_default().push(record);
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(name, records)| {
+            let mut latency: Vec<Duration> = records.iter().map(|r| r.latency).collect();
+            latency.sort();
+            UpstreamStats {
+                name: name.to_owned(),
+                count: records.len(),
+                failed: records.iter().filter(|r| !r.success).count(),
+                p50: percentile(&latency, 50.0),
+                p99: percentile(&latency, 99.0),
+                latency,
+            }
+        })
+        .collect()
+}
+
+// 나머지 업스트림보다 눈에 띄게 느린 업스트림 (p50 이 나머지 중앙값의 2배 이상)
+pub fn is_outlier(stats: &[UpstreamStats], index: usize) -> bool {
+    if stats.len() < 2 {
+        return false;
+    }
+    let mut others: Vec<Duration> = stats.iter().enumerate().filter(|(i, _)| *i != index).map(|(_, s)| s.p50).collect();
+    others.sort();
+    stats[index].p50 >= percentile(&others, 50.0) * 2
+}
+
+// 헤지 요약: 헤지 비율과 버려진(먼저 끝나지 않은) 요청 수
+pub fn hedge_summary(records: &[RequestRecord]) -> String {
+    let hedged = records.iter().filter(|r| r.hedged).count();
+    let won = records.iter().filter(|r| r.hedge_won).count();
+    format!(
+        "Hedging: {} of {} requests hedged ({:.1}%), hedge won {}, wasted requests {}",
+        hedged, records.len(), hedged as f64 * 100.0 / records.len().max(1) as f64, won, hedged
+    )
+}
+
+// 지연 분해 요약. 업스트림 처리 시간을 받은 요청이 없으면 None
+// injected_delay 가 있으면 프록시 구간이 그보다 긴 요청을 지연 주입된 것으로 센다
+pub fn attribution_summary(records: &[RequestRecord], injected_delay: Option<Duration>) -> Option<String> {
+    let pairs: Vec<(Duration, Duration)> = records
+        .iter()
+        .filter_map(|r| r.upstream_time.map(|upstream| (r.latency.saturating_sub(upstream), upstream)))
+        .collect();
+    if pairs.is_empty() {
+        return None;
+    }
+
+    let count = pairs.len() as u32;
+    let proxy = pairs.iter().map(|(p, _)| *p).sum::<Duration>() / count;
+    let upstream = pairs.iter().map(|(_, u)| *u).sum::<Duration>() / count;
+    let share = proxy.as_secs_f64() * 100.0 / (proxy + upstream).as_secs_f64().max(f64::EPSILON);
+    let mut summary = format!(
+        "Latency attribution: {} requests | avg proxy {} ({:.1}%) | avg upstream {}",
+        pairs.len(), format_ms(proxy), share, format_ms(upstream)
+    );
+    if let Some(delay) = injected_delay {
+        let injected = pairs.iter().filter(|(p, _)| *p >= delay).count();
+        summary.push_str(&format!(" | proxy time >= injected {}: {} requests", format_ms(delay), injected));
+    }
+    Some(summary)
+}
@@ -0,0 +1,140 @@
+use std::{
+    collections::BTreeSet,
+    time::{Duration, Instant},
+};
+
+use tokio::{io::AsyncWriteExt, time::timeout};
+
+use crate::{conn, frames, preflight::CheckResult, utils::RequestTemplate};
+
+pub const NAME: &str = "Stream limit";
+
+// 스트림을 연 뒤 상대의 반응(RST_STREAM, GOAWAY)을 기다리는 시간. 이 시간 동안 프레임이 없으면 다음 단계로
+const QUIET: Duration = Duration::from_millis(200);
+
+// 한 연결에서 끝내지 않은 스트림 수를 1, 2, 4, ... 씩 늘려 열면서 REFUSED_STREAM 이 오는 시점을 찾는다
+// 스트림은 END_STREAM 없이 열어 두므로 응답을 받아도 반쯤 닫힌 채 동시 스트림 수에 남는다
+struct Ramp {
+    settings: bool,
+    // SETTINGS_MAX_CONCURRENT_STREAMS (알리지 않았으면 None = 무제한)
+    advertised: Option<u32>,
+    open: BTreeSet<u32>,
+    opened: usize,
+    peak: usize,
+    refused: usize,
+    // 거절된 스트림보다 먼저 열려 아직 열려 있던 스트림 수 (상대는 스트림을 순서대로 받으므로 이 값이 실제 한도)
+    refused_at: Option<usize>,
+    // 상대가 리셋한 다른 이유 (마지막 하나)
+    reset: Option<String>,
+    goaway: Option<String>,
+}
+
+impl Ramp {
+    // 조용해질 때까지 받은 프레임 처리. SETTINGS, PING 에는 ACK 로 답한다
+    async fn drain<S: conn::Io>(&mut self, io: &mut S) -> Result<(), String> {
+        loop {
+            let frame = match timeout(QUIET, frames::read(io)).await {
+                Err(_) => return Ok(()),
+                Ok(Ok(frame)) => frame,
+                Ok(Err(e)) if self.goaway.is_some() => return Err(format!("connection closed after GOAWAY: {}", e)),
+                Ok(Err(e)) => return Err(format!("connection lost: {}", e)),
+            };
+            match frame.kind {
+                frames::SETTINGS if frame.flags & frames::FLAG_ACK == 0 => {
+                    self.settings = true;
+                    if let Some((_, value)) = frames::settings(&frame.payload).into_iter().rev().find(|(id, _)| *id == 0x3) {
+                        self.advertised = Some(value);
+                    }
+                    frames::write(io, frames::SETTINGS, frames::FLAG_ACK, 0, &[]).await.map_err(|e| e.to_string())?;
+                }
+                frames::PING if frame.flags & frames::FLAG_ACK == 0 => {
+                    frames::write(io, frames::PING, frames::FLAG_ACK, 0, &frame.payload).await.map_err(|e| e.to_string())?;
+                }
+                frames::RST_STREAM => {
+                    self.open.remove(&frame.stream);
+                    match frames::error_code(&frame) {
+                        Some(frames::REFUSED_STREAM) => {
+                            self.refused += 1;
+                            let before = self.open.range(..frame.stream).count();
+                            self.refused_at = Some(self.refused_at.unwrap_or(0).max(before));
+                        }
+                        // NO_ERROR 는 응답을 끝낸 뒤 남은 스트림을 닫는 정상 종료
+                        Some(code) if code != 0 => self.reset = Some(frames::error_name(code)),
+                        _ => {}
+                    }
+                }
+                frames::GOAWAY => {
+                    self.goaway = Some(frames::error_code(&frame).map(frames::error_name).unwrap_or_default());
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn describe(&self) -> Result<String, String> {
+        let advertised = match self.advertised {
+            Some(max) => format!("SETTINGS max_concurrent_streams {}", max),
+            None => String::from("SETTINGS max_concurrent_streams not advertised"),
+        };
+        if let Some(goaway) = &self.goaway {
+            return Err(format!("{} | GOAWAY {} after {} streams (peak {} open)", advertised, goaway, self.opened, self.peak));
+        }
+        let mut text = match self.refused_at {
+            Some(effective) => {
+                let mut text = format!("{} | REFUSED_STREAM beyond {} open streams (effective max_concurrent_streams {})", advertised, effective, effective);
+                if self.advertised.is_some_and(|max| max as usize != effective) {
+                    text.push_str(" (differs from SETTINGS)");
+                }
+                text.push_str(&format!(", {} of {} streams refused", self.refused, self.opened));
+                text
+            }
+            None => format!("{} | no REFUSED_STREAM up to {} open streams", advertised, self.peak),
+        };
+        if let Some(reset) = &self.reset {
+            text.push_str(&format!(" | other resets ({})", reset));
+        }
+        Ok(text)
+    }
+}
+
+async fn ramp(template: &RequestTemplate, max: usize) -> Result<String, String> {
+    let (mut io, _) = conn::open_h2(template).await?;
+    io.write_all(frames::PREFACE).await.map_err(|e| e.to_string())?;
+    let mut ramp = Ramp { settings: false, advertised: None, open: BTreeSet::new(), opened: 0, peak: 0, refused: 0, refused_at: None, reset: None, goaway: None };
+    let greeting = ramp.drain(&mut io).await;
+    if !ramp.settings {
+        return Err(match greeting {
+            Ok(()) => String::from("no SETTINGS from the listener (not speaking HTTP/2?)"),
+            Err(e) => format!("no SETTINGS from the listener (not speaking HTTP/2?): {}", e),
+        });
+    }
+
+    let headers = frames::request_headers(template);
+    let mut batch = 1;
+    while ramp.opened < max && ramp.refused_at.is_none() && ramp.goaway.is_none() {
+        for _ in 0..batch.min(max - ramp.opened) {
+            let stream = ramp.opened as u32 * 2 + 1;
+            frames::write(&mut io, frames::HEADERS, frames::FLAG_END_HEADERS, stream, &headers).await.map_err(|e| e.to_string())?;
+            ramp.open.insert(stream);
+            ramp.opened += 1;
+        }
+        ramp.peak = ramp.peak.max(ramp.open.len());
+        ramp.drain(&mut io).await?;
+        batch *= 2;
+    }
+    ramp.describe()
+}
+
+// 사전 점검에 이어 실행하는 동시 스트림 한도 프로브 (max 개까지)
+pub async fn run(template: &RequestTemplate, max: usize) -> CheckResult {
+    let started = Instant::now();
+    let result = ramp(template, max).await;
+    CheckResult::new(NAME, started, result)
+}
+
+// 점검 창을 닫은 뒤에도 볼 수 있도록 실행 로그에 남길 줄
+pub fn log_line(results: &[CheckResult]) -> Option<String> {
+    let result = results.iter().find(|r| r.name == NAME && r.ok.is_some())?;
+    Some(format!("Stream limit {}: {}", if result.ok == Some(true) { "OK" } else { "FAIL" }, result.detail))
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+// 성공으로 볼 응답 기준 (오류율을 SLO 정의에 맞추기 위함)
+#[derive(Clone)]
+pub struct Criteria {
+    // 성공으로 볼 상태코드 범위 ("2xx", "404", "400-499")
+    statuses: Vec<(u16, u16)>,
+    // 이보다 느린 응답은 상태코드와 관계없이 실패
+    pub max_latency: Option<Duration>,
+}
+
+impl Criteria {
+    pub fn new(statuses: &str, max_latency_ms: u64) -> Result<Self, String> {
+        Ok(Self { statuses: parse_statuses(statuses)?, max_latency: (max_latency_ms > 0).then(|| Duration::from_millis(max_latency_ms)) })
+    }
+
+    // 경로 목록에 기대 상태코드가 있으면 그 값만 성공으로 본다 (404 를 기대하는 경로 등)
+    pub fn accepts(&self, status: StatusCode, expected: Option<u16>) -> bool {
+        let status = status.as_u16();
+        match expected {
+            Some(expected) => status == expected,
+            None => self.statuses.iter().any(|(from, to)| (*from..=*to).contains(&status)),
+        }
+    }
+}
+
+// 쉼표로 구분한 상태코드, 클래스(2xx), 범위(400-499)
+pub fn parse_statuses(value: &str) -> Result<Vec<(u16, u16)>, String> {
+    let statuses = value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let invalid = || format!("invalid status '{}' (e.g. 2xx, 404, 400-499)", s);
+            let range = if let Some(class) = s.strip_suffix("xx").or_else(|| s.strip_suffix("XX")) {
+                // 700xx 같은 값은 u16 을 넘으므로 곱하기 전에 걸러낸다
+                let from = class.parse::<u16>().ok().and_then(|class| class.checked_mul(100)).ok_or_else(invalid)?;
+                (from, from.saturating_add(99))
+            } else if let Some((from, to)) = s.split_once('-') {
+                (from.trim().parse::<u16>().map_err(|_| invalid())?, to.trim().parse::<u16>().map_err(|_| invalid())?)
+            } else {
+                let status = s.parse::<u16>().map_err(|_| invalid())?;
+                (status, status)
+            };
+            if !(100..=599).contains(&range.0) || !(100..=599).contains(&range.1) || range.0 > range.1 {
+                return Err(invalid());
+            }
+            Ok(range)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if statuses.is_empty() {
+        return Err(String::from("at least one success status is required"));
+    }
+    Ok(statuses)
+}
@@ -0,0 +1,210 @@
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use rand::{distr::Alphanumeric, Rng};
+use reqwest::Url;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    config::RunConfig,
+    error_code::ErrorCode,
+    events::Bus,
+    stats::{self, format_ms, RequestRecord},
+    timing::Timing,
+    transport::{AddressFamily, TimeoutKind},
+    utils::RequestTemplate,
+    AppState,
+};
+
+// tcp://host:port 대상 (Envoy tcp_proxy 리스너)
+// 연결마다 같은 페이로드를 rounds 번 보내고, 에코를 기대하면 보낸 만큼 돌려받아 비교한다
+pub struct TcpTarget {
+    // lookup_host 에 넘길 "host:port" (IPv6 는 대괄호 포함)
+    addr: String,
+    payload: Bytes,
+    rounds: usize,
+    echo: bool,
+}
+
+impl TcpTarget {
+    pub fn new(url: &Url, config: &RunConfig, rng: &mut impl Rng) -> Result<Self, String> {
+        let host = url.host_str().ok_or_else(|| format!("TCP target '{}' has no host", url))?;
+        let port = url.port().ok_or_else(|| format!("TCP target '{}' needs a port", url))?;
+        if !config.compare_url.is_empty() || !config.routes_file.trim().is_empty() {
+            return Err(String::from("TCP mode cannot be combined with A/B compare or a routes file"));
+        }
+        let payload: Vec<u8> = rng.sample_iter(&Alphanumeric).take(config.tcp_payload_bytes).collect();
+        Ok(Self { addr: format!("{}:{}", host, port), payload: Bytes::from(payload), rounds: config.tcp_rounds, echo: config.tcp_echo })
+    }
+}
+
+// 연결 하나의 실패 (원인, 타임아웃 단계, 에러 코드)
+type Failure = (String, Option<TimeoutKind>, ErrorCode);
+
+// 정책 순서대로 주소마다 연결을 시도 (주소마다 연결 타임아웃 적용)
+async fn connect(addr: &str, family: AddressFamily, timeout: Duration) -> Result<(TcpStream, Duration), Failure> {
+    let started = Instant::now();
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host(addr).await.map_err(|e| (format!("DNS lookup failed: {}", e), None, ErrorCode::Connect))?.collect();
+    let dns = started.elapsed();
+    let mut last = (format!("no usable address for {}", addr), None, ErrorCode::Connect);
+    for addr in family.order(addrs) {
+        match tokio::time::timeout(timeout, TcpStream::connect(addr)).await {
+            Ok(Ok(stream)) => return Ok((stream, dns)),
+            Ok(Err(e)) => last = (format!("connect to {} failed: {}", addr, e), None, ErrorCode::Connect),
+            Err(_) => last = (format!("connect to {} timed out", addr), Some(TimeoutKind::Connect), ErrorCode::Connect),
+        }
+    }
+    Err(last)
+}
+
+// 페이로드를 보내고 에코를 비교한다. 보내고 받은 바이트 수와 쓰기/읽기에 걸린 시간을 timing 에 누적
+async fn exchange(stream: &mut TcpStream, target: &TcpTarget, timeout: Duration, transferred: &mut (u64, u64), timing: &mut Timing) -> Result<(), Failure> {
+    let mut echoed = vec![0u8; target.payload.len()];
+    for round in 0..target.rounds {
+        let started = Instant::now();
+        match tokio::time::timeout(timeout, stream.write_all(&target.payload)).await {
+            Ok(Ok(())) => transferred.0 += target.payload.len() as u64,
+            Ok(Err(e)) => return Err((format!("write failed in round {}: {}", round + 1, e), None, ErrorCode::Send)),
+            Err(_) => return Err((format!("write timed out in round {}", round + 1), Some(TimeoutKind::Request), ErrorCode::Timeout)),
+        }
+        timing.send += started.elapsed();
+        if !target.echo {
+            continue;
+        }
+
+        let started = Instant::now();
+        match tokio::time::timeout(timeout, stream.read_exact(&mut echoed)).await {
+            Ok(Ok(_)) => transferred.1 += echoed.len() as u64,
+            Ok(Err(e)) => return Err((format!("echo read failed in round {}: {}", round + 1, e), None, ErrorCode::Receive)),
+            Err(_) => return Err((format!("echo timed out in round {}", round + 1), Some(TimeoutKind::Request), ErrorCode::Timeout)),
+        }
+        timing.receive += started.elapsed();
+        if echoed != target.payload {
+            return Err((format!("echo mismatch in round {}", round + 1), None, ErrorCode::Assert));
+        }
+    }
+    // 에코를 기대하지 않으면 쓰기 쪽을 닫아 보낸 데이터를 모두 넘겼음을 알린다
+    if !target.echo {
+        stream.shutdown().await.map_err(|e| (format!("shutdown failed: {}", e), None, ErrorCode::Send))?;
+    }
+    Ok(())
+}
+
+// MB/s (바이트 수 / 시간)
+fn throughput(bytes: u64, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() { 0.0 } else { bytes as f64 / elapsed.as_secs_f64() / 1_000_000.0 }
+}
+
+// 연결 하나의 결과 기록과 로그 줄
+async fn session(template: &RequestTemplate, target: &TcpTarget, id: String, scheduled_at: Instant) -> (RequestRecord, String) {
+    let sent_at = Instant::now();
+    let mut record = RequestRecord::new(id, sent_at);
+    let mut timing = Timing { detailed: true, ..Timing::default() };
+    let mut transferred = (0, 0);
+
+    let result = match connect(&target.addr, template.family, template.connect_timeout).await {
+        Ok((mut stream, dns)) => {
+            timing.dns = dns;
+            timing.connect = sent_at.elapsed().saturating_sub(dns);
+            record.remote = stream.peer_addr().ok();
+            exchange(&mut stream, target, template.request_timeout, &mut transferred, &mut timing).await
+        }
+        Err(failure) => Err(failure),
+    };
+
+    record.queue_delay = sent_at.duration_since(scheduled_at);
+    record.latency = sent_at.elapsed();
+    record.transferred = Some(transferred);
+    record.timing = Some(timing);
+    let mut log = match result {
+        Ok(()) => {
+            record.success = true;
+            let verb = if target.echo { "echoed" } else { "sent" };
+            format!(
+                "Connection {} Succeded: {} x {}B {} | connect {} | {:.2} MB/s",
+                record.id,
+                target.rounds,
+                target.payload.len(),
+                verb,
+                format_ms(timing.connect),
+                throughput(transferred.0 + transferred.1, timing.send + timing.receive)
+            )
+        }
+        Err((reason, timeout, code)) => {
+            record.timeout = timeout;
+            record.error = Some(code);
+            format!("Connection {} Failed: {} [{}]", record.id, reason, code.as_str())
+        }
+    };
+    if let Some(remote) = record.remote {
+        log.push_str(&format!(" [{}]", remote));
+    }
+    log.push_str(&format!(" (queue {}, total {})", format_ms(record.queue_delay), format_ms(record.latency)));
+    (record, log)
+}
+
+// 작업 스레드가 반복마다 띄우는 TCP 연결
+pub async fn send(template: Arc<RequestTemplate>, id: String, scheduled_at: Instant, state: Arc<Mutex<AppState>>, bus: Bus) {
+    let Some(target) = &template.tcp else {
+        return;
+    };
+    let (record, log) = session(&template, target, id, scheduled_at).await;
+    let events = {
+        let mut app_state = state.lock().unwrap();
+        app_state.add_log(&log);
+        app_state.add_record(record)
+    };
+    bus.publish_all(events);
+}
+
+// 실행 종료 보고: 연결 오류, 에코 불일치, 연결 시간, 연결별/전체 처리량
+pub fn summarize(records: &[RequestRecord], percentiles: &[f64]) -> Vec<String> {
+    let records: Vec<&RequestRecord> = records.iter().filter(|r| r.transferred.is_some()).collect();
+    let Some(first) = records.iter().map(|r| r.sent_at).min() else {
+        return Vec::new();
+    };
+    let failed = |code| records.iter().filter(|r| r.error == Some(code)).count();
+    let (sent, received) = records.iter().filter_map(|r| r.transferred).fold((0, 0), |(s, r), (a, b)| (s + a, r + b));
+
+    let mut connect: Vec<Duration> = records.iter().filter(|r| r.error != Some(ErrorCode::Connect)).filter_map(|r| r.timing).map(|t| t.connect).collect();
+    connect.sort();
+    let mut rates: Vec<f64> = records
+        .iter()
+        .filter(|r| r.success)
+        .filter_map(|r| Some((r.transferred?, r.timing?)))
+        .map(|((sent, received), timing)| throughput(sent + received, timing.send + timing.receive))
+        .collect();
+    rates.sort_by(f64::total_cmp);
+    // 전체 처리량은 첫 연결 시작부터 마지막 연결 종료까지의 벽시계 시간 기준
+    let last = records.iter().map(|r| r.sent_at + r.latency).max().unwrap_or(first);
+    let average = if rates.is_empty() { 0.0 } else { rates.iter().sum::<f64>() / rates.len() as f64 };
+
+    vec![
+        format!(
+            "TCP: {} connections ({} failed: connect {}, timeout {}, send {}, receive {}, echo mismatch {}) | sent {}, received {}",
+            records.len(),
+            records.iter().filter(|r| !r.success).count(),
+            failed(ErrorCode::Connect),
+            failed(ErrorCode::Timeout),
+            failed(ErrorCode::Send),
+            failed(ErrorCode::Receive),
+            failed(ErrorCode::Assert),
+            stats::format_bytes(sent),
+            stats::format_bytes(received),
+        ),
+        format!(
+            "TCP connect: {} | throughput per connection avg {:.2} MB/s min {:.2} MB/s | aggregate {:.2} MB/s",
+            stats::format_percentiles(&connect, percentiles),
+            average,
+            rates.first().copied().unwrap_or(0.0),
+            throughput(sent + received, last.duration_since(first)),
+        ),
+    ]
+}
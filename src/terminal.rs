@@ -0,0 +1,193 @@
+use std::{
+    io::{self, Stdout},
+    panic,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+
+// 터미널 준비/복원과 키 입력 변환. 플랫폼마다 다르게 들어오는 입력을 여기서 맞춘다
+
+// 대체 화면에 들어가 있는지 (패닉 훅과 Drop 이 두 번 복원하지 않게)
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Platform {
+    Unix,
+    MacOs,
+    Windows,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else {
+            Platform::Unix
+        }
+    }
+}
+
+// 화면 루프가 받는 입력
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Input {
+    Key(KeyCode),
+    // Ctrl+C: 어느 화면에서든 q 처럼 세션을 저장하고 끝낸다 (raw 모드에서는 SIGINT 대신 키로 들어온다)
+    Interrupt,
+}
+
+// 키 이벤트 하나를 화면 루프 입력으로 바꾼다. 버릴 입력이면 None
+pub fn translate(key: &KeyEvent, platform: Platform) -> Option<Input> {
+    // Windows 는 뗄 때도 이벤트를 보낸다. 누르고 있을 때의 반복은 누름으로 친다
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    match key.code {
+        // Windows 의 AltGr 는 Ctrl+Alt 로 들어오지만 실제로는 글자 입력이다 (독일어 자판의 @, [, ] 등)
+        KeyCode::Char(c) if ctrl && alt && platform == Platform::Windows => Some(Input::Key(KeyCode::Char(c))),
+        KeyCode::Char('c' | 'C') if ctrl && !alt => Some(Input::Interrupt),
+        // Backspace 를 BS(0x08) 로 보내는 터미널은 Ctrl+H 로 읽힌다
+        KeyCode::Char('h') if ctrl && !alt => Some(Input::Key(KeyCode::Backspace)),
+        // macOS 터미널의 Option+←/→ 는 ESC b / ESC f (단어 이동) 로 들어온다
+        KeyCode::Char('b') if alt && !ctrl && platform == Platform::MacOs => Some(Input::Key(KeyCode::Left)),
+        KeyCode::Char('f') if alt && !ctrl && platform == Platform::MacOs => Some(Input::Key(KeyCode::Right)),
+        // 나머지 Ctrl/Alt 조합은 글자 단축키 (c, s, q ...) 로 오인하지 않게 버린다
+        KeyCode::Char(_) if ctrl || alt => None,
+        code => Some(Input::Key(code)),
+    }
+}
+
+// 대체 화면과 raw 모드를 켜고, 끝나거나 패닉이 나도 되돌린다
+pub struct Screen {
+    pub terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl Screen {
+    pub fn enter() -> io::Result<Self> {
+        install_panic_hook();
+        enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        // 마우스 이벤트는 쓰지 않지만 켜 둔다. 끄면 macOS Terminal.app 과 Windows Terminal 이
+        // 대체 화면에서 휠을 ↑/↓ 키로 바꿔 보내 입력창 값이 바뀐다
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        // Windows 콘솔 호스트는 대체 화면을 흉내 내면서 이전 내용을 남길 수 있어 처음에 지운다
+        if Platform::current() == Platform::Windows {
+            terminal.clear()?;
+        }
+        Ok(Self { terminal })
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// 복원은 한 번만 한다. 실패해도 더 할 수 있는 일이 없으므로 오류는 무시한다
+fn restore() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show);
+}
+
+// 패닉 메시지가 대체 화면과 함께 사라지지 않게 먼저 터미널을 되돌린다
+fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        hook(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn with_kind(code: KeyCode, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent::new_with_kind(code, KeyModifiers::NONE, kind)
+    }
+
+    const ALL: [Platform; 3] = [Platform::Unix, Platform::MacOs, Platform::Windows];
+
+    #[test]
+    fn plain_keys_pass_through() {
+        for platform in ALL {
+            for code in [KeyCode::Char('q'), KeyCode::Char('+'), KeyCode::Enter, KeyCode::Esc, KeyCode::Tab, KeyCode::Up, KeyCode::Backspace] {
+                assert_eq!(translate(&key(code, KeyModifiers::NONE), platform), Some(Input::Key(code)));
+            }
+            // 대문자와 Shift+Tab 은 Shift 가 붙어 와도 그대로
+            assert_eq!(translate(&key(KeyCode::Char('H'), KeyModifiers::SHIFT), platform), Some(Input::Key(KeyCode::Char('H'))));
+            assert_eq!(translate(&key(KeyCode::BackTab, KeyModifiers::SHIFT), platform), Some(Input::Key(KeyCode::BackTab)));
+        }
+    }
+
+    #[test]
+    fn release_is_dropped_and_repeat_kept() {
+        for platform in ALL {
+            assert_eq!(translate(&with_kind(KeyCode::Char('q'), KeyEventKind::Release), platform), None);
+            assert_eq!(translate(&with_kind(KeyCode::Char('='), KeyEventKind::Repeat), platform), Some(Input::Key(KeyCode::Char('='))));
+        }
+    }
+
+    #[test]
+    fn ctrl_c_interrupts_everywhere() {
+        for platform in ALL {
+            assert_eq!(translate(&key(KeyCode::Char('c'), KeyModifiers::CONTROL), platform), Some(Input::Interrupt));
+            // Caps Lock 이나 Shift 가 켜진 채여도
+            assert_eq!(translate(&key(KeyCode::Char('C'), KeyModifiers::CONTROL | KeyModifiers::SHIFT), platform), Some(Input::Interrupt));
+            // 뗄 때 오는 이벤트로 두 번 끝내지 않는다
+            let release = KeyEvent::new_with_kind(KeyCode::Char('c'), KeyModifiers::CONTROL, KeyEventKind::Release);
+            assert_eq!(translate(&release, platform), None);
+        }
+    }
+
+    #[test]
+    fn ctrl_h_is_backspace() {
+        for platform in ALL {
+            assert_eq!(translate(&key(KeyCode::Char('h'), KeyModifiers::CONTROL), platform), Some(Input::Key(KeyCode::Backspace)));
+        }
+    }
+
+    #[test]
+    fn other_modified_chars_do_not_trigger_shortcuts() {
+        for platform in ALL {
+            assert_eq!(translate(&key(KeyCode::Char('s'), KeyModifiers::CONTROL), platform), None);
+            assert_eq!(translate(&key(KeyCode::Char('q'), KeyModifiers::ALT), platform), None);
+        }
+    }
+
+    #[test]
+    fn altgr_types_text_only_on_windows() {
+        let altgr = key(KeyCode::Char('@'), KeyModifiers::CONTROL | KeyModifiers::ALT);
+        assert_eq!(translate(&altgr, Platform::Windows), Some(Input::Key(KeyCode::Char('@'))));
+        assert_eq!(translate(&altgr, Platform::Unix), None);
+        // AltGr+C 로 글자를 치는 자판에서 실행이 끝나면 안 된다
+        let altgr_c = key(KeyCode::Char('c'), KeyModifiers::CONTROL | KeyModifiers::ALT);
+        assert_eq!(translate(&altgr_c, Platform::Windows), Some(Input::Key(KeyCode::Char('c'))));
+    }
+
+    #[test]
+    fn option_arrows_move_on_macos() {
+        assert_eq!(translate(&key(KeyCode::Char('b'), KeyModifiers::ALT), Platform::MacOs), Some(Input::Key(KeyCode::Left)));
+        assert_eq!(translate(&key(KeyCode::Char('f'), KeyModifiers::ALT), Platform::MacOs), Some(Input::Key(KeyCode::Right)));
+        assert_eq!(translate(&key(KeyCode::Char('b'), KeyModifiers::ALT), Platform::Unix), None);
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+// 연결 위에서 지금 오가는 요청 하나의 입출력 시각
+#[derive(Default)]
+pub struct IoMarks {
+    current: Mutex<Exchange>,
+}
+
+// 요청 하나의 입출력 시각 (요청 전송 시작과 완료, 첫 응답 바이트)
+#[derive(Clone, Copy, Default)]
+pub struct Exchange {
+    // 연결 위에서 몇 번째 요청인지 (0 이 연결을 맺은 요청)
+    pub index: usize,
+    first_write: Option<Instant>,
+    last_write: Option<Instant>,
+    first_read: Option<Instant>,
+}
+
+impl IoMarks {
+    pub fn wrote(&self) {
+        let now = Instant::now();
+        let mut current = self.current.lock().unwrap();
+        // 응답을 읽은 뒤 다시 쓰면 같은 연결의 다음 요청이다 (HTTP/1 은 연결마다 한 번에 요청 하나)
+        if current.first_read.is_some() {
+            *current = Exchange { index: current.index + 1, ..Exchange::default() };
+        }
+        current.first_write.get_or_insert(now);
+        current.last_write = Some(now);
+    }
+
+    pub fn read(&self) {
+        self.current.lock().unwrap().first_read.get_or_insert_with(Instant::now);
+    }
+
+    // 응답 헤더를 받았을 때의 시각들. 본문을 다 읽기 전에는 연결이 다음 요청에 쓰이지 않는다
+    pub fn exchange(&self) -> Exchange {
+        *self.current.lock().unwrap()
+    }
+}
+
+// 전송 계층이 연결을 맺으면서 잰 단계별 시간
+#[derive(Clone)]
+pub struct ConnPhases {
+    pub dns: Duration,
+    // TCP 연결 (PROXY protocol 헤더 전송 포함)
+    pub connect: Duration,
+    pub tls: Duration,
+    // 연결이 준비된 시각 (요청 전송 시작)
+    pub ready: Instant,
+    pub io: Arc<IoMarks>,
+}
+
+impl ConnPhases {
+    // 본문 수신이 끝난 시각 기준으로 요청 하나의 단계별 시간 계산
+    // DNS, 연결, TLS 는 연결을 맺은 요청에만 들어가고, 다시 쓴 연결의 요청은 자기 전송 시작부터 잰다
+    pub fn timing(&self, exchange: Exchange, done: Instant) -> Timing {
+        let reused = exchange.index > 0;
+        let start = if reused { exchange.first_write.unwrap_or(done) } else { self.ready };
+        let last_write = exchange.last_write.unwrap_or(start);
+        // 요청을 다 쓰기 전에 응답이 오는 경우도 있으므로 역전되면 0 으로 본다
+        let first_read = exchange.first_read.unwrap_or(done).max(last_write);
+        let (dns, connect, tls) = if reused { Default::default() } else { (self.dns, self.connect, self.tls) };
+        Timing {
+            dns,
+            connect,
+            tls,
+            send: last_write.saturating_duration_since(start),
+            wait: first_read.saturating_duration_since(last_write),
+            receive: done.saturating_duration_since(first_read),
+            detailed: true,
+            reused,
+        }
+    }
+}
+
+// 요청 하나의 단계별 시간
+// reqwest 로 보낸 요청은 연결 단계를 나눌 수 없어 응답 헤더까지(wait)와 본문 수신(receive)만 잰다
+#[derive(Clone, Copy, Default)]
+pub struct Timing {
+    pub dns: Duration,
+    pub connect: Duration,
+    pub tls: Duration,
+    pub send: Duration,
+    pub wait: Duration,
+    pub receive: Duration,
+    pub detailed: bool,
+    // 다시 쓴 연결의 요청 (DNS, 연결, TLS 는 앞 요청이 치렀다)
+    pub reused: bool,
+}
+
+// 워터폴 막대 하나 (단계 이름, 시간, 시작 칸, 길이 칸)
+pub struct Bar {
+    pub phase: &'static str,
+    pub duration: Duration,
+    pub offset: usize,
+    pub width: usize,
+}
+
+impl Timing {
+    pub fn phases(&self) -> [(&'static str, Duration); 6] {
+        [
+            ("DNS", self.dns),
+            ("Connect", self.connect),
+            ("TLS", self.tls),
+            ("Send", self.send),
+            ("Wait", self.wait),
+            ("Receive", self.receive),
+        ]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases().iter().map(|(_, d)| *d).sum()
+    }
+
+    // 전체 시간을 width 칸으로 보고 단계마다 앞 단계가 끝난 칸부터 그린다
+    // 0 이 아닌 단계는 짧아도 한 칸은 차지한다
+    pub fn waterfall(&self, width: usize) -> Vec<Bar> {
+        let total = self.total().as_secs_f64();
+        let scale = if total > 0.0 { width as f64 / total } else { 0.0 };
+        let mut elapsed = Duration::ZERO;
+        self.phases()
+            .into_iter()
+            .map(|(phase, duration)| {
+                let start = ((elapsed.as_secs_f64() * scale).round() as usize).min(width);
+                elapsed += duration;
+                let end = ((elapsed.as_secs_f64() * scale).round() as usize).min(width);
+                let bar = if duration.is_zero() { 0 } else { (end - start).max(1) };
+                Bar { phase, duration, offset: start.min(width.saturating_sub(bar)), width: bar }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,127 @@
+use std::fs;
+
+use reqwest::ClientBuilder;
+use tokio_native_tls::native_tls;
+
+use crate::config::RunConfig;
+
+const BEGIN_CERTIFICATE: &str = "-----BEGIN CERTIFICATE-----";
+const END_CERTIFICATE: &str = "-----END CERTIFICATE-----";
+
+// 대상 연결의 TLS 설정. 실행 시작 때 파일을 읽고 검증해 두고, reqwest 클라이언트와 native-tls 커넥터에 똑같이 적용한다
+#[derive(Clone, Default)]
+pub struct Settings {
+    // 시스템 저장소에 더해 믿을 CA 인증서 (PEM 블록 하나씩)
+    roots: Vec<Vec<u8>>,
+    // mTLS 클라이언트 인증서 체인과 PKCS#8 키 (PEM)
+    identity: Option<(Vec<u8>, Vec<u8>)>,
+    // 인증서와 호스트 이름 검증 생략
+    insecure: bool,
+}
+
+fn read(kind: &str, path: &str) -> Result<Vec<u8>, String> {
+    fs::read(path.trim()).map_err(|e| format!("Failed to read TLS {} '{}': {}", kind, path.trim(), e))
+}
+
+// CA 묶음 파일을 인증서 블록으로 나눈다 (native-tls 는 PEM 하나에 인증서 하나만 읽는다)
+fn certificates(pem: &[u8]) -> Vec<Vec<u8>> {
+    let text = String::from_utf8_lossy(pem);
+    let mut blocks = Vec::new();
+    let mut rest = text.as_ref();
+    while let Some(start) = rest.find(BEGIN_CERTIFICATE) {
+        let Some(end) = rest[start..].find(END_CERTIFICATE) else {
+            break;
+        };
+        let end = start + end + END_CERTIFICATE.len();
+        blocks.push(rest.as_bytes()[start..end].to_vec());
+        rest = &rest[end..];
+    }
+    blocks
+}
+
+impl Settings {
+    pub fn load(config: &RunConfig) -> Result<Self, String> {
+        let mut roots = Vec::new();
+        if !config.tls_ca_file.trim().is_empty() {
+            roots = certificates(&read("CA bundle", &config.tls_ca_file)?);
+            if roots.is_empty() {
+                return Err(format!("TLS CA bundle '{}' has no PEM certificates", config.tls_ca_file.trim()));
+            }
+            for root in &roots {
+                native_tls::Certificate::from_pem(root).map_err(|e| format!("invalid certificate in TLS CA bundle '{}': {}", config.tls_ca_file.trim(), e))?;
+            }
+        }
+
+        let identity = match (config.tls_client_cert.trim(), config.tls_client_key.trim()) {
+            ("", "") => None,
+            ("", _) | (_, "") => return Err(String::from("TLS client certificate and key must be set together")),
+            (cert, key) => {
+                let (cert, key) = (read("client certificate", cert)?, read("client key", key)?);
+                native_tls::Identity::from_pkcs8(&cert, &key).map_err(|e| format!("invalid TLS client certificate or key (PEM certificate and PKCS#8 key): {}", e))?;
+                Some((cert, key))
+            }
+        };
+        Ok(Self { roots, identity, insecure: config.tls_insecure })
+    }
+
+    // reqwest 클라이언트 (일반 요청 경로, 공유 연결)
+    pub fn apply(&self, mut builder: ClientBuilder) -> reqwest::Result<ClientBuilder> {
+        for root in &self.roots {
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(root)?);
+        }
+        if let Some((cert, key)) = &self.identity {
+            builder = builder.identity(reqwest::Identity::from_pkcs8_pem(cert, key)?);
+        }
+        Ok(builder.danger_accept_invalid_certs(self.insecure))
+    }
+
+    // native-tls 커넥터 (전송 계층, h2 연결, 사전 점검). ALPN 은 부르는 쪽에서 정한다
+    pub fn connector(&self) -> native_tls::TlsConnectorBuilder {
+        let mut builder = native_tls::TlsConnector::builder();
+        // 불러올 때 검증했으므로 여기서는 실패하지 않는다
+        for root in self.roots.iter().filter_map(|root| native_tls::Certificate::from_pem(root).ok()) {
+            builder.add_root_certificate(root);
+        }
+        if let Some(identity) = self.identity.as_ref().and_then(|(cert, key)| native_tls::Identity::from_pkcs8(cert, key).ok()) {
+            builder.identity(identity);
+        }
+        builder.danger_accept_invalid_certs(self.insecure).danger_accept_invalid_hostnames(self.insecure);
+        builder
+    }
+}
+
+// 서버가 보낸 핸드셰이크 바이트에서 협상된 TLS 버전 읽기
+// TLS 1.3 은 ServerHello 의 supported_versions 확장에, 그 이전은 ServerHello 의 버전 필드에 있다
+pub fn server_hello_version(bytes: &[u8]) -> Option<&'static str> {
+    // 레코드 헤더: 종류(22 = handshake), 버전 2, 길이 2
+    let (&[22, _, _, len_hi, len_lo], rest) = bytes.split_first_chunk::<5>()? else {
+        return None;
+    };
+    let record = rest.get(..usize::from(u16::from_be_bytes([len_hi, len_lo])))?;
+    // 핸드셰이크 헤더: 종류(2 = ServerHello), 길이 3
+    let (&[2, _, _, _], hello) = record.split_first_chunk::<4>()? else {
+        return None;
+    };
+    let mut version = u16::from_be_bytes([*hello.first()?, *hello.get(1)?]);
+    // 버전 2, random 32, 세션 id, 암호 스위트 2, 압축 1
+    let session_len = usize::from(*hello.get(34)?);
+    let mut at = 35 + session_len + 3;
+    let extensions_end = (at + 2 + usize::from(u16::from_be_bytes([*hello.get(at)?, *hello.get(at + 1)?]))).min(hello.len());
+    at += 2;
+    while at + 4 <= extensions_end {
+        let kind = u16::from_be_bytes([hello[at], hello[at + 1]]);
+        let len = usize::from(u16::from_be_bytes([hello[at + 2], hello[at + 3]]));
+        // supported_versions
+        if kind == 0x002b && len == 2 {
+            version = u16::from_be_bytes([*hello.get(at + 4)?, *hello.get(at + 5)?]);
+        }
+        at += 4 + len;
+    }
+    match version {
+        0x0304 => Some("TLSv1.3"),
+        0x0303 => Some("TLSv1.2"),
+        0x0302 => Some("TLSv1.1"),
+        0x0301 => Some("TLSv1.0"),
+        _ => None,
+    }
+}
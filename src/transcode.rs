@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use reqwest::StatusCode;
+
+use crate::stats::RequestRecord;
+
+// gRPC 상태코드 이름과 grpc_json_transcoder 가 돌려줘야 하는 HTTP 상태 (google.rpc.Code 매핑)
+const GRPC_CODES: [(&str, u16); 17] = [
+    ("OK", 200),
+    ("CANCELLED", 499),
+    ("UNKNOWN", 500),
+    ("INVALID_ARGUMENT", 400),
+    ("DEADLINE_EXCEEDED", 504),
+    ("NOT_FOUND", 404),
+    ("ALREADY_EXISTS", 409),
+    ("PERMISSION_DENIED", 403),
+    ("RESOURCE_EXHAUSTED", 429),
+    ("FAILED_PRECONDITION", 400),
+    ("ABORTED", 409),
+    ("OUT_OF_RANGE", 400),
+    ("UNIMPLEMENTED", 501),
+    ("INTERNAL", 500),
+    ("UNAVAILABLE", 503),
+    ("DATA_LOSS", 500),
+    ("UNAUTHENTICATED", 401),
+];
+
+pub fn code_name(code: u32) -> &'static str {
+    GRPC_CODES.get(code as usize).map_or("UNRECOGNIZED", |(name, _)| name)
+}
+
+pub fn expected_http(code: u32) -> Option<u16> {
+    GRPC_CODES.get(code as usize).map(|(_, status)| *status)
+}
+
+// 오류 응답의 gRPC 상태: grpc-status 헤더, 없으면 google.rpc.Status JSON 본문의 code
+pub fn grpc_code(grpc_status: Option<u32>, body: &[u8]) -> Option<u32> {
+    grpc_status.or_else(|| {
+        serde_json::from_slice::<serde_json::Value>(body).ok()?.get("code")?.as_u64().map(|code| code as u32)
+    })
+}
+
+// 트랜스코딩된 응답 검증. 성공 응답은 JSON 본문이어야 하고, gRPC 오류는 정해진 HTTP 상태로 바뀌어야 한다
+pub fn validate(status: StatusCode, code: Option<u32>, content_type: Option<&str>, body: &[u8]) -> Result<(), String> {
+    if let Some(code) = code
+        && code != 0
+    {
+        return match expected_http(code) {
+            Some(expected) if expected == status.as_u16() => Ok(()),
+            Some(expected) => Err(format!("grpc {} mapped to HTTP {} (expected {})", code_name(code), status.as_u16(), expected)),
+            None => Err(format!("unknown grpc code {}", code)),
+        };
+    }
+    if !status.is_success() {
+        return Ok(());
+    }
+    if !content_type.is_some_and(|c| c.to_ascii_lowercase().starts_with("application/json")) {
+        return Err(format!("content-type {} is not JSON", content_type.unwrap_or("(none)")));
+    }
+    serde_json::from_slice::<serde_json::Value>(body).map(|_| ()).map_err(|e| format!("invalid JSON body: {}", e))
+}
+
+// 트랜스코딩 검증 요약과 gRPC 상태 -> HTTP 상태 매핑 집계
+pub fn summarize(records: &[RequestRecord]) -> Vec<String> {
+    let responses: Vec<&RequestRecord> = records.iter().filter(|r| r.status.is_some()).collect();
+    let invalid: Vec<&RequestRecord> = responses.iter().copied().filter(|r| r.validation.is_some()).collect();
+    let mut summary = format!("Transcoding: {} responses, {} valid, {} invalid", responses.len(), responses.len() - invalid.len(), invalid.len());
+    if let Some(reason) = invalid.last().and_then(|r| r.validation.as_deref()) {
+        summary.push_str(&format!(" (last: {})", reason));
+    }
+
+    let mut mapping: BTreeMap<(u32, u16), usize> = BTreeMap::new();
+    for record in &responses {
+        if let (Some(code), Some(status)) = (record.grpc_status, record.status) {
+            *mapping.entry((code, status)).or_default() += 1;
+        }
+    }
+    let mut lines = vec![summary];
+    lines.extend(mapping.into_iter().map(|((code, status), count)| {
+        let verdict = match expected_http(code) {
+            Some(expected) if expected == status => String::from("OK"),
+            Some(expected) => format!("expected {}", expected),
+            None => String::from("unknown code"),
+        };
+        format!("Transcoding map: grpc {} ({}) -> HTTP {}: {} [{}]", code, code_name(code), status, count, verdict)
+    }));
+    lines
+}
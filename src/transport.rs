@@ -0,0 +1,478 @@
+use std::{
+    future::Future,
+    io,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Incoming, http::response::Parts, Request, Response, Uri};
+use hyper_util::{
+    client::legacy::{connect::{Connected, Connection}, Client},
+    rt::{TokioExecutor, TokioIo},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf},
+    net::{TcpSocket, TcpStream},
+    task::JoinSet,
+    time::timeout,
+};
+use tokio_native_tls::{native_tls, TlsConnector, TlsStream};
+
+use crate::{conn_log::{ConnEvents, Lifecycle, Recorder, Tracker}, error_code::ErrorCode, proxy_protocol, timing::{ConnPhases, IoMarks}, tls};
+
+// reqwest 로는 제어할 수 없는 연결 수준 옵션을 위한 전송 계층
+// (소스 주소/포트 지정, PROXY protocol 등). 연결 이벤트를 남기며 재사용할 때가 아니면 요청마다 새 연결을 맺는다
+pub type RawClient = Client<Connector, Full<Bytes>>;
+
+// 응답 extensions 로 전달되는 연결 정보
+#[derive(Clone)]
+pub struct ConnInfo {
+    pub local: SocketAddr,
+    // 실제로 연결된 목적지 주소 (주소 계열 확인용)
+    pub remote: SocketAddr,
+    // PROXY protocol 헤더로 보낸 클라이언트 주소
+    pub proxy_source: Option<SocketAddr>,
+    // 연결 단계별 시간과 요청/응답 바이트를 주고받은 시각 (인스펙터 워터폴)
+    pub phases: ConnPhases,
+    // TLS 핸드셰이크에서 협상된 ALPN 프로토콜
+    pub alpn: Option<String>,
+    // 연결 이벤트를 남길 때 연결 id 와 요청 수
+    pub tracker: Option<Tracker>,
+}
+
+// 어느 단계의 타임아웃이 발생했는지 (Envoy 쪽 원인이 각각 다르다)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimeoutKind {
+    Connect,
+    Tls,
+    Request,
+}
+
+impl std::fmt::Display for TimeoutKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TimeoutKind::Connect => "connect timeout",
+            TimeoutKind::Tls => "TLS handshake timeout",
+            TimeoutKind::Request => "request timeout",
+        })
+    }
+}
+
+impl std::error::Error for TimeoutKind {}
+
+// 에러 체인에서 타임아웃 종류 찾기
+pub fn find_timeout(err: &(dyn std::error::Error + 'static)) -> Option<TimeoutKind> {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if let Some(kind) = e.downcast_ref::<TimeoutKind>() {
+            return Some(*kind);
+        }
+        if let Some(kind) = e.downcast_ref::<io::Error>().and_then(|io| io.get_ref()).and_then(|inner| inner.downcast_ref::<TimeoutKind>()) {
+            return Some(*kind);
+        }
+        current = e.source();
+    }
+    None
+}
+
+// 에러 체인에 TLS 에러가 있는지 (핸드셰이크, 인증서 검증 실패)
+pub fn is_tls_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if e.is::<native_tls::Error>() || e.downcast_ref::<io::Error>().and_then(|io| io.get_ref()).is_some_and(|inner| inner.is::<native_tls::Error>()) {
+            return true;
+        }
+        current = e.source();
+    }
+    false
+}
+
+// hyper 기본 커넥터(reqwest)의 Happy Eyeballs 대기와 같은 값
+pub const DEFAULT_FALLBACK_DELAY_MS: u64 = 300;
+
+// 호스트가 여러 주소 계열로 해석될 때의 연결 정책
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AddressFamily {
+    // 리졸버 순서대로 v4/v6 를 번갈아 시도 (RFC 8305)
+    Auto,
+    V4Only,
+    V6Only,
+    PreferV4,
+    PreferV6,
+}
+
+impl AddressFamily {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "" | "auto" => Ok(AddressFamily::Auto),
+            "v4" => Ok(AddressFamily::V4Only),
+            "v6" => Ok(AddressFamily::V6Only),
+            "prefer-v4" => Ok(AddressFamily::PreferV4),
+            "prefer-v6" => Ok(AddressFamily::PreferV6),
+            _ => Err(format!("unknown address family '{}' (auto/v4/v6/prefer-v4/prefer-v6)", value)),
+        }
+    }
+
+    // 연결을 시도할 순서로 주소 정렬. 정책에 맞지 않는 계열은 제외
+    pub fn order(&self, addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+        let v6_first = addrs.first().is_some_and(|a| a.is_ipv6());
+        let (v4, v6): (Vec<_>, Vec<_>) = addrs.into_iter().partition(|a| a.is_ipv4());
+        match self {
+            AddressFamily::V4Only => v4,
+            AddressFamily::V6Only => v6,
+            AddressFamily::PreferV4 => v4.into_iter().chain(v6).collect(),
+            AddressFamily::PreferV6 => v6.into_iter().chain(v4).collect(),
+            AddressFamily::Auto => {
+                // 리졸버가 먼저 준 계열부터 번갈아 배치
+                let (first, second) = if v6_first { (v6, v4) } else { (v4, v6) };
+                let mut ordered = Vec::with_capacity(first.len() + second.len());
+                let mut second = second.into_iter();
+                for addr in first {
+                    ordered.push(addr);
+                    ordered.extend(second.next());
+                }
+                ordered.extend(second);
+                ordered
+            }
+        }
+    }
+}
+
+pub fn family_name(addr: &SocketAddr) -> &'static str {
+    if addr.is_ipv4() { "IPv4" } else { "IPv6" }
+}
+
+// 전송 계층 옵션
+pub struct TransportOptions {
+    pub connect_timeout: Duration,
+    pub tls_timeout: Duration,
+    pub tls: tls::Settings,
+    pub family: AddressFamily,
+    // 앞 주소의 연결이 끝나지 않았을 때 다음 주소를 시도하기까지의 대기 (0 이면 실패 후 시도)
+    pub fallback_delay: Duration,
+    // 순환하며 사용할 소스 주소 (비어 있으면 OS 가 선택)
+    pub sources: Vec<SocketAddr>,
+    pub proxy_protocol: Option<(proxy_protocol::Version, proxy_protocol::Source)>,
+    // 연결 이벤트를 쌓을 곳 (None 이면 남기지 않음)
+    pub events: Option<ConnEvents>,
+    // 연결을 풀에 남겨 다음 요청이 다시 쓴다
+    pub reuse: bool,
+    // 실행 시드 (PROXY 헤더의 임의 소스 주소를 같은 순서로 만든다)
+    pub seed: u64,
+}
+
+#[derive(Clone)]
+pub struct Connector {
+    inner: Arc<ConnectorInner>,
+}
+
+struct ConnectorInner {
+    options: TransportOptions,
+    next: AtomicUsize,
+    tls: TlsConnector,
+    rng: Mutex<StdRng>,
+}
+
+// "10.0.0.1,10.0.0.2" 와 "40000-40099" 조합으로 소스 주소 풀 생성
+// 포트 0 은 OS 가 선택
+pub fn parse_source_pool(addrs: &str, ports: &str) -> Result<Vec<SocketAddr>, String> {
+    if addrs.trim().is_empty() && ports.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let ips = addrs
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<IpAddr>().map_err(|_| format!("invalid source address '{}'", s)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ports = match ports.trim() {
+        "" => vec![0],
+        range => {
+            let (start, end) = range.split_once('-').unwrap_or((range, range));
+            let start = start.trim().parse::<u16>().map_err(|_| format!("invalid source port range '{}'", range))?;
+            let end = end.trim().parse::<u16>().map_err(|_| format!("invalid source port range '{}'", range))?;
+            if start == 0 || start > end {
+                return Err(format!("invalid source port range '{}'", range));
+            }
+            (start..=end).collect()
+        }
+    };
+
+    // 주소가 없으면 목적지 주소 계열에 맞춰 unspecified 로 바인드
+    let ips = if ips.is_empty() { vec![IpAddr::from([0, 0, 0, 0])] } else { ips };
+    Ok(ports.iter().flat_map(|port| ips.iter().map(move |ip| SocketAddr::new(*ip, *port))).collect())
+}
+
+pub fn build_client(options: TransportOptions) -> Result<RawClient, String> {
+    // 이 전송 계층은 HTTP/1.1 만 말하므로 ALPN 도 http/1.1 만 제안한다
+    let tls = options.tls.connector().request_alpns(&["http/1.1"]).build().map_err(|e| e.to_string())?;
+    let max_idle = if options.reuse { usize::MAX } else { 0 };
+    let connector = Connector {
+        inner: Arc::new(ConnectorInner { rng: Mutex::new(StdRng::seed_from_u64(options.seed)), options, next: AtomicUsize::new(0), tls: TlsConnector::from(tls) }),
+    };
+    Ok(Client::builder(TokioExecutor::new()).pool_max_idle_per_host(max_idle).build(connector))
+}
+
+// 실패한 전송이나 본문 수신의 에러 메시지와 타임아웃 종류, 에러 코드
+pub type Failure = (String, Option<TimeoutKind>, ErrorCode);
+
+// 요청 전송. 응답 헤더를 받으면 본문 수신 결과와 함께 돌려준다
+pub async fn send(client: &RawClient, request: Request<Full<Bytes>>, request_timeout: Duration) -> Result<(Parts, Result<Bytes, Failure>), Failure> {
+    let exchange = async {
+        let response: Response<Incoming> = client.request(request).await.map_err(|e| {
+            let timeout = find_timeout(&e);
+            (format!("{:?}", e), timeout, ErrorCode::from_send_error(timeout, is_tls_error(&e), e.is_connect()))
+        })?;
+        // 이 요청의 입출력 시각은 본문을 다 읽어 연결이 풀에 돌아가기 전에 챙긴다
+        let exchange = response.extensions().get::<ConnInfo>().map(|info| info.phases.io.exchange());
+        let (mut parts, body) = response.into_parts();
+        if let Some(exchange) = exchange {
+            parts.extensions.insert(exchange);
+        }
+        let body = body.collect().await.map(|b| b.to_bytes()).map_err(|e| (e.to_string(), None, ErrorCode::Receive));
+        Ok((parts, body))
+    };
+
+    match timeout(request_timeout, exchange).await {
+        Ok(result) => result,
+        Err(_) => Err((TimeoutKind::Request.to_string(), Some(TimeoutKind::Request), ErrorCode::Timeout)),
+    }
+}
+
+impl Connector {
+    // 주소를 순서대로 시도하되, 앞 시도가 fallback_delay 안에 끝나지 않거나 실패하면 다음 주소를 함께 시도
+    // 가장 먼저 성공한 연결을 사용하고 나머지 시도는 취소된다
+    async fn connect_any(&self, addrs: Vec<SocketAddr>) -> io::Result<TcpStream> {
+        let fallback_delay = self.inner.options.fallback_delay;
+        let mut pending = addrs.into_iter();
+        let mut attempts = JoinSet::new();
+        let mut last_err = io::Error::new(io::ErrorKind::NotFound, "no address for the selected address family");
+
+        loop {
+            if attempts.is_empty() {
+                match pending.next() {
+                    Some(addr) => {
+                        let this = self.clone();
+                        attempts.spawn(async move { this.connect_tcp(addr).await });
+                    }
+                    None => return Err(last_err),
+                }
+            }
+
+            let joined = if pending.len() > 0 && !fallback_delay.is_zero() {
+                match timeout(fallback_delay, attempts.join_next()).await {
+                    Ok(joined) => joined,
+                    Err(_) => {
+                        let (this, addr) = (self.clone(), pending.next().unwrap());
+                        attempts.spawn(async move { this.connect_tcp(addr).await });
+                        continue;
+                    }
+                }
+            } else {
+                attempts.join_next().await
+            };
+
+            match joined {
+                Some(Ok(Ok(stream))) => return Ok(stream),
+                Some(Ok(Err(e))) => last_err = e,
+                Some(Err(e)) => last_err = io::Error::other(e),
+                None => {}
+            }
+            // 실패하면 대기 없이 다음 주소 시도
+            if let Some(addr) = pending.next() {
+                let this = self.clone();
+                attempts.spawn(async move { this.connect_tcp(addr).await });
+            }
+        }
+    }
+
+    // 다음 소스 주소에 바인드해서 연결. 사용 중인 포트는 건너뛴다
+    async fn connect_tcp(&self, remote: SocketAddr) -> io::Result<TcpStream> {
+        let sources = &self.inner.options.sources;
+        let mut last_err = io::Error::new(io::ErrorKind::AddrNotAvailable, "empty source pool");
+
+        for _ in 0..sources.len().max(1) {
+            let index = self.inner.next.fetch_add(1, Ordering::Relaxed);
+            let source = sources.get(index % sources.len().max(1)).copied();
+
+            let socket = if remote.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+            socket.set_reuseaddr(true)?;
+            socket.set_nodelay(true)?;
+            if let Some(mut source) = source {
+                // 주소 계열이 다르면 unspecified 로 맞춘다
+                if source.ip().is_unspecified() && remote.is_ipv6() {
+                    source.set_ip(IpAddr::from([0u16; 8]));
+                }
+                if source.is_ipv4() != remote.is_ipv4() {
+                    last_err = io::Error::new(io::ErrorKind::AddrNotAvailable, format!("source {} does not match {}", source, remote));
+                    continue;
+                }
+                if let Err(e) = socket.bind(source) {
+                    last_err = e;
+                    continue;
+                }
+            }
+            match socket.connect(remote).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) if e.kind() == io::ErrorKind::AddrNotAvailable || e.kind() == io::ErrorKind::AddrInUse => last_err = e,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_err)
+    }
+}
+
+impl tower_service::Service<Uri> for Connector {
+    type Response = TokioIo<Stream>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<Self::Response>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let this = self.clone();
+        Box::pin(async move {
+            let host = uri.host().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing host"))?.trim_matches(['[', ']']).to_owned();
+            let https = uri.scheme_str() == Some("https");
+            let port = uri.port_u16().unwrap_or(if https { 443 } else { 80 });
+
+            let options = &this.inner.options;
+            let started = Instant::now();
+            let addrs = options.family.order(tokio::net::lookup_host((host.as_str(), port)).await?.collect());
+            let resolved = Instant::now();
+            let mut tcp = timeout(options.connect_timeout, this.connect_any(addrs))
+                .await
+                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, TimeoutKind::Connect))??;
+            let remote = tcp.peer_addr()?;
+            let phases = ConnPhases {
+                dns: resolved - started,
+                connect: Duration::ZERO,
+                tls: Duration::ZERO,
+                ready: resolved,
+                io: Arc::new(IoMarks::default()),
+            };
+            let mut info = ConnInfo { local: tcp.local_addr()?, remote, proxy_source: None, phases, alpn: None, tracker: None };
+
+            // TLS 보다 먼저 PROXY protocol 헤더 전송
+            if let Some((version, source)) = options.proxy_protocol {
+                let src = source.resolve(info.local, remote, &mut *this.inner.rng.lock().unwrap());
+                let header = proxy_protocol::header(version, src, remote).map_err(io::Error::other)?;
+                tcp.write_all(&header).await?;
+                info.proxy_source = Some(src);
+            }
+            let connected = Instant::now();
+            info.phases.connect = connected - resolved;
+            let mut lifecycle = options.events.as_ref().map(|events| Lifecycle::open(events, info.local, remote, info.phases.connect));
+            info.tracker = lifecycle.as_ref().map(|l| l.tracker.clone());
+
+            let io = if https {
+                let handshake = timeout(options.tls_timeout, this.inner.tls.connect(&host, Recorder::new(tcp, lifecycle.is_some()))).await;
+                let mut tls = match handshake {
+                    Ok(Ok(tls)) => tls,
+                    Ok(Err(e)) => {
+                        if let Some(lifecycle) = lifecycle.as_mut() {
+                            lifecycle.fail(format!("TLS handshake failed: {}", e));
+                        }
+                        return Err(io::Error::other(e));
+                    }
+                    Err(_) => {
+                        if let Some(lifecycle) = lifecycle.as_mut() {
+                            lifecycle.fail(TimeoutKind::Tls.to_string());
+                        }
+                        return Err(io::Error::new(io::ErrorKind::TimedOut, TimeoutKind::Tls));
+                    }
+                };
+                info.phases.tls = connected.elapsed();
+                info.phases.ready = Instant::now();
+                info.alpn = tls.get_ref().negotiated_alpn().ok().flatten().map(|p| String::from_utf8_lossy(&p).into_owned());
+                if let Some(lifecycle) = &lifecycle {
+                    let hello = tls.get_mut().get_mut().get_mut().finish();
+                    lifecycle.tls(tls::server_hello_version(&hello), info.alpn.as_deref(), info.phases.tls);
+                }
+                StreamIo::Tls(Box::new(tls))
+            } else {
+                info.phases.ready = connected;
+                StreamIo::Plain(tcp)
+            };
+            Ok(TokioIo::new(Stream { io, info, lifecycle }))
+        })
+    }
+}
+
+enum StreamIo {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<Recorder>>),
+}
+
+pub struct Stream {
+    io: StreamIo,
+    info: ConnInfo,
+    // 연결 이벤트를 남길 때만 (버려질 때 닫힘을 남긴다)
+    lifecycle: Option<Lifecycle>,
+}
+
+impl Connection for Stream {
+    fn connected(&self) -> Connected {
+        Connected::new().extra(self.info.clone())
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let (filled, room) = (buf.filled().len(), buf.remaining() > 0);
+        let poll = match &mut this.io {
+            StreamIo::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            StreamIo::Tls(s) => Pin::new(s.as_mut()).poll_read(cx, buf),
+        };
+        let read = buf.filled().len() - filled;
+        if matches!(poll, Poll::Ready(Ok(()))) && read > 0 {
+            this.info.phases.io.read();
+        }
+        if let Some(lifecycle) = this.lifecycle.as_mut().filter(|_| room) {
+            lifecycle.observe_read(&poll, read);
+        }
+        poll
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = match &mut this.io {
+            StreamIo::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            StreamIo::Tls(s) => Pin::new(s.as_mut()).poll_write(cx, buf),
+        };
+        if matches!(poll, Poll::Ready(Ok(n)) if n > 0) {
+            this.info.phases.io.wrote();
+        }
+        if let Some(lifecycle) = this.lifecycle.as_mut() {
+            lifecycle.observe_write(&poll);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().io {
+            StreamIo::Plain(s) => Pin::new(s).poll_flush(cx),
+            StreamIo::Tls(s) => Pin::new(s.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().io {
+            StreamIo::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            StreamIo::Tls(s) => Pin::new(s.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use std::{
+    collections::HashMap,
+    sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex, Weak},
+    time::{Duration, Instant},
+};
+
+use rand::{distr::Alphanumeric, Rng};
+use reqwest::Url;
+use tokio::{net::UdpSocket, sync::{oneshot, OnceCell}};
+
+use crate::{
+    config::RunConfig,
+    error_code::ErrorCode,
+    events::Bus,
+    stats::{self, format_ms, RequestRecord},
+    transport::TimeoutKind,
+    utils::RequestTemplate,
+    AppState,
+};
+
+// 패킷 앞의 순번 (에코를 보낸 패킷과 짝지음)
+const SEQ_BYTES: usize = 8;
+// IPv4 UDP 페이로드 최대 크기
+pub const MAX_PACKET_BYTES: usize = 65507;
+
+// 실행 하나가 공유하는 소켓과 에코를 기다리는 패킷들
+// Envoy UDP proxy 는 출발 주소마다 세션을 만들므로 소켓 하나로 보내야 한 세션을 시험한다
+struct Session {
+    socket: UdpSocket,
+    pending: Mutex<HashMap<u64, oneshot::Sender<(Instant, bool)>>>,
+    // 기다림이 끝난 뒤 도착한 에코, 보낸 적 없는 순번의 패킷
+    late: AtomicUsize,
+    unknown: AtomicUsize,
+}
+
+// udp://host:port 대상 (Envoy UDP proxy 리스너)
+// 반복마다 순번을 붙인 패킷을 하나 보내고, 에코를 기대하면 같은 내용이 돌아오기를 기다린다
+pub struct UdpTarget {
+    addr: String,
+    // 순번 뒤에 채우는 내용 (모든 패킷이 같음)
+    fill: Vec<u8>,
+    echo: bool,
+    session: OnceCell<Arc<Session>>,
+}
+
+impl UdpTarget {
+    pub fn new(url: &Url, config: &RunConfig, rng: &mut impl Rng) -> Result<Self, String> {
+        let host = url.host_str().ok_or_else(|| format!("UDP target '{}' has no host", url))?;
+        let port = url.port().ok_or_else(|| format!("UDP target '{}' needs a port", url))?;
+        if !config.compare_url.is_empty() || !config.routes_file.trim().is_empty() {
+            return Err(String::from("UDP mode cannot be combined with A/B compare or a routes file"));
+        }
+        let fill = rng.sample_iter(&Alphanumeric).take(config.udp_packet_bytes - SEQ_BYTES).collect();
+        Ok(Self { addr: format!("{}:{}", host, port), fill, echo: config.udp_echo, session: OnceCell::new() })
+    }
+
+    fn packet_bytes(&self) -> usize {
+        SEQ_BYTES + self.fill.len()
+    }
+
+    // 첫 패킷을 보낼 때 소켓을 열고 에코 수신 작업을 띄운다
+    async fn session(&self) -> Result<&Arc<Session>, String> {
+        self.session
+            .get_or_try_init(|| async {
+                let remote = tokio::net::lookup_host(&self.addr)
+                    .await
+                    .map_err(|e| format!("DNS lookup failed: {}", e))?
+                    .next()
+                    .ok_or_else(|| format!("no address for {}", self.addr))?;
+                let local = if remote.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = UdpSocket::bind(local).await.map_err(|e| format!("bind failed: {}", e))?;
+                socket.connect(remote).await.map_err(|e| format!("connect to {} failed: {}", remote, e))?;
+                let session = Arc::new(Session { socket, pending: Mutex::new(HashMap::new()), late: AtomicUsize::new(0), unknown: AtomicUsize::new(0) });
+                tokio::spawn(receive(Arc::downgrade(&session), self.fill.clone()));
+                Ok(session)
+            })
+            .await
+    }
+}
+
+// 에코를 순번으로 찾아 기다리는 패킷에 넘긴다. 실행이 끝나 세션이 사라지면 종료
+async fn receive(session: Weak<Session>, fill: Vec<u8>) {
+    let mut buf = vec![0u8; MAX_PACKET_BYTES];
+    loop {
+        let Some(session) = session.upgrade() else {
+            return;
+        };
+        // 주기적으로 깨어나 세션이 남아 있는지 확인
+        let Ok(received) = tokio::time::timeout(Duration::from_secs(1), session.socket.recv(&mut buf)).await else {
+            continue;
+        };
+        // 닫힌 포트로 보낸 뒤의 ICMP 오류도 recv 오류로 온다 (보낸 쪽은 타임아웃으로 집계)
+        let Ok(len) = received else {
+            continue;
+        };
+        let now = Instant::now();
+        let Some(seq) = buf.get(..SEQ_BYTES).filter(|_| len >= SEQ_BYTES).map(|b| u64::from_be_bytes(b.try_into().unwrap())) else {
+            session.unknown.fetch_add(1, Ordering::Relaxed);
+            continue;
+        };
+        let intact = buf[SEQ_BYTES..len] == fill[..];
+        match session.pending.lock().unwrap().remove(&seq) {
+            Some(waiter) => {
+                let _ = waiter.send((now, intact));
+            }
+            None => {
+                session.late.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+// 패킷 하나의 결과 기록과 로그 줄
+async fn exchange(template: &RequestTemplate, target: &UdpTarget, seq: u64, id: String, scheduled_at: Instant) -> (RequestRecord, String) {
+    let sent_at = Instant::now();
+    let mut record = RequestRecord::new(id, sent_at);
+    record.queue_delay = sent_at.duration_since(scheduled_at);
+    let size = target.packet_bytes() as u64;
+
+    let result = async {
+        let session = target.session().await.map_err(|e| (e, None, ErrorCode::Connect))?;
+        record.remote = session.socket.peer_addr().ok();
+        let waiter = target.echo.then(|| {
+            let (tx, rx) = oneshot::channel();
+            session.pending.lock().unwrap().insert(seq, tx);
+            rx
+        });
+        let mut packet = Vec::with_capacity(size as usize);
+        packet.extend_from_slice(&seq.to_be_bytes());
+        packet.extend_from_slice(&target.fill);
+        if let Err(e) = session.socket.send(&packet).await {
+            session.pending.lock().unwrap().remove(&seq);
+            return Err((format!("send failed: {}", e), None, ErrorCode::Send));
+        }
+        record.transferred = Some((size, 0));
+        let Some(waiter) = waiter else {
+            return Ok(None);
+        };
+        match tokio::time::timeout(template.request_timeout, waiter).await {
+            Ok(Ok((at, true))) => {
+                record.transferred = Some((size, size));
+                Ok(Some(at.duration_since(sent_at)))
+            }
+            Ok(Ok((_, false))) => {
+                record.transferred = Some((size, size));
+                Err((String::from("echo mismatch"), None, ErrorCode::Assert))
+            }
+            _ => {
+                session.pending.lock().unwrap().remove(&seq);
+                Err((format!("no echo within {} (lost)", format_ms(template.request_timeout)), Some(TimeoutKind::Request), ErrorCode::Timeout))
+            }
+        }
+    }
+    .await;
+
+    record.latency = sent_at.elapsed();
+    record.transferred.get_or_insert((0, 0));
+    let mut log = match result {
+        Ok(rtt) => {
+            record.success = true;
+            match rtt {
+                Some(rtt) => format!("Datagram {} Succeded: {}B echoed | rtt {}", record.id, size, format_ms(rtt)),
+                None => format!("Datagram {} Succeded: {}B sent", record.id, size),
+            }
+        }
+        Err((reason, timeout, code)) => {
+            record.timeout = timeout;
+            record.error = Some(code);
+            format!("Datagram {} Failed: {} [{}]", record.id, reason, code.as_str())
+        }
+    };
+    if let Some(remote) = record.remote {
+        log.push_str(&format!(" [{}]", remote));
+    }
+    log.push_str(&format!(" (queue {})", format_ms(record.queue_delay)));
+    (record, log)
+}
+
+// 작업 스레드가 반복마다 띄우는 패킷 전송 (반복 번호가 순번)
+pub async fn send(template: Arc<RequestTemplate>, index: usize, id: String, scheduled_at: Instant, state: Arc<Mutex<AppState>>, bus: Bus) {
+    let Some(target) = &template.udp else {
+        return;
+    };
+    let (record, log) = exchange(&template, target, index as u64, id, scheduled_at).await;
+    let events = {
+        let mut app_state = state.lock().unwrap();
+        app_state.add_log(&log);
+        app_state.add_record(record)
+    };
+    bus.publish_all(events);
+}
+
+// 실행 종료 보고: 손실률, RTT 백분위, 늦게 온 에코
+pub fn summarize(target: &UdpTarget, records: &[RequestRecord], percentiles: &[f64]) -> Vec<String> {
+    let records: Vec<&RequestRecord> = records.iter().filter(|r| r.transferred.is_some()).collect();
+    if records.is_empty() {
+        return Vec::new();
+    }
+    let sent = records.iter().filter(|r| r.transferred.is_some_and(|(s, _)| s > 0)).count();
+    let (bytes_sent, bytes_received) = records.iter().filter_map(|r| r.transferred).fold((0, 0), |(s, r), (a, b)| (s + a, r + b));
+    let errors = records.iter().filter(|r| matches!(r.error, Some(ErrorCode::Connect | ErrorCode::Send))).count();
+    let mut line = format!(
+        "UDP: {} packets sent ({} send errors) | {}B each, sent {}, received {}",
+        sent,
+        errors,
+        target.packet_bytes(),
+        stats::format_bytes(bytes_sent),
+        stats::format_bytes(bytes_received)
+    );
+    if !target.echo {
+        line.push_str(" | no echo expected");
+        return vec![line];
+    }
+
+    let lost = records.iter().filter(|r| r.error == Some(ErrorCode::Timeout)).count();
+    let mismatched = records.iter().filter(|r| r.error == Some(ErrorCode::Assert)).count();
+    let (late, unknown) = match target.session.get() {
+        Some(session) => (session.late.load(Ordering::Relaxed), session.unknown.load(Ordering::Relaxed)),
+        None => (0, 0),
+    };
+    line.push_str(&format!(
+        " | lost {} ({:.2}%), echo mismatch {}, late echoes {}, unknown packets {}",
+        lost,
+        lost as f64 * 100.0 / sent.max(1) as f64,
+        mismatched,
+        late,
+        unknown
+    ));
+    let mut rtt: Vec<Duration> = records.iter().filter(|r| r.success).map(|r| r.latency).collect();
+    rtt.sort();
+    vec![line, format!("UDP RTT: {} max {}", stats::format_percentiles(&rtt, percentiles), format_ms(rtt.last().copied().unwrap_or_default()))]
+}
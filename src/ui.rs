@@ -1,40 +1,115 @@
+use std::time::Duration;
+
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Position},
+    layout::{Constraint, Direction, Flex, Layout, Position, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Tabs},
+    widgets::{Bar, BarChart, BarGroup, Block, Borders, Clear, List, ListItem, ListState, Paragraph, Sparkline, Tabs, Wrap},
     Frame
 };
 
-use crate::{App, InputMode};
+use crate::{als, compare::SideStats, config::RunConfig, distribution, headers, inspector::Inspector, logview::{self, LogFilter}, metrics::LiveMetrics, preflight::CheckResult, routes::RouteResult, stats::{self, format_ms, UpstreamStats}, wizard::Wizard, App, InputMode, LoadModel};
+
+// 입력창마다 빈 칸 안내와 단위/범위 안내 (주소, 지연시간, 헤더 크기, 반복 횟수, 동시 요청 수)
+const INPUT_HINTS: [(&str, &str); 5] = [
+    ("http://127.0.0.1:10000/path (https://, tcp://, udp:// too)", ""),
+    ("100", "ms between requests, 0 = back-to-back, ←/→ RPS"),
+    ("1", "KB of random payload, Envoy rejects > 60 by default"),
+    ("1", "requests, ignored when duration_s is set"),
+    ("0", "in flight, 0 = unbounded"),
+];
+// 지연 입력창을 Target RPS 모델로 바꿨을 때
+const RATE_HINT: (&str, &str) = ("100", "req/s regardless of latency, ramp_up_s option, ←/→ delay");
 
 fn input_widget_builder<'a>(app: &'a mut App, index: usize, title: String, mode: InputMode) -> Paragraph<'a> {
     let text = if index == 0 {app.dst_url.as_str()} 
+                else if index == 1 && app.load_model == LoadModel::Rate {app.rps.as_str()}
                 else if index == 1 {app.delay_ms.as_str()} 
                 else if index == 2 {app.header_size_kb.as_str()}
-                else {app.iteration.as_str()};
+                else if index == 3 {app.iteration.as_str()}
+                else {app.concurrency.as_str()};
 
     let delay_style = if app.focused_item == index {
         Style::default().fg(Color::Yellow)
     } else {
         Style::default()
     };
+
+    // 실제로 쓰일 값이 입력과 다르면 노란색, 주소를 읽을 수 없으면 빨간색, 아니면 단위 안내
+    let (placeholder, unit) = if index == 1 && app.load_model == LoadModel::Rate { RATE_HINT } else { INPUT_HINTS[index] };
+    let hint = match app.fallback(index) {
+        Some(value) => Span::styled(format!(" using {} ", value), Style::default().fg(Color::Yellow)),
+        None if index == 0 && !text.is_empty() && reqwest::Url::parse(text).is_err() => Span::styled(" invalid URL ", Style::default().fg(Color::Red)),
+        // 포커스가 있으면 단위 대신 조정 키 안내
+        None if index > 0 && app.focused_item == index && app.input_mode != mode => {
+            let step = app.step_size(index);
+            Span::styled(format!(" -/= ±{}, Shift ±{} ", step, step * 10), Style::default().fg(Color::DarkGray))
+        }
+        None if unit.is_empty() => Span::raw(""),
+        None => Span::styled(format!(" {} ", unit), Style::default().fg(Color::DarkGray)),
+    };
     
     let delay_block = Block::default()
         .borders(Borders::ALL)
-        .title(title)
+        .title(title + marker(app, index, app.input_mode == mode))
+        .title_bottom(Line::from(hint).right_aligned())
         .border_style(delay_style);
-    
-    let delay_text = Paragraph::new(text)
+
+    if text.is_empty() && app.input_mode != mode {
+        return Paragraph::new(placeholder).block(delay_block).style(Style::default().fg(Color::DarkGray));
+    }
+    Paragraph::new(text)
         .block(delay_block)
         .style(
             if app.input_mode == mode { Style::default().fg(Color::Yellow) } else { Style::default() }
-        );
+        )
+}
+
+// 색 없는 화면에서 포커스와 편집 상태를 제목 글자로 알린다
+fn marker(app: &App, index: usize, editing: bool) -> &'static str {
+    match (app.plain, editing, app.focused_item == index) {
+        (false, _, _) => "",
+        (true, true, _) => " [EDITING]",
+        (true, false, true) => " [FOCUSED]",
+        (true, false, false) => "",
+    }
+}
 
-    return delay_text;
+// 색 없는 화면: 색을 모두 지우고 배경색으로 구분하던 칸은 반전으로 남긴다
+fn strip_colors(f: &mut Frame) {
+    for cell in f.buffer_mut().content.iter_mut() {
+        if cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
 }
 
-pub fn ui(f: &mut Frame, app: &mut App) {
+// 로그 영역 오른쪽 패널 종류
+enum SidePanel {
+    Metrics,
+    Upstreams,
+    Routes,
+    Attribution,
+    Ping,
+    Compare,
+}
+
+pub fn ui(f: &mut Frame, app: &mut App, tabs: &[String], active: usize) {
+    // 워크스페이스 탭이 둘 이상이면 맨 위에 탭 목록 표시
+    let mut area = f.area();
+    if tabs.len() > 1 {
+        let [bar, rest] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+        // 색 없는 화면이면 보고 있는 탭을 글자로 표시
+        let titles = tabs.iter().enumerate().map(|(i, title)| if app.plain && i == active { format!("[ACTIVE] {}", title) } else { title.clone() });
+        let workspaces = Tabs::new(titles)
+            .select(active)
+            .highlight_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD));
+        f.render_widget(workspaces, bar);
+        area = rest;
+    }
+
     // 메인 레이아웃 분할 (상단 입력 영역, 하단 로그 영역)
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -42,7 +117,7 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             Constraint::Length(12), // 입력 영역
             Constraint::Min(3),   // 로그 영역
         ])
-        .split(f.area());
+        .split(area);
 
     // 입력 영역 내부 레이아웃
     let input_chunks = Layout::default()
@@ -50,13 +125,17 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         .constraints([
             Constraint::Length(3), // 주소 입력창
             Constraint::Length(3), // 지연시간, 헤더 크기 입력
-            Constraint::Length(3), // 반복 횟수, HTTP 프로토콜 선택
+            Constraint::Length(3), // 반복 횟수, 동시 요청 수, 요청 값 위치 선택
             Constraint::Length(3), // 실행 버튼
         ])
         .split(chunks[0]);
     
     // 주소입력 행
-    let dst_url_text = input_widget_builder(app, 0, "Destination URL".to_owned(), InputMode::EditingDstUrl);
+    let dst_url_title = match &app.profile {
+        Some(profile) => format!("Destination URL (profile {})", profile),
+        None => "Destination URL".to_owned(),
+    };
+    let dst_url_text = input_widget_builder(app, 0, dst_url_title, InputMode::EditingDstUrl);
     f.render_widget(dst_url_text, input_chunks[0]);
 
     // 첫 번째 행 (지연시간, 헤더 크기 입력)
@@ -68,16 +147,18 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         ])
         .split(input_chunks[1]);
 
-    // 두번째 행 (반복 횟수, 프로토콜)
+    // 두번째 행 (반복 횟수, 동시 요청 수, 프로토콜)
     let third_row_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Percentage(50),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
             Constraint::Percentage(50)
         ]).split(input_chunks[2]);
 
     // 지연시간 입력 필드
-    let delay_text = input_widget_builder(app, 1, "Delay (ms)".to_owned(), InputMode::EditingDelay);
+    let load_title = if app.load_model == LoadModel::Rate { "Target RPS" } else { "Delay (ms)" };
+    let delay_text = input_widget_builder(app, 1, load_title.to_owned(), InputMode::EditingDelay);
     f.render_widget(delay_text, second_row_chunks[0]);
 
     // 헤더 크기 입력 필드
@@ -88,8 +169,12 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     let iter_text = input_widget_builder(app, 3, "Iteration".to_owned(), InputMode::EditingIteration);
     f.render_widget(iter_text, third_row_chunks[0]);
 
-    // HTTP 프로토콜 선택
-    let protocol_style = if app.focused_item == 4 {
+    // 동시 요청 수 입력 필드
+    let concurrency_text = input_widget_builder(app, 4, "Concurrency".to_owned(), InputMode::EditingConcurrency);
+    f.render_widget(concurrency_text, third_row_chunks[1]);
+
+    // 요청 값 위치 선택
+    let protocol_style = if app.focused_item == 5 {
         Style::default().fg(Color::Yellow)
     } else {
         Style::default()
@@ -100,7 +185,9 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         .iter()
         .enumerate()
         .map(|(i, p)| {
-            if i == app.protocol_index {
+            if app.plain {
+                Line::from(format!("[{}] {}", if i == app.protocol_index { "x" } else { " " }, p))
+            } else if i == app.protocol_index {
                 Line::from(vec![Span::styled(
                     *p,
                     Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
@@ -114,7 +201,7 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     let tabs = Tabs::new(protocols)
         .block(
             Block::default()
-                .title("WhereToTest")
+                .title(format!("WhereToTest{}", marker(app, 5, false)))
                 .borders(Borders::ALL)
                 .border_style(protocol_style),
         )
@@ -122,37 +209,126 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         .style(Style::default())
         .highlight_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD));
     
-    f.render_widget(tabs, third_row_chunks[1]);
+    f.render_widget(tabs, third_row_chunks[2]);
 
     // 실행 버튼
-    let button_style = if app.focused_item == 5 {
+    let button_style = if app.focused_item == 6 {
         Style::default().fg(Color::Yellow)
     } else {
         Style::default()
     };
 
-    let button_text = if app.running { "Stop" } else { "Start" };
+    let button_text = match (app.running, app.plain) {
+        (true, false) => "Stop",
+        (true, true) => "Stop [RUNNING]",
+        (false, _) => "Start",
+    };
     let button_color = if app.running { Color::Red } else { Color::Green };
     
+    // 실행 버튼 테두리를 상태 표시줄로 써서 마지막 경보 표시
+    // 경보가 없으면 기본값으로 바뀌는 입력 경고
+    let warning = app.input_warnings().into_iter().next();
+    let alert = match (&app.alert, warning) {
+        (Some(alert), _) if app.plain => Some(Span::raw(format!(" Alert: {} ", alert))),
+        (Some(alert), _) => Some(Span::styled(format!(" {} ", alert), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))),
+        (None, Some(warning)) => Some(Span::styled(format!(" Warning: {} ", warning), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+        (None, None) => None,
+    };
+    // 관찰 모드에선 붙은 곳과 연결 상태를 대신 표시
+    let alert = match &app.observing {
+        Some(observing) if app.plain => Some(Span::raw(format!(" {} ", observing))),
+        Some(observing) => Some(Span::styled(format!(" {} ", observing), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))),
+        None => alert,
+    };
+    // 좁은 터미널과 화면 낭독기를 위해 색 없는 화면에선 자주 쓰는 키만 안내
+    let keys = if app.observing.is_some() {
+        " j/k: scroll logs | f: filter | q: quit "
+    } else if app.plain {
+        " o: options | H: headers | :: command | t/[ ]: tabs | q: quit "
+    } else {
+        " o: options | H: headers | :: command | p/P: profiles | r: replay | e: export upstreams | x: export results | c: clear | t/w/[ ]: tabs | q: quit "
+    };
+    let mut title = vec![alert.unwrap_or_default()];
+    if !marker(app, 6, false).is_empty() {
+        title.push(Span::raw(marker(app, 6, false)));
+    }
     let button = Paragraph::new(button_text)
         .style(Style::default().fg(button_color).add_modifier(Modifier::BOLD))
         .alignment(ratatui::layout::Alignment::Center)
         .block(
             Block::default()
                 .borders(Borders::ALL)
+                .title(Line::from(title))
+                .title_bottom(Line::from(keys).right_aligned())
                 .border_style(button_style),
         );
     
     f.render_widget(button, input_chunks[3]);
 
+    // 표시할 데이터가 있는 패널만 로그 영역 오른쪽에 세로로 나눠 표시
+    let mut side_panels = Vec::new();
+    if app.metrics.is_some() {
+        side_panels.push(SidePanel::Metrics);
+    }
+    if app.compare.is_some() {
+        side_panels.push(SidePanel::Compare);
+    }
+    if !app.upstream_stats.is_empty() {
+        side_panels.push(SidePanel::Upstreams);
+    }
+    if !app.route_results.is_empty() {
+        side_panels.push(SidePanel::Routes);
+    }
+    if !app.attribution.is_empty() {
+        side_panels.push(SidePanel::Attribution);
+    }
+    if !app.pings.0.is_empty() {
+        side_panels.push(SidePanel::Ping);
+    }
+    let log_area = if app.plain {
+        // 색 없는 화면은 차트 대신 지표 한 줄만 로그 위에 둔다
+        match &app.metrics {
+            Some(metrics) => {
+                let [metrics_area, log_area] = Layout::vertical([Constraint::Length(1), Constraint::Min(3)]).areas(chunks[1]);
+                f.render_widget(Line::from(metrics_line(metrics)), metrics_area);
+                log_area
+            }
+            None => chunks[1],
+        }
+    } else if side_panels.is_empty() {
+        chunks[1]
+    } else {
+        let [log_area, side_area] = Layout::horizontal([Constraint::Min(20), Constraint::Length(56)]).areas(chunks[1]);
+        let areas = Layout::vertical(side_panels.iter().map(|_| Constraint::Ratio(1, side_panels.len() as u32))).split(side_area);
+        for (panel, area) in side_panels.iter().zip(areas.iter()) {
+            match panel {
+                SidePanel::Metrics => {
+                    if let Some(metrics) = &app.metrics {
+                        metrics_panel(f, *area, metrics);
+                    }
+                }
+                SidePanel::Upstreams => upstream_panel(f, *area, &app.upstream_stats),
+                SidePanel::Routes => route_panel(f, *area, &app.route_results),
+                SidePanel::Attribution => attribution_panel(f, *area, &app.attribution),
+                SidePanel::Ping => ping_panel(f, *area, &app.pings.0, &app.pings.1),
+                SidePanel::Compare => {
+                    if let Some((a, b)) = &app.compare {
+                        compare_panel(f, *area, a, b);
+                    }
+                }
+            }
+        }
+        log_area
+    };
+
     // 로그 영역
-    let log_style = if app.focused_item == 6 {
+    let log_style = if app.focused_item == 7 {
         Style::default().fg(Color::Yellow)
     } else {
         Style::default()
     };
     
-    let visible_height = chunks[1].height as usize - 2; // 테두리 제외 높이
+    let visible_height = log_area.height as usize - 2; // 테두리 제외 높이
     
     // 표시할 로그 항목 계산
     let logs_count = app.logs.len();
@@ -164,22 +340,43 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     };
     
     let end_index = logs_count;
+    // 로그 영역에 포커스가 있으면 Enter 로 열 줄을 강조
+    let selected = logs_count.checked_sub(app.log_scroll + 1).filter(|_| app.focused_item == 7);
     
     let logs: Vec<ListItem> = app
         .logs
         .iter()
+        .enumerate()
         .skip(start_index)
         .take(end_index - start_index)
-        .map(|log| {
-            ListItem::new(Line::from(log.to_owned()))
+        .map(|(i, log)| {
+            let style = if Some(i) == selected { Style::default().add_modifier(Modifier::REVERSED) } else { Style::default() };
+            let mut line = highlight(log, &app.log_search);
+            if app.plain && Some(i) == selected {
+                line.spans.insert(0, Span::raw("> "));
+            }
+            ListItem::new(line).style(style)
         })
         .collect();
 
-    let log_title = if app.focused_item == 6 {
-        format!("Log [{}/{}]", app.log_scroll, logs_count.saturating_sub(1).max(0))
-    } else {
-        "Log".to_string()
-    };
+    // 검색어를 맨 앞에 두어 입력 중 커서 위치를 바로 계산한다
+    let mut log_title = String::from("Log");
+    if !app.log_search.is_empty() || app.input_mode == InputMode::SearchingLog {
+        log_title.push_str(&format!(" /{}", app.log_search));
+    }
+    if app.log_filter != LogFilter::All {
+        log_title.push_str(&format!(" ({})", app.log_filter.label()));
+    }
+    if let Some(replay) = &app.replay {
+        log_title = replay.describe();
+    } else if app.input_mode == InputMode::SearchingLog {
+        log_title.push_str(&format!(" [{} match(es)] Enter: keep | Esc: clear", logs_count));
+    } else if app.observing.is_some() {
+        log_title.push_str(&format!(" [{}/{}] f: {}", app.log_scroll, logs_count.saturating_sub(1), app.log_filter.next().label()));
+    } else if app.focused_item == 7 {
+        log_title.push_str(&format!(" [{}/{}] Enter: inspect | /: search | f: {}", app.log_scroll, logs_count.saturating_sub(1), app.log_filter.next().label()));
+    }
+    log_title.push_str(marker(app, 7, app.input_mode == InputMode::SearchingLog));
 
     let logs_list = List::new(logs)
         .block(Block::default()
@@ -188,7 +385,10 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             .border_style(log_style))
         .style(Style::default());
     
-    f.render_widget(logs_list, chunks[1]);
+    f.render_widget(logs_list, log_area);
+    if app.input_mode == InputMode::SearchingLog {
+        f.set_cursor_position(Position { x: log_area.x + 1 + "Log /".len() as u16 + app.log_search.len() as u16, y: log_area.y });
+    }
 
     // 커서 위치 (입력 모드일 때만)
     match app.input_mode {
@@ -200,7 +400,7 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         }
         InputMode::EditingDelay => {
             f.set_cursor_position(Position {
-                x: second_row_chunks[0].x + app.delay_ms.len() as u16 + 1,
+                x: second_row_chunks[0].x + if app.load_model == LoadModel::Rate { app.rps.len() } else { app.delay_ms.len() } as u16 + 1,
                 y: second_row_chunks[0].y + 1,
             });
         }
@@ -216,6 +416,545 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                 y: third_row_chunks[0].y + 1,
             });
         }
+        InputMode::EditingConcurrency => {
+            f.set_cursor_position(Position {
+                x: third_row_chunks[1].x + app.concurrency.len() as u16 + 1,
+                y: third_row_chunks[1].y + 1,
+            });
+        }
         _ => {}
     }
-}
\ No newline at end of file
+
+    if app.show_options {
+        options_popup(f, app);
+    }
+    if let Some(editor) = &app.header_editor {
+        headers_popup(f, editor, &app.custom_headers, app.plain);
+    }
+    if let Some(input) = &app.command {
+        command_bar(f, chunks[1], input);
+    }
+    if let Some(inspector) = &app.inspector {
+        inspector_popup(f, inspector);
+    }
+    if let Some(results) = &app.preflight {
+        preflight_popup(f, results.as_deref());
+    }
+    if let Some(wizard) = &app.wizard {
+        wizard_popup(f, wizard);
+    }
+    if app.plain {
+        strip_colors(f);
+    }
+}
+
+// 검색어와 맞는 부분을 강조한 로그 줄
+fn highlight<'a>(log: &'a str, pattern: &str) -> Line<'a> {
+    let mut spans = Vec::new();
+    let mut at = 0;
+    for (start, end) in logview::matches(log, pattern) {
+        spans.push(Span::raw(&log[at..start]));
+        spans.push(Span::styled(&log[start..end], Style::default().fg(Color::Black).bg(Color::Yellow)));
+        at = end;
+    }
+    spans.push(Span::raw(&log[at..]));
+    Line::from(spans)
+}
+
+// 화면 중앙에 팝업 영역 계산
+fn centered_rect(area: Rect, width_percent: u16, height: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(area);
+    let [area] = Layout::horizontal([Constraint::Percentage(width_percent)]).flex(Flex::Center).areas(area);
+    area
+}
+
+// 색 없는 화면의 목록 창에서 고른 줄 앞에 붙이는 표시 (다른 줄도 같은 폭만큼 띄운다)
+fn select_marker(plain: bool, selected: bool) -> &'static str {
+    match (plain, selected) {
+        (false, _) => "",
+        (true, true) => "> ",
+        (true, false) => "  ",
+    }
+}
+
+// 고급 옵션 창
+fn options_popup(f: &mut Frame, app: &App) {
+    let area = centered_rect(f.area(), 70, app.options.len() as u16 + 2);
+    let label_width = app.options.iter().map(|o| o.label.len()).max().unwrap_or(0);
+
+    let items: Vec<ListItem> = app
+        .options
+        .iter()
+        .enumerate()
+        .map(|(i, option)| {
+            let style = if i != app.option_index {
+                Style::default()
+            } else if app.input_mode == InputMode::EditingOption {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+            };
+            let mut spans = vec![
+                Span::styled(format!("{}{:width$} : ", select_marker(app.plain, i == app.option_index), option.label, width = label_width), style),
+                Span::styled(option.value.as_str(), style),
+            ];
+            // 실행할 때 거절될 값은 이유와 함께 빨간색으로
+            if let Err(e) = RunConfig::default().apply_option(option.key, &option.value) {
+                let reason = e.strip_prefix(&format!("option '{}': ", option.key)).unwrap_or(&e).to_owned();
+                spans[1] = Span::styled(option.value.as_str(), Style::default().fg(Color::Red));
+                spans.push(Span::styled(format!("  ({})", reason), Style::default().fg(Color::Red)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(if app.input_mode == InputMode::EditingOption && app.plain { "Options [EDITING]" } else { "Options" })
+            .title_bottom(Line::from(" Enter: edit | Space: on/off | Esc: close ").right_aligned())
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+
+    // 옵션이 화면보다 많으면 선택한 옵션이 보이도록 스크롤
+    let mut list_state = ListState::default().with_selected(Some(app.option_index));
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut list_state);
+
+    if app.input_mode == InputMode::EditingOption {
+        let option = &app.options[app.option_index];
+        f.set_cursor_position(Position {
+            x: area.x + 1 + select_marker(app.plain, true).len() as u16 + label_width as u16 + 3 + option.value.len() as u16,
+            y: area.y + 1 + (app.option_index - list_state.offset()) as u16,
+        });
+    }
+}
+
+// 사용자 헤더 편집 창 (잘못된 줄은 이유와 함께 빨간색으로)
+fn headers_popup(f: &mut Frame, editor: &headers::Editor, lines: &[String], plain: bool) {
+    let area = centered_rect(f.area(), 70, lines.len().max(1) as u16 + 2);
+
+    let items: Vec<ListItem> = if lines.is_empty() {
+        vec![ListItem::new(Span::styled("No custom headers (a: add a 'name: value' line)", Style::default().fg(Color::DarkGray)))]
+    } else {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let style = if i != editor.index {
+                    Style::default()
+                } else if editor.editing {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+                };
+                let mut spans = vec![Span::raw(select_marker(plain, i == editor.index)), Span::styled(line.as_str(), style)];
+                if !(editor.editing && i == editor.index) && let Err(e) = headers::parse(line) {
+                    spans[1] = Span::styled(line.as_str(), Style::default().fg(Color::Red));
+                    spans.push(Span::styled(format!("  ({})", e), Style::default().fg(Color::Red)));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    };
+
+    let hint = if editor.editing { " Enter: done | Esc: done " } else { " a: add | Enter: edit | d: delete | Esc: close " };
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(if plain && editor.editing { "Custom headers [EDITING]" } else { "Custom headers" })
+            .title_bottom(Line::from(hint).right_aligned())
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+
+    let mut list_state = ListState::default().with_selected((!lines.is_empty()).then_some(editor.index));
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut list_state);
+
+    if editor.editing {
+        f.set_cursor_position(Position {
+            x: area.x + 1 + select_marker(plain, true).len() as u16 + lines[editor.index].len() as u16,
+            y: area.y + 1 + (editor.index - list_state.offset()) as u16,
+        });
+    }
+}
+
+// 첫 실행 안내 창 (질문, 입력, 잘못된 답의 이유)
+fn wizard_popup(f: &mut Frame, wizard: &Wizard) {
+    let area = centered_rect(f.area(), 70, 7);
+    let mut lines = vec![
+        Line::from(Span::styled(format!("Step {}/{}: {}", wizard.step + 1, wizard.steps(), wizard.prompt()), Style::default().add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(format!("> {}", wizard.input)),
+    ];
+    if let Some(error) = &wizard.error {
+        lines.push(Line::from(Span::styled(error.as_str(), Style::default().fg(Color::Red))));
+    }
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Setup")
+            .title_bottom(Line::from(" Enter: next | Esc: skip setup ").right_aligned())
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+    f.set_cursor_position(Position { x: area.x + 3 + wizard.input.len() as u16, y: area.y + 3 });
+}
+
+// 사전 점검 결과 창
+fn preflight_popup(f: &mut Frame, results: Option<&[CheckResult]>) {
+    // 프로토콜 불일치 프로브를 켜면 줄이 늘어난다
+    let area = centered_rect(f.area(), 70, results.map_or(0, |r| r.len() as u16 + 2).max(8));
+
+    let lines: Vec<Line> = match results {
+        None => vec![Line::from("Running pre-flight checks...")],
+        Some(results) => results
+            .iter()
+            .map(|r| {
+                let (mark, color) = match r.ok {
+                    Some(true) => ("OK  ", Color::Green),
+                    Some(false) => ("FAIL", Color::Red),
+                    None => ("SKIP", Color::DarkGray),
+                };
+                Line::from(vec![
+                    Span::styled(format!("[{}] ", mark), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                    Span::raw(format!("{:14} {:>10}  {}", r.name, format_ms(r.elapsed), r.detail)),
+                ])
+            })
+            .collect(),
+    };
+
+    let hint = if results.is_some() { " Enter: start run | Esc: cancel " } else { " Esc: cancel " };
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Pre-flight check")
+            .title_bottom(Line::from(hint).right_aligned())
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+// 요청 하나의 상세와 단계별 시간 워터폴. 다시 보낸 요청이 있으면 오른쪽에 나란히 표시
+fn inspector_popup(f: &mut Frame, inspector: &Inspector) {
+    // HTTP/2 SETTINGS 를 받았으면 두 줄 더
+    let settings_height = if inspector.settings.is_some() { 2 } else { 0 };
+    // Envoy 접근 로그를 받았으면 한 줄 더
+    // 응답 플래그가 있으면 플래그마다 뜻을 한 줄씩
+    let proxy_height = inspector.proxy.as_ref().map_or(0, |proxy| 1 + proxy.flags.len() as u16);
+    let area = centered_rect(f.area(), 80, 16 + settings_height + proxy_height);
+    let record = &inspector.record;
+    let title = match record.side {
+        Some(side) => format!("Request {} [{}]", record.id, side.as_str()),
+        None => format!("Request {}", record.id),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_bottom(Line::from(" r: replay | Esc: close ").right_aligned())
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let [log_area, settings_area, proxy_area, body] =
+        Layout::vertical([Constraint::Length(2), Constraint::Length(settings_height), Constraint::Length(proxy_height), Constraint::Min(0)]).areas(inner);
+    let log = Paragraph::new(Span::styled(inspector.log.as_str(), Style::default().fg(Color::DarkGray))).wrap(Wrap { trim: false });
+    f.render_widget(log, log_area);
+    if let Some(settings) = &inspector.settings {
+        let line = match settings {
+            Ok(settings) => Line::from(format!("SETTINGS {}", settings)),
+            Err(e) => Line::styled(format!("SETTINGS not captured: {}", e), Style::default().fg(Color::Red)),
+        };
+        f.render_widget(Paragraph::new(line).wrap(Wrap { trim: false }), settings_area);
+    }
+    if let Some(proxy) = &inspector.proxy {
+        let color = if proxy.flags.is_empty() { Color::Reset } else { Color::Yellow };
+        let mut lines = vec![Line::styled(proxy.describe(), Style::default().fg(color))];
+        for flag in &proxy.flags {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:5} ", flag), Style::default().fg(Color::Yellow)),
+                Span::raw(als::explain(flag).unwrap_or("unknown response flag")),
+            ]));
+        }
+        f.render_widget(Paragraph::new(lines), proxy_area);
+    }
+
+    match &inspector.resend {
+        None => f.render_widget(Paragraph::new(request_lines(record, body.width)), body),
+        Some(resend) => {
+            let [left, right] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).spacing(2).areas(body);
+            let mut original = vec![Line::styled("Original", Style::default().add_modifier(Modifier::BOLD))];
+            original.extend(request_lines(record, left.width));
+            let mut replayed = vec![Line::styled("Replay", Style::default().add_modifier(Modifier::BOLD))];
+            match resend {
+                Some(result) => replayed.extend(request_lines(result, right.width)),
+                None => replayed.push(Line::from("Sending...")),
+            }
+            f.render_widget(Paragraph::new(original), left);
+            f.render_widget(Paragraph::new(replayed), right);
+        }
+    }
+}
+
+// 인스펙터에 표시할 요청 결과와 워터폴 줄
+fn request_lines(record: &stats::RequestRecord, width: u16) -> Vec<Line<'static>> {
+    const PHASE_COLORS: [Color; 6] = [Color::Cyan, Color::Yellow, Color::Magenta, Color::Blue, Color::Green, Color::LightRed];
+    // 단계 이름과 시간 표시 뒤에 남는 폭을 막대로 쓴다
+    let bar_width = (width as usize).saturating_sub(22 + 2).max(10);
+
+    let result = if record.success { Span::styled("OK", Style::default().fg(Color::Green)) } else { Span::styled("FAILED", Style::default().fg(Color::Red)) };
+    let status = record.status.map_or(String::from("no response"), |s| format!("HTTP {}", s));
+    let mut target = Vec::new();
+    if let Some(upstream) = &record.upstream {
+        target.push(format!("upstream {}", upstream));
+    }
+    if let Some(remote) = record.remote {
+        target.push(remote.to_string());
+    }
+    if let Some(alpn) = &record.alpn {
+        target.push(format!("ALPN {}", alpn));
+    }
+    let mut lines = vec![
+        Line::from(vec![Span::raw(format!("{} | ", status)), result, Span::raw(format!(" | total {}", format_ms(record.latency)))]),
+        Line::from(target.join(" | ")),
+        Line::from(""),
+    ];
+
+    match &record.timing {
+        Some(timing) => {
+            for (bar, color) in timing.waterfall(bar_width).iter().zip(PHASE_COLORS) {
+                // 다시 쓴 연결이면 연결 단계는 시간 대신 reused 로 표시
+                let duration = match bar.phase {
+                    "DNS" | "Connect" | "TLS" if timing.reused => String::from("reused"),
+                    _ => format_ms(bar.duration),
+                };
+                lines.push(Line::from(vec![
+                    Span::raw(format!("{:8} {:>12} ", bar.phase, duration)),
+                    Span::raw("|".to_string() + &" ".repeat(bar.offset)),
+                    Span::styled("█".repeat(bar.width), Style::default().fg(color)),
+                    Span::raw(" ".repeat(bar_width - bar.offset - bar.width) + "|"),
+                ]));
+            }
+            if !timing.detailed {
+                lines.push(Line::styled(
+                    "Wait includes client setup, DNS, connect, TLS and send; set phase_timing=on to split them",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+        }
+        None => lines.push(Line::from("No timing: the request got no response")),
+    }
+    lines
+}
+
+// 명령 팔레트 입력 줄 (로그 영역 하단)
+fn command_bar(f: &mut Frame, area: Rect, input: &str) {
+    let [area] = Layout::vertical([Constraint::Length(3)]).flex(Flex::End).areas(area);
+    let paragraph = Paragraph::new(format!(":{}", input)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Command")
+            .title_bottom(Line::from(" Enter: run | Esc: close ").right_aligned())
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+    f.set_cursor_position(Position { x: area.x + 2 + input.len() as u16, y: area.y + 1 });
+}
+
+// 업스트림별 p50/p99 (느린 업스트림은 빨간색)
+fn upstream_panel(f: &mut Frame, area: Rect, upstreams: &[UpstreamStats]) {
+    let total: usize = upstreams.iter().map(|u| u.count).sum();
+    let mut lines = vec![Line::from(Span::styled(
+        format!("{:16} {:>6} {:>5} {:>11} {:>11}", "upstream", "reqs", "fail", "p50", "p99"),
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    lines.extend(upstreams.iter().enumerate().map(|(i, u)| {
+        let style = if stats::is_outlier(upstreams, i) { Style::default().fg(Color::Red) } else { Style::default() };
+        Line::from(Span::styled(
+            format!("{:16.16} {:>6} {:>5} {:>11} {:>11}", u.name, u.count, u.failed, format_ms(u.p50), format_ms(u.p99)),
+            style,
+        ))
+    }));
+
+    // 제목에 공정성 지수, 표 아래에 요청 몫 막대 (라운드 로빈이면 막대 길이가 같다)
+    let title = match distribution::fairness(upstreams) {
+        Some(fairness) => format!("Upstreams | Jain {:.3}, max/min {:.2}", fairness.jain, fairness.max_min_ratio),
+        None => String::from("Upstreams"),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let [table_area, bar_area] = Layout::vertical([Constraint::Length(lines.len() as u16 + 1), Constraint::Fill(1)]).areas(inner);
+    f.render_widget(Paragraph::new(lines), table_area);
+
+    let bars: Vec<Bar> = upstreams
+        .iter()
+        .enumerate()
+        .map(|(i, u)| {
+            let color = if stats::is_outlier(upstreams, i) { Color::Red } else { Color::Cyan };
+            Bar::default()
+                .value(u.count as u64)
+                .label(Line::from(format!("{:16.16}", u.name)))
+                .text_value(format!("{:.1}%", u.count as f64 * 100.0 / total.max(1) as f64))
+                .style(Style::default().fg(color))
+                .value_style(Style::default().fg(Color::Black).bg(color))
+        })
+        .collect();
+    let chart = BarChart::default().direction(Direction::Horizontal).bar_width(1).bar_gap(0).data(BarGroup::default().bars(&bars));
+    f.render_widget(chart, bar_area);
+}
+
+// A/B 비교 실행의 대상별 지표. B 가 더 나쁘면 빨강, 더 좋으면 초록
+fn compare_panel(f: &mut Frame, area: Rect, a: &SideStats, b: &SideStats) {
+    let mut lines = vec![Line::from(Span::styled(
+        format!("{:10} {:>14} {:>14} {:>12}", "", "A", "B", "B-A"),
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    lines.push(Line::raw(format!("{:10} {:>14} {:>14} {:>12}", "requests", a.count, b.count, b.count as i64 - a.count as i64)));
+    let diff_style = |worse: bool, better: bool| match (worse, better) {
+        (true, _) => Style::default().fg(Color::Red),
+        (_, true) => Style::default().fg(Color::Green),
+        _ => Style::default(),
+    };
+    let (rate_a, rate_b) = (a.error_rate(), b.error_rate());
+    lines.push(Line::styled(
+        format!("{:10} {:>13.2}% {:>13.2}% {:>+11.2}%", "errors", rate_a, rate_b, rate_b - rate_a),
+        diff_style(rate_b > rate_a, rate_b < rate_a),
+    ));
+    for (name, va, vb) in [("avg", a.avg, b.avg), ("p50", a.p50, b.p50), ("p90", a.p90, b.p90), ("p99", a.p99, b.p99)] {
+        let diff = (vb.as_secs_f64() - va.as_secs_f64()) * 1000.0;
+        lines.push(Line::styled(
+            format!("{:10} {:>14} {:>14} {:>+10.3}ms", name, format_ms(va), format_ms(vb), diff),
+            diff_style(vb > va, vb < va),
+        ));
+    }
+
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("A/B comparison"));
+    f.render_widget(paragraph, area);
+}
+
+// 경로별 통과/실패 매트릭스
+fn route_panel(f: &mut Frame, area: Rect, results: &[RouteResult]) {
+    let mut lines = vec![Line::from(Span::styled(
+        format!("{:4} {:28} {:>6} {:>6}", "", "route", "pass", "fail"),
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    lines.extend(results.iter().map(|r| {
+        let (mark, color) = match (r.passed, r.failed) {
+            (0, 0) => ("....", Color::DarkGray),
+            (_, 0) => ("PASS", Color::Green),
+            _ => ("FAIL", Color::Red),
+        };
+        Line::from(vec![
+            Span::styled(format!("{:4} ", mark), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{:28.28} {:>6} {:>6}", r.path, r.passed, r.failed)),
+        ])
+    }));
+
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Routes"));
+    f.render_widget(paragraph, area);
+}
+
+// 요청별 프록시 구간(전체 - 업스트림 처리 시간)과 업스트림 처리 시간 차트
+fn attribution_panel(f: &mut Frame, area: Rect, series: &[(u64, u64)]) {
+    let block = Block::default().borders(Borders::ALL).title("Proxy vs upstream (ms)");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // 오른쪽 끝이 최신 요청이 되도록 폭에 맞춰 자른다
+    let series = &series[series.len().saturating_sub(inner.width as usize)..];
+    let proxy: Vec<u64> = series.iter().map(|(p, _)| *p).collect();
+    let upstream: Vec<u64> = series.iter().map(|(_, u)| *u).collect();
+    let max = proxy.iter().chain(upstream.iter()).copied().max().unwrap_or(0).max(1);
+
+    let [proxy_title, proxy_area, upstream_title, upstream_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Fill(1), Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+    let last = |v: &[u64]| v.last().copied().unwrap_or(0);
+    f.render_widget(Line::from(format!("proxy (last {}ms)", last(&proxy))).style(Style::default().fg(Color::Magenta)), proxy_title);
+    f.render_widget(Sparkline::default().data(&proxy).max(max).style(Style::default().fg(Color::Magenta)), proxy_area);
+    f.render_widget(Line::from(format!("upstream (last {}ms)", last(&upstream))).style(Style::default().fg(Color::Cyan)), upstream_title);
+    f.render_widget(Sparkline::default().data(&upstream).max(max).style(Style::default().fg(Color::Cyan)), upstream_area);
+}
+
+// 설정한 백분위 지연 ("p50 1.000ms p99 2.000ms")
+fn latency_percentiles(metrics: &LiveMetrics) -> String {
+    let values: Vec<String> = metrics.latency.iter().map(|(p, latency)| format!("{} {}", stats::percentile_label(*p), format_ms(*latency))).collect();
+    values.join(" ")
+}
+
+// 색 없는 화면에서 지표 패널 대신 쓰는 한 줄
+fn metrics_line(metrics: &LiveMetrics) -> String {
+    format!(
+        "sent {} ok {} fail {} in flight {} | {} req/s | {}",
+        metrics.sent,
+        metrics.succeeded,
+        metrics.failed,
+        metrics.sent - metrics.succeeded - metrics.failed,
+        metrics.rps,
+        latency_percentiles(metrics)
+    )
+}
+
+// 보낸/성공/실패 수, 초당 처리량, 지연 백분위와 초마다 끝난 요청 수 차트
+fn metrics_panel(f: &mut Frame, area: Rect, metrics: &LiveMetrics) {
+    let title = match metrics.downsampled {
+        0 => String::from("Metrics"),
+        n => format!("Metrics | {} older downsampled, percentiles ±5%", n),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let [counts_line, latency_line, status_line, chart_title, chart_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Length(1), Constraint::Length(1), Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+    let in_flight = metrics.sent - metrics.succeeded - metrics.failed;
+    f.render_widget(
+        Line::from(vec![
+            Span::raw(format!("sent {} ", metrics.sent)),
+            Span::styled(format!("ok {} ", metrics.succeeded), Style::default().fg(Color::Green)),
+            Span::styled(format!("fail {} ", metrics.failed), Style::default().fg(if metrics.failed > 0 { Color::Red } else { Color::DarkGray })),
+            Span::raw(format!("in flight {} | {} req/s", in_flight, metrics.rps)),
+        ]),
+        counts_line,
+    );
+    f.render_widget(Line::from(latency_percentiles(metrics)), latency_line);
+    let [ok, redirect, client, server, none] = metrics.classes;
+    let mut status = format!("2xx {} 3xx {} 4xx {} 5xx {} none {}", ok, redirect, client, server, none);
+    if let Some(size) = metrics.avg_size {
+        status.push_str(&format!(" | avg body {}", stats::format_bytes(size)));
+    }
+    f.render_widget(Line::from(status), status_line);
+
+    // 오른쪽 끝이 진행 중인 초가 되도록 폭에 맞춰 자른다
+    let series = &metrics.rps_series[metrics.rps_series.len().saturating_sub(chart_area.width as usize)..];
+    let peak = series.iter().copied().max().unwrap_or(0);
+    f.render_widget(Line::from(format!("completed per second (peak {})", peak)).style(Style::default().fg(Color::Blue)), chart_title);
+    f.render_widget(Sparkline::default().data(series).max(peak.max(1)).style(Style::default().fg(Color::Blue)), chart_area);
+}
+
+// h2 PING 으로 잰 연결 RTT 와 요청 지연 차트. 같은 눈금이라 두 차트의 차이가 프록시/업스트림 처리 시간
+fn ping_panel(f: &mut Frame, area: Rect, rtt: &[u64], latency: &[u64]) {
+    let block = Block::default().borders(Borders::ALL).title("PING RTT vs request");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let width = inner.width as usize;
+    let rtt = &rtt[rtt.len().saturating_sub(width)..];
+    let latency = &latency[latency.len().saturating_sub(width)..];
+    let max = rtt.iter().chain(latency.iter()).copied().max().unwrap_or(0).max(1);
+
+    let [rtt_title, rtt_area, latency_title, latency_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Fill(1), Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+    let last = |v: &[u64]| format_ms(Duration::from_micros(v.last().copied().unwrap_or(0)));
+    f.render_widget(Line::from(format!("PING RTT (last {})", last(rtt))).style(Style::default().fg(Color::Green)), rtt_title);
+    f.render_widget(Sparkline::default().data(rtt).max(max).style(Style::default().fg(Color::Green)), rtt_area);
+    f.render_widget(Line::from(format!("request (last {})", last(latency))).style(Style::default().fg(Color::Yellow)), latency_title);
+    f.render_widget(Sparkline::default().data(latency).max(max).style(Style::default().fg(Color::Yellow)), latency_area);
+}
@@ -1,73 +1,900 @@
-use std::{error::Error, sync::{Arc, Mutex}, time::Duration};
+use std::{error::Error, hash::{DefaultHasher, Hash, Hasher}, net::SocketAddr, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex}, time::{Duration, Instant}};
 
+use bytes::Bytes;
 use crossterm::event::KeyCode;
-use rand::{distr::Alphanumeric, Rng};
-use reqwest::{header::{HeaderMap, HeaderValue}, Client, Version};
+use http_body_util::{BodyExt, Full};
+use hyper::http;
+use rand::{distr::{Distribution, StandardUniform}, rngs::StdRng, Rng, SeedableRng};
+use reqwest::{header::{HeaderMap, HeaderName, HeaderValue}, Client, Method, StatusCode, Url, Version};
+use tokio_native_tls::native_tls;
 
-use crate::AppState;
+use crate::{alpn, canary, compare::Side, config::RunConfig, conn_log::ConnEvents, deadline, events::Bus, drain, error_code::ErrorCode, graphql, header_matrix, headers, hosts, idle, longpoll, payload, priority, proxy_protocol, query, routes::{self, Route}, settings, stats::{format_ms, RequestRecord}, success, tcp, tls, timing::{Exchange, Timing}, transcode, transport::{self, AddressFamily, ConnInfo, RawClient, TimeoutKind, TransportOptions}, udp, AppState};
 
-fn random_string(size: usize) -> String {
-    rand::rng().sample_iter(&Alphanumeric).take(size * 1024).map(char::from).collect::<String>()
+// 실행 설정마다 한 번만 만들어 두는 요청 템플릿
+// 요청마다 달라지는 값은 my_id 뿐이므로 큰 헤더/쿼리스트링은 미리 생성, 검증해 둔다
+pub struct RequestTemplate {
+    url: Url,
+    headers: HeaderMap,
+    // 연결 수준 옵션이 있을 때만 사용하는 전송 계층
+    raw: Option<RawClient>,
+    pub connect_timeout: Duration,
+    pub tls_timeout: Duration,
+    pub request_timeout: Duration,
+    // CA 묶음, 클라이언트 인증서, 검증 생략 (모든 연결 경로에 적용)
+    pub tls: tls::Settings,
+    pub family: AddressFamily,
+    // 업스트림 식별에 쓰는 응답 헤더
+    upstream_headers: Vec<HeaderName>,
+    cluster_header: Option<HeaderName>,
+    // 순환할 경로 목록 (비어 있으면 url 만 사용)
+    routes: Vec<Route>,
+    // 일부 요청에 붙이는 지연 헤더와 비율 (least-request 검증)
+    slow_header: Option<(HeaderName, HeaderValue)>,
+    pub slow_percent: u64,
+    // 요청별 데드라인 헤더 방식과 분포
+    deadline: Option<(deadline::Mode, deadline::Distribution)>,
+    // 성공으로 볼 응답 기준
+    pub success: success::Criteria,
+    // 요청 메서드와 본문 (JSON 본문, 정해진 크기의 본문, 비정상 입력을 쓰지 않으면 비어 있음)
+    method: Method,
+    body: Bytes,
+    // gRPC-JSON 트랜스코딩 응답 검증
+    pub transcode: bool,
+    // GraphQL 쿼리 템플릿 (응답의 errors[] 를 실패로 봄)
+    graphql: Option<graphql::QueryTemplate>,
+    // 요청마다 치환해 붙이는 쿼리 파라미터
+    query: Option<query::QueryTemplate>,
+    // 롱폴링 모드에서 업스트림이 요청을 잡아 둘 것으로 기대하는 시간
+    pub long_poll: Option<Duration>,
+    // 호스트별 동시 요청 수, 초당 요청 수 상한
+    hosts: Option<hosts::HostLimits>,
+    // 멱등성 키 헤더, 키 하나를 쓰는 연속 요청 수, 실행마다 다른 키 접두어
+    idempotency: Option<(HeaderName, usize, String)>,
+    // 요청마다 돌려 가며 붙이는 우선순위 헤더
+    pub priority: Option<priority::PrioritySweep>,
+    // 요청마다 돌려 가며 붙이는 헤더 값 조합 (라우팅 매트릭스)
+    pub header_matrix: Option<header_matrix::HeaderMatrix>,
+    // tcp:// 대상이면 HTTP 요청 대신 연결마다 페이로드를 주고받는다
+    pub tcp: Option<tcp::TcpTarget>,
+    // udp:// 대상이면 반복마다 패킷을 하나 보낸다
+    pub udp: Option<udp::UdpTarget>,
+    // TLS 핸드셰이크에서 제안할 ALPN 목록
+    pub alpn: alpn::Offer,
+    // 유휴 연결 실험이면 반복마다 연결 하나를 열어 두고 닫힐 때까지 관찰한다
+    pub idle: Option<idle::IdleProbe>,
+    // h2 로 응답한 주소마다 SETTINGS 를 따로 받아 기록
+    pub h2_settings: bool,
+    // 부하와 함께 낮은 속도로 보내는 카나리 요청
+    pub canary: Option<canary::Canary>,
+    // 전송 계층이 쌓는 연결 이벤트 (conn_events)
+    pub conn_events: Option<ConnEvents>,
+    // 요청마다 돌려 가며 쓰는 클라이언트 (비어 있으면 요청마다 새 클라이언트, 곧 새 연결)
+    clients: Vec<Client>,
+    // 요청마다 새 클라이언트를 만들 때 같이 쓰는 TLS 설정 (시스템 인증서 저장소를 요청마다 다시 읽지 않도록 한 번만 만든다)
+    fresh_tls: Option<native_tls::TlsConnector>,
+    // 공유 클라이언트가 쉬는 연결을 남겨 둘 수 (동시 요청 수만큼 두어야 연결이 다시 맺히지 않는다)
+    max_idle: usize,
+    next_client: AtomicUsize,
+    // 실행 시드로 초기화한 난수 생성기 (같은 시드면 같은 페이로드, id, 변형 순서)
+    rng: Mutex<StdRng>,
 }
 
-fn create_header(id: &str, size: usize) -> HeaderMap {
-    // 헤더 생성
-    let mut headers = HeaderMap::new();
-    headers.insert("my_id", HeaderValue::from_str(id).unwrap());
-    headers.insert("random_header", HeaderValue::from_str(
-        &random_string(size)
-    ).expect("Failed to add random header"));
-    headers
+impl RequestTemplate {
+    pub fn new(config: &RunConfig) -> Result<Self, String> {
+        let mut url = Url::parse(&config.dst_url).map_err(|e| format!("Invalid URL '{}': {}", config.dst_url, e))?;
+        let seed = config.seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+        // 사전 파일이 있으면 실제와 비슷한 값(토큰, JWT, 쿠키)으로 채운다
+        let content = if config.payload_dictionary.trim().is_empty() {
+            payload::random_value(&mut rng, payload::parse_charset(&config.header_charset)?, config.header_size_kb)
+        } else if config.header_charset != "alnum" {
+            return Err(String::from("header charset cannot be combined with a payload dictionary"));
+        } else {
+            payload::from_dictionary(&mut rng, &payload::load_dictionary(config.payload_dictionary.trim())?, config.header_size_kb)
+        };
+        let mut headers = HeaderMap::new();
+
+        if config.protocol == "queryString" && config.header_charset == "percent" {
+            // 퍼센트 인코딩을 다시 인코딩하지 않고 그대로 보낸다
+            let query = url.query().map(|query| format!("{}&content={}", query, content)).unwrap_or_else(|| format!("content={}", content));
+            url.set_query(Some(&query));
+        } else if config.protocol == "queryString" {
+            url.query_pairs_mut().append_pair("content", &content);
+        } else {
+            // HeaderValue 는 내부적으로 Bytes 라서 clone 시 복사 없이 공유된다. UTF-8 값은 obs-text 바이트로 싣는다
+            let value = HeaderValue::from_bytes(content.as_bytes()).map_err(|e| format!("Failed to build random header: {}", e))?;
+            headers.insert("random_header", value);
+        }
+
+        // 0 이면 전체 요청 타임아웃만 적용
+        let request_timeout = Duration::from_millis(config.request_timeout_ms.max(1));
+        let or_request = |ms: u64| if ms == 0 { request_timeout } else { Duration::from_millis(ms) };
+        let connect_timeout = or_request(config.connect_timeout_ms);
+        // 클라이언트 타임아웃이 유지 시간보다 짧으면 Envoy 가 아니라 우리가 요청을 끊게 된다
+        let long_poll = (config.long_poll_s > 0).then(|| Duration::from_secs(config.long_poll_s));
+        if let Some(hold) = long_poll
+            && request_timeout <= hold
+        {
+            return Err(format!("request timeout ({}ms) must be longer than the long-poll hold time ({}s)", request_timeout.as_millis(), hold.as_secs()));
+        }
+        let tls_timeout = or_request(config.tls_timeout_ms);
+        let tls = tls::Settings::load(config)?;
+
+        let upstream_headers = config
+            .upstream_header
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| HeaderName::from_bytes(s.as_bytes()).map_err(|_| format!("invalid upstream header name '{}'", s)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // 비정상 입력 본문/헤더는 위험 페이로드 옵션을 명시적으로 켠 경우에만 만든다
+        let json_body = config.json_body.trim();
+        let graphql = graphql::QueryTemplate::new(&config.graphql_query, &config.graphql_variables)?;
+        if graphql.is_some() && (!json_body.is_empty() || config.grpc_transcode || config.pathological_payload != "off" || config.body_size_kb > 0) {
+            return Err(String::from("GraphQL mode cannot be combined with a JSON body, body size, transcoding check or pathological payload"));
+        }
+        if config.body_size_kb > 0 && (!json_body.is_empty() || config.grpc_transcode || config.pathological_payload != "off") {
+            return Err(String::from("body size cannot be combined with a JSON body, transcoding check or pathological payload"));
+        }
+        let method = Method::from_bytes(config.method.as_bytes()).map_err(|_| format!("invalid HTTP method '{}'", config.method))?;
+        if graphql.is_some() {
+            headers.insert(reqwest::header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        }
+        let body = match payload::parse_pathological(&config.pathological_payload)? {
+            None if !json_body.is_empty() || config.grpc_transcode => {
+                let body = payload::json_body(json_body)?;
+                headers.insert(reqwest::header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                body
+            }
+            None if config.body_size_kb > 0 => {
+                let (body, content_type) = payload::sized_body(&mut rng, payload::parse_body_type(&config.body_type)?, config.body_size_kb)?;
+                headers.insert(reqwest::header::CONTENT_TYPE, content_type);
+                body
+            }
+            None => Bytes::new(),
+            Some(_) if !json_body.is_empty() => return Err(String::from("JSON body and pathological payload cannot be used together")),
+            Some(_) if !config.dangerous_payloads => {
+                return Err(format!("pathological payload '{}' requires the dangerous payloads option", config.pathological_payload));
+            }
+            Some(kind) => {
+                let generated = payload::generate(kind, config.pathological_size)?;
+                headers.extend(generated.headers);
+                generated.body
+            }
+        };
+
+        // fault 주입 헤더는 모든 요청에 동일하게 붙는다
+        for (name, value) in config.fault()?.headers() {
+            headers.insert(name, value);
+        }
+        if !config.auth_header.trim().is_empty() {
+            let value = HeaderValue::from_str(config.auth_header.trim()).map_err(|_| String::from("invalid Authorization header value"))?;
+            headers.insert(reqwest::header::AUTHORIZATION, value);
+        }
+        // 사용자 헤더는 생성된 헤더보다 나중에 붙여 같은 이름이면 덮어쓴다 (my_id 같은 요청별 헤더는 덮어쓰지 않는다)
+        headers::merge(&mut headers, &config.custom_headers)?;
+
+        let cluster_header = match config.cluster_header.trim() {
+            "" => None,
+            name => Some(HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("invalid cluster header name '{}'", name))?),
+        };
+        let mut routes = if config.routes_file.trim().is_empty() {
+            Vec::new()
+        } else {
+            routes::load(config.routes_file.trim(), &Url::parse(&config.dst_url).map_err(|e| e.to_string())?)?
+        };
+        if config.protocol == "queryString" {
+            for route in routes.iter_mut() {
+                route.url.query_pairs_mut().append_pair("content", &content);
+            }
+        }
+
+        let hosts = hosts::HostLimits::new(std::iter::once(&url).chain(routes.iter().map(|r| &r.url)), config.host_concurrency, config.host_rps);
+
+        let idempotency = match config.idempotency_header.trim() {
+            "" => None,
+            name => {
+                let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("invalid idempotency header name '{}'", name))?;
+                Some((name, config.idempotency_repeat.max(1), new_request_id(&mut rng)))
+            }
+        };
+
+        let tcp = match url.scheme() {
+            "tcp" => Some(tcp::TcpTarget::new(&url, config, &mut rng)?),
+            _ => None,
+        };
+        let udp = match url.scheme() {
+            "udp" => Some(udp::UdpTarget::new(&url, config, &mut rng)?),
+            _ => None,
+        };
+
+        if config.ping_interval_ms > 0 && (tcp.is_some() || udp.is_some()) {
+            return Err(String::from("PING RTT sampling needs an http or https target"));
+        }
+
+        let slow_header = match config.slow_header.split_once(':') {
+            _ if config.slow_header.trim().is_empty() => None,
+            Some((name, value)) => Some((
+                HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| format!("invalid slow header name '{}'", name.trim()))?,
+                HeaderValue::from_str(value.trim()).map_err(|_| format!("invalid slow header value '{}'", value.trim()))?,
+            )),
+            None => return Err(format!("invalid slow header '{}' (expected 'name: value')", config.slow_header)),
+        };
+
+        let deadline = match deadline::parse_mode(&config.deadline_mode)? {
+            Some(mode) => Some((mode, deadline::Distribution::parse(&config.deadline_ms)?)),
+            None => None,
+        };
+
+        let family = AddressFamily::parse(&config.address_family)?;
+        let options = TransportOptions {
+            connect_timeout,
+            tls_timeout,
+            tls: tls.clone(),
+            family,
+            fallback_delay: Duration::from_millis(config.fallback_delay_ms),
+            sources: transport::parse_source_pool(&config.source_addrs, &config.source_ports)?,
+            proxy_protocol: match proxy_protocol::parse_version(&config.proxy_protocol)? {
+                Some(version) => Some((version, proxy_protocol::parse_source(&config.proxy_source)?)),
+                None => None,
+            },
+            events: config.conn_events.then(ConnEvents::default),
+            // 단계별 시간만 잴 때는 연결 단계가 요청마다 보이도록 새 연결을 맺는다
+            reuse: config.conn_events && !config.new_connection,
+            seed,
+        };
+        let conn_events = options.events.clone();
+        // reqwest 는 TCP 연결과 TLS 핸드셰이크 타임아웃을 구분하지 못하므로 TLS 타임아웃은 전송 계층에서 처리
+        let separate_tls_timeout = config.tls_timeout_ms > 0 && url.scheme() == "https";
+        // 주소 계열 정책과 Happy Eyeballs 대기도 reqwest 로는 바꿀 수 없다
+        let custom_fallback = family != AddressFamily::Auto || config.fallback_delay_ms != transport::DEFAULT_FALLBACK_DELAY_MS;
+        // 단계별 시간과 연결 이벤트는 전송 계층에서만 나눠 잴 수 있다
+        let raw = if !config.phase_timing && !config.conn_events && options.sources.is_empty() && options.proxy_protocol.is_none() && !separate_tls_timeout && !custom_fallback {
+            None
+        } else {
+            Some(transport::build_client(options)?)
+        };
+        let alpn = alpn::Offer::parse(&config.alpn)?;
+        if raw.is_some() && alpn != alpn::Offer::Http1 {
+            return Err(String::from("ALPN offers other than http1 cannot be combined with connection-level options (phase timing, connection events, source addresses, PROXY protocol, TLS timeout, address family)"));
+        }
+        if config.h2_connections > 0 && alpn == alpn::Offer::Http1 {
+            return Err(String::from("shared HTTP/2 connections need the ALPN offer set to h2 or both"));
+        }
+
+        let canary = canary::Canary::new(config, &url, &tls, request_timeout)?;
+        let mut template = Self {
+            url,
+            headers,
+            raw,
+            connect_timeout,
+            tls_timeout,
+            request_timeout,
+            tls,
+            family,
+            upstream_headers,
+            cluster_header,
+            routes,
+            slow_percent: if slow_header.is_some() { config.slow_percent } else { 0 },
+            slow_header,
+            deadline,
+            success: success::Criteria::new(&config.success_status, config.slow_failure_ms)?,
+            rng: Mutex::new(rng),
+            body,
+            method,
+            transcode: config.grpc_transcode,
+            graphql,
+            query: query::QueryTemplate::new(&config.query_params)?,
+            long_poll,
+            hosts,
+            idempotency,
+            priority: priority::parse(&config.priority_header)?,
+            header_matrix: header_matrix::parse(&config.header_matrix)?,
+            canary,
+            tcp,
+            udp,
+            alpn,
+            idle: idle::IdleProbe::new(config)?,
+            h2_settings: config.h2_settings,
+            conn_events,
+            clients: Vec::new(),
+            fresh_tls: None,
+            max_idle: if config.concurrency == 0 { usize::MAX } else { config.concurrency.max(5) },
+            next_client: AtomicUsize::new(0),
+        };
+        // 벤치마크는 작업마다 연결을 하나씩 맺어 두고 돌려 쓴다 (공유 h2 연결 수를 정했으면 그 값)
+        // 그 외에는 실행 동안 클라이언트 하나의 연결 풀을 같이 쓰고, new_connection 이면 요청마다 새로 만든다
+        let shared = if config.h2_connections > 0 {
+            config.h2_connections
+        } else if config.benchmark {
+            config.concurrency
+        } else if config.new_connection {
+            0
+        } else {
+            1
+        };
+        if shared == 0 {
+            template.fresh_tls = Some(template.tls.connector().request_alpns(template.alpn.protocols()).build().map_err(|e| e.to_string())?);
+        }
+        template.clients = (0..shared).map(|_| build_client(&template)).collect::<reqwest::Result<_>>().map_err(|e| e.to_string())?;
+        Ok(template)
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    // 요청마다 돌려 쓰는 클라이언트 (공유 클라이언트, 공유 h2 연결, 벤치마크의 미리 맺은 연결)
+    pub fn clients(&self) -> &[Client] {
+        &self.clients
+    }
+
+    // 요청 밖에서 쓰는 임의 값 (사전 점검의 핸드셰이크 바이트 등). 실행 시드를 따른다
+    pub fn random<T>(&self) -> T
+    where
+        StandardUniform: Distribution<T>,
+    {
+        self.rng.lock().unwrap().random()
+    }
+
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    // index 번째 요청이 사용할 경로 번호와 URL
+    fn target(&self, index: usize) -> (Option<usize>, &Url) {
+        if self.routes.is_empty() {
+            (None, &self.url)
+        } else {
+            let route = index % self.routes.len();
+            (Some(route), &self.routes[route].url)
+        }
+    }
+
+    // 응답 헤더에서 업스트림 식별값 찾기
+    pub fn upstream_of(&self, headers: &HeaderMap) -> Option<String> {
+        self.upstream_headers
+            .iter()
+            .find_map(|name| headers.get(name))
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+    }
+
+    fn cluster_of(&self, headers: &HeaderMap) -> Option<String> {
+        self.cluster_header
+            .as_ref()
+            .and_then(|name| headers.get(name))
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+    }
+
+    fn headers_with_id(&self, id: &str, variant: &Variant) -> HeaderMap {
+        let mut headers = self.headers.clone();
+        headers.insert("my_id", HeaderValue::from_str(id).unwrap());
+        if variant.slowed && let Some((name, value)) = &self.slow_header {
+            headers.insert(name.clone(), value.clone());
+        }
+        if let (Some((mode, _)), Some(deadline)) = (self.deadline, variant.deadline) {
+            let (name, value) = deadline::header(mode, deadline);
+            headers.insert(name, value);
+        }
+        if let (Some((name, _, _)), Some(key)) = (&self.idempotency, &variant.idempotency_key) {
+            headers.insert(name.clone(), HeaderValue::from_str(key).unwrap());
+        }
+        if let (Some(sweep), Some(priority)) = (&self.priority, variant.priority) {
+            headers.insert(sweep.name.clone(), sweep.values[priority].clone());
+        }
+        if let (Some(matrix), Some(combination)) = (&self.header_matrix, variant.combination) {
+            for (name, value) in matrix.values(combination) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+        headers
+    }
+
+    // 다음 요청의 id 와 요청마다 달라지는 값 정하기
+    // 재현할 수 있도록 작업 스레드에서 요청 순서대로 호출한다
+    pub fn next_request(&self, index: usize) -> (String, Variant) {
+        let mut rng = self.rng.lock().unwrap();
+        let id = new_request_id(&mut *rng);
+        let variant = Variant {
+            slowed: self.slow_percent > 0 && rng.random_range(0..100) < self.slow_percent,
+            deadline: self.deadline.map(|(_, distribution)| distribution.sample(&mut *rng)),
+            body: self.graphql.as_ref().map(|graphql| graphql.body(&id, index, rng.random())),
+            query: self.query.as_ref().map(|query| query.render(&id, index, &mut *rng)),
+            // 연속한 repeat 개 요청이 같은 키를 쓴다
+            idempotency_key: self.idempotency.as_ref().map(|(_, repeat, prefix)| format!("{}-{}", prefix, index / repeat)),
+            priority: self.priority.as_ref().map(|sweep| sweep.pick(index)),
+            combination: self.header_matrix.as_ref().map(|matrix| matrix.pick(index)),
+        };
+        (id, variant)
+    }
+}
+
+// 요청마다 달라지는 헤더 값
+#[derive(Default)]
+pub struct Variant {
+    // 지연 헤더를 붙일지 (least-request 검증)
+    slowed: bool,
+    // 요청에 실어 보낼 데드라인
+    deadline: Option<Duration>,
+    // 요청마다 만드는 본문 (GraphQL 변수 치환)
+    body: Option<Bytes>,
+    // 요청마다 만든 쿼리 파라미터 (쿼리 템플릿 치환)
+    query: Option<String>,
+    idempotency_key: Option<String>,
+    // 우선순위 헤더 값 번호
+    priority: Option<usize>,
+    // 헤더 매트릭스의 조합 번호
+    combination: Option<usize>,
 }
 
-pub async fn send_request(url: &str, header_size: usize, http_v: &str, state: Arc<Mutex<AppState>>) -> reqwest::Result<()> {
-    let client = Client::builder()
-        .timeout(Duration::from_secs(30))
+fn build_client(template: &RequestTemplate) -> reqwest::Result<Client> {
+    let builder = Client::builder()
+        .connect_timeout(template.connect_timeout)
+        .timeout(template.request_timeout)
         .tcp_keepalive(Duration::from_secs(60)).tcp_nodelay(true)
-        .pool_max_idle_per_host(5).pool_idle_timeout(Duration::from_secs(90))
-        .http1_only().build()?.post(url);
-    
-    // HTTP Request 보내기
-    let random_bytes: [u8; 8] = rand::rng().random();
-    let my_id = base62::encode(u64::from_be_bytes(random_bytes));
-    let headers = create_header(&my_id, header_size);
+        .pool_max_idle_per_host(template.max_idle).pool_idle_timeout(Duration::from_secs(90));
+    let builder = match &template.fresh_tls {
+        Some(tls) => builder.use_preconfigured_tls(tls.clone()),
+        None => template.tls.apply(builder)?,
+    };
+    // 평문 대상에 h2 만 제안하면 prior knowledge(h2c)로 보낸다
+    match template.alpn {
+        alpn::Offer::Http1 => builder.http1_only(),
+        alpn::Offer::H2 => builder.http2_prior_knowledge(),
+        alpn::Offer::Both => builder,
+    }
+    .build()
+}
+
+fn new_request_id(rng: &mut impl Rng) -> String {
+    let random_bytes: [u8; 8] = rng.random();
+    base62::encode(u64::from_be_bytes(random_bytes))
+}
+
+// 응답 헤더를 받은 요청의 결과
+struct Reply {
+    status: StatusCode,
+    version: Version,
+    // x-envoy-upstream-service-time
+    upstream_time: Option<Duration>,
+    // grpc-status (trailers-only 응답이면 헤더, 아니면 트레일러)
+    grpc_status: Option<u32>,
+    // 응답에 Connection: close 가 있었는지
+    connection_close: bool,
+    content_type: Option<String>,
+    // 본문 수신 실패 시 에러 메시지와 타임아웃 종류, 에러 코드
+    body: Result<Bytes, transport::Failure>,
+    // 응답 헤더로 식별한 업스트림과 클러스터
+    upstream: Option<String>,
+    cluster: Option<String>,
+    // 실제로 연결된 목적지 주소
+    remote: Option<SocketAddr>,
+    // 전송 계층을 사용한 경우의 연결 정보
+    conn: Option<ConnInfo>,
+    timing: Timing,
+    // TLS 응답의 협상된 ALPN 프로토콜
+    alpn: Option<String>,
+}
+
+// 전송 실패: 에러 메시지와 타임아웃 종류, 에러 코드, 드레인 신호 (상대가 보낸 GOAWAY)
+struct SendError {
+    message: String,
+    timeout: Option<TimeoutKind>,
+    code: ErrorCode,
+    drain: Option<&'static str>,
+}
+
+impl From<String> for SendError {
+    fn from(message: String) -> Self {
+        Self { message, timeout: None, code: ErrorCode::Send, drain: None }
+    }
+}
 
-    let sender = if http_v == "queryString" {
-        client.query(&[("content", &random_string(header_size))]).header("my_id", my_id.clone())
+// fresh: 요청마다 새 연결을 쓸 때 보내기 전에 만들어 둔 클라이언트 (없으면 공유 클라이언트를 돌려 쓴다)
+async fn execute(template: &RequestTemplate, url: &Url, my_id: &str, variant: &Variant, fresh: Option<Result<Client, String>>) -> Result<Reply, SendError> {
+    let headers = template.headers_with_id(my_id, variant);
+
+    if let Some(raw) = &template.raw {
+        let mut request = hyper::Request::builder()
+            .method(template.method.clone())
+            .uri(url.as_str())
+            .body(Full::new(variant.body.clone().unwrap_or_else(|| template.body.clone())))
+            .map_err(|e| e.to_string())?;
+        request.headers_mut().extend(headers);
+
+        let started = Instant::now();
+        let (parts, body) = transport::send(raw, request, template.request_timeout)
+            .await
+            // 전송 계층은 HTTP/1 만 쓰므로 GOAWAY 가 오지 않는다
+            .map_err(|(message, timeout, code)| SendError { message, timeout, code, drain: None })?;
+        let done = Instant::now();
+        let conn = parts.extensions.get::<ConnInfo>().cloned();
+        if let Some(tracker) = conn.as_ref().and_then(|c| c.tracker.as_ref()) {
+            tracker.served(my_id);
+        }
+        let timing = match (&conn, parts.extensions.get::<Exchange>()) {
+            (Some(c), Some(exchange)) => c.phases.timing(*exchange, done),
+            _ => Timing { wait: done - started, ..Timing::default() },
+        };
+        let upstream = template.upstream_of(&parts.headers);
+        let cluster = template.cluster_of(&parts.headers);
+        let connection_close = connection_close(&parts.headers);
+        let grpc_status = grpc_status(&parts.headers);
+        let content_type = content_type(&parts.headers);
+        let upstream_time = upstream_service_time(&parts.headers);
+        let alpn = (url.scheme() == "https").then(|| conn.as_ref().and_then(|c| c.alpn.clone()).unwrap_or_else(|| String::from("none")));
+        return Ok(Reply { status: parts.status, version: parts.version, upstream_time, grpc_status, connection_close, content_type, body, upstream, cluster, remote: conn.as_ref().map(|c| c.remote), conn, timing, alpn });
+    }
+
+    let started = Instant::now();
+    let client = match fresh {
+        Some(client) => client?,
+        None => template.clients[template.next_client.fetch_add(1, Ordering::Relaxed) % template.clients.len()].clone(),
+    };
+    let response = client
+        .request(template.method.clone(), url.clone())
+        .headers(headers)
+        .body(variant.body.clone().unwrap_or_else(|| template.body.clone()))
+        .send()
+        .await
+        .map_err(|e| {
+            // reqwest 의 connect 타임아웃은 TLS 핸드셰이크까지 포함한다
+            let timeout = if e.is_timeout() && e.is_connect() {
+                Some(TimeoutKind::Connect)
+            } else if e.is_timeout() {
+                Some(TimeoutKind::Request)
+            } else {
+                None
+            };
+            let code = ErrorCode::from_send_error(timeout, transport::is_tls_error(&e), e.is_connect());
+            SendError { message: format!("{:?}", e.source()), timeout, code, drain: drain::from_error(&e) }
+        })?;
+
+    let headers_at = Instant::now();
+    let (status, version, remote) = (response.status(), response.version(), response.remote_addr());
+    let upstream = template.upstream_of(response.headers());
+    let cluster = template.cluster_of(response.headers());
+    let connection_close = connection_close(response.headers());
+    let grpc_status = grpc_status(response.headers());
+    let content_type = content_type(response.headers());
+    let upstream_time = upstream_service_time(response.headers());
+    // 트레일러까지 받아야 본문 뒤에 온 grpc-status 를 읽을 수 있다
+    let body = http::Response::<reqwest::Body>::from(response).into_body().collect().await;
+    let trailers = body.as_ref().ok().and_then(|body| body.trailers().cloned());
+    let grpc_status = grpc_status.or_else(|| trailers.as_ref().and_then(self::grpc_status));
+    let body = body.map(|body| body.to_bytes()).map_err(|e| match e.is_timeout() {
+        true => (TimeoutKind::Request.to_string(), Some(TimeoutKind::Request), ErrorCode::Timeout),
+        false => (e.to_string(), None, ErrorCode::Receive),
+    });
+    let timing = Timing { wait: headers_at - started, receive: headers_at.elapsed(), ..Timing::default() };
+    let alpn = (url.scheme() == "https").then(|| alpn::from_version(version));
+    Ok(Reply { status, version, upstream_time, grpc_status, connection_close, content_type, body, upstream, cluster, remote, conn: None, timing, alpn })
+}
+
+// new_connection 이면 요청에 쓸 새 클라이언트 (공유 클라이언트나 전송 계층을 쓰면 None)
+fn fresh_client(template: &RequestTemplate) -> Option<Result<Client, String>> {
+    (template.raw.is_none() && template.clients.is_empty()).then(|| build_client(template).map_err(|e| e.to_string()))
+}
+
+fn upstream_service_time(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get("x-envoy-upstream-service-time")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_millis)
+}
+
+fn grpc_status(headers: &HeaderMap) -> Option<u32> {
+    headers.get("grpc-status").and_then(|v| v.to_str().ok()).and_then(|v| v.parse().ok())
+}
+
+fn content_type(headers: &HeaderMap) -> Option<String> {
+    headers.get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(str::to_owned)
+}
+
+fn connection_close(headers: &HeaderMap) -> bool {
+    headers.get_all(reqwest::header::CONNECTION).iter().any(|v| v.to_str().is_ok_and(|v| v.to_ascii_lowercase().contains("close")))
+}
+
+// 사전 점검용 단일 요청. 실제 실행과 같은 템플릿으로 보낸다
+pub async fn probe_request(template: &RequestTemplate) -> Result<String, String> {
+    let id = new_request_id(&mut *template.rng.lock().unwrap());
+    let reply = execute(template, &template.url, &id, &Variant::default(), fresh_client(template)).await.map_err(|e| match e.timeout {
+        Some(kind) => kind.to_string(),
+        None => e.message,
+    })?;
+    if template.success.accepts(reply.status, None) {
+        Ok(format!("HTTP {} ({:?})", reply.status, reply.version))
     } else {
-        client.headers(headers)
+        Err(format!("HTTP {}", reply.status))
+    }
+}
+
+// index: 실행 내 요청 순번 (경로 순환용)
+// scheduled_at: 작업 스레드가 요청을 예약한 시각. 실제 전송까지의 차이를 클라이언트 대기로 따로 기록한다
+// 요청 하나 전송. 비교 대상(B)이 있으면 같은 id, 같은 변형으로 양쪽에 동시에 보낸다
+pub async fn send_request(template: Arc<RequestTemplate>, compare: Option<Arc<RequestTemplate>>, index: usize, request: (String, Variant), scheduled_at: Instant, state: Arc<Mutex<AppState>>, bus: Bus) {
+    match compare {
+        None => deliver(&template, None, index, &request, scheduled_at, &state, &bus).await,
+        Some(compare) => {
+            tokio::join!(
+                deliver(&template, Some(Side::A), index, &request, scheduled_at, &state, &bus),
+                deliver(&compare, Some(Side::B), index, &request, scheduled_at, &state, &bus),
+            );
+        }
+    }
+}
+
+async fn deliver(template: &RequestTemplate, side: Option<Side>, index: usize, request: &(String, Variant), scheduled_at: Instant, state: &Mutex<AppState>, bus: &Bus) {
+    let (my_id, variant) = request;
+    let hedge_delay = state.lock().unwrap().hedge_delay;
+    let (record, result_log) = exchange(template, side, index, my_id, variant, scheduled_at, hedge_delay).await;
+    let tracked = template.h2_settings.then(|| record.clone());
+
+    let events = {
+        let mut app_state = state.lock().unwrap();
+        app_state.add_log(&result_log);
+        app_state.add_record(record)
     };
+    bus.publish_all(events);
+    if let Some(record) = tracked {
+        settings::track(template, &record, state).await;
+    }
+}
+
+// 인스펙터에서 고른 요청을 같은 헤더, 본문, 변형으로 한 번 더 보낸다
+// id 에는 -replay 를 붙여 구분하고, 헤지 없이 보내며 실행 통계에는 넣지 않는다
+pub async fn resend(template: &RequestTemplate, record: &RequestRecord) -> (RequestRecord, String) {
+    let variant = Variant { slowed: record.slowed, deadline: record.deadline, body: record.body.clone(), query: record.query.clone(), idempotency_key: record.idempotency_key.clone(), priority: record.priority, combination: record.combination };
+    let id = format!("{}-replay", record.id);
+    // 경로 번호로 다시 고르면 같은 경로가 나온다
+    exchange(template, record.side, record.route.unwrap_or(0), &id, &variant, Instant::now(), None).await
+}
+
+// 요청 하나를 보내고 결과 기록과 로그 줄을 만든다
+async fn exchange(template: &RequestTemplate, side: Option<Side>, index: usize, my_id: &str, variant: &Variant, scheduled_at: Instant, hedge_delay: Option<Duration>) -> (RequestRecord, String) {
+    let (route, url) = template.target(index);
+    let templated = variant.query.as_ref().map(|query| query::append(url, query));
+    let url = templated.as_ref().unwrap_or(url);
+    // 호스트 상한에 걸리면 그 호스트 몫으로만 대기 (대기 시간은 클라이언트 대기로 기록)
+    let _permit = match &template.hosts {
+        Some(hosts) => hosts.acquire(url).await,
+        None => None,
+    };
+
+    // 새 클라이언트는 보내기 전에 만들고, 만드는 시간은 지연에도 클라이언트 대기에도 넣지 않는다
+    // 헤지 요청도 새 연결로 나가야 하므로 하나 더 만들어 둔다
+    let preparing = Instant::now();
+    let fresh = fresh_client(template);
+    let fresh_hedge = hedge_delay.and_then(|_| fresh_client(template));
+    let setup = preparing.elapsed();
+
+    // HTTP Request 보내기
+    let sent_at = Instant::now();
+    let mut success = false;
+    let mut timeout = None;
+    let mut remote = None;
+    let mut upstream = None;
+    let mut response_status = None;
+    let mut cluster = None;
+    let mut deadline_exceeded = false;
+    let mut upstream_time = None;
+    let mut grpc_code = None;
+    // 모드별 응답 검증 실패 이유 (트랜스코딩)
+    let mut validation = None;
+    let mut timing = None;
+    let mut alpn = None;
+    let mut response_size = None;
+    let mut error = None;
+    // 멱등성 검증용 응답 본문 해시 (중복 요청이 같은 응답을 받았는지)
+    let mut body_hash = None;
+    let drain;
 
-    let result_log = match sender.send().await {
-        Ok(response) => {
-            let status = response.status();
-            if status.is_success() {
-                match response.text().await {
-                    Ok(_) => format!("Request {} Succeded", &my_id),
-                    Err(e) => format!("Response {} Failed. HTTP {}: {}", &my_id, &status, e)
+    // 헤지: 지연 안에 응답이 없으면 같은 요청을 한 번 더 보내 먼저 온 응답 사용
+    let mut hedged = false;
+    let mut hedge_won = false;
+    let primary = execute(template, url, my_id, variant, fresh);
+    let result = match hedge_delay {
+        None => primary.await,
+        Some(delay) => {
+            tokio::pin!(primary);
+            match tokio::time::timeout(delay, &mut primary).await {
+                Ok(result) => result,
+                Err(_) => {
+                    hedged = true;
+                    let hedge = execute(template, url, my_id, variant, fresh_hedge);
+                    tokio::select! {
+                        result = &mut primary => result,
+                        result = hedge => {
+                            hedge_won = true;
+                            result
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    let mut result_log = match result {
+        Ok(reply) => {
+            let status = reply.status;
+            response_status = Some(status.as_u16());
+            cluster = reply.cluster;
+            upstream_time = reply.upstream_time;
+            timing = Some(reply.timing);
+            alpn = reply.alpn;
+            response_size = reply.body.as_ref().ok().map(|body| body.len() as u64);
+            if template.idempotency.is_some()
+                && let Ok(body) = &reply.body
+            {
+                let mut hasher = DefaultHasher::new();
+                body.hash(&mut hasher);
+                body_hash = Some(hasher.finish());
+            }
+            if let Some((mode, _)) = template.deadline {
+                deadline_exceeded = deadline::is_exceeded(mode, status, reply.grpc_status);
+            }
+            drain = drain::from_response(status, reply.connection_close, reply.body.as_deref().ok());
+            grpc_code = reply.grpc_status;
+            if template.transcode
+                && let Ok(body) = &reply.body
+            {
+                grpc_code = transcode::grpc_code(reply.grpc_status, body);
+                validation = transcode::validate(status, grpc_code, reply.content_type.as_deref(), body).err();
+            }
+            if template.graphql.is_some()
+                && status.is_success()
+                && let Ok(body) = &reply.body
+            {
+                validation = graphql::validate(body).err();
+            }
+            let expected = route.and_then(|route| template.routes[route].expect_status);
+            let mut log = if template.success.accepts(status, expected) {
+                match reply.body {
+                    Ok(_) => {
+                        success = true;
+                        format!("Request {} Succeded", &my_id)
+                    }
+                    // 본문을 받는 동안 요청 타임아웃이 지나면 요청 타임아웃으로 센다
+                    Err((message, kind, code)) => {
+                        timeout = kind;
+                        error = Some(code);
+                        format!("Response {} Failed. HTTP {}: {}", &my_id, &status, message)
+                    }
                 }
             }
             else {
+                error = Some(ErrorCode::from_status(status.as_u16()));
                 format!("Request {} Failed. HTTP {}", &my_id, &status)
+            };
+            if let Some(reason) = &validation {
+                success = false;
+                error.get_or_insert(ErrorCode::Assert);
+                log.push_str(&format!(" [{}: {}]", if template.transcode { "transcode" } else { "graphql" }, reason));
+            }
+            // h2 를 제안했으면 실제로 오간 HTTP 버전을 남긴다
+            if template.alpn != alpn::Offer::Http1 {
+                log.push_str(&format!(" [{:?}]", reply.version));
+            }
+            if let Some(name) = reply.upstream {
+                log.push_str(&format!(" [upstream {}]", name));
+                upstream = Some(name);
+            }
+            if let Some(addr) = reply.remote {
+                log.push_str(&format!(" [{} {}]", transport::family_name(&addr), addr));
+                remote = Some(addr);
+            }
+            if let Some(conn) = reply.conn {
+                log.push_str(&format!(" [src {}]", conn.local));
+                if let Some(proxy_source) = conn.proxy_source {
+                    log.push_str(&format!(" [proxy {}]", proxy_source));
+                }
+            }
+            log
+        }
+        Err(e) => {
+            timeout = e.timeout;
+            error = Some(e.code);
+            drain = e.drain;
+            match e.timeout {
+                Some(kind) => format!("Request {} failed: {} [{}]", &my_id, kind, e.message),
+                None => format!("Request {} failed to send with error: {}", &my_id, e.message),
             }
         }
-        Err(e) => format!("Request {} failed to send with error: {:?}", &my_id, e.source())
     };
 
-    let mut app_state = state.lock().unwrap();
-    app_state.add_log(&result_log);
-
-    drop(app_state);
-
-    Ok(())
+    if variant.slowed {
+        result_log.push_str(" [slowed]");
+    }
+    if let Some(deadline) = variant.deadline {
+        result_log.push_str(&format!(" [deadline {}{}]", format_ms(deadline), if deadline_exceeded { ", exceeded" } else { "" }));
+    }
+    if hedged {
+        result_log.push_str(if hedge_won { " [hedged, hedge won]" } else { " [hedged]" });
+    }
+    if let Some(signal) = drain {
+        result_log.push_str(&format!(" [drain: {}]", signal));
+    }
+    if let Some(route) = route {
+        result_log.push_str(&format!(" [route {}]", template.routes[route].path));
+    }
+    if let Some(query) = &variant.query {
+        result_log.push_str(&format!(" [query {}]", query));
+    }
+    if let Some(key) = &variant.idempotency_key {
+        result_log.push_str(&format!(" [idempotency-key {}]", key));
+    }
+    if let (Some(sweep), Some(priority)) = (&template.priority, variant.priority) {
+        result_log.push_str(&format!(" [priority {}]", String::from_utf8_lossy(sweep.values[priority].as_bytes())));
+    }
+    if let (Some(matrix), Some(combination)) = (&template.header_matrix, variant.combination) {
+        result_log.push_str(&format!(" [matrix {}]", matrix.label(combination)));
+    }
+    if let Some(side) = side {
+        result_log.insert_str(0, &format!("[{}] ", side.as_str()));
+    }
+    // 기준보다 느린 응답은 실패로 센다
+    let latency = sent_at.elapsed();
+    if success
+        && let Some(max_latency) = template.success.max_latency
+        && latency > max_latency
+    {
+        success = false;
+        error = Some(ErrorCode::Assert);
+        result_log.push_str(&format!(" [too slow: > {}]", format_ms(max_latency)));
+    }
+    if let Some(code) = error {
+        result_log.push_str(&format!(" [{}]", code.as_str()));
+    }
+    if let Some(hold) = template.long_poll
+        && longpoll::ended_early(latency, hold)
+    {
+        result_log.push_str(&format!(" [long-poll ended early: < {}s]", hold.as_secs()));
+    }
+    let record = RequestRecord {
+        id: my_id.to_owned(),
+        status: response_status,
+        upstream,
+        cluster,
+        route,
+        sent_at,
+        slowed: variant.slowed,
+        upstream_time,
+        deadline: variant.deadline,
+        deadline_exceeded,
+        hedged,
+        hedge_won,
+        drain,
+        queue_delay: sent_at.duration_since(scheduled_at).saturating_sub(setup),
+        latency,
+        success,
+        timeout,
+        remote,
+        side,
+        grpc_status: grpc_code,
+        validation,
+        timing,
+        body: variant.body.clone(),
+        query: variant.query.clone(),
+        error,
+        idempotency_key: variant.idempotency_key.clone(),
+        body_hash,
+        priority: variant.priority,
+        combination: variant.combination,
+        transferred: None,
+        response_size,
+        alpn,
+        idle: None,
+    };
+    result_log.push_str(&format!(" (queue {}, server {})", format_ms(record.queue_delay), format_ms(record.latency)));
+    (record, result_log)
 }
 
 pub fn input_handling_num(input: &mut String, key: KeyCode) {
     match key {
-        KeyCode::Char(c) => {
-            if c.is_digit(10) {
-                input.push(c);
-            }
+        KeyCode::Char(c) if c.is_ascii_digit() => {
+            input.push(c);
         }
         KeyCode::Backspace => {
             input.pop();
@@ -86,4 +913,4 @@ pub fn input_handling(input: &mut String, key: KeyCode) {
         }
         _ => {}
     }
-}
\ No newline at end of file
+}
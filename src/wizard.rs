@@ -0,0 +1,89 @@
+use crossterm::event::KeyCode;
+use reqwest::Url;
+
+use crate::{
+    config::{RunConfig, CONFIG_FILE},
+    utils::input_handling,
+};
+
+// 첫 실행 안내에서 차례로 묻는 옵션 (옵션 키, 질문). 마지막에 설정 파일로 저장할지 묻는다
+const STEPS: [(&str, &str); 4] = [
+    ("dst_url", "Target URL (e.g. http://127.0.0.1:10000/)"),
+    ("alpn", "HTTP protocol to offer (http1/h2/both)"),
+    ("auth_header", "Authorization header (e.g. Bearer <token>, empty=none)"),
+    ("rps", "Request rate (req/s, 0=send every 100ms)"),
+];
+
+// 안내가 끝난 방식
+pub enum Outcome {
+    // 설정 파일 저장 여부
+    Done(bool),
+    Skipped,
+}
+
+// 설정 파일 없이 처음 띄웠을 때 빈 입력창 대신 보여 주는 안내
+pub struct Wizard {
+    // STEPS.len() 이면 저장 질문
+    pub step: usize,
+    pub input: String,
+    pub error: Option<String>,
+    // 지금까지 답한 설정
+    pub config: RunConfig,
+}
+
+impl Wizard {
+    pub fn new() -> Self {
+        let config = RunConfig::default();
+        Self { step: 0, input: config.option_value(STEPS[0].0), error: None, config }
+    }
+
+    pub fn steps(&self) -> usize {
+        STEPS.len() + 1
+    }
+
+    pub fn prompt(&self) -> String {
+        match STEPS.get(self.step) {
+            Some((_, prompt)) => prompt.to_string(),
+            None => format!("Save these settings to {} so they load on the next start? (y/n)", CONFIG_FILE),
+        }
+    }
+
+    // Enter: 답을 검증하고 다음 질문, Esc: 안내를 건너뛰고 빈 입력창으로
+    pub fn key(&mut self, key: KeyCode) -> Option<Outcome> {
+        match key {
+            KeyCode::Esc => return Some(Outcome::Skipped),
+            KeyCode::Enter => return self.answer(),
+            key => input_handling(&mut self.input, key),
+        }
+        None
+    }
+
+    fn answer(&mut self) -> Option<Outcome> {
+        let Some((key, _)) = STEPS.get(self.step) else {
+            return match self.input.trim().to_ascii_lowercase().as_str() {
+                "y" | "yes" => Some(Outcome::Done(true)),
+                "n" | "no" => Some(Outcome::Done(false)),
+                _ => {
+                    self.error = Some(String::from("answer y or n"));
+                    None
+                }
+            };
+        };
+        let result = match *key {
+            "dst_url" if self.input.trim().is_empty() => Err(String::from("a target URL is required")),
+            "dst_url" => Url::parse(self.input.trim()).map(|_| ()).map_err(|e| format!("invalid URL: {}", e)),
+            _ => Ok(()),
+        };
+        if let Err(e) = result.and_then(|()| self.config.apply_option(key, &self.input)) {
+            self.error = Some(e);
+            return None;
+        }
+        self.step += 1;
+        self.error = None;
+        self.input = match STEPS.get(self.step) {
+            Some((key, _)) => self.config.option_value(key),
+            None => String::from("y"),
+        };
+        None
+    }
+}
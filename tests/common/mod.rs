@@ -0,0 +1,120 @@
+use std::{
+    io,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    runtime::Runtime,
+};
+
+// 모의 프록시가 요청마다 하는 일
+#[derive(Clone, Default)]
+pub struct Behavior {
+    // 응답 전 지연
+    pub delay: Duration,
+    // n 번째 요청마다 503 (0 이면 실패 없음)
+    pub fail_every: usize,
+    // 요청 헤더 블록이 이보다 크면 431 로 거절하고 연결을 닫는다 (0 이면 제한 없음)
+    pub max_header_bytes: usize,
+    // 요청마다 돌아가며 x-upstream-host 로 알려줄 업스트림 이름
+    pub upstreams: Vec<&'static str>,
+}
+
+// 같은 프로세스에 띄우는 HTTP/1.1 모의 프록시. 자기 런타임에서 돌아 생성기의 런타임과 섞이지 않는다
+pub struct MockProxy {
+    pub url: String,
+    served: Arc<AtomicUsize>,
+    failed: Arc<AtomicUsize>,
+    _rt: Runtime,
+}
+
+impl MockProxy {
+    pub fn start(behavior: Behavior) -> Self {
+        let rt = Runtime::new().expect("Failed to create Tokio runtime");
+        let listener = rt.block_on(TcpListener::bind("127.0.0.1:0")).expect("Failed to bind mock proxy");
+        let addr: SocketAddr = listener.local_addr().unwrap();
+        let (served, failed) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let counters = Counters { behavior: Arc::new(behavior), served: served.clone(), failed: failed.clone() };
+        rt.spawn(async move {
+            loop {
+                if let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(serve(stream, counters.clone()));
+                }
+            }
+        });
+        Self { url: format!("http://{}/", addr), served, failed, _rt: rt }
+    }
+
+    // 응답한 요청 수 (거절 포함)
+    pub fn served(&self) -> usize {
+        self.served.load(Ordering::SeqCst)
+    }
+
+    // 4xx/5xx 로 응답한 요청 수
+    pub fn failed(&self) -> usize {
+        self.failed.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Clone)]
+struct Counters {
+    behavior: Arc<Behavior>,
+    served: Arc<AtomicUsize>,
+    failed: Arc<AtomicUsize>,
+}
+
+async fn serve(mut stream: TcpStream, counters: Counters) -> io::Result<()> {
+    let behavior = &counters.behavior;
+    let mut pending = Vec::with_capacity(16 * 1024);
+    let mut chunk = vec![0u8; 16 * 1024];
+    loop {
+        // 헤더와 Content-Length 만큼의 본문을 다 받은 요청마다 응답
+        while let Some(end) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&pending[..end]).to_ascii_lowercase();
+            let length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if behavior.max_header_bytes > 0 && end > behavior.max_header_bytes {
+                counters.served.fetch_add(1, Ordering::SeqCst);
+                counters.failed.fetch_add(1, Ordering::SeqCst);
+                stream.write_all(b"HTTP/1.1 431 Request Header Fields Too Large\r\ncontent-length: 0\r\nconnection: close\r\n\r\n").await?;
+                return Ok(());
+            }
+            if pending.len() < end + 4 + length {
+                break;
+            }
+            pending.drain(..end + 4 + length);
+
+            let index = counters.served.fetch_add(1, Ordering::SeqCst);
+            if !behavior.delay.is_zero() {
+                tokio::time::sleep(behavior.delay).await;
+            }
+            let status = if behavior.fail_every > 0 && (index + 1).is_multiple_of(behavior.fail_every) {
+                counters.failed.fetch_add(1, Ordering::SeqCst);
+                "503 Service Unavailable"
+            } else {
+                "200 OK"
+            };
+            let mut response = format!("HTTP/1.1 {}\r\ncontent-length: 2\r\ncontent-type: text/plain\r\n", status);
+            if !behavior.upstreams.is_empty() {
+                response.push_str(&format!("x-upstream-host: {}\r\n", behavior.upstreams[index % behavior.upstreams.len()]));
+            }
+            response.push_str("\r\nok");
+            stream.write_all(response.as_bytes()).await?;
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        pending.extend_from_slice(&chunk[..read]);
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{Behavior, MockProxy};
+use envoy_lb_client::{LoadGenerator, Report, RunConfig};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+// 모의 프록시로 순차 요청 (지연 없이 보내고 나머지는 기본값)
+fn config(proxy: &MockProxy, iteration: usize) -> RunConfig {
+    let mut config = RunConfig::default();
+    config.apply_option("dst_url", &proxy.url).unwrap();
+    config.iteration = iteration;
+    config.delay_ms = 0;
+    config
+}
+
+fn statuses(report: &Report, status: u16) -> usize {
+    report.records.iter().filter(|r| r.status == Some(status)).count()
+}
+
+#[test]
+fn every_request_is_recorded() {
+    let proxy = MockProxy::start(Behavior::default());
+    let report = LoadGenerator::new(config(&proxy, 20)).run();
+    assert_eq!(report.completed, 20);
+    assert_eq!(report.failed, 0);
+    assert_eq!(statuses(&report, 200), 20);
+    assert_eq!(proxy.served(), 20);
+    assert!(report.logs.iter().any(|log| log.contains("Process Done")));
+}
+
+#[test]
+fn server_errors_count_as_failures() {
+    let proxy = MockProxy::start(Behavior { fail_every: 4, ..Behavior::default() });
+    let report = LoadGenerator::new(config(&proxy, 20)).run();
+    assert_eq!(report.completed, 20);
+    assert_eq!(report.failed, 5);
+    assert_eq!(statuses(&report, 503), 5);
+    assert!(report.records.iter().filter(|r| !r.success).all(|r| r.status == Some(503)));
+}
+
+#[test]
+fn latency_includes_the_server_delay() {
+    let delay = Duration::from_millis(50);
+    let proxy = MockProxy::start(Behavior { delay, ..Behavior::default() });
+    let report = LoadGenerator::new(config(&proxy, 5)).run();
+    assert_eq!(report.completed, 5);
+    assert!(report.records.iter().all(|r| r.latency >= delay), "every latency should include the {:?} server delay", delay);
+}
+
+#[test]
+fn oversized_headers_are_rejected() {
+    let proxy = MockProxy::start(Behavior { max_header_bytes: 4096, ..Behavior::default() });
+    let mut config = config(&proxy, 3);
+    config.apply_option("protocol", "headerKey").unwrap();
+    config.header_size_kb = 8;
+    let report = LoadGenerator::new(config).run();
+    assert_eq!(report.completed, 3);
+    assert_eq!(report.failed, 3);
+    assert_eq!(statuses(&report, 431), 3);
+}
+
+#[test]
+fn responses_are_attributed_to_upstreams() {
+    let proxy = MockProxy::start(Behavior { upstreams: vec!["up-a", "up-b"], ..Behavior::default() });
+    let report = LoadGenerator::new(config(&proxy, 10)).run();
+    let count = |name: &str| report.records.iter().filter(|r| r.upstream.as_deref() == Some(name)).count();
+    assert_eq!((count("up-a"), count("up-b")), (5, 5));
+}
+
+#[test]
+fn run_aborts_after_the_failure_limit() {
+    let proxy = MockProxy::start(Behavior { fail_every: 1, ..Behavior::default() });
+    let mut config = config(&proxy, 200);
+    config.delay_ms = 5;
+    config.abort_after_failures = 3;
+    let report = LoadGenerator::new(config).run();
+    assert!(report.failed >= 3);
+    assert!(report.completed < 200, "run should stop early, completed {}", report.completed);
+    assert!(report.logs.iter().any(|log| log.contains("Process Aborted: 3 failures reached")));
+}
+
+// 무작위 반복 횟수, 동시 요청 수, 실패 주기에서도 보낸 요청과 서버가 센 요청, 실패 수가 맞아야 한다
+// 시드를 고정해 실패하면 같은 경우로 다시 돌릴 수 있다
+#[test]
+fn counts_match_the_server_for_random_runs() {
+    let mut rng = StdRng::seed_from_u64(1272);
+    for case in 0..8 {
+        let (iteration, concurrency, fail_every) = (rng.random_range(1..40), rng.random_range(0..4), rng.random_range(0..6));
+        let proxy = MockProxy::start(Behavior { fail_every, ..Behavior::default() });
+        let mut config = config(&proxy, iteration);
+        config.concurrency = concurrency;
+        let report = LoadGenerator::new(config).run();
+        let context = format!("case {}: iteration {}, concurrency {}, fail_every {}", case, iteration, concurrency, fail_every);
+        assert_eq!(report.completed, iteration, "{}", context);
+        assert_eq!(report.records.len(), iteration, "{}", context);
+        assert_eq!(proxy.served(), iteration, "{}", context);
+        assert_eq!(report.failed, proxy.failed(), "{}", context);
+        assert_eq!(statuses(&report, 503), proxy.failed(), "{}", context);
+    }
+}